@@ -8,20 +8,50 @@
 //! The service runs in the background alongside the API server.
 
 use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{mpsc, RwLock};
 use tokio::time::{interval, Instant};
 use tracing::{debug, error, info, instrument, warn};
 
+use crate::events::{ApiEventKind, EventBus};
 use crate::rest::ApiConfig;
 use alloy_primitives::{Bytes, FixedBytes};
 use arithmetic_db::{
-    get_batch_by_id, get_pending_transactions, get_proven_unposted_batches,
-    mark_batch_posted_to_contract, update_batch_proof, IndexedMerkleTreeADS,
+    claim_outbox_jobs, complete_outbox_job, get_batch_by_id, get_batch_submissions,
+    get_outbox_jobs, get_pending_transactions, get_proven_unposted_batches,
+    mark_batch_posted_to_contract, queue_outbox_job, record_batch_submission, retry_outbox_job,
+    update_batch_proof, update_batch_proof_in, BatchSubmission, BatchSubmissionUpdate,
+    IndexedMerkleTreeADS, OutboxJob, OUTBOX_CONTRACT_POST, OUTBOX_PROOF_REQUEST,
 };
 use arithmetic_lib::proof::{generate_batch_proof, BatchProofGenerationRequest, ProofSystem};
-use ethereum_client::{Config as EthConfig, EthereumClient};
+use ethereum_client::{
+    batch_state_id, batch_state_root, EthereumClient, EthereumError, SettlementConfig,
+    SettlementTarget,
+};
+
+/// Contract update for one batch: `(state_id, new_state_root, proof, public_values)`
+type ContractUpdate = (FixedBytes<32>, FixedBytes<32>, Bytes, Bytes);
+
+/// Connected client of one settlement target, reused across monitor cycles
+struct TargetClient {
+    client: EthereumClient,
+    chain_id: i64,
+}
+
+/// Proof requests dispatched per monitor cycle, to avoid overwhelming Sindri
+const PROOF_REQUESTS_PER_CYCLE: usize = 5;
+
+/// Wait before a proof request that failed to dispatch is tried again
+const PROOF_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// How long a claimed proof request is left to its worker before another may take it
+const PROOF_REQUEST_LEASE: Duration = Duration::from_secs(5 * 60);
+
+/// How long claimed contract posts are left to their worker, long enough for
+/// gas-bumped replacements of a stuck submission
+const CONTRACT_POST_LEASE: Duration = Duration::from_secs(30 * 60);
 
 // ============================================================================
 // BATCH PROCESSOR CONFIGURATION
@@ -119,6 +149,7 @@ pub struct BackgroundBatchProcessor {
     command_rx: mpsc::UnboundedReceiver<BatchProcessorCommand>,
     stats: Arc<RwLock<BatchProcessorStats>>,
     ads_service: Arc<RwLock<IndexedMerkleTreeADS>>,
+    events: EventBus,
 }
 
 /// Handle for communicating with the background batch processor
@@ -162,6 +193,7 @@ impl BackgroundBatchProcessor {
         pool: PgPool,
         config: BatchProcessorConfig,
         ads_service: Arc<RwLock<IndexedMerkleTreeADS>>,
+        events: EventBus,
     ) -> (Self, BatchProcessorHandle) {
         let (command_tx, command_rx) = mpsc::unbounded_channel();
         let stats = Arc::new(RwLock::new(BatchProcessorStats::default()));
@@ -172,6 +204,7 @@ impl BackgroundBatchProcessor {
             command_rx,
             stats: stats.clone(),
             ads_service,
+            events,
         };
 
         let handle = BatchProcessorHandle { command_tx, stats };
@@ -200,8 +233,9 @@ impl BackgroundBatchProcessor {
 
         // Start the continuous batch monitoring service
         let monitor_pool = self.pool.clone();
+        let monitor_events = self.events.clone();
         tokio::spawn(async move {
-            Self::run_batch_monitor_service(monitor_pool).await;
+            Self::run_batch_monitor_service(monitor_pool, monitor_events).await;
         });
 
         let mut timer = interval(Duration::from_secs(self.config.timer_interval_seconds));
@@ -352,8 +386,10 @@ impl BackgroundBatchProcessor {
                 self.update_stats(result.batch_id, result.transaction_count)
                     .await;
 
-                // Trigger proof generation asynchronously
-                self.trigger_proof_generation(result.batch_id);
+                self.events.publish(ApiEventKind::BatchCreated {
+                    batch_id: result.batch_id,
+                    transaction_count: result.transaction_count,
+                });
 
                 Ok(Some(result.batch_id))
             }
@@ -401,28 +437,54 @@ impl BackgroundBatchProcessor {
     }
 
     /// Continuous batch monitoring service that runs independently
-    async fn run_batch_monitor_service(pool: PgPool) {
+    ///
+    /// Proof requests and contract posts come from the outbox, so every
+    /// replica can run this loop: a job is claimed by one worker at a time.
+    async fn run_batch_monitor_service(pool: PgPool, events: EventBus) {
         info!("🔄 Starting continuous batch monitoring service...");
 
+        let settlement = match SettlementConfig::from_env() {
+            Ok(settlement) => Some(settlement),
+            Err(e) => {
+                error!("❌ Settlement configuration not found: {}", e);
+                error!("   Smart contract posting will be skipped");
+                None
+            }
+        };
+        let mut clients = HashMap::new();
+        for target in settlement.iter().flat_map(|s| &s.targets) {
+            match Self::connect_target(target).await {
+                Ok(client) => {
+                    clients.insert(target.name.clone(), client);
+                }
+                Err(e) => error!("❌ Failed to connect to {}: {}", target.name, e),
+            }
+        }
+
         let mut interval = tokio::time::interval(Duration::from_secs(30)); // Check every 30 seconds
         interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
         loop {
             interval.tick().await;
 
-            // Phase 1: Submit proofs for batches missing Sindri proof IDs
-            if let Err(e) = Self::submit_missing_proofs(&pool).await {
-                error!("❌ Failed to submit missing proofs: {}", e);
+            // Phase 1: Dispatch queued proof requests to Sindri
+            if let Err(e) = Self::dispatch_proof_requests(&pool, &events).await {
+                error!("❌ Failed to dispatch proof requests: {}", e);
             }
 
             // Phase 2: Update status for pending proofs
-            if let Err(e) = Self::update_proof_statuses(&pool).await {
+            if let Err(e) = Self::update_proof_statuses(&pool, &events).await {
                 error!("❌ Failed to update proof statuses: {}", e);
             }
 
             // Phase 3: Post proven batches to smart contract
-            if let Err(e) = Self::post_proven_batches_to_contract(&pool).await {
-                error!("❌ Failed to post proven batches to contract: {}", e);
+            if let Some(settlement) = &settlement {
+                if let Err(e) =
+                    Self::post_proven_batches_to_contract(&pool, &events, settlement, &mut clients)
+                        .await
+                {
+                    error!("❌ Failed to post proven batches to contract: {}", e);
+                }
             }
 
             // Small delay before next cycle
@@ -430,42 +492,38 @@ impl BackgroundBatchProcessor {
         }
     }
 
-    /// Phase 1: Submit proofs for batches missing Sindri proof IDs
-    async fn submit_missing_proofs(pool: &PgPool) -> Result<(), String> {
-        let batches_missing_proofs = sqlx::query!(
-            "SELECT id FROM proof_batches
-             WHERE sindri_proof_id IS NULL
-                OR sindri_proof_id = ''
-                OR sindri_proof_id LIKE 'failed_%'
-                OR sindri_proof_id LIKE 'error_%'
-             ORDER BY id ASC
-             LIMIT 5" // Process in small batches to avoid overwhelming
-        )
-        .fetch_all(pool)
-        .await
-        .map_err(|e| format!("Failed to query batches missing proof IDs: {}", e))?;
-
-        if batches_missing_proofs.is_empty() {
-            return Ok(()); // No work to do
-        }
-
-        info!(
-            "🔍 Found {} batches missing valid proof IDs, submitting...",
-            batches_missing_proofs.len()
-        );
-
-        for batch_record in batches_missing_proofs {
-            let batch_id = batch_record.id;
-
-            // Submit proof asynchronously and immediately store the proof ID
-            tokio::spawn({
-                let pool = pool.clone();
-                async move {
-                    if let Err(e) = Self::submit_proof_fast(&pool, batch_id).await {
-                        error!("❌ Failed to submit proof for batch {}: {}", batch_id, e);
+    /// Phase 1: Dispatch queued proof requests to Sindri
+    ///
+    /// Each job is claimed under a lease before Sindri is called, and the
+    /// outcome is recorded afterwards in its own transaction. A crash in
+    /// between leaves the job in flight until the lease runs out, and it is
+    /// then dispatched again.
+    async fn dispatch_proof_requests(pool: &PgPool, events: &EventBus) -> Result<(), String> {
+        for _ in 0..PROOF_REQUESTS_PER_CYCLE {
+            let Some(job) =
+                claim_outbox_jobs(pool, OUTBOX_PROOF_REQUEST, None, 1, PROOF_REQUEST_LEASE)
+                    .await
+                    .map_err(|e| format!("Failed to claim proof requests: {}", e))?
+                    .pop()
+            else {
+                return Ok(()); // No work to do
+            };
+
+            match Self::dispatch_proof_request(pool, &job).await {
+                Ok(Some(status)) => events.publish_proof_status(job.batch_id, status),
+                Ok(None) => {}
+                Err(e) => {
+                    error!(
+                        "❌ Failed to dispatch proof request for batch {}: {}",
+                        job.batch_id, e
+                    );
+                    if let Err(e) =
+                        retry_outbox_job(pool, &job, PROOF_RETRY_DELAY, Some(e.as_str())).await
+                    {
+                        error!("❌ Failed to reschedule job {}: {}", job.id, e);
                     }
                 }
-            });
+            }
 
             // Small delay to avoid overwhelming Sindri
             tokio::time::sleep(Duration::from_millis(200)).await;
@@ -474,71 +532,52 @@ impl BackgroundBatchProcessor {
         Ok(())
     }
 
-    /// Phase 2: Update status for batches with pending proofs
-    async fn update_proof_statuses(pool: &PgPool) -> Result<(), String> {
-        let pending_batches = sqlx::query!(
-            "SELECT id, sindri_proof_id FROM proof_batches
-             WHERE proof_status = 'pending'
-               AND sindri_proof_id IS NOT NULL
-               AND sindri_proof_id != ''
-               AND sindri_proof_id NOT LIKE 'failed_%'
-               AND sindri_proof_id NOT LIKE 'error_%'
-             ORDER BY id ASC
-             LIMIT 10" // Check statuses in small batches
-        )
-        .fetch_all(pool)
-        .await
-        .map_err(|e| format!("Failed to query pending batches: {}", e))?;
-
-        if pending_batches.is_empty() {
-            return Ok(()); // No pending proofs to check
-        }
-
+    /// Submit the proof request of a claimed job and record the outcome
+    ///
+    /// Returns the proof status written to the batch, `None` if the batch no
+    /// longer needs a proof.
+    async fn dispatch_proof_request(
+        pool: &PgPool,
+        job: &OutboxJob,
+    ) -> Result<Option<&'static str>, String> {
+        let batch_id = job.batch_id;
         info!(
-            "🔍 Checking status for {} pending proofs...",
-            pending_batches.len()
+            "🚀 Submitting proof request for batch {} (attempt {})",
+            batch_id, job.attempts
         );
 
-        for batch_record in pending_batches {
-            let batch_id = batch_record.id;
-            let proof_id = batch_record.sindri_proof_id.unwrap_or_default();
-
-            if proof_id.is_empty() {
-                continue;
-            }
-
-            // Check proof status asynchronously
-            tokio::spawn({
-                let pool = pool.clone();
-                let proof_id = proof_id.clone();
-                async move {
-                    if let Err(e) =
-                        Self::check_and_update_proof_status(&pool, batch_id, &proof_id).await
-                    {
-                        error!(
-                            "❌ Failed to check status for batch {} (proof {}): {}",
-                            batch_id, proof_id, e
-                        );
-                    }
-                }
-            });
-
-            // Small delay between status checks
-            tokio::time::sleep(Duration::from_millis(100)).await;
-        }
-
-        Ok(())
-    }
-
-    /// Fast proof submission that immediately stores the proof ID
-    async fn submit_proof_fast(pool: &PgPool, batch_id: i32) -> Result<(), String> {
-        info!("🚀 Fast proof submission for batch {}", batch_id);
-
         // Get batch details and transaction amounts
         let batch = get_batch_by_id(pool, batch_id)
             .await
             .map_err(|e| format!("Failed to get batch {}: {}", batch_id, e))?;
 
+        if batch.proof_status == "unwound" {
+            info!(
+                "⏭️ Batch {} was unwound, dropping its proof request",
+                batch_id
+            );
+            complete_outbox_job(pool, job)
+                .await
+                .map_err(|e| format!("Failed to complete job: {}", e))?;
+            return Ok(None);
+        }
+
+        // An earlier attempt already got a proof ID; submitting again would
+        // pay for a second proof
+        let has_proof_id = batch.sindri_proof_id.as_deref().is_some_and(|id| {
+            !id.is_empty() && !id.starts_with("failed_") && !id.starts_with("error_")
+        });
+        if has_proof_id {
+            info!(
+                "⏭️ Batch {} already has a Sindri proof, not submitting again",
+                batch_id
+            );
+            complete_outbox_job(pool, job)
+                .await
+                .map_err(|e| format!("Failed to complete job: {}", e))?;
+            return Ok(None);
+        }
+
         let transaction_amounts: Vec<i32> = sqlx::query!(
             "SELECT amount FROM incoming_transactions WHERE id = ANY($1) ORDER BY id",
             &batch.transaction_ids
@@ -551,6 +590,16 @@ impl BackgroundBatchProcessor {
         .collect();
 
         let initial_balance = batch.previous_counter_value as i32;
+        let expected_final = initial_balance + transaction_amounts.iter().sum::<i32>();
+        let actual_final = batch.final_counter_value as i32;
+
+        // Sanity check
+        if expected_final != actual_final {
+            return Err(format!(
+                "Batch {} transaction sum mismatch: expected {}, got {}",
+                batch_id, expected_final, actual_final
+            ));
+        }
 
         // Create proof request
         let proof_request = BatchProofGenerationRequest {
@@ -560,56 +609,115 @@ impl BackgroundBatchProcessor {
             generate_fixtures: false,
         };
 
-        // Submit to Sindri and immediately store the proof ID
-        match generate_batch_proof(proof_request).await {
+        // Submit to Sindri outside any transaction; the proof ID and the
+        // finished job commit together afterwards
+        let submission = generate_batch_proof(proof_request).await;
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+
+        let status = match submission {
             Ok(proof_response) => {
                 info!(
                     "✅ Got Sindri proof ID for batch {}: {}",
                     batch_id, proof_response.proof_id
                 );
 
-                // Immediately store the proof ID in database
                 let status = match proof_response.status.as_str() {
                     "Ready" => "proven",
                     "Failed" => "failed",
                     _ => "pending",
                 };
 
-                if let Err(e) =
-                    update_batch_proof(pool, batch_id, &proof_response.proof_id, status).await
-                {
-                    error!("❌ Failed to store proof ID for batch {}: {}", batch_id, e);
-                    return Err(format!("Failed to store proof ID: {}", e));
-                }
+                update_batch_proof_in(&mut tx, batch_id, &proof_response.proof_id, status)
+                    .await
+                    .map_err(|e| format!("Failed to store proof ID: {}", e))?;
+                complete_outbox_job(&mut *tx, job)
+                    .await
+                    .map_err(|e| format!("Failed to complete job: {}", e))?;
 
                 info!(
                     "📝 Stored proof ID {} for batch {} with status {}",
                     proof_response.proof_id, batch_id, status
                 );
-                Ok(())
+                status
             }
             Err(e) => {
                 error!("❌ Sindri submission failed for batch {}: {}", batch_id, e);
 
-                // Store error state
+                // Store error state and keep the job for a later attempt
                 let error_id = format!("error_{}", batch_id);
-                if let Err(update_err) =
-                    update_batch_proof(pool, batch_id, &error_id, "failed").await
-                {
-                    error!(
-                        "❌ Failed to store error state for batch {}: {}",
-                        batch_id, update_err
-                    );
-                }
+                update_batch_proof_in(&mut tx, batch_id, &error_id, "failed")
+                    .await
+                    .map_err(|e| format!("Failed to store error state: {}", e))?;
+                let reason = format!("Sindri submission failed: {}", e);
+                retry_outbox_job(&mut *tx, job, PROOF_RETRY_DELAY, Some(reason.as_str()))
+                    .await
+                    .map_err(|e| format!("Failed to reschedule job: {}", e))?;
+                "failed"
+            }
+        };
+
+        tx.commit()
+            .await
+            .map_err(|e| format!("Failed to commit proof request: {}", e))?;
+        Ok(Some(status))
+    }
+
+    /// Phase 2: Update status for batches with pending proofs
+    async fn update_proof_statuses(pool: &PgPool, events: &EventBus) -> Result<(), String> {
+        let pending_batches = sqlx::query!(
+            "SELECT id, sindri_proof_id FROM proof_batches
+             WHERE proof_status = 'pending'
+               AND sindri_proof_id IS NOT NULL
+               AND sindri_proof_id != ''
+               AND sindri_proof_id NOT LIKE 'failed_%'
+               AND sindri_proof_id NOT LIKE 'error_%'
+             ORDER BY id ASC
+             LIMIT 10" // Check statuses in small batches
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to query pending batches: {}", e))?;
+
+        if pending_batches.is_empty() {
+            return Ok(()); // No pending proofs to check
+        }
+
+        info!(
+            "🔍 Checking status for {} pending proofs...",
+            pending_batches.len()
+        );
 
-                Err(format!("Sindri submission failed: {}", e))
+        for batch_record in pending_batches {
+            let batch_id = batch_record.id;
+            let proof_id = batch_record.sindri_proof_id.unwrap_or_default();
+
+            if proof_id.is_empty() {
+                continue;
             }
+
+            if let Err(e) =
+                Self::check_and_update_proof_status(pool, events, batch_id, &proof_id).await
+            {
+                error!(
+                    "❌ Failed to check status for batch {} (proof {}): {}",
+                    batch_id, proof_id, e
+                );
+            }
+
+            // Small delay between status checks
+            tokio::time::sleep(Duration::from_millis(100)).await;
         }
+
+        Ok(())
     }
 
     /// Check proof status on Sindri and update database
     async fn check_and_update_proof_status(
         pool: &PgPool,
+        events: &EventBus,
         batch_id: i32,
         proof_id: &str,
     ) -> Result<(), String> {
@@ -630,6 +738,7 @@ impl BackgroundBatchProcessor {
                         return Err(format!("Failed to update status: {}", e));
                     }
                     info!("📝 Updated batch {} status to {}", batch_id, new_status);
+                    events.publish_proof_status(batch_id, new_status);
                 }
                 Ok(())
             }
@@ -640,229 +749,517 @@ impl BackgroundBatchProcessor {
         }
     }
 
-    /// Trigger proof generation for a batch asynchronously
-    fn trigger_proof_generation(&self, batch_id: i32) {
-        // The continuous monitoring service will pick up this batch automatically
-        // No need to do anything here - just log that the batch was created
-        info!(
-            "📦 Batch {} created - monitoring service will handle proof generation",
-            batch_id
-        );
-    }
+    /// Phase 3: Post proven batches to every settlement target
+    async fn post_proven_batches_to_contract(
+        pool: &PgPool,
+        events: &EventBus,
+        settlement: &SettlementConfig,
+        clients: &mut HashMap<String, TargetClient>,
+    ) -> Result<(), String> {
+        // Get proven batches that haven't been posted to contract yet
+        let unposted_batches = get_proven_unposted_batches(pool, Some(5))
+            .await
+            .map_err(|e| format!("Failed to get proven unposted batches: {}", e))?;
 
-    /// Generate a ZK proof for a specific batch
-    pub async fn generate_proof_for_batch(pool: &PgPool, batch_id: i32) -> Result<(), String> {
-        info!("🔐 Starting proof generation for batch: {}", batch_id);
+        if unposted_batches.is_empty() {
+            return Ok(()); // No work to do
+        }
 
-        // Get batch details
-        info!("📋 Fetching batch details for batch {}", batch_id);
-        let batch = get_batch_by_id(pool, batch_id).await.map_err(|e| {
-            error!("❌ Failed to get batch {}: {}", batch_id, e);
-            format!("Failed to get batch {}: {}", batch_id, e)
-        })?;
         info!(
-            "✅ Retrieved batch details: transaction_ids={:?}",
-            batch.transaction_ids
+            "🔗 Found {} proven batches to post to smart contract",
+            unposted_batches.len()
         );
 
-        // Get individual transaction amounts for the batch
-        info!("📋 Fetching transaction amounts for batch {}", batch_id);
-        let transaction_amounts: Vec<i32> = sqlx::query!(
-            "SELECT amount FROM incoming_transactions WHERE id = ANY($1) ORDER BY id",
-            &batch.transaction_ids
+        // Claim the contract post jobs under a lease; no other worker posts
+        // these batches until they are released or the lease runs out
+        let unposted_ids: Vec<i32> = unposted_batches.iter().map(|b| b.id).collect();
+        let jobs: HashMap<i32, OutboxJob> = claim_outbox_jobs(
+            pool,
+            OUTBOX_CONTRACT_POST,
+            Some(&unposted_ids),
+            unposted_ids.len() as i64,
+            CONTRACT_POST_LEASE,
         )
-        .fetch_all(pool)
         .await
-        .map_err(|e| {
-            error!(
-                "❌ Failed to get transaction amounts for batch {}: {}",
-                batch_id, e
-            );
-            format!("Failed to get transaction amounts: {}", e)
-        })?
+        .map_err(|e| format!("Failed to claim contract posts: {}", e))?
         .into_iter()
-        .map(|row| row.amount)
+        .map(|job| (job.batch_id, job))
         .collect();
-        info!(
-            "✅ Retrieved transaction amounts: {:?}",
-            transaction_amounts
-        );
-
-        let initial_balance = batch.previous_counter_value as i32;
-        let expected_final = initial_balance + transaction_amounts.iter().sum::<i32>();
-        let actual_final = batch.final_counter_value as i32;
-
-        info!(
-            "🧮 Batch {} calculation check: initial={}, sum={}, expected_final={}, actual_final={}",
-            batch_id,
-            initial_balance,
-            transaction_amounts.iter().sum::<i32>(),
-            expected_final,
-            actual_final
-        );
 
-        // Sanity check
-        if expected_final != actual_final {
-            error!(
-                "❌ Batch {} transaction sum mismatch: expected {}, got {}",
-                batch_id, expected_final, actual_final
-            );
-            return Err(format!(
-                "Batch {} transaction sum mismatch: expected {}, got {}",
-                batch_id, expected_final, actual_final
-            ));
+        let (claimed_batches, unclaimed_batches): (Vec<_>, Vec<_>) = unposted_batches
+            .into_iter()
+            .partition(|batch| jobs.contains_key(&batch.id));
+        if !unclaimed_batches.is_empty() {
+            if let Err(e) = Self::check_unclaimed_posts(pool, &unclaimed_batches).await {
+                error!("❌ Failed to check unclaimed contract posts: {}", e);
+            }
+        }
+        if claimed_batches.is_empty() {
+            return Ok(());
         }
 
-        info!(
-            "📊 Batch {} proof parameters: initial={}, transactions={:?}, final={}",
-            batch_id, initial_balance, transaction_amounts, actual_final
-        );
-
-        // Create batch proof generation request
-        info!(
-            "📝 Creating batch proof generation request for batch {}",
-            batch_id
-        );
-        let proof_request = BatchProofGenerationRequest {
-            initial_balance,
-            transactions: transaction_amounts.clone(),
-            proof_system: ProofSystem::default(), // Use Groth16 by default
-            generate_fixtures: false,             // Don't generate fixtures in production
-        };
-        info!(
-            "✅ Created proof request: initial={}, transactions={:?}, proof_system={:?}",
-            proof_request.initial_balance, proof_request.transactions, proof_request.proof_system
-        );
-
-        // Generate proof via Sindri
-        info!(
-            "🚀 Submitting batch proof request to Sindri for batch {}",
-            batch_id
-        );
-        match generate_batch_proof(proof_request).await {
-            Ok(proof_response) => {
-                info!(
-                    "✅ Proof submitted to Sindri for batch {}: proof_id={}",
-                    batch_id, proof_response.proof_id
-                );
-
-                // Update batch with Sindri proof ID and appropriate status
-                let status = match proof_response.status.as_str() {
-                    "Ready" => "proven",
-                    "Failed" => "failed",
-                    _ => "pending", // Default for "Pending" or other statuses
-                };
-
-                if let Err(e) =
-                    update_batch_proof(pool, batch_id, &proof_response.proof_id, status).await
-                {
-                    error!("Failed to update batch {} with proof ID: {}", batch_id, e);
-                    return Err(format!("Failed to update batch with proof ID: {}", e));
+        // Proofs are fetched from Sindri once and reused for every target
+        let mut prepared = HashMap::new();
+        for target in &settlement.targets {
+            // A target that could not be reached at startup is retried here
+            if !clients.contains_key(&target.name) {
+                match Self::connect_target(target).await {
+                    Ok(client) => {
+                        clients.insert(target.name.clone(), client);
+                    }
+                    Err(e) => {
+                        error!("❌ Failed to connect to {}: {}", target.name, e);
+                        continue;
+                    }
                 }
+            }
 
-                info!(
-                    "📝 Updated batch {} with Sindri proof ID: {} (status: {})",
-                    batch_id, proof_response.proof_id, status
-                );
+            let client = &clients[&target.name];
+            if let Err(e) = Self::settle_on_target(
+                pool,
+                client,
+                target,
+                &claimed_batches,
+                &unposted_ids,
+                &mut prepared,
+            )
+            .await
+            {
+                error!("❌ Settlement on {} failed: {}", target.name, e);
+            }
+        }
 
-                if status == "failed" {
-                    Err(format!(
-                        "Sindri proof generation failed for batch {}",
-                        batch_id
-                    ))
-                } else {
-                    Ok(())
+        // A batch counts as posted once every target has confirmed it
+        let batch_ids: Vec<i32> = claimed_batches.iter().map(|b| b.id).collect();
+        let submissions = get_batch_submissions(pool, &batch_ids)
+            .await
+            .map_err(|e| format!("Failed to get batch submissions: {}", e))?;
+
+        for batch in &claimed_batches {
+            let job = &jobs[&batch.id];
+            let confirmed_everywhere = settlement.targets.iter().all(|target| {
+                submissions.iter().any(|s| {
+                    s.batch_id == batch.id && s.target == target.name && s.status == "confirmed"
+                })
+            });
+
+            if confirmed_everywhere {
+                if Self::mark_posted(pool, batch.id, job).await {
+                    info!("✅ Successfully posted batch {} to contract", batch.id);
+                    events.publish(ApiEventKind::BatchPosted { batch_id: batch.id });
                 }
-            }
-            Err(e) => {
+            } else if let Err(e) = retry_outbox_job(pool, job, Duration::ZERO, None).await {
+                // Batches still waiting for confirmations are picked up next cycle
                 error!(
-                    "Failed to submit proof request to Sindri for batch {}: {}",
-                    batch_id, e
+                    "❌ Failed to release contract post of batch {}: {}",
+                    batch.id, e
                 );
+            }
+        }
 
-                // Only update status to failed, don't create a fake proof ID
-                // This handles cases where we couldn't even submit the request to Sindri
-                if let Err(update_err) = update_batch_proof(
-                    pool,
-                    batch_id,
-                    &format!("error_{}", batch_id), // Temporary ID to indicate submission error
-                    "failed",
-                )
-                .await
-                {
-                    error!(
-                        "Failed to update batch {} status to failed: {}",
-                        batch_id, update_err
+        Ok(())
+    }
+
+    /// Account for proven, unposted batches whose contract post was not claimed
+    ///
+    /// Another worker holding the job, or a job waiting out a retry delay, is
+    /// expected. A batch with no pending job at all would never be posted, so
+    /// its job is queued again.
+    async fn check_unclaimed_posts(
+        pool: &PgPool,
+        batches: &[arithmetic_db::ProofBatch],
+    ) -> Result<(), String> {
+        let batch_ids: Vec<i32> = batches.iter().map(|b| b.id).collect();
+        let jobs = get_outbox_jobs(pool, &batch_ids)
+            .await
+            .map_err(|e| format!("Failed to get outbox jobs: {}", e))?;
+
+        for batch_id in batch_ids {
+            let job = jobs
+                .iter()
+                .find(|job| job.batch_id == batch_id && job.kind == OUTBOX_CONTRACT_POST);
+            match job.map(|job| job.status.as_str()) {
+                Some("in_flight") => debug!(
+                    "Contract post of batch {} is held by another worker",
+                    batch_id
+                ),
+                Some("pending") => debug!(
+                    "Contract post of batch {} is waiting to be retried",
+                    batch_id
+                ),
+                status => {
+                    warn!(
+                        "⚠️ Batch {} is proven and unposted but its contract post is {}, queueing it again",
+                        batch_id,
+                        status.unwrap_or("missing")
                     );
+                    queue_outbox_job(pool, batch_id, OUTBOX_CONTRACT_POST)
+                        .await
+                        .map_err(|e| format!("Failed to queue contract post: {}", e))?;
                 }
-
-                Err(format!("Failed to submit proof request: {}", e))
             }
         }
+
+        Ok(())
     }
 
-    /// Phase 3: Post proven batches to smart contract
-    async fn post_proven_batches_to_contract(pool: &PgPool) -> Result<(), String> {
-        // Get proven batches that haven't been posted to contract yet
-        let unposted_batches = get_proven_unposted_batches(pool, Some(5))
+    /// Submit batches that are not yet live on `target` and confirm the ones
+    /// that are deep enough
+    ///
+    /// `unposted_ids` are all proven batches not yet posted everywhere,
+    /// including ones other workers are posting.
+    async fn settle_on_target(
+        pool: &PgPool,
+        client: &TargetClient,
+        target: &SettlementTarget,
+        batches: &[arithmetic_db::ProofBatch],
+        unposted_ids: &[i32],
+        prepared: &mut HashMap<i32, ContractUpdate>,
+    ) -> Result<(), String> {
+        let (eth_client, chain_id) = (&client.client, client.chain_id);
+
+        let batch_ids: Vec<i32> = batches.iter().map(|b| b.id).collect();
+        let mut live: HashSet<i32> = get_batch_submissions(pool, unposted_ids)
             .await
-            .map_err(|e| format!("Failed to get proven unposted batches: {}", e))?;
+            .map_err(|e| format!("Failed to get batch submissions: {}", e))?
+            .into_iter()
+            .filter(|s| {
+                s.target == target.name && matches!(s.status.as_str(), "submitted" | "confirmed")
+            })
+            .map(|s| s.batch_id)
+            .collect();
+
+        // A batch is only submitted once its parent is live on this target; a
+        // parent that is not unposted has already been posted everywhere. A
+        // batch the target rejected waits for the next cycle, and so do its
+        // children.
+        let mut attempted = HashSet::new();
+        loop {
+            let mut ready = Vec::new();
+            for batch in batches
+                .iter()
+                .filter(|b| !live.contains(&b.id) && !attempted.contains(&b.id))
+            {
+                let parent_live = batch.parent_batch_id.map_or(true, |parent| {
+                    live.contains(&parent) || !unposted_ids.contains(&parent)
+                });
+                if !parent_live {
+                    continue;
+                }
+                if !prepared.contains_key(&batch.id) {
+                    match Self::prepare_contract_update(batch).await {
+                        Ok(update) => {
+                            prepared.insert(batch.id, update);
+                        }
+                        Err(e) => {
+                            error!(
+                                "❌ Failed to prepare batch {} for contract: {}",
+                                batch.id, e
+                            );
+                            continue;
+                        }
+                    }
+                }
+                ready.push((batch.id, prepared[&batch.id].clone()));
+            }
 
-        if unposted_batches.is_empty() {
-            return Ok(()); // No work to do
+            if ready.is_empty() {
+                break;
+            }
+            attempted.extend(ready.iter().map(|(batch_id, _)| *batch_id));
+            let landed = Self::submit_to_target(pool, eth_client, target, chain_id, ready).await;
+            if landed.is_empty() {
+                break;
+            }
+            live.extend(landed);
         }
 
-        info!(
-            "🔗 Found {} proven batches to post to smart contract",
-            unposted_batches.len()
-        );
+        // Promote mined submissions once they are buried deep enough
+        let submitted = get_batch_submissions(pool, &batch_ids)
+            .await
+            .map_err(|e| format!("Failed to get batch submissions: {}", e))?
+            .into_iter()
+            .filter(|s| s.target == target.name && s.status == "submitted");
+
+        for submission in submitted {
+            let Some(tx_hash) = submission
+                .transaction_hash
+                .as_deref()
+                .and_then(|h| h.parse::<FixedBytes<32>>().ok())
+            else {
+                continue;
+            };
 
-        // Try to initialize Ethereum client (graceful fallback if not configured)
-        let eth_client = match EthConfig::from_env() {
-            Ok(config) => match EthereumClient::new(config).await {
-                Ok(client) => Some(client),
-                Err(e) => {
-                    error!("❌ Failed to initialize Ethereum client: {}", e);
-                    error!("   Smart contract posting will be skipped");
-                    return Ok(());
+            match eth_client.confirmation_depth(tx_hash).await {
+                Ok(Some(depth)) if depth >= target.confirmations => {
+                    info!(
+                        "✅ Batch {} confirmed on {} ({} blocks deep)",
+                        submission.batch_id, target.name, depth
+                    );
+                    Self::record_submission(
+                        pool,
+                        BatchSubmissionUpdate {
+                            status: "confirmed".to_string(),
+                            ..Self::submission_update(&submission)
+                        },
+                    )
+                    .await;
                 }
-            },
-            Err(e) => {
-                error!("❌ Ethereum configuration not found: {}", e);
-                error!("   Smart contract posting will be skipped");
-                return Ok(());
+                Ok(Some(_)) => {}
+                // Sent but never seen mined, e.g. after a replacement timeout
+                Ok(None) if submission.block_number.is_none() => debug!(
+                    "Submission of batch {} on {} not mined yet",
+                    submission.batch_id, target.name
+                ),
+                Ok(None) => {
+                    warn!(
+                        "⚠️ Submission of batch {} on {} disappeared (reorg?), will resubmit",
+                        submission.batch_id, target.name
+                    );
+                    Self::record_submission(
+                        pool,
+                        BatchSubmissionUpdate {
+                            status: "pending".to_string(),
+                            transaction_hash: None,
+                            block_number: None,
+                            ..Self::submission_update(&submission)
+                        },
+                    )
+                    .await;
+                }
+                Err(e) => warn!(
+                    "⚠️ Failed to check confirmations for batch {} on {}: {}",
+                    submission.batch_id, target.name, e
+                ),
             }
+        }
+
+        Ok(())
+    }
+
+    /// Post `ready` batches to one target and record what landed
+    ///
+    /// The parent of every batch in `ready` is already live on the target, and
+    /// the contract judges each item on its own, so one rejected batch does not
+    /// hold back the others; its children wait for it in `settle_on_target`.
+    /// Returns the ids of the batches that were submitted.
+    async fn submit_to_target(
+        pool: &PgPool,
+        eth_client: &EthereumClient,
+        target: &SettlementTarget,
+        chain_id: i64,
+        ready: Vec<(i32, ContractUpdate)>,
+    ) -> Vec<i32> {
+        let new_update = |batch_id: i32, status: &str| BatchSubmissionUpdate {
+            batch_id,
+            target: target.name.clone(),
+            chain_id,
+            status: status.to_string(),
+            transaction_hash: None,
+            block_number: None,
+            error: None,
         };
+        let mut landed = Vec::new();
 
-        let eth_client = eth_client.unwrap();
+        let updates = ready.iter().map(|(_, update)| update.clone()).collect();
+        match eth_client.batch_update_states(updates).await {
+            Ok(result) => {
+                info!(
+                    "🔗 Batch update {} on {} landed {} of {} batches",
+                    result.transaction_hash,
+                    target.name,
+                    result.success_flags.iter().filter(|ok| **ok).count(),
+                    ready.len()
+                );
 
-        for batch in unposted_batches {
-            if let Err(e) = Self::submit_batch_to_contract(pool, &eth_client, &batch).await {
-                error!("❌ Failed to submit batch {} to contract: {}", batch.id, e);
-                continue; // Continue with next batch
+                // Only batches whose proof the contract accepted count as submitted
+                for ((batch_id, _), accepted) in ready.iter().zip(&result.success_flags) {
+                    let update = if *accepted {
+                        landed.push(*batch_id);
+                        BatchSubmissionUpdate {
+                            transaction_hash: Some(result.transaction_hash.to_string()),
+                            block_number: i64::try_from(result.block_number).ok(),
+                            ..new_update(*batch_id, "submitted")
+                        }
+                    } else {
+                        error!(
+                            "❌ Contract on {} rejected the proof for batch {} in tx {}",
+                            target.name, batch_id, result.transaction_hash
+                        );
+                        BatchSubmissionUpdate {
+                            error: Some("Contract rejected the proof".to_string()),
+                            ..new_update(*batch_id, "failed")
+                        }
+                    };
+                    Self::record_submission(pool, update).await;
+                }
+            }
+            Err(EthereumError::TransactionTimeout { tx_hash, message }) => {
+                // The call may still be mined, so the batches wait for it instead
+                // of being sent again
+                warn!(
+                    "⚠️ Batch update on {} not mined yet ({}), waiting for {}",
+                    target.name, message, tx_hash
+                );
+                for (batch_id, _) in ready {
+                    Self::record_submission(
+                        pool,
+                        BatchSubmissionUpdate {
+                            transaction_hash: Some(tx_hash.to_string()),
+                            ..new_update(batch_id, "submitted")
+                        },
+                    )
+                    .await;
+                    landed.push(batch_id);
+                }
+            }
+            Err(e) if !e.is_revert() => {
+                // Nothing reached the contract (spend limit, RPC trouble), so the
+                // batches stay pending and are tried again next cycle
+                error!(
+                    "❌ Batch contract update on {} failed, {} batches left pending: {}",
+                    target.name,
+                    ready.len(),
+                    e
+                );
             }
+            Err(e) => {
+                // A single bad item (e.g. an already stored proof) reverts the whole
+                // batch call, so fall back to posting batches one at a time
+                warn!(
+                    "⚠️ Batch contract update on {} reverted ({}), posting {} batches individually",
+                    target.name,
+                    e,
+                    ready.len()
+                );
+
+                for (batch_id, (state_id, new_state_root, proof, public_values)) in ready {
+                    match eth_client
+                        .update_state(state_id, new_state_root, proof, public_values)
+                        .await
+                    {
+                        Ok(result) => {
+                            Self::record_submission(
+                                pool,
+                                BatchSubmissionUpdate {
+                                    transaction_hash: result
+                                        .transaction_hash
+                                        .map(|h| h.to_string()),
+                                    block_number: result
+                                        .block_number
+                                        .and_then(|b| i64::try_from(b).ok()),
+                                    ..new_update(batch_id, "submitted")
+                                },
+                            )
+                            .await;
+                            landed.push(batch_id);
+                        }
+                        Err(EthereumError::TransactionTimeout { tx_hash, message }) => {
+                            warn!(
+                                "⚠️ Batch {} on {} not mined yet ({}), waiting for {}",
+                                batch_id, target.name, message, tx_hash
+                            );
+                            Self::record_submission(
+                                pool,
+                                BatchSubmissionUpdate {
+                                    transaction_hash: Some(tx_hash.to_string()),
+                                    ..new_update(batch_id, "submitted")
+                                },
+                            )
+                            .await;
+                            landed.push(batch_id);
+                            break;
+                        }
+                        Err(e) if !e.is_revert() => {
+                            error!(
+                                "❌ Failed to submit batch {} to {}, left pending: {}",
+                                batch_id, target.name, e
+                            );
+                            break;
+                        }
+                        Err(e) => {
+                            error!(
+                                "❌ Failed to submit batch {} to {}: {}",
+                                batch_id, target.name, e
+                            );
+                            Self::record_submission(
+                                pool,
+                                BatchSubmissionUpdate {
+                                    error: Some(e.to_string()),
+                                    ..new_update(batch_id, "failed")
+                                },
+                            )
+                            .await;
+                        }
+                    }
 
-            // Mark as posted after successful submission
-            if let Err(e) = mark_batch_posted_to_contract(pool, batch.id).await {
-                error!("❌ Failed to mark batch {} as posted: {}", batch.id, e);
-            } else {
-                info!("✅ Successfully posted batch {} to contract", batch.id);
+                    // Small delay between submissions to avoid overwhelming the network
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                }
             }
+        }
 
-            // Small delay between submissions to avoid overwhelming the network
-            tokio::time::sleep(Duration::from_millis(500)).await;
+        landed
+    }
+
+    /// Connect to a settlement target's contract
+    async fn connect_target(target: &SettlementTarget) -> Result<TargetClient, String> {
+        let config = target
+            .to_config()
+            .map_err(|e| format!("Invalid configuration: {}", e))?;
+        let chain_id = i64::try_from(config.network.chain_id)
+            .map_err(|_| format!("Chain id {} out of range", config.network.chain_id))?;
+        let client = EthereumClient::new(config)
+            .await
+            .map_err(|e| format!("Failed to initialize Ethereum client: {}", e))?;
+
+        Ok(TargetClient { client, chain_id })
+    }
+
+    /// Update carrying over every field of an existing submission
+    fn submission_update(submission: &BatchSubmission) -> BatchSubmissionUpdate {
+        BatchSubmissionUpdate {
+            batch_id: submission.batch_id,
+            target: submission.target.clone(),
+            chain_id: submission.chain_id,
+            status: submission.status.clone(),
+            transaction_hash: submission.transaction_hash.clone(),
+            block_number: submission.block_number,
+            error: submission.error.clone(),
         }
+    }
 
-        Ok(())
+    async fn record_submission(pool: &PgPool, update: BatchSubmissionUpdate) {
+        if let Err(e) = record_batch_submission(pool, &update).await {
+            error!(
+                "❌ Failed to record {} status for batch {} on {}: {}",
+                update.status, update.batch_id, update.target, e
+            );
+        }
     }
 
-    /// Submit a single batch to the smart contract
-    async fn submit_batch_to_contract(
-        _pool: &PgPool,
-        eth_client: &EthereumClient,
+    /// Mark a batch as posted and finish its contract post job in one transaction
+    async fn mark_posted(pool: &PgPool, batch_id: i32, job: &OutboxJob) -> bool {
+        let result: Result<(), sqlx::Error> = async {
+            let mut tx = pool.begin().await?;
+            mark_batch_posted_to_contract(&mut *tx, batch_id).await?;
+            complete_outbox_job(&mut *tx, job).await?;
+            tx.commit().await
+        }
+        .await;
+
+        if let Err(e) = result {
+            error!("❌ Failed to mark batch {} as posted: {}", batch_id, e);
+            return false;
+        }
+        true
+    }
+
+    /// Build the `(state_id, new_state_root, proof, public_values)` contract update for a batch
+    async fn prepare_contract_update(
         batch: &arithmetic_db::ProofBatch,
-    ) -> Result<(), String> {
-        info!("🚀 Submitting batch {} to smart contract", batch.id);
+    ) -> Result<ContractUpdate, String> {
+        info!("🚀 Preparing batch {} for smart contract", batch.id);
 
         // Get the Sindri proof ID from the batch
         let sindri_proof_id = batch
@@ -891,14 +1288,8 @@ impl BackgroundBatchProcessor {
 
         // For now, use a random 32-byte hash for state management
         // until the ADS state root issue is fixed
-        let state_id = FixedBytes::from_slice(
-            &alloy_primitives::keccak256(format!("batch_{}", batch.id).as_bytes())[..32],
-        );
-        let new_state_root = FixedBytes::from_slice(
-            &alloy_primitives::keccak256(
-                format!("state_root_{}", batch.final_counter_value).as_bytes(),
-            )[..32],
-        );
+        let state_id = batch_state_id(batch.id);
+        let new_state_root = batch_state_root(batch.final_counter_value);
 
         // Use real proof data from Sindri
         let proof_bytes = Bytes::from(proof_data.proof_bytes);
@@ -926,31 +1317,12 @@ impl BackgroundBatchProcessor {
             }
         }
 
-        info!("🔐 Submitting real SP1 proof to smart contract");
+        info!("🔐 Prepared real SP1 proof for smart contract");
         info!("   State ID: {}", state_id);
         info!("   New state root: {}", new_state_root);
+        info!("   Used Sindri proof ID: {}", sindri_proof_id);
 
-        // Submit to smart contract
-        match eth_client
-            .update_state(state_id, new_state_root, proof_bytes, public_values)
-            .await
-        {
-            Ok(result) => {
-                info!(
-                    "✅ Batch {} submitted to contract successfully with REAL proof data!",
-                    batch.id
-                );
-                info!("   Transaction hash: {:?}", result.transaction_hash);
-                info!("   State ID: {}", result.state_id);
-                info!("   New state root: {}", result.new_state_root);
-                info!("   Used Sindri proof ID: {}", sindri_proof_id);
-                Ok(())
-            }
-            Err(e) => {
-                error!("❌ Failed to submit batch {} to contract: {}", batch.id, e);
-                Err(format!("Smart contract submission failed: {}", e))
-            }
-        }
+        Ok((state_id, new_state_root, proof_bytes, public_values))
     }
 }
 
@@ -963,8 +1335,9 @@ pub async fn start_batch_processor(
     pool: PgPool,
     config: BatchProcessorConfig,
     ads_service: Arc<RwLock<IndexedMerkleTreeADS>>,
+    events: EventBus,
 ) -> BatchProcessorHandle {
-    let (processor, handle) = BackgroundBatchProcessor::new(pool, config, ads_service);
+    let (processor, handle) = BackgroundBatchProcessor::new(pool, config, ads_service, events);
 
     // Spawn the processor in the background
     tokio::spawn(async move {
@@ -8,7 +8,7 @@
 //! cd api && cargo run --bin server
 //! ```
 
-use api::{ApiConfig, ApiServer, ApiServerConfig};
+use api::{ApiConfig, ApiServer, ApiServerConfig, ValidationConfig};
 use arithmetic_db::init_db;
 use clap::Parser;
 use tracing::{error, info};
@@ -33,6 +33,10 @@ struct Args {
     #[arg(long, default_value = "50", value_parser = clap::value_parser!(u32))]
     max_batch_size: u32,
 
+    /// Maximum number of transactions accepted in one bulk submission
+    #[arg(long, default_value = "1000")]
+    max_bulk_items: usize,
+
     /// Maximum request size in bytes
     #[arg(long, default_value = "1048576")]
     max_request_size: usize,
@@ -92,6 +96,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         version: "2.0.0".to_string(),
         max_batch_size: args.max_batch_size,
         enable_debug_endpoints: args.debug,
+        validation: ValidationConfig {
+            max_batch_size: args.max_bulk_items,
+            ..ValidationConfig::default()
+        },
     };
 
     // Create server configuration
@@ -146,6 +154,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     println!();
     println!("📚 Available Endpoints:");
     println!("   • POST   /api/v2/transactions           - Submit new transaction");
+    println!("   • POST   /api/v2/transactions/bulk      - Submit many transactions");
     println!("   • GET    /api/v2/transactions/pending   - View pending transactions");
     println!("   • POST   /api/v2/batches                - Create batch from pending");
     println!("   • GET    /api/v2/batches                - List historical batches");
@@ -156,6 +165,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     println!("   • GET    /api/v2/state/current          - Get current counter state");
     println!("   • GET    /api/v2/state/{{id}}/contract     - Get contract submission data");
     println!("   • GET    /api/v2/health                 - Health check");
+    println!("   • GET    /api/v2/openapi.json           - OpenAPI document");
+    println!("   • GET    /api/v2/docs                   - API documentation (Redoc)");
     println!("   • GET    /health                        - Health check (legacy path)");
 
     println!();
@@ -0,0 +1,176 @@
+//! Typed errors returned by `BatchApiClient`
+
+use std::fmt;
+use std::time::Duration;
+
+use thiserror::Error;
+
+/// Error codes reported by the API server
+///
+/// JSON error bodies carry an `error` code (for example `RATE_LIMIT_EXCEEDED`);
+/// plain-text error bodies are classified by HTTP status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiErrorCode {
+    BadRequest,
+    InvalidContentType,
+    MissingContentType,
+    MissingRequiredHeader,
+    InvalidNullifierValue,
+    InvalidNullifierFormat,
+    AuthenticationRequired,
+    Forbidden,
+    NotFound,
+    Conflict,
+    PayloadTooLarge,
+    Unprocessable,
+    RateLimitExceeded,
+    Internal,
+    ServiceUnavailable,
+    /// A code this client version does not know about
+    Other(String),
+}
+
+impl ApiErrorCode {
+    /// Parse the `error` field of a JSON error body
+    pub fn from_code(code: &str) -> Self {
+        match code {
+            "BAD_REQUEST" => Self::BadRequest,
+            "INVALID_CONTENT_TYPE" => Self::InvalidContentType,
+            "MISSING_CONTENT_TYPE" => Self::MissingContentType,
+            "MISSING_REQUIRED_HEADER" => Self::MissingRequiredHeader,
+            "INVALID_NULLIFIER_VALUE" => Self::InvalidNullifierValue,
+            "INVALID_NULLIFIER_FORMAT" => Self::InvalidNullifierFormat,
+            "AUTHENTICATION_REQUIRED" => Self::AuthenticationRequired,
+            "FORBIDDEN" => Self::Forbidden,
+            "NOT_FOUND" => Self::NotFound,
+            "CONFLICT" => Self::Conflict,
+            "PAYLOAD_TOO_LARGE" => Self::PayloadTooLarge,
+            "UNPROCESSABLE_ENTITY" => Self::Unprocessable,
+            "RATE_LIMIT_EXCEEDED" => Self::RateLimitExceeded,
+            "INTERNAL_ERROR" => Self::Internal,
+            "SERVICE_UNAVAILABLE" => Self::ServiceUnavailable,
+            other => Self::Other(other.to_string()),
+        }
+    }
+
+    /// Classify an error response that carries no code
+    pub fn from_status(status: u16) -> Self {
+        match status {
+            400 => Self::BadRequest,
+            401 => Self::AuthenticationRequired,
+            403 => Self::Forbidden,
+            404 => Self::NotFound,
+            409 => Self::Conflict,
+            413 => Self::PayloadTooLarge,
+            422 => Self::Unprocessable,
+            429 => Self::RateLimitExceeded,
+            503 => Self::ServiceUnavailable,
+            500..=599 => Self::Internal,
+            other => Self::Other(format!("HTTP_{other}")),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::BadRequest => "BAD_REQUEST",
+            Self::InvalidContentType => "INVALID_CONTENT_TYPE",
+            Self::MissingContentType => "MISSING_CONTENT_TYPE",
+            Self::MissingRequiredHeader => "MISSING_REQUIRED_HEADER",
+            Self::InvalidNullifierValue => "INVALID_NULLIFIER_VALUE",
+            Self::InvalidNullifierFormat => "INVALID_NULLIFIER_FORMAT",
+            Self::AuthenticationRequired => "AUTHENTICATION_REQUIRED",
+            Self::Forbidden => "FORBIDDEN",
+            Self::NotFound => "NOT_FOUND",
+            Self::Conflict => "CONFLICT",
+            Self::PayloadTooLarge => "PAYLOAD_TOO_LARGE",
+            Self::Unprocessable => "UNPROCESSABLE_ENTITY",
+            Self::RateLimitExceeded => "RATE_LIMIT_EXCEEDED",
+            Self::Internal => "INTERNAL_ERROR",
+            Self::ServiceUnavailable => "SERVICE_UNAVAILABLE",
+            Self::Other(code) => code,
+        }
+    }
+
+    /// Whether the same request may succeed if sent again later
+    pub const fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::RateLimitExceeded | Self::Internal | Self::ServiceUnavailable
+        )
+    }
+}
+
+impl fmt::Display for ApiErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// API client errors
+#[derive(Error, Debug)]
+pub enum ApiClientError {
+    #[error("HTTP request failed: {0}")]
+    RequestFailed(#[from] reqwest::Error),
+
+    #[error("API returned error: {status} {code} - {message}")]
+    ApiError {
+        status: u16,
+        code: ApiErrorCode,
+        message: String,
+        /// Server-suggested delay before retrying (`Retry-After`)
+        retry_after: Option<Duration>,
+    },
+
+    #[error("Failed to serialize/deserialize: {0}")]
+    SerializationError(#[from] serde_json::Error),
+
+    #[error("Invalid client configuration: {0}")]
+    InvalidConfig(String),
+
+    #[error("Timed out after {waited:?} waiting for batch {batch_id} to be {target}")]
+    WaitTimeout {
+        batch_id: i32,
+        target: &'static str,
+        waited: Duration,
+    },
+
+    #[error("Batch {batch_id} proof generation failed")]
+    BatchFailed { batch_id: i32 },
+
+    #[error("Event stream error: {0}")]
+    EventStream(String),
+
+    #[error("Invalid proof bundle: {0}")]
+    InvalidBundle(#[from] arithmetic_lib::bundle::BundleError),
+}
+
+impl ApiClientError {
+    /// Error code for API errors
+    pub const fn code(&self) -> Option<&ApiErrorCode> {
+        match self {
+            Self::ApiError { code, .. } => Some(code),
+            _ => None,
+        }
+    }
+
+    /// HTTP status for API errors
+    pub const fn status(&self) -> Option<u16> {
+        match self {
+            Self::ApiError { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        self.code() == Some(&ApiErrorCode::NotFound)
+    }
+
+    /// Whether the failure is transient (transport error, 429 or 5xx)
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::RequestFailed(e) => e.is_timeout() || e.is_connect() || e.is_request(),
+            Self::ApiError { code, .. } => code.is_transient(),
+            _ => false,
+        }
+    }
+}
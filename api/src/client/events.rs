@@ -0,0 +1,112 @@
+//! Client side of the `/api/v2/events` server-sent event feed
+
+use std::pin::Pin;
+
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::Deserialize;
+
+use super::error::ApiClientError;
+
+/// A batch lifecycle change reported by the server
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ApiEventKind {
+    TransactionSubmitted {
+        transaction_id: i32,
+        amount: i32,
+    },
+    BatchCreated {
+        batch_id: i32,
+        transaction_count: usize,
+    },
+    BatchProven {
+        batch_id: i32,
+    },
+    BatchFailed {
+        batch_id: i32,
+    },
+    BatchPosted {
+        batch_id: i32,
+    },
+    /// The connection fell behind and the server dropped this many events
+    Lagged {
+        skipped: u64,
+    },
+}
+
+/// Event received from the feed
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ApiEvent {
+    #[serde(flatten)]
+    pub kind: ApiEventKind,
+    /// RFC 3339 timestamp; empty for client-synthesized events
+    #[serde(default)]
+    pub timestamp: String,
+}
+
+/// Stream of events returned by `BatchApiClient::events`
+pub type EventStream = Pin<Box<dyn Stream<Item = Result<ApiEvent, ApiClientError>> + Send>>;
+
+/// Turn an SSE response body into a stream of events
+pub(super) fn event_stream(response: reqwest::Response) -> EventStream {
+    let bytes = response.bytes_stream().boxed();
+    let state = (bytes, String::new());
+
+    stream::unfold(state, |(mut bytes, mut buffer)| async move {
+        loop {
+            // Frames are separated by a blank line
+            if let Some(end) = buffer.find("\n\n") {
+                let frame: String = buffer.drain(..end + 2).collect();
+                match parse_frame(&frame) {
+                    Some(item) => return Some((item, (bytes, buffer))),
+                    None => continue,
+                }
+            }
+
+            match bytes.next().await {
+                Some(Ok(chunk)) => {
+                    buffer.push_str(&String::from_utf8_lossy(&chunk).replace("\r\n", "\n"))
+                }
+                Some(Err(e)) => {
+                    return Some((Err(ApiClientError::RequestFailed(e)), (bytes, buffer)))
+                }
+                None => return None,
+            }
+        }
+    })
+    .boxed()
+}
+
+/// Parse one SSE frame; keep-alive comments yield `None`
+fn parse_frame(frame: &str) -> Option<Result<ApiEvent, ApiClientError>> {
+    let mut name = None;
+    let mut data = String::new();
+    for line in frame.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            name = Some(value.trim());
+        } else if let Some(value) = line.strip_prefix("data:") {
+            if !data.is_empty() {
+                data.push('\n');
+            }
+            data.push_str(value.strip_prefix(' ').unwrap_or(value));
+        }
+    }
+
+    if data.is_empty() {
+        return None;
+    }
+
+    if name == Some("lagged") {
+        return Some(
+            data.trim()
+                .parse()
+                .map(|skipped| ApiEvent {
+                    kind: ApiEventKind::Lagged { skipped },
+                    timestamp: String::new(),
+                })
+                .map_err(|_| ApiClientError::EventStream(format!("bad lagged frame: {data}"))),
+        );
+    }
+
+    Some(serde_json::from_str(&data).map_err(ApiClientError::from))
+}
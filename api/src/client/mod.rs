@@ -2,30 +2,123 @@
 //!
 //! This client provides a typed interface for interacting with the batch processing API server.
 //! The CLI uses this instead of direct database access.
+//!
+//! Use `BatchApiClient::builder` to set credentials, timeouts and the retry
+//! policy; `BatchApiClient::new` uses the defaults. Reads and keyed submissions
+//! are retried on transport errors, 429 and 5xx responses; other writes are sent
+//! once.
+
+mod error;
+mod events;
+mod retry;
+
+pub use error::{ApiClientError, ApiErrorCode};
+pub use events::{ApiEvent, ApiEventKind, EventStream};
+pub use retry::RetryPolicy;
 
-use reqwest::{Client, Response};
+use arithmetic_lib::bundle::ProofBundle;
+use arithmetic_lib::receipt::TransactionReceipt;
+use futures_util::StreamExt;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, RETRY_AFTER};
+use reqwest::{Client, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
-use thiserror::Error;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Header carrying the idempotency key for transaction submission
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Header carrying the API key
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// Default per-request timeout
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default TCP connect timeout
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often the `wait_for_*` helpers re-read the batch when no event arrives
+const WAIT_POLL_INTERVAL: Duration = Duration::from_secs(5);
 
 /// Client for interacting with the batch processing API
 #[derive(Debug, Clone)]
 pub struct BatchApiClient {
     client: Client,
     base_url: String,
+    timeout: Duration,
+    retry: RetryPolicy,
+}
+
+/// Builder for `BatchApiClient`
+#[derive(Debug, Clone)]
+pub struct BatchApiClientBuilder {
+    base_url: String,
+    api_key: Option<String>,
+    bearer_token: Option<String>,
+    timeout: Duration,
+    connect_timeout: Duration,
+    retry: RetryPolicy,
 }
 
-/// API client errors
-#[derive(Error, Debug)]
-pub enum ApiClientError {
-    #[error("HTTP request failed: {0}")]
-    RequestFailed(#[from] reqwest::Error),
+impl BatchApiClientBuilder {
+    /// Send `x-api-key` with every request
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Send `Authorization: Bearer <token>` (JWT) with every request
+    pub fn bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.bearer_token = Some(token.into());
+        self
+    }
+
+    /// Per-request timeout (does not apply to the event stream)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    pub fn retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
 
-    #[error("API returned error: {status} - {message}")]
-    ApiError { status: u16, message: String },
+    pub fn build(self) -> Result<BatchApiClient, ApiClientError> {
+        let mut headers = HeaderMap::new();
+        if let Some(api_key) = &self.api_key {
+            headers.insert(API_KEY_HEADER, sensitive_header(api_key)?);
+        }
+        if let Some(token) = &self.bearer_token {
+            headers.insert(AUTHORIZATION, sensitive_header(&format!("Bearer {token}"))?);
+        }
 
-    #[error("Failed to serialize/deserialize: {0}")]
-    SerializationError(#[from] serde_json::Error),
+        let client = Client::builder()
+            .default_headers(headers)
+            .connect_timeout(self.connect_timeout)
+            .build()?;
+
+        Ok(BatchApiClient {
+            client,
+            base_url: self.base_url.trim_end_matches('/').to_string(),
+            timeout: self.timeout,
+            retry: self.retry,
+        })
+    }
+}
+
+fn sensitive_header(value: &str) -> Result<HeaderValue, ApiClientError> {
+    let mut value = HeaderValue::from_str(value).map_err(|_| {
+        ApiClientError::InvalidConfig("credentials must be visible ASCII".to_string())
+    })?;
+    value.set_sensitive(true);
+    Ok(value)
 }
 
 // ============================================================================
@@ -36,6 +129,8 @@ pub enum ApiClientError {
 #[derive(Debug, Serialize)]
 pub struct SubmitTransactionRequest {
     pub amount: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_tx_id: Option<String>,
 }
 
 /// Response from transaction submission
@@ -45,6 +140,41 @@ pub struct SubmitTransactionResponse {
     pub amount: i32,
     pub status: String,
     pub created_at: String,
+    #[serde(default)]
+    pub client_tx_id: Option<String>,
+}
+
+/// One transaction in a bulk submission
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkTransactionItem {
+    pub amount: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_tx_id: Option<String>,
+}
+
+/// Outcome of one item in a bulk submission
+#[derive(Debug, Deserialize)]
+pub struct BulkItemResult {
+    pub index: usize,
+    pub transaction_id: Option<i32>,
+    #[serde(default)]
+    pub client_tx_id: Option<String>,
+    #[serde(default)]
+    pub replayed: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Response from bulk transaction submission
+#[derive(Debug, Deserialize)]
+pub struct BulkSubmitResponse {
+    /// "atomic" or "best_effort"
+    pub mode: String,
+    pub committed: bool,
+    pub submitted: usize,
+    pub replayed: usize,
+    pub failed: usize,
+    pub results: Vec<BulkItemResult>,
 }
 
 /// Request to create a batch
@@ -77,14 +207,72 @@ pub struct PendingTransactionsResponse {
 pub struct TransactionInfo {
     pub id: i32,
     pub amount: i32,
+    #[serde(default)]
+    pub included_in_batch_id: Option<i32>,
     pub created_at: String,
 }
 
+/// Response for transaction listing
+#[derive(Debug, Deserialize)]
+pub struct TransactionListResponse {
+    pub transactions: Vec<TransactionInfo>,
+    pub total_count: usize,
+    #[serde(default)]
+    pub next_cursor: Option<String>,
+}
+
 /// Response for batch listing
 #[derive(Debug, Deserialize)]
 pub struct BatchListResponse {
     pub batches: Vec<BatchInfo>,
     pub total_count: usize,
+    #[serde(default)]
+    pub next_cursor: Option<String>,
+}
+
+/// Filters and paging for `GET /api/v2/batches`
+///
+/// Timestamps are RFC 3339 strings; `order` is "asc" or "desc".
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BatchListParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<String>,
+}
+
+/// Filters and paging for `GET /api/v2/transactions`
+///
+/// Timestamps are RFC 3339 strings; `status` is "pending" or "batched";
+/// `order` is "asc" or "desc".
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TransactionListParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_amount: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_amount: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<String>,
 }
 
 /// Batch information
@@ -98,6 +286,26 @@ pub struct BatchInfo {
     pub sindri_proof_id: Option<String>,
     pub created_at: String,
     pub proven_at: Option<String>,
+    #[serde(default)]
+    pub posted_to_contract_at: Option<String>,
+    /// Batch this batch builds on, absent for the first batch
+    #[serde(default)]
+    pub parent_batch_id: Option<i32>,
+    /// Settlement status on each target network
+    #[serde(default)]
+    pub settlements: Vec<SettlementInfo>,
+}
+
+/// Settlement status of a batch on one target network
+#[derive(Debug, Deserialize)]
+pub struct SettlementInfo {
+    pub target: String,
+    pub chain_id: i64,
+    pub status: String,
+    pub transaction_hash: Option<String>,
+    pub block_number: Option<i64>,
+    pub error: Option<String>,
+    pub updated_at: String,
 }
 
 /// Response for current state
@@ -107,6 +315,11 @@ pub struct CurrentStateResponse {
     pub has_merkle_root: bool,
     pub last_batch_id: Option<i32>,
     pub last_proven_batch_id: Option<i32>,
+    /// Hex-encoded root of the live IMT
+    #[serde(default)]
+    pub imt_root: Option<String>,
+    #[serde(default)]
+    pub nullifier_count: Option<u64>,
 }
 
 /// Request to update batch with proof
@@ -144,6 +357,111 @@ pub struct HealthResponse {
     pub status: String,
     pub timestamp: String,
     pub database_connected: bool,
+    #[serde(default)]
+    pub chains: Vec<ChainHealthInfo>,
+}
+
+/// Health of one settlement network
+#[derive(Debug, Deserialize)]
+pub struct ChainHealthInfo {
+    pub network: String,
+    pub expected_chain_id: u64,
+    pub healthy: bool,
+    pub chain_id: Option<u64>,
+    pub block_number: Option<u64>,
+    pub head_age_seconds: Option<u64>,
+    pub gas_price: Option<String>,
+    pub base_fee: Option<String>,
+    pub synced: Option<bool>,
+    pub issues: Vec<String>,
+    pub error: Option<String>,
+    pub checked_at: Option<String>,
+}
+
+/// Result of comparing the settlement contracts with the database
+#[derive(Debug, Deserialize)]
+pub struct ReconciliationReport {
+    pub checked_at: String,
+    pub targets: Vec<String>,
+    pub checked_batches: usize,
+    pub discrepancies: Vec<Discrepancy>,
+    pub repairs: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// One disagreement between a settlement target and the database
+#[derive(Debug, Deserialize)]
+pub struct Discrepancy {
+    pub batch_id: i32,
+    /// Empty for database-only findings
+    pub target: String,
+    /// `missing`, `extra`, `out_of_order`, `unknown_root`, `missing_state_commit`,
+    /// `root_mismatch` or `settled_before_parent`
+    pub kind: String,
+    pub repairable: bool,
+    pub expected_root: String,
+    pub onchain_roots: Vec<String>,
+    pub detail: String,
+}
+
+/// Result of recovering a failed batch
+#[derive(Debug, Deserialize)]
+pub struct BatchRecoveryResponse {
+    pub failed_batch_id: i32,
+    pub parent_batch_id: Option<i32>,
+    pub unwound_batch_ids: Vec<i32>,
+    pub released_transaction_ids: Vec<i32>,
+    pub restored_root: String,
+    pub rebatched_batch_ids: Vec<i32>,
+}
+
+/// Result of replaying the transaction log into a fresh IMT
+#[derive(Debug, Deserialize)]
+pub struct StateRebuildResponse {
+    pub batches_replayed: usize,
+    pub transactions_replayed: usize,
+    pub nullifier_count: usize,
+    pub rebuilt_root: String,
+    pub divergence: Option<RebuildDivergence>,
+    pub live: Option<LiveStateComparison>,
+    pub swapped: bool,
+}
+
+/// Batch at which a replay stopped
+#[derive(Debug, Deserialize)]
+pub struct RebuildDivergence {
+    pub batch_id: i32,
+    pub committed_root: Option<String>,
+    pub replayed_root: Option<String>,
+    pub reason: String,
+}
+
+/// Live IMT tables compared with the rebuilt tree
+#[derive(Debug, Deserialize)]
+pub struct LiveStateComparison {
+    pub live_root: String,
+    pub root_matches: bool,
+    pub nullifier_mismatches: usize,
+    pub node_mismatches: usize,
+}
+
+/// Manual batch trigger response
+#[derive(Debug, Deserialize)]
+pub struct TriggerBatchResponse {
+    pub triggered: bool,
+    pub message: String,
+}
+
+/// Batch processor statistics
+#[derive(Debug, Deserialize)]
+pub struct BatchProcessorStatsResponse {
+    pub total_batches_created: u64,
+    pub total_transactions_processed: u64,
+    pub timer_triggers: u64,
+    pub count_triggers: u64,
+    pub manual_triggers: u64,
+    pub errors: u64,
+    pub last_batch_time: Option<String>,
 }
 
 /// API info response
@@ -167,38 +485,113 @@ pub struct EndpointInfo {
 // ============================================================================
 
 impl BatchApiClient {
-    /// Create a new API client
+    /// Create a new API client with default settings
     pub fn new(base_url: impl Into<String>) -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
+        Self::builder(base_url)
             .build()
-            .expect("Failed to create HTTP client");
+            .expect("Failed to create HTTP client")
+    }
 
-        Self {
-            client,
+    /// Start configuring a client for `base_url`
+    pub fn builder(base_url: impl Into<String>) -> BatchApiClientBuilder {
+        BatchApiClientBuilder {
             base_url: base_url.into(),
+            api_key: None,
+            bearer_token: None,
+            timeout: DEFAULT_TIMEOUT,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            retry: RetryPolicy::default(),
         }
     }
 
     /// Submit a new transaction
+    ///
+    /// A fresh idempotency key is generated and reused across retries, so a
+    /// timed-out attempt that actually reached the server is never enqueued twice.
     pub async fn submit_transaction(
         &self,
         amount: i32,
     ) -> Result<SubmitTransactionResponse, ApiClientError> {
-        let url = format!("{}/api/v2/transactions", self.base_url);
-        let request = SubmitTransactionRequest { amount };
+        let key = uuid::Uuid::new_v4().to_string();
+        self.submit_transaction_with_key(amount, &key).await
+    }
 
-        let response = self.client.post(&url).json(&request).send().await?;
-        self.handle_response(response).await
+    /// Submit a transaction with a caller-chosen idempotency key, retrying
+    /// transient failures with the same key
+    pub async fn submit_transaction_with_key(
+        &self,
+        amount: i32,
+        idempotency_key: &str,
+    ) -> Result<SubmitTransactionResponse, ApiClientError> {
+        let url = self.url("/api/v2/transactions");
+        let request = SubmitTransactionRequest {
+            amount,
+            client_tx_id: Some(idempotency_key.to_string()),
+        };
+
+        let response = self
+            .send(
+                || {
+                    self.client
+                        .post(&url)
+                        .header(IDEMPOTENCY_KEY_HEADER, idempotency_key)
+                        .json(&request)
+                },
+                true,
+            )
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    /// Submit many transactions in one request
+    ///
+    /// `best_effort` commits the valid items even if others fail; otherwise any
+    /// failure rejects the whole request. A rejected atomic request is returned
+    /// as a response with `committed == false` so per-item errors are visible.
+    /// The request is retried only when every item carries an idempotency key.
+    pub async fn submit_transactions_bulk(
+        &self,
+        items: &[BulkTransactionItem],
+        best_effort: bool,
+    ) -> Result<BulkSubmitResponse, ApiClientError> {
+        let url = self.url("/api/v2/transactions/bulk");
+        let mode = if best_effort { "best_effort" } else { "atomic" };
+        let retry = items.iter().all(|item| item.client_tx_id.is_some());
+
+        let result = self
+            .send(
+                || self.client.post(&url).query(&[("mode", mode)]).json(items),
+                retry,
+            )
+            .await;
+
+        match result {
+            Ok(response) => Ok(response.json().await?),
+            Err(ApiClientError::ApiError {
+                code: ApiErrorCode::Unprocessable,
+                message,
+                ..
+            }) => Ok(serde_json::from_str(&message)?),
+            Err(e) => Err(e),
+        }
     }
 
     /// Get pending transactions
     pub async fn get_pending_transactions(
         &self,
     ) -> Result<PendingTransactionsResponse, ApiClientError> {
-        let url = format!("{}/api/v2/transactions/pending", self.base_url);
-        let response = self.client.get(&url).send().await?;
-        self.handle_response(response).await
+        self.get_json("/api/v2/transactions/pending", &()).await
+    }
+
+    /// Get the inclusion receipt of a batched transaction
+    ///
+    /// The receipt is not checked; use `TransactionReceipt::verify`.
+    pub async fn get_transaction_receipt(
+        &self,
+        transaction_id: i32,
+    ) -> Result<TransactionReceipt, ApiClientError> {
+        let path = format!("/api/v2/transactions/{}/receipt", transaction_id);
+        self.get_json(&path, &()).await
     }
 
     /// Create a new batch
@@ -206,36 +599,95 @@ impl BatchApiClient {
         &self,
         batch_size: Option<i32>,
     ) -> Result<CreateBatchResponse, ApiClientError> {
-        let url = format!("{}/api/v2/batches", self.base_url);
         let request = CreateBatchRequest { batch_size };
-
-        let response = self.client.post(&url).json(&request).send().await?;
-        self.handle_response(response).await
+        self.post_json("/api/v2/batches", &request).await
     }
 
-    /// Get all batches (paginated)
+    /// Get the most recent batches
     pub async fn get_batches(
         &self,
         limit: Option<i32>,
     ) -> Result<BatchListResponse, ApiClientError> {
-        let mut url = format!("{}/api/v2/batches", self.base_url);
-        if let Some(limit) = limit {
-            url = format!("{}?limit={}", url, limit);
+        let params = BatchListParams {
+            limit,
+            ..BatchListParams::default()
+        };
+        self.list_batches(&params).await
+    }
+
+    /// Get one page of batches matching the given filters
+    pub async fn list_batches(
+        &self,
+        params: &BatchListParams,
+    ) -> Result<BatchListResponse, ApiClientError> {
+        self.get_json("/api/v2/batches", params).await
+    }
+
+    /// Follow `next_cursor` until all matching batches (up to `max_items`) are fetched
+    pub async fn list_all_batches(
+        &self,
+        mut params: BatchListParams,
+        max_items: Option<usize>,
+    ) -> Result<Vec<BatchInfo>, ApiClientError> {
+        let mut batches = Vec::new();
+        loop {
+            let page = self.list_batches(&params).await?;
+            batches.extend(page.batches);
+
+            if let Some(max) = max_items {
+                if batches.len() >= max {
+                    batches.truncate(max);
+                    break;
+                }
+            }
+            match page.next_cursor {
+                Some(cursor) => params.cursor = Some(cursor),
+                None => break,
+            }
         }
+        Ok(batches)
+    }
 
-        let response = self.client.get(&url).send().await?;
-        self.handle_response(response).await
+    /// Get one page of transactions matching the given filters
+    pub async fn list_transactions(
+        &self,
+        params: &TransactionListParams,
+    ) -> Result<TransactionListResponse, ApiClientError> {
+        self.get_json("/api/v2/transactions", params).await
+    }
+
+    /// Follow `next_cursor` until all matching transactions (up to `max_items`) are fetched
+    pub async fn list_all_transactions(
+        &self,
+        mut params: TransactionListParams,
+        max_items: Option<usize>,
+    ) -> Result<Vec<TransactionInfo>, ApiClientError> {
+        let mut transactions = Vec::new();
+        loop {
+            let page = self.list_transactions(&params).await?;
+            transactions.extend(page.transactions);
+
+            if let Some(max) = max_items {
+                if transactions.len() >= max {
+                    transactions.truncate(max);
+                    break;
+                }
+            }
+            match page.next_cursor {
+                Some(cursor) => params.cursor = Some(cursor),
+                None => break,
+            }
+        }
+        Ok(transactions)
     }
 
     /// Get specific batch by ID
     pub async fn get_batch(&self, batch_id: i32) -> Result<Option<BatchInfo>, ApiClientError> {
-        let url = format!("{}/api/v2/batches/{}", self.base_url, batch_id);
-        let response = self.client.get(&url).send().await?;
-
-        match response.status().as_u16() {
-            200 => Ok(Some(self.handle_response(response).await?)),
-            404 => Ok(None),
-            _ => Err(self.handle_error_response(response).await),
+        let path = format!("/api/v2/batches/{}", batch_id);
+        match self.get_json(&path, &()).await {
+            Ok(batch) => Ok(Some(batch)),
+            Err(e) if e.is_not_found() => Ok(None),
+            Err(e) => Err(e),
         }
     }
 
@@ -247,22 +699,73 @@ impl BatchApiClient {
         status: String,
         merkle_root: Option<String>,
     ) -> Result<BatchInfo, ApiClientError> {
-        let url = format!("{}/api/v2/batches/{}/proof", self.base_url, batch_id);
+        let path = format!("/api/v2/batches/{}/proof", batch_id);
         let request = UpdateBatchProofRequest {
             sindri_proof_id,
             status,
             merkle_root,
         };
+        self.post_json(&path, &request).await
+    }
+
+    /// Put a failed batch back in the proof queue
+    pub async fn retry_batch_proof(&self, batch_id: i32) -> Result<BatchInfo, ApiClientError> {
+        let path = format!("/api/v2/batches/{}/retry-proof", batch_id);
+        self.post_json(&path, &()).await
+    }
+
+    /// Unwind a failed batch and its descendants and re-batch their transactions
+    pub async fn recover_batch(
+        &self,
+        batch_id: i32,
+    ) -> Result<BatchRecoveryResponse, ApiClientError> {
+        let path = format!("/api/v2/batches/{}/recover", batch_id);
+        self.post_json(&path, &serde_json::json!({ "confirm": true }))
+            .await
+    }
+
+    /// Download the proof bundle of a proven batch
+    ///
+    /// The bundle is fetched as CBOR and checked structurally (format, version,
+    /// checksum); use `arithmetic_lib::bundle::verify_bundle` to verify the proof.
+    /// `include_transactions` asks for the operator view with transaction amounts.
+    pub async fn download_proof_bundle(
+        &self,
+        batch_id: i32,
+        include_transactions: bool,
+    ) -> Result<ProofBundle, ApiClientError> {
+        let url = self.url(&format!("/api/v2/batches/{}/proof-bundle", batch_id));
+        let response = self
+            .send(
+                || {
+                    self.client
+                        .get(&url)
+                        .query(&[("include_transactions", include_transactions)])
+                        .header(ACCEPT, "application/cbor")
+                },
+                true,
+            )
+            .await?;
+        let bundle = ProofBundle::decode(&response.bytes().await?)?;
+        bundle.validate()?;
+        Ok(bundle)
+    }
 
-        let response = self.client.post(&url).json(&request).send().await?;
-        self.handle_response(response).await
+    /// Ask the background processor to create a batch now
+    pub async fn trigger_batch(&self) -> Result<TriggerBatchResponse, ApiClientError> {
+        self.post_json("/api/v2/batches/trigger", &()).await
+    }
+
+    /// Get background batch processor statistics
+    pub async fn get_batch_processor_stats(
+        &self,
+    ) -> Result<BatchProcessorStatsResponse, ApiClientError> {
+        self.get_json("/api/v2/batches/stats", &()).await
     }
 
     /// Get current counter state
     pub async fn get_current_state(&self) -> Result<CurrentStateResponse, ApiClientError> {
-        let url = format!("{}/api/v2/state/current", self.base_url);
-        let response = self.client.get(&url).send().await?;
-        self.handle_response(response).await
+        self.get_json("/api/v2/state/current", &()).await
     }
 
     /// Get contract submission data (dry run)
@@ -270,52 +773,281 @@ impl BatchApiClient {
         &self,
         batch_id: i32,
     ) -> Result<Option<ContractSubmissionData>, ApiClientError> {
-        let url = format!("{}/api/v2/state/{}/contract", self.base_url, batch_id);
-        let response = self.client.get(&url).send().await?;
-
-        match response.status().as_u16() {
-            200 => Ok(Some(self.handle_response(response).await?)),
-            404 => Ok(None),
-            _ => Err(self.handle_error_response(response).await),
+        let path = format!("/api/v2/state/{}/contract", batch_id);
+        match self.get_json(&path, &()).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.is_not_found() => Ok(None),
+            Err(e) => Err(e),
         }
     }
 
+    /// Compare on-chain state history with the settlement records, from
+    /// `from_batch` on (every proven batch when `None`)
+    pub async fn reconcile(
+        &self,
+        from_batch: Option<i32>,
+    ) -> Result<ReconciliationReport, ApiClientError> {
+        self.get_json("/api/v2/reconciliation", &[("from_batch", from_batch)])
+            .await
+    }
+
+    /// Reconcile and repair what the database can be brought in line with
+    pub async fn repair_reconciliation(
+        &self,
+        from_batch: Option<i32>,
+    ) -> Result<ReconciliationReport, ApiClientError> {
+        self.post_json(
+            "/api/v2/reconciliation/repair",
+            &serde_json::json!({ "confirm": true, "from_batch": from_batch }),
+        )
+        .await
+    }
+
+    /// Replay the transaction log into a fresh IMT and compare it with the
+    /// live tables; with `swap` the rebuilt tree replaces them when nothing diverged
+    pub async fn rebuild_state(&self, swap: bool) -> Result<StateRebuildResponse, ApiClientError> {
+        self.post_json(
+            "/api/v2/state/rebuild",
+            &serde_json::json!({ "swap": swap }),
+        )
+        .await
+    }
+
     /// Check API health
     pub async fn health_check(&self) -> Result<HealthResponse, ApiClientError> {
-        let url = format!("{}/api/v2/health", self.base_url);
-        let response = self.client.get(&url).send().await?;
-        self.handle_response(response).await
+        self.get_json("/api/v2/health", &()).await
     }
 
     /// Get API information
     pub async fn get_api_info(&self) -> Result<ApiInfoResponse, ApiClientError> {
-        let url = format!("{}/api/v2/info", self.base_url);
-        let response = self.client.get(&url).send().await?;
-        self.handle_response(response).await
+        self.get_json("/api/v2/info", &()).await
+    }
+
+    /// Subscribe to the server's batch lifecycle event feed
+    ///
+    /// The feed is live only; reconnecting callers should re-read any state they
+    /// depend on. A `Lagged` event means events were dropped for this connection.
+    pub async fn events(&self, batch_id: Option<i32>) -> Result<EventStream, ApiClientError> {
+        let url = self.url("/api/v2/events");
+        let mut request = self.client.get(&url).header(ACCEPT, "text/event-stream");
+        if let Some(batch_id) = batch_id {
+            request = request.query(&[("batch_id", batch_id)]);
+        }
+
+        // No request timeout here: the response body stays open indefinitely
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(error_from_response(response).await);
+        }
+        Ok(events::event_stream(response))
+    }
+
+    /// Wait until the batch's proof is ready
+    ///
+    /// Fails with `BatchFailed` if proof generation fails and `WaitTimeout` if
+    /// `timeout` elapses first.
+    pub async fn wait_for_batch_proven(
+        &self,
+        batch_id: i32,
+        timeout: Duration,
+    ) -> Result<BatchInfo, ApiClientError> {
+        self.wait_for_batch(batch_id, timeout, "proven", |batch| {
+            match batch.proof_status.as_str() {
+                "proven" => Ok(true),
+                "failed" => Err(ApiClientError::BatchFailed { batch_id }),
+                _ => Ok(false),
+            }
+        })
+        .await
+    }
+
+    /// Wait until the batch has been posted to the settlement contract
+    pub async fn wait_for_batch_posted(
+        &self,
+        batch_id: i32,
+        timeout: Duration,
+    ) -> Result<BatchInfo, ApiClientError> {
+        self.wait_for_batch(batch_id, timeout, "posted", |batch| {
+            if batch.posted_to_contract_at.is_some() {
+                Ok(true)
+            } else if batch.proof_status == "failed" {
+                Err(ApiClientError::BatchFailed { batch_id })
+            } else {
+                Ok(false)
+            }
+        })
+        .await
+    }
+
+    /// Re-read the batch until `done` accepts it
+    ///
+    /// Events for the batch only wake the loop early; the batch endpoint stays
+    /// the source of truth, so a missed event or an unavailable feed degrades to
+    /// polling every `WAIT_POLL_INTERVAL`.
+    async fn wait_for_batch(
+        &self,
+        batch_id: i32,
+        timeout: Duration,
+        target: &'static str,
+        done: impl Fn(&BatchInfo) -> Result<bool, ApiClientError>,
+    ) -> Result<BatchInfo, ApiClientError> {
+        let started = Instant::now();
+        let mut events = self.events(Some(batch_id)).await.ok();
+
+        let wait = async {
+            loop {
+                if let Some(batch) = self.get_batch(batch_id).await? {
+                    if done(&batch)? {
+                        return Ok(batch);
+                    }
+                }
+
+                let feed_closed = match events.as_mut() {
+                    Some(stream) => matches!(
+                        tokio::time::timeout(WAIT_POLL_INTERVAL, stream.next()).await,
+                        Ok(None | Some(Err(_)))
+                    ),
+                    None => {
+                        tokio::time::sleep(WAIT_POLL_INTERVAL).await;
+                        false
+                    }
+                };
+                if feed_closed {
+                    debug!("Event feed closed while waiting for batch {batch_id}, polling");
+                    events = None;
+                }
+            }
+        };
+
+        tokio::time::timeout(timeout, wait)
+            .await
+            .unwrap_or_else(|_| {
+                Err(ApiClientError::WaitTimeout {
+                    batch_id,
+                    target,
+                    waited: started.elapsed(),
+                })
+            })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
     }
 
-    /// Generic response handler
-    async fn handle_response<T: for<'de> Deserialize<'de>>(
+    /// GET a JSON resource (retried)
+    async fn get_json<T: DeserializeOwned>(
         &self,
-        response: Response,
+        path: &str,
+        query: &(impl Serialize + ?Sized),
     ) -> Result<T, ApiClientError> {
-        if response.status().is_success() {
-            let json = response.json().await?;
-            Ok(json)
+        let url = self.url(path);
+        let response = self
+            .send(|| self.client.get(&url).query(query), true)
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    /// POST a JSON body (sent once)
+    async fn post_json<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &(impl Serialize + ?Sized),
+    ) -> Result<T, ApiClientError> {
+        let url = self.url(path);
+        let response = self
+            .send(|| self.client.post(&url).json(body), false)
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    /// Send a request, retrying transient failures per the retry policy when
+    /// `retry` is set; non-success responses become `ApiError`s
+    async fn send(
+        &self,
+        build: impl Fn() -> RequestBuilder,
+        retry: bool,
+    ) -> Result<Response, ApiClientError> {
+        let max_attempts = if retry {
+            self.retry.max_attempts.max(1)
         } else {
-            Err(self.handle_error_response(response).await)
+            1
+        };
+
+        let mut attempt = 1;
+        loop {
+            let error = match build().timeout(self.timeout).send().await {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) => error_from_response(response).await,
+                Err(e) => ApiClientError::from(e),
+            };
+
+            if attempt >= max_attempts || !error.is_retryable() {
+                return Err(error);
+            }
+
+            let mut delay = self.retry.delay_for(attempt);
+            if let ApiClientError::ApiError {
+                retry_after: Some(retry_after),
+                ..
+            } = &error
+            {
+                delay = delay.max(*retry_after);
+            }
+            debug!("Attempt {attempt} failed ({error}), retrying in {delay:?}");
+            tokio::time::sleep(delay).await;
+            attempt += 1;
         }
     }
+}
 
-    /// Handle error responses
-    async fn handle_error_response(&self, response: Response) -> ApiClientError {
-        let status = response.status().as_u16();
-        let message = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unknown error".to_string());
+/// Convert an error response into a typed `ApiError`
+///
+/// JSON bodies from the middleware carry `error`, `message` and optionally
+/// `details.retry_after_seconds`; REST handlers reply with plain text.
+async fn error_from_response(response: Response) -> ApiClientError {
+    let status = response.status();
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs);
+    let body = response.text().await.unwrap_or_default();
+
+    let json = serde_json::from_str::<serde_json::Value>(&body).ok();
+    let field = |name: &str| {
+        json.as_ref()
+            .and_then(|value| value.get(name))
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string)
+    };
+
+    let code = field("error").map_or_else(
+        || ApiErrorCode::from_status(status.as_u16()),
+        |code| ApiErrorCode::from_code(&code),
+    );
+    let retry_after = retry_after.or_else(|| {
+        json.as_ref()
+            .and_then(|value| value.pointer("/details/retry_after_seconds"))
+            .and_then(serde_json::Value::as_u64)
+            .map(Duration::from_secs)
+    });
+    let message = field("message").unwrap_or_else(|| {
+        if body.is_empty() {
+            status
+                .canonical_reason()
+                .unwrap_or("Unknown error")
+                .to_string()
+        } else {
+            body.clone()
+        }
+    });
 
-        ApiClientError::ApiError { status, message }
+    ApiClientError::ApiError {
+        status: status.as_u16(),
+        code,
+        message,
+        retry_after,
     }
 }
 
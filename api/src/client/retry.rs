@@ -0,0 +1,55 @@
+//! Retry policy for `BatchApiClient`
+
+use std::time::Duration;
+
+use rand::Rng;
+
+/// Exponential backoff with optional jitter
+///
+/// Only requests that are safe to repeat are retried: reads, and submissions
+/// that carry idempotency keys.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts including the first one (1 disables retries)
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled for each further retry
+    pub base_delay: Duration,
+    /// Upper bound on any single delay
+    pub max_delay: Duration,
+    /// Randomize each delay within [delay / 2, delay] to spread out retries
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(10),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Send every request exactly once
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before retry number `retry` (1-based)
+    pub fn delay_for(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        if !self.jitter || delay.is_zero() {
+            return delay;
+        }
+
+        let half = delay / 2;
+        let spread = rand::thread_rng().gen_range(0..=half.as_millis() as u64);
+        half + Duration::from_millis(spread)
+    }
+}
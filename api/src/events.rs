@@ -0,0 +1,182 @@
+//! Batch lifecycle event feed
+//!
+//! The REST handlers and the background batch processor publish `ApiEvent`s on
+//! an in-process broadcast bus. Clients follow them over server-sent events at
+//! `GET /api/v2/events`. The feed is live only: events published while a client
+//! is disconnected are not replayed, so clients should re-read state after
+//! reconnecting.
+
+use std::convert::Infallible;
+use std::time::Duration;
+
+use axum::{
+    extract::{Query, State},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, Stream};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{debug, info, instrument, warn};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::rest::ApiState;
+
+/// Events buffered per subscriber before the slowest one starts lagging
+pub const DEFAULT_EVENT_BUFFER: usize = 1024;
+
+/// Interval between SSE keep-alive comments
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// A batch lifecycle change
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ApiEventKind {
+    TransactionSubmitted {
+        transaction_id: i32,
+        amount: i32,
+    },
+    BatchCreated {
+        batch_id: i32,
+        transaction_count: usize,
+    },
+    BatchProven {
+        batch_id: i32,
+    },
+    BatchFailed {
+        batch_id: i32,
+    },
+    BatchPosted {
+        batch_id: i32,
+    },
+}
+
+impl ApiEventKind {
+    /// Batch the event refers to, if any
+    pub const fn batch_id(&self) -> Option<i32> {
+        match self {
+            Self::TransactionSubmitted { .. } => None,
+            Self::BatchCreated { batch_id, .. }
+            | Self::BatchProven { batch_id }
+            | Self::BatchFailed { batch_id }
+            | Self::BatchPosted { batch_id } => Some(*batch_id),
+        }
+    }
+
+    /// SSE event name (matches the serialized `type` tag)
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::TransactionSubmitted { .. } => "transaction_submitted",
+            Self::BatchCreated { .. } => "batch_created",
+            Self::BatchProven { .. } => "batch_proven",
+            Self::BatchFailed { .. } => "batch_failed",
+            Self::BatchPosted { .. } => "batch_posted",
+        }
+    }
+}
+
+/// Event as delivered on the feed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ApiEvent {
+    #[serde(flatten)]
+    pub kind: ApiEventKind,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// In-process publisher for `ApiEvent`s
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<ApiEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_BUFFER)
+    }
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Publish an event; a no-op when nobody is subscribed
+    pub fn publish(&self, kind: ApiEventKind) {
+        debug!("📣 Event: {:?}", kind);
+        let _ = self.sender.send(ApiEvent {
+            kind,
+            timestamp: Utc::now(),
+        });
+    }
+
+    /// Publish the event matching a proof status written by `update_batch_proof`
+    pub fn publish_proof_status(&self, batch_id: i32, status: &str) {
+        match status {
+            "proven" => self.publish(ApiEventKind::BatchProven { batch_id }),
+            "failed" => self.publish(ApiEventKind::BatchFailed { batch_id }),
+            _ => {}
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ApiEvent> {
+        self.sender.subscribe()
+    }
+}
+
+/// Filters for the event feed
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EventFeedQuery {
+    /// Only events about this batch
+    pub batch_id: Option<i32>,
+}
+
+/// Stream batch lifecycle events
+///
+/// Each SSE message is named after the event type and carries the JSON-encoded
+/// `ApiEvent`. A `lagged` message with the number of dropped events is sent when
+/// the client falls too far behind.
+#[utoipa::path(
+    get,
+    path = "/api/v2/events",
+    tag = "system",
+    summary = "Stream batch lifecycle events (server-sent events)",
+    params(EventFeedQuery),
+    responses((status = 200, description = "text/event-stream of ApiEvent", body = ApiEvent, content_type = "text/event-stream"))
+)]
+#[instrument(skip(state), level = "info")]
+pub(crate) async fn event_feed_endpoint(
+    State(state): State<ApiState>,
+    Query(query): Query<EventFeedQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    info!("📡 API: Event feed subscriber connected");
+
+    let batch_filter = query.batch_id;
+    let receiver = state.events.subscribe();
+    let stream = stream::unfold(receiver, move |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    if batch_filter.is_some_and(|batch_id| event.kind.batch_id() != Some(batch_id))
+                    {
+                        continue;
+                    }
+                    let message = Event::default()
+                        .event(event.kind.name())
+                        .json_data(&event)
+                        .unwrap_or_else(|_| Event::default().comment("unserializable event"));
+                    return Some((Ok(message), receiver));
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Event feed subscriber lagged, {} events dropped", skipped);
+                    let message = Event::default().event("lagged").data(skipped.to_string());
+                    return Some((Ok(message), receiver));
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::new().interval(KEEP_ALIVE_INTERVAL))
+}
@@ -35,6 +35,9 @@
 
 pub mod batch_processor;
 pub mod client;
+pub mod events;
+pub mod middleware;
+pub mod reconciliation;
 pub mod rest;
 pub mod server;
 pub mod unified_batch_service;
@@ -42,36 +45,62 @@ pub mod unified_batch_service;
 // Temporarily disabled for minimal PoC:
 // pub mod graphql;       // 883 lines - GraphQL API for old nullifier system (depends on disabled modules)
 // pub mod integration;   // 480 lines - Complex deployment/scaling configs (depends on disabled modules)
-// pub mod middleware;    // 654 lines - Comprehensive middleware (rate limiting, auth, etc. - overkill for PoC)
 
 // Re-export main API types for convenience
 pub use client::{
     ApiClientError,
+    ApiErrorCode,
+    ApiEvent,
+    ApiEventKind,
     ArithmeticApiClient, // Keep old name for compatibility
     BatchApiClient,
+    BatchApiClientBuilder,
     BatchInfo,
+    BatchListParams,
     BatchListResponse,
+    BatchProcessorStatsResponse,
+    BatchRecoveryResponse,
+    BulkItemResult,
+    BulkSubmitResponse,
+    BulkTransactionItem,
+    ChainHealthInfo,
     ContractSubmissionData,
     CreateBatchRequest,
     CreateBatchResponse,
     CurrentStateResponse,
+    Discrepancy,
+    EventStream,
     HealthResponse,
+    LiveStateComparison,
     PendingTransactionsResponse,
+    RebuildDivergence,
+    ReconciliationReport,
+    RetryPolicy,
+    SettlementInfo,
+    StateRebuildResponse,
     SubmitTransactionRequest,
     SubmitTransactionResponse,
     TransactionInfo,
+    TransactionListParams,
+    TransactionListResponse,
+    TriggerBatchResponse,
 };
 
 pub use rest::{
-    create_router, ApiConfig, ApiInfoResponse, ApiState, BatchListQuery,
-    BatchListResponse as RestBatchListResponse, CreateBatchRequest as RestCreateBatchRequest,
-    CreateBatchResponse as RestCreateBatchResponse,
+    create_router, ApiConfig, ApiDoc, ApiInfoResponse, ApiState, BatchListQuery,
+    BatchListResponse as RestBatchListResponse, BulkItemResult as RestBulkItemResult,
+    BulkSubmitMode, BulkSubmitQuery, BulkSubmitResponse as RestBulkSubmitResponse,
+    CreateBatchRequest as RestCreateBatchRequest, CreateBatchResponse as RestCreateBatchResponse,
     CurrentStateResponse as RestCurrentStateResponse, EndpointInfo,
-    PendingTransactionsResponse as RestPendingTransactionsResponse,
+    PendingTransactionsResponse as RestPendingTransactionsResponse, ReconciliationQuery,
+    ReconciliationRepairRequest, RecoverBatchRequest, StateRebuildRequest,
     SubmitTransactionRequest as RestSubmitTransactionRequest,
-    SubmitTransactionResponse as RestSubmitTransactionResponse, UpdateBatchProofRequest,
+    SubmitTransactionResponse as RestSubmitTransactionResponse, TransactionListQuery,
+    TransactionListResponse as RestTransactionListResponse, UpdateBatchProofRequest,
 };
 
+pub use events::EventBus;
+pub use middleware::{RateLimiter, ValidationConfig};
 pub use server::{ApiServer, ApiServerBuilder, ApiServerConfig};
 
 pub use unified_batch_service::{BatchCreationResult, UnifiedBatchService};
@@ -86,6 +115,8 @@ pub use arithmetic_db::{
     get_pending_transactions,
     // Database functions
     init_db,
+    list_batches,
+    list_transactions,
     store_ads_state_commit,
     submit_transaction,
     update_batch_proof,
@@ -102,4 +133,6 @@ pub use arithmetic_db::{
     // New batch processing types
     IncomingTransaction,
     ProofBatch,
+    SortOrder,
+    TransactionStatus,
 };
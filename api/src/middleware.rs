@@ -1,7 +1,7 @@
 use axum::{
     body::Body,
     extract::{Request, State},
-    http::{HeaderValue, Method, StatusCode},
+    http::{HeaderMap, HeaderValue, Method, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
     Json,
@@ -157,15 +157,20 @@ pub async fn rate_limit_middleware(
 }
 
 fn extract_client_id(request: &Request<Body>) -> String {
+    client_id_from_headers(request.headers())
+}
+
+/// Derive the rate-limit client identifier from request headers
+pub(crate) fn client_id_from_headers(headers: &HeaderMap) -> String {
     // Try API key first
-    if let Some(api_key) = request.headers().get("x-api-key") {
+    if let Some(api_key) = headers.get("x-api-key") {
         if let Ok(key_str) = api_key.to_str() {
             return format!("api_key:{key_str}");
         }
     }
 
     // Try authorization header
-    if let Some(auth) = request.headers().get("authorization") {
+    if let Some(auth) = headers.get("authorization") {
         if let Ok(auth_str) = auth.to_str() {
             if let Some(token) = auth_str.strip_prefix("Bearer ") {
                 return format!("bearer:{token}");
@@ -174,10 +179,9 @@ fn extract_client_id(request: &Request<Body>) -> String {
     }
 
     // Fall back to IP address (simplified - would need proper extraction in real implementation)
-    request
-        .headers()
+    headers
         .get("x-forwarded-for")
-        .or_else(|| request.headers().get("x-real-ip"))
+        .or_else(|| headers.get("x-real-ip"))
         .and_then(|ip| ip.to_str().ok())
         .map(|ip| format!("ip:{ip}"))
         .unwrap_or_else(|| "unknown".to_string())
@@ -205,6 +209,15 @@ fn calculate_token_cost(request: &Request<Body>) -> u32 {
     }
 }
 
+/// Number of bulk-submitted transactions covered by one rate-limit token
+pub const BULK_ITEMS_PER_TOKEN: usize = 100;
+
+/// Token cost of a bulk submission: one token per request plus one per
+/// `BULK_ITEMS_PER_TOKEN` transactions
+pub fn bulk_token_cost(item_count: usize) -> u32 {
+    u32::try_from(1 + item_count.div_ceil(BULK_ITEMS_PER_TOKEN)).unwrap_or(u32::MAX)
+}
+
 fn add_rate_limit_headers(
     mut response: Response,
     rate_limiter: &RateLimiter,
@@ -0,0 +1,626 @@
+//! On-chain ↔ off-chain settlement reconciliation
+//!
+//! Every proven batch is settled under its own state id (`batch_state_id`)
+//! with the root `batch_state_root(final_counter_value)`. For each settlement
+//! target this walks `stateHistory(stateId)` of every proven batch and compares
+//! it with what the database records: the target's row in `batch_submissions`
+//! (or, for batches settled before per-target tracking, `posted_to_contract`)
+//! and the batch's `ads_state_commits` row. A history entry belongs to the
+//! batch if it is either the posted root or the committed ADS root. The
+//! contract's `currentState` of the latest settled batch must be one of its
+//! roots, and no batch may land on chain before its parent batch.
+//!
+//! Findings are reported as [`Discrepancy`] entries. `repair` fixes the ones
+//! the database can be brought in line with: a root that is on chain but not
+//! recorded is marked confirmed (the batch processor then sets the posted
+//! flag), and a recorded root that is missing on chain has its posted flag
+//! cleared and its submission reset to `pending` so the batch processor posts
+//! it again. Roots that match no batch or were overwritten need an operator.
+
+use alloy_primitives::FixedBytes;
+use arithmetic_db::{
+    get_ads_state_commits, get_batch_submissions, list_batches, record_batch_submission,
+    unmark_batch_posted_to_contract_in, BatchListFilter, BatchSubmission, BatchSubmissionUpdate,
+    ProofBatch, SortOrder,
+};
+use chrono::{DateTime, Utc};
+use ethereum_client::{
+    batch_state_id, batch_state_root, EthereumClient, SettlementConfig, SettlementTarget,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+/// Proven batches loaded per database round trip
+const BATCH_PAGE_SIZE: i64 = 200;
+
+/// How the contract and the database disagree about one batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscrepancyKind {
+    /// Recorded as settled, but the batch root is not in the contract's history
+    Missing,
+    /// The batch root is on chain, but the database does not record the settlement
+    Extra,
+    /// The batch root is on chain but a later write replaced it as the current state
+    OutOfOrder,
+    /// The contract's history holds a root that belongs to no batch
+    UnknownRoot,
+    /// The batch was proven without an `ads_state_commits` row
+    MissingStateCommit,
+    /// The contract's current state is not a root of the latest settled batch
+    RootMismatch,
+    /// The batch root landed on chain before its parent batch's root
+    SettledBeforeParent,
+}
+
+impl DiscrepancyKind {
+    /// Whether `repair` can bring the database in line
+    #[must_use]
+    pub const fn repairable(self) -> bool {
+        matches!(self, Self::Missing | Self::Extra)
+    }
+}
+
+/// One disagreement between a settlement target and the database
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Discrepancy {
+    pub batch_id: i32,
+    /// Settlement target name; empty for database-only findings
+    pub target: String,
+    pub kind: DiscrepancyKind,
+    pub repairable: bool,
+    /// Root the batch processor posts for the batch
+    pub expected_root: String,
+    /// `stateHistory` of the batch's state id, oldest first
+    pub onchain_roots: Vec<String>,
+    pub detail: String,
+}
+
+/// Result of one reconciliation run
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReconciliationReport {
+    pub checked_at: DateTime<Utc>,
+    pub targets: Vec<String>,
+    pub checked_batches: usize,
+    pub discrepancies: Vec<Discrepancy>,
+    /// Changes made to the database (only when repairing)
+    pub repairs: Vec<String>,
+    /// Targets or batches that could not be checked
+    pub errors: Vec<String>,
+}
+
+/// What the database says about a batch on one target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Recorded {
+    Settled,
+    InFlight,
+    NotSettled,
+}
+
+impl Recorded {
+    fn of(batch: &ProofBatch, submission: Option<&BatchSubmission>) -> Self {
+        match submission.map(|s| s.status.as_str()) {
+            Some("confirmed") => Self::Settled,
+            Some("submitted") => Self::InFlight,
+            Some(_) => Self::NotSettled,
+            // Settled before settlements were tracked per target
+            None if batch.posted_to_contract_at.is_some() => Self::Settled,
+            None => Self::NotSettled,
+        }
+    }
+}
+
+/// Roots a batch's settlement may carry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BatchRoots {
+    /// `batch_state_root(final_counter_value)`, what the batch processor posts
+    posted: FixedBytes<32>,
+    /// The batch's `ads_state_commits.merkle_root`
+    committed: Option<FixedBytes<32>>,
+}
+
+impl BatchRoots {
+    fn contains(&self, root: &FixedBytes<32>) -> bool {
+        *root == self.posted || self.committed.as_ref() == Some(root)
+    }
+}
+
+/// Compare one batch's on-chain history with its database record
+fn classify(
+    recorded: Recorded,
+    roots: &BatchRoots,
+    history: &[FixedBytes<32>],
+) -> Vec<(DiscrepancyKind, String)> {
+    let mut found = Vec::new();
+    let position = history.iter().position(|root| roots.contains(root));
+
+    match (position, recorded) {
+        (None, Recorded::Settled) => found.push((
+            DiscrepancyKind::Missing,
+            "recorded as settled but the root is not on chain".to_string(),
+        )),
+        (Some(_), Recorded::NotSettled) => found.push((
+            DiscrepancyKind::Extra,
+            "root is on chain but the settlement is not recorded".to_string(),
+        )),
+        _ => {}
+    }
+
+    if let Some(position) = position.filter(|p| p + 1 < history.len()) {
+        found.push((
+            DiscrepancyKind::OutOfOrder,
+            format!(
+                "root is entry {} of {}; a later write replaced it",
+                position + 1,
+                history.len()
+            ),
+        ));
+    }
+
+    let unknown = history.iter().filter(|root| !roots.contains(root)).count();
+    if unknown > 0 {
+        found.push((
+            DiscrepancyKind::UnknownRoot,
+            format!("{unknown} root(s) in the history belong to no batch"),
+        ));
+    }
+
+    found
+}
+
+/// Compare the contract's current state with the roots of the latest
+/// settled batch
+fn check_current_state(
+    roots: &BatchRoots,
+    current: FixedBytes<32>,
+) -> Option<(DiscrepancyKind, String)> {
+    (!roots.contains(&current)).then(|| {
+        (
+            DiscrepancyKind::RootMismatch,
+            format!(
+                "current state {current} is neither the posted root nor the ADS state commit of the latest settled batch"
+            ),
+        )
+    })
+}
+
+/// Batches whose root landed on chain before their parent batch's
+///
+/// `lineage` pairs each checked batch with its `parent_batch_id`, `landed`
+/// maps the batches found on chain to the block their root landed in (0 if
+/// unknown). Parents outside `lineage` are not checked.
+fn check_lineage(lineage: &[(i32, Option<i32>)], landed: &HashMap<i32, u64>) -> Vec<(i32, String)> {
+    let checked: HashSet<i32> = lineage.iter().map(|(id, _)| *id).collect();
+
+    lineage
+        .iter()
+        .filter_map(|&(id, parent)| {
+            let parent = parent.filter(|parent| checked.contains(parent))?;
+            let block = *landed.get(&id)?;
+            match landed.get(&parent) {
+                None => Some((
+                    id,
+                    format!("root is on chain but parent batch {parent} has none"),
+                )),
+                Some(&parent_block) if block != 0 && parent_block > block => Some((
+                    id,
+                    format!(
+                        "root landed in block {block}, before parent batch {parent} in block {parent_block}"
+                    ),
+                )),
+                Some(_) => None,
+            }
+        })
+        .collect()
+}
+
+/// Check every proven batch from `from_batch` on against every settlement
+/// target, applying the repairable fixes when `repair` is set
+pub async fn reconcile(
+    pool: &PgPool,
+    settlement: &SettlementConfig,
+    from_batch: Option<i32>,
+    repair: bool,
+) -> Result<ReconciliationReport, sqlx::Error> {
+    let batches = load_proven_batches(pool, from_batch).await?;
+    let batch_ids: Vec<i32> = batches.iter().map(|b| b.id).collect();
+    let submissions = get_batch_submissions(pool, &batch_ids).await?;
+    let committed: HashMap<i32, Option<FixedBytes<32>>> = get_ads_state_commits(pool, &batch_ids)
+        .await?
+        .into_iter()
+        .map(|commit| {
+            let root = FixedBytes::try_from(commit.merkle_root.as_slice()).ok();
+            (commit.batch_id, root)
+        })
+        .collect();
+
+    let mut report = ReconciliationReport {
+        checked_at: Utc::now(),
+        targets: settlement.targets.iter().map(|t| t.name.clone()).collect(),
+        checked_batches: batches.len(),
+        discrepancies: Vec::new(),
+        repairs: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    for batch in batches.iter().filter(|b| !committed.contains_key(&b.id)) {
+        report.discrepancies.push(Discrepancy {
+            batch_id: batch.id,
+            target: String::new(),
+            kind: DiscrepancyKind::MissingStateCommit,
+            repairable: false,
+            expected_root: batch_state_root(batch.final_counter_value).to_string(),
+            onchain_roots: Vec::new(),
+            detail: "proven batch has no ADS state commit".to_string(),
+        });
+    }
+
+    for target in &settlement.targets {
+        if let Err(e) = reconcile_target(
+            pool,
+            target,
+            &batches,
+            &submissions,
+            &committed,
+            repair,
+            &mut report,
+        )
+        .await
+        {
+            warn!("⚠️ Reconciliation on {} failed: {}", target.name, e);
+            report.errors.push(format!("{}: {}", target.name, e));
+        }
+    }
+
+    info!(
+        "🔎 Reconciled {} batches on {} targets: {} discrepancies, {} repairs",
+        report.checked_batches,
+        report.targets.len(),
+        report.discrepancies.len(),
+        report.repairs.len()
+    );
+    Ok(report)
+}
+
+async fn load_proven_batches(
+    pool: &PgPool,
+    from_batch: Option<i32>,
+) -> Result<Vec<ProofBatch>, sqlx::Error> {
+    let mut batches = Vec::new();
+    let mut filter = BatchListFilter {
+        limit: BATCH_PAGE_SIZE,
+        after_id: from_batch.map(|id| id - 1),
+        order: SortOrder::Asc,
+        proof_status: Some("proven".to_string()),
+        ..BatchListFilter::default()
+    };
+
+    loop {
+        let page = list_batches(pool, &filter).await?;
+        batches.extend(page.items);
+        match page.next_cursor {
+            Some(cursor) => filter.after_id = Some(cursor),
+            None => return Ok(batches),
+        }
+    }
+}
+
+async fn reconcile_target(
+    pool: &PgPool,
+    target: &SettlementTarget,
+    batches: &[ProofBatch],
+    submissions: &[BatchSubmission],
+    committed: &HashMap<i32, Option<FixedBytes<32>>>,
+    repair: bool,
+    report: &mut ReconciliationReport,
+) -> Result<(), String> {
+    let config = target
+        .to_config()
+        .map_err(|e| format!("Invalid configuration: {}", e))?;
+    let chain_id = i64::try_from(config.network.chain_id)
+        .map_err(|_| format!("Chain id {} out of range", config.network.chain_id))?;
+    let client = EthereumClient::new_without_validation(config)
+        .await
+        .map_err(|e| format!("Failed to initialize Ethereum client: {}", e))?;
+
+    let mut checked = HashMap::with_capacity(batches.len());
+    let mut landed = HashMap::new();
+    let mut latest_settled = None;
+    for batch in batches {
+        let roots = BatchRoots {
+            posted: batch_state_root(batch.final_counter_value),
+            committed: committed.get(&batch.id).copied().flatten(),
+        };
+        let history = match client
+            .get_historical_states(batch_state_id(batch.id), Some(0))
+            .await
+        {
+            Ok(history) => history,
+            Err(e) => {
+                report.errors.push(format!(
+                    "{}: batch {}: failed to read state history: {}",
+                    target.name, batch.id, e
+                ));
+                continue;
+            }
+        };
+        if let Some(position) = history.state_roots.iter().position(|r| roots.contains(r)) {
+            landed.insert(
+                batch.id,
+                history.block_numbers.get(position).copied().unwrap_or(0),
+            );
+        }
+
+        let submission = submissions
+            .iter()
+            .find(|s| s.batch_id == batch.id && s.target == target.name);
+        let recorded = Recorded::of(batch, submission);
+        if recorded == Recorded::Settled {
+            latest_settled = Some(batch.id);
+        }
+        let onchain_roots: Vec<String> = history
+            .state_roots
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        for (kind, detail) in classify(recorded, &roots, &history.state_roots) {
+            record_discrepancy(
+                pool,
+                Discrepancy {
+                    batch_id: batch.id,
+                    target: target.name.clone(),
+                    kind,
+                    repairable: kind.repairable(),
+                    expected_root: roots.posted.to_string(),
+                    onchain_roots: onchain_roots.clone(),
+                    detail,
+                },
+                chain_id,
+                repair,
+                report,
+            )
+            .await;
+        }
+        checked.insert(batch.id, (roots, onchain_roots));
+    }
+
+    // Batches are checked in id order, so the last settled one is the latest
+    if let Some(batch_id) = latest_settled {
+        let (roots, onchain_roots) = &checked[&batch_id];
+        match client.get_state_root(batch_state_id(batch_id)).await {
+            Ok(current) => {
+                if let Some((kind, detail)) = check_current_state(roots, current) {
+                    report.discrepancies.push(Discrepancy {
+                        batch_id,
+                        target: target.name.clone(),
+                        kind,
+                        repairable: kind.repairable(),
+                        expected_root: roots.posted.to_string(),
+                        onchain_roots: onchain_roots.clone(),
+                        detail,
+                    });
+                }
+            }
+            Err(e) => report.errors.push(format!(
+                "{}: batch {}: failed to read current state: {}",
+                target.name, batch_id, e
+            )),
+        }
+    }
+
+    let lineage: Vec<(i32, Option<i32>)> = batches
+        .iter()
+        .filter(|batch| checked.contains_key(&batch.id))
+        .map(|batch| (batch.id, batch.parent_batch_id))
+        .collect();
+    for (batch_id, detail) in check_lineage(&lineage, &landed) {
+        let (roots, onchain_roots) = &checked[&batch_id];
+        let kind = DiscrepancyKind::SettledBeforeParent;
+        report.discrepancies.push(Discrepancy {
+            batch_id,
+            target: target.name.clone(),
+            kind,
+            repairable: kind.repairable(),
+            expected_root: roots.posted.to_string(),
+            onchain_roots: onchain_roots.clone(),
+            detail,
+        });
+    }
+
+    Ok(())
+}
+
+/// Add `discrepancy` to `report`, repairing it first when `repair` is set
+async fn record_discrepancy(
+    pool: &PgPool,
+    discrepancy: Discrepancy,
+    chain_id: i64,
+    repair: bool,
+    report: &mut ReconciliationReport,
+) {
+    if repair && discrepancy.repairable {
+        match apply_repair(pool, &discrepancy, chain_id).await {
+            Ok(action) => report.repairs.push(action),
+            Err(e) => report.errors.push(format!(
+                "{}: batch {}: repair failed: {}",
+                discrepancy.target, discrepancy.batch_id, e
+            )),
+        }
+    }
+    report.discrepancies.push(discrepancy);
+}
+
+/// Bring the database in line with the chain for one repairable discrepancy
+async fn apply_repair(
+    pool: &PgPool,
+    discrepancy: &Discrepancy,
+    chain_id: i64,
+) -> Result<String, sqlx::Error> {
+    let update = |status: &str, error: Option<String>| BatchSubmissionUpdate {
+        batch_id: discrepancy.batch_id,
+        target: discrepancy.target.clone(),
+        chain_id,
+        status: status.to_string(),
+        transaction_hash: None,
+        block_number: None,
+        error,
+    };
+
+    match discrepancy.kind {
+        DiscrepancyKind::Extra => {
+            record_batch_submission(pool, &update("confirmed", None)).await?;
+            info!(
+                "🔧 Recorded batch {} as confirmed on {}",
+                discrepancy.batch_id, discrepancy.target
+            );
+            Ok(format!(
+                "batch {}: recorded as confirmed on {}",
+                discrepancy.batch_id, discrepancy.target
+            ))
+        }
+        DiscrepancyKind::Missing => {
+            // The reset and the re-queued post land together or not at all
+            let mut tx = pool.begin().await?;
+            record_batch_submission(
+                &mut *tx,
+                &update(
+                    "pending",
+                    Some("Root missing on chain; re-queued by reconciliation".to_string()),
+                ),
+            )
+            .await?;
+            unmark_batch_posted_to_contract_in(&mut tx, discrepancy.batch_id).await?;
+            tx.commit().await?;
+            info!(
+                "🔧 Re-queued batch {} for posting on {}",
+                discrepancy.batch_id, discrepancy.target
+            );
+            Ok(format!(
+                "batch {}: re-queued for posting on {}",
+                discrepancy.batch_id, discrepancy.target
+            ))
+        }
+        kind => unreachable!("{kind:?} is not repairable"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root(byte: u8) -> FixedBytes<32> {
+        FixedBytes::repeat_byte(byte)
+    }
+
+    const ROOTS: BatchRoots = BatchRoots {
+        posted: FixedBytes::repeat_byte(1),
+        committed: Some(FixedBytes::repeat_byte(3)),
+    };
+
+    fn kinds(recorded: Recorded, history: &[FixedBytes<32>]) -> Vec<DiscrepancyKind> {
+        classify(recorded, &ROOTS, history)
+            .into_iter()
+            .map(|(kind, _)| kind)
+            .collect()
+    }
+
+    #[test]
+    fn consistent_batches_have_no_discrepancies() {
+        assert!(kinds(Recorded::Settled, &[root(1)]).is_empty());
+        assert!(kinds(Recorded::NotSettled, &[]).is_empty());
+        assert!(kinds(Recorded::InFlight, &[]).is_empty());
+        assert!(kinds(Recorded::InFlight, &[root(1)]).is_empty());
+    }
+
+    #[test]
+    fn detects_missing_and_extra_roots() {
+        assert_eq!(
+            kinds(Recorded::Settled, &[]),
+            vec![DiscrepancyKind::Missing]
+        );
+        assert_eq!(
+            kinds(Recorded::NotSettled, &[root(1)]),
+            vec![DiscrepancyKind::Extra]
+        );
+    }
+
+    #[test]
+    fn detects_overwritten_and_unknown_roots() {
+        assert_eq!(
+            kinds(Recorded::Settled, &[root(1), root(2)]),
+            vec![DiscrepancyKind::OutOfOrder, DiscrepancyKind::UnknownRoot]
+        );
+        assert_eq!(
+            kinds(Recorded::Settled, &[root(2)]),
+            vec![DiscrepancyKind::Missing, DiscrepancyKind::UnknownRoot]
+        );
+    }
+
+    #[test]
+    fn committed_ads_roots_belong_to_the_batch() {
+        assert!(kinds(Recorded::Settled, &[root(3)]).is_empty());
+        assert_eq!(
+            kinds(Recorded::NotSettled, &[root(3)]),
+            vec![DiscrepancyKind::Extra]
+        );
+
+        let uncommitted = BatchRoots {
+            committed: None,
+            ..ROOTS
+        };
+        assert_eq!(
+            classify(Recorded::Settled, &uncommitted, &[root(3)])
+                .into_iter()
+                .map(|(kind, _)| kind)
+                .collect::<Vec<_>>(),
+            vec![DiscrepancyKind::Missing, DiscrepancyKind::UnknownRoot]
+        );
+    }
+
+    #[test]
+    fn detects_a_mismatched_current_state() {
+        assert!(check_current_state(&ROOTS, root(1)).is_none());
+        assert!(check_current_state(&ROOTS, root(3)).is_none());
+        assert_eq!(
+            check_current_state(&ROOTS, root(2)).map(|(kind, _)| kind),
+            Some(DiscrepancyKind::RootMismatch)
+        );
+    }
+
+    #[test]
+    fn detects_batches_settled_before_their_parent() {
+        let lineage = [(1, None), (2, Some(1)), (3, Some(2))];
+        let flagged = |landed: &[(i32, u64)]| -> Vec<i32> {
+            check_lineage(&lineage, &landed.iter().copied().collect())
+                .into_iter()
+                .map(|(batch_id, _)| batch_id)
+                .collect()
+        };
+
+        assert!(flagged(&[(1, 10), (2, 11), (3, 11)]).is_empty());
+        assert!(flagged(&[(1, 10)]).is_empty());
+        assert_eq!(flagged(&[(1, 10), (2, 12), (3, 11)]), vec![3]);
+        assert_eq!(flagged(&[(1, 10), (3, 11)]), vec![3]);
+        assert_eq!(flagged(&[(2, 12)]), vec![2]);
+        // Blocks of roots without a matching event are unknown
+        assert!(flagged(&[(1, 10), (2, 0)]).is_empty());
+
+        // Parents outside the checked range are not compared
+        assert!(check_lineage(&[(5, Some(4))], &HashMap::from([(5, 1)])).is_empty());
+    }
+
+    #[test]
+    fn only_flag_discrepancies_are_repairable() {
+        assert!(DiscrepancyKind::Missing.repairable());
+        assert!(DiscrepancyKind::Extra.repairable());
+        assert!(!DiscrepancyKind::OutOfOrder.repairable());
+        assert!(!DiscrepancyKind::UnknownRoot.repairable());
+        assert!(!DiscrepancyKind::MissingStateCommit.repairable());
+        assert!(!DiscrepancyKind::RootMismatch.repairable());
+        assert!(!DiscrepancyKind::SettledBeforeParent.repairable());
+    }
+}
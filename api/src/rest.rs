@@ -1,8 +1,13 @@
 use axum::{
+    body::Bytes,
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
-    routing::{get, post},
+    handler::Handler,
+    http::{
+        header::{ACCEPT, CONTENT_TYPE},
+        HeaderMap, StatusCode,
+    },
+    response::{IntoResponse, Json, Response},
+    routing::{get, post, MethodRouter},
     Router,
 };
 use chrono::{DateTime, Utc};
@@ -12,13 +17,26 @@ use sqlx::{Error as SqlxError, PgPool};
 use tracing::{error, info, instrument, warn};
 
 use crate::batch_processor::BatchProcessorHandle;
+use crate::events::{ApiEventKind, EventBus};
+use crate::middleware::{bulk_token_cost, client_id_from_headers, RateLimiter, ValidationConfig};
+use crate::reconciliation::{reconcile, ReconciliationReport};
 use arithmetic_db::{
-    get_all_batches, get_batch_by_id, get_contract_submission_data, get_current_state,
-    get_pending_transactions, store_ads_state_commit, submit_transaction, update_batch_proof,
-    ContractSubmissionData, IndexedMerkleTreeADS,
+    get_batch_by_id, get_batch_submissions, get_contract_submission_data, get_current_state,
+    get_pending_transactions, get_transaction_by_id, get_transaction_inclusion, list_batches,
+    list_transactions, replay_state, reset_failed_batch_proof, store_ads_state_commit,
+    submit_transaction_idempotent, submit_transactions_bulk, swap_in_replayed_state,
+    unwind_failed_batch, update_batch_proof, AuthenticatedDataStructure, BatchListFilter,
+    BatchSubmission, ContractSubmissionData, DbError, IndexedMerkleTreeADS, NewTransaction,
+    ProofBatch, SortOrder, TransactionListFilter, TransactionStatus,
 };
+use arithmetic_lib::bundle::parse_hex_array;
+use arithmetic_lib::receipt::{ImtMembershipProof, ReceiptSettlement, TransactionReceipt};
+use ethereum_client::{ChainHealth, ChainHealthMonitor, SettlementConfig};
+use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use utoipa_redoc::{Redoc, Servable};
 
 // ============================================================================
 // API STATE
@@ -31,6 +49,12 @@ pub struct ApiState {
     pub config: ApiConfig,
     pub batch_processor: Option<BatchProcessorHandle>,
     pub ads_service: Arc<RwLock<IndexedMerkleTreeADS>>,
+    /// Per-client token buckets; bulk submissions are charged per item
+    pub rate_limiter: Option<RateLimiter>,
+    /// Publisher for the `/api/v2/events` feed
+    pub events: EventBus,
+    /// Health monitors of the settlement networks, reported by `/api/v2/health`
+    pub chain_health: Vec<ChainHealthMonitor>,
 }
 
 /// Configuration for API server
@@ -40,6 +64,8 @@ pub struct ApiConfig {
     pub version: String,
     pub max_batch_size: u32,
     pub enable_debug_endpoints: bool,
+    /// Request limits; `max_batch_size` caps the items in one bulk submission
+    pub validation: ValidationConfig,
 }
 
 impl Default for ApiConfig {
@@ -49,6 +75,7 @@ impl Default for ApiConfig {
             version: "2.0.0".to_string(),
             max_batch_size: 50,
             enable_debug_endpoints: false,
+            validation: ValidationConfig::default(),
         }
     }
 }
@@ -57,29 +84,104 @@ impl Default for ApiConfig {
 // REQUEST/RESPONSE MODELS
 // ============================================================================
 
+/// Header carrying a client-supplied idempotency key
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Maximum length of an idempotency key (matches the column width)
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
 /// Request to submit a single transaction
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct SubmitTransactionRequest {
     pub amount: i32,
+    /// Idempotency key (alternative to the `Idempotency-Key` header)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_tx_id: Option<String>,
 }
 
 /// Response from transaction submission
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct SubmitTransactionResponse {
     pub transaction_id: i32,
     pub amount: i32,
     pub status: String, // "pending"
     pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_tx_id: Option<String>,
+}
+
+/// How a bulk submission handles items that fail validation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkSubmitMode {
+    /// Any failed item rejects the whole request and nothing is enqueued
+    #[default]
+    Atomic,
+    /// Valid items are enqueued; failed items are reported individually
+    BestEffort,
+}
+
+/// One transaction in a bulk submission body
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BulkTransactionItem {
+    pub amount: i32,
+    /// Optional idempotency key for this item
+    #[serde(default)]
+    pub client_tx_id: Option<String>,
+}
+
+impl From<BulkTransactionItem> for NewTransaction {
+    fn from(item: BulkTransactionItem) -> Self {
+        Self {
+            amount: item.amount,
+            client_tx_id: item.client_tx_id,
+        }
+    }
+}
+
+/// Query parameters for bulk submission
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BulkSubmitQuery {
+    #[serde(default)]
+    pub mode: BulkSubmitMode,
+}
+
+/// Outcome of one item in a bulk submission
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BulkItemResult {
+    /// Position of the item in the request body
+    pub index: usize,
+    pub transaction_id: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_tx_id: Option<String>,
+    /// True when the idempotency key matched an earlier submission
+    #[serde(default)]
+    pub replayed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Response from bulk transaction submission
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BulkSubmitResponse {
+    pub mode: BulkSubmitMode,
+    /// False when an atomic request was rejected and rolled back
+    pub committed: bool,
+    pub submitted: usize,
+    pub replayed: usize,
+    pub failed: usize,
+    pub results: Vec<BulkItemResult>,
 }
 
 /// Request to create a batch
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateBatchRequest {
     pub batch_size: Option<i32>,
 }
 
 /// Response from batch creation
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateBatchResponse {
     pub batch_id: i32,
     pub previous_counter_value: i64,
@@ -90,7 +192,7 @@ pub struct CreateBatchResponse {
 }
 
 /// Response for pending transactions
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct PendingTransactionsResponse {
     pub transactions: Vec<TransactionInfo>,
     pub total_count: usize,
@@ -98,22 +200,34 @@ pub struct PendingTransactionsResponse {
 }
 
 /// Transaction info for API responses
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct TransactionInfo {
     pub id: i32,
     pub amount: i32,
+    pub included_in_batch_id: Option<i32>,
     pub created_at: DateTime<Utc>,
 }
 
+/// Response for transaction listing
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TransactionListResponse {
+    pub transactions: Vec<TransactionInfo>,
+    pub total_count: usize,
+    /// Cursor for the next page (pass back as `cursor`), absent on the last page
+    pub next_cursor: Option<String>,
+}
+
 /// Response for batch listing
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct BatchListResponse {
     pub batches: Vec<BatchInfo>,
     pub total_count: usize,
+    /// Cursor for the next page (pass back as `cursor`), absent on the last page
+    pub next_cursor: Option<String>,
 }
 
 /// Batch info for API responses
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct BatchInfo {
     pub id: i32,
     pub previous_counter_value: i64,
@@ -123,33 +237,226 @@ pub struct BatchInfo {
     pub sindri_proof_id: Option<String>,
     pub created_at: DateTime<Utc>,
     pub proven_at: Option<DateTime<Utc>>,
+    /// When the batch was confirmed on every settlement target
+    pub posted_to_contract_at: Option<DateTime<Utc>>,
+    /// Batch whose final state this batch builds on, absent for the first batch
+    pub parent_batch_id: Option<i32>,
+    /// Settlement status on each target network
+    pub settlements: Vec<SettlementInfo>,
+}
+
+/// Settlement status of a batch on one target network
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SettlementInfo {
+    pub target: String,
+    pub chain_id: i64,
+    /// One of `pending`, `submitted`, `confirmed`, `failed`
+    pub status: String,
+    pub transaction_hash: Option<String>,
+    pub block_number: Option<i64>,
+    pub error: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl BatchInfo {
+    /// Build the API view of `batch`, picking its rows out of `submissions`
+    fn new(batch: ProofBatch, submissions: &[BatchSubmission]) -> Self {
+        let settlements = submissions
+            .iter()
+            .filter(|s| s.batch_id == batch.id)
+            .map(|s| SettlementInfo {
+                target: s.target.clone(),
+                chain_id: s.chain_id,
+                status: s.status.clone(),
+                transaction_hash: s.transaction_hash.clone(),
+                block_number: s.block_number,
+                error: s.error.clone(),
+                updated_at: s.updated_at,
+            })
+            .collect();
+
+        Self {
+            id: batch.id,
+            previous_counter_value: batch.previous_counter_value,
+            final_counter_value: batch.final_counter_value,
+            transaction_count: batch.transaction_ids.len(),
+            proof_status: batch.proof_status,
+            sindri_proof_id: batch.sindri_proof_id,
+            created_at: batch.created_at,
+            proven_at: batch.proven_at,
+            posted_to_contract_at: batch.posted_to_contract_at,
+            parent_batch_id: batch.parent_batch_id,
+            settlements,
+        }
+    }
 }
 
 /// Response for current state
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CurrentStateResponse {
     pub counter_value: i64,
     pub has_merkle_root: bool,
     pub last_batch_id: Option<i32>,
     pub last_proven_batch_id: Option<i32>,
+    /// Hex-encoded root of the live IMT, absent if the tree can't be read
+    pub imt_root: Option<String>,
+    /// Nullifiers in the live IMT
+    pub nullifier_count: Option<u64>,
 }
 
 /// Request to update batch with proof
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UpdateBatchProofRequest {
     pub sindri_proof_id: String,
     pub status: String,              // "proven", "failed"
     pub merkle_root: Option<String>, // hex encoded
 }
 
+/// Default page size for listing endpoints
+const DEFAULT_PAGE_SIZE: i64 = 20;
+
+/// Maximum page size for listing endpoints
+const MAX_PAGE_SIZE: i64 = 500;
+
 /// Query parameters for batch listing
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct BatchListQuery {
     pub limit: Option<i32>,
+    /// Opaque cursor returned as `next_cursor` by the previous page
+    pub cursor: Option<String>,
+    /// Proof status: "pending", "proven", "failed" or "unwound"
+    pub status: Option<String>,
+    /// Only batches created at or after this time (RFC 3339)
+    pub since: Option<DateTime<Utc>>,
+    /// Only batches created before this time (RFC 3339)
+    pub until: Option<DateTime<Utc>>,
+    /// Sort direction by id, "desc" (newest first) by default
+    #[param(value_type = Option<String>)]
+    pub order: Option<SortOrder>,
+}
+
+/// Query parameters for transaction listing
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TransactionListQuery {
+    pub limit: Option<i32>,
+    /// Opaque cursor returned as `next_cursor` by the previous page
+    pub cursor: Option<String>,
+    /// Queue status: "pending" or "batched"
+    #[param(value_type = Option<String>)]
+    pub status: Option<TransactionStatus>,
+    /// Only transactions created at or after this time (RFC 3339)
+    pub since: Option<DateTime<Utc>>,
+    /// Only transactions created before this time (RFC 3339)
+    pub until: Option<DateTime<Utc>>,
+    pub min_amount: Option<i32>,
+    pub max_amount: Option<i32>,
+    pub batch_id: Option<i32>,
+    /// Sort direction by id, "desc" (newest first) by default
+    #[param(value_type = Option<String>)]
+    pub order: Option<SortOrder>,
+}
+
+/// Query parameters for reconciliation
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReconciliationQuery {
+    /// First batch id to check; every proven batch by default
+    pub from_batch: Option<i32>,
+}
+
+/// Request to repair the discrepancies reconciliation can fix
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReconciliationRepairRequest {
+    /// Must be `true`; repairs rewrite settlement records
+    pub confirm: bool,
+    /// First batch id to check; every proven batch by default
+    #[serde(default)]
+    pub from_batch: Option<i32>,
+}
+
+/// Query parameters for proof bundle download
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProofBundleQuery {
+    /// Include the batch transaction amounts (operator view)
+    #[serde(default)]
+    pub include_transactions: bool,
+}
+
+/// Request to unwind a failed batch and re-batch its transactions
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RecoverBatchRequest {
+    /// Must be `true`; recovery discards the batch and every batch built on it
+    pub confirm: bool,
+}
+
+/// Result of recovering a failed batch
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BatchRecoveryResponse {
+    pub failed_batch_id: i32,
+    /// Batch the chain continues from, absent when the first batch failed
+    pub parent_batch_id: Option<i32>,
+    /// The failed batch and its descendants, now marked `unwound`
+    pub unwound_batch_ids: Vec<i32>,
+    /// Transactions returned to the pending queue
+    pub released_transaction_ids: Vec<i32>,
+    /// Hex-encoded IMT root after the rollback
+    pub restored_root: String,
+    /// Batches created from the released transactions
+    pub rebatched_batch_ids: Vec<i32>,
+}
+
+/// Request to rebuild the IMT from the transaction log
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct StateRebuildRequest {
+    /// Replace the IMT tables with the rebuilt tree when every committed root
+    /// is reproduced; only compares by default
+    #[serde(default)]
+    pub swap: bool,
+}
+
+/// Result of replaying the transaction log
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct StateRebuildResponse {
+    pub batches_replayed: usize,
+    pub transactions_replayed: usize,
+    /// Nullifiers in the rebuilt tree, genesis included
+    pub nullifier_count: usize,
+    /// Hex-encoded root of the rebuilt tree
+    pub rebuilt_root: String,
+    /// First batch that doesn't reproduce its committed root
+    pub divergence: Option<RebuildDivergence>,
+    /// Comparison with the live tables before any swap, absent after a divergence
+    pub live: Option<LiveStateComparison>,
+    /// Whether the rebuilt tree replaced the live tables
+    pub swapped: bool,
+}
+
+/// Batch at which a replay stopped
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RebuildDivergence {
+    pub batch_id: i32,
+    /// Hex-encoded root committed for the batch
+    pub committed_root: Option<String>,
+    /// Hex-encoded root the replay reached, when the batch could be replayed
+    pub replayed_root: Option<String>,
+    pub reason: String,
+}
+
+/// Live IMT tables compared with the rebuilt tree
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LiveStateComparison {
+    /// Hex-encoded root in `tree_state`
+    pub live_root: String,
+    pub root_matches: bool,
+    pub nullifier_mismatches: usize,
+    pub node_mismatches: usize,
 }
 
 /// API information response
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ApiInfoResponse {
     pub server_name: String,
     pub version: String,
@@ -157,7 +464,7 @@ pub struct ApiInfoResponse {
     pub endpoints: Vec<EndpointInfo>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct EndpointInfo {
     pub method: String,
     pub path: String,
@@ -165,15 +472,79 @@ pub struct EndpointInfo {
 }
 
 /// Health check response
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct HealthResponse {
+    /// `healthy`, `degraded` (a settlement network has issues) or `unhealthy`
     pub status: String,
     pub timestamp: DateTime<Utc>,
     pub database_connected: bool,
+    /// Latest health check of each settlement network
+    pub chains: Vec<ChainHealthInfo>,
+}
+
+/// Health of one settlement network as of its latest check
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ChainHealthInfo {
+    pub network: String,
+    pub expected_chain_id: u64,
+    /// `false` until the first check completes
+    pub healthy: bool,
+    pub chain_id: Option<u64>,
+    pub block_number: Option<u64>,
+    /// Seconds since the latest block was produced
+    pub head_age_seconds: Option<u64>,
+    /// Gas price in wei, as a decimal string
+    pub gas_price: Option<String>,
+    /// Base fee of the latest block in wei, as a decimal string
+    pub base_fee: Option<String>,
+    pub synced: Option<bool>,
+    pub issues: Vec<String>,
+    /// Why the node could not be queried
+    pub error: Option<String>,
+    pub checked_at: Option<DateTime<Utc>>,
+}
+
+impl ChainHealthInfo {
+    fn new(monitor: &ChainHealthMonitor, health: Option<ChainHealth>) -> Self {
+        let Some(health) = health else {
+            return Self {
+                network: monitor.network_name().to_string(),
+                expected_chain_id: monitor.expected_chain_id(),
+                healthy: false,
+                chain_id: None,
+                block_number: None,
+                head_age_seconds: None,
+                gas_price: None,
+                base_fee: None,
+                synced: None,
+                issues: Vec::new(),
+                error: Some("No health check has completed yet".to_string()),
+                checked_at: None,
+            };
+        };
+
+        let stats = health.stats.as_ref();
+        Self {
+            network: health.network_name.clone(),
+            expected_chain_id: health.expected_chain_id,
+            healthy: health.is_healthy(),
+            chain_id: stats.map(|s| s.chain_id),
+            block_number: stats.map(|s| s.block_number),
+            head_age_seconds: stats.map(|s| health.checked_at.saturating_sub(s.block_timestamp)),
+            gas_price: stats.map(|s| s.gas_price.to_string()),
+            base_fee: stats.and_then(|s| s.base_fee.map(|fee| fee.to_string())),
+            synced: stats.map(|s| s.sync_status),
+            issues: health.issues.iter().map(ToString::to_string).collect(),
+            error: health.error.clone(),
+            checked_at: i64::try_from(health.checked_at)
+                .ok()
+                .and_then(|secs| DateTime::from_timestamp(secs, 0)),
+        }
+    }
 }
 
 /// Batch processor stats response
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct BatchProcessorStatsResponse {
     pub total_batches_created: u64,
     pub total_transactions_processed: u64,
@@ -185,7 +556,7 @@ pub struct BatchProcessorStatsResponse {
 }
 
 /// Manual batch trigger response
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct TriggerBatchResponse {
     pub triggered: bool,
     pub message: String,
@@ -195,40 +566,182 @@ pub struct TriggerBatchResponse {
 // ROUTER SETUP
 // ============================================================================
 
-/// Create the main API router with all batch processing endpoints
-pub fn create_router(state: ApiState) -> Router {
-    Router::new()
+/// One registered route: its method, path and handler
+struct ApiRoute {
+    method: &'static str,
+    path: &'static str,
+    handler: MethodRouter<ApiState>,
+}
+
+impl ApiRoute {
+    fn get<H, T>(path: &'static str, handler: H) -> Self
+    where
+        H: Handler<T, ApiState>,
+        T: 'static,
+    {
+        Self {
+            method: "GET",
+            path,
+            handler: get(handler),
+        }
+    }
+
+    fn post<H, T>(path: &'static str, handler: H) -> Self
+    where
+        H: Handler<T, ApiState>,
+        T: 'static,
+    {
+        Self {
+            method: "POST",
+            path,
+            handler: post(handler),
+        }
+    }
+}
+
+/// Every route of the API
+///
+/// `create_router` registers exactly these, and the tests check them against
+/// `ApiDoc`.
+fn api_routes() -> Vec<ApiRoute> {
+    vec![
         // Health and info endpoints (additional legacy paths)
-        .route("/health", get(health_check))
-        .route("/", get(api_info))
-        .route("/api/v2/health", get(health_check))
-        .route("/api/v2/info", get(api_info))
+        ApiRoute::get("/health", health_check),
+        ApiRoute::get("/", api_info),
+        ApiRoute::get("/api/v2/health", health_check),
+        ApiRoute::get("/api/v2/info", api_info),
         // Transaction operations
-        .route("/api/v2/transactions", post(submit_transaction_endpoint))
-        .route(
+        ApiRoute::post("/api/v2/transactions", submit_transaction_endpoint),
+        ApiRoute::get("/api/v2/transactions", list_transactions_endpoint),
+        ApiRoute::post(
+            "/api/v2/transactions/bulk",
+            submit_transactions_bulk_endpoint,
+        ),
+        ApiRoute::get(
             "/api/v2/transactions/pending",
-            get(get_pending_transactions_endpoint),
-        )
+            get_pending_transactions_endpoint,
+        ),
+        ApiRoute::get(
+            "/api/v2/transactions/{transaction_id}/receipt",
+            get_transaction_receipt_endpoint,
+        ),
         // Batch operations
-        .route("/api/v2/batches", post(create_batch_endpoint))
-        .route("/api/v2/batches", get(get_batches_endpoint))
-        .route("/api/v2/batches/{batch_id}", get(get_batch_endpoint))
-        .route(
+        ApiRoute::post("/api/v2/batches", create_batch_endpoint),
+        ApiRoute::get("/api/v2/batches", get_batches_endpoint),
+        ApiRoute::get("/api/v2/batches/{batch_id}", get_batch_endpoint),
+        ApiRoute::post(
             "/api/v2/batches/{batch_id}/proof",
-            post(update_batch_proof_endpoint),
-        )
-        .route("/api/v2/batches/trigger", post(trigger_batch_endpoint))
-        .route(
-            "/api/v2/batches/stats",
-            get(get_batch_processor_stats_endpoint),
-        )
+            update_batch_proof_endpoint,
+        ),
+        ApiRoute::get(
+            "/api/v2/batches/{batch_id}/proof-bundle",
+            get_proof_bundle_endpoint,
+        ),
+        ApiRoute::post(
+            "/api/v2/batches/{batch_id}/retry-proof",
+            retry_batch_proof_endpoint,
+        ),
+        ApiRoute::post("/api/v2/batches/{batch_id}/recover", recover_batch_endpoint),
+        ApiRoute::post("/api/v2/batches/trigger", trigger_batch_endpoint),
+        ApiRoute::get("/api/v2/batches/stats", get_batch_processor_stats_endpoint),
         // State operations
-        .route("/api/v2/state/current", get(get_current_state_endpoint))
-        .route(
+        ApiRoute::get("/api/v2/state/current", get_current_state_endpoint),
+        ApiRoute::get(
             "/api/v2/state/{batch_id}/contract",
-            get(get_contract_data_endpoint),
-        )
+            get_contract_data_endpoint,
+        ),
+        ApiRoute::post("/api/v2/state/rebuild", rebuild_state_endpoint),
+        ApiRoute::get("/api/v2/reconciliation", reconciliation_endpoint),
+        ApiRoute::post(
+            "/api/v2/reconciliation/repair",
+            reconciliation_repair_endpoint,
+        ),
+        // Event feed
+        ApiRoute::get("/api/v2/events", crate::events::event_feed_endpoint),
+        // API documentation
+        ApiRoute::get("/api/v2/openapi.json", openapi_json),
+    ]
+}
+
+/// Create the main API router with all batch processing endpoints
+pub fn create_router(state: ApiState) -> Router {
+    api_routes()
+        .into_iter()
+        .fold(Router::new(), |router, route| {
+            router.route(route.path, route.handler)
+        })
         .with_state(state)
+        .merge(Redoc::with_url("/api/v2/docs", ApiDoc::openapi()))
+}
+
+// ============================================================================
+// OPENAPI DOCUMENT
+// ============================================================================
+
+/// OpenAPI document generated from the handler annotations below
+///
+/// Every route in `api_routes` must be listed in `paths`;
+/// `tests::every_route_has_an_openapi_entry` enforces this.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Batch Processing API", description = "Transaction batching and ZK proof API"),
+    paths(
+        health_check,
+        api_info,
+        openapi_json,
+        submit_transaction_endpoint,
+        list_transactions_endpoint,
+        submit_transactions_bulk_endpoint,
+        get_pending_transactions_endpoint,
+        get_transaction_receipt_endpoint,
+        create_batch_endpoint,
+        get_batches_endpoint,
+        get_batch_endpoint,
+        update_batch_proof_endpoint,
+        get_proof_bundle_endpoint,
+        retry_batch_proof_endpoint,
+        recover_batch_endpoint,
+        trigger_batch_endpoint,
+        get_batch_processor_stats_endpoint,
+        get_current_state_endpoint,
+        get_contract_data_endpoint,
+        rebuild_state_endpoint,
+        reconciliation_endpoint,
+        reconciliation_repair_endpoint,
+        crate::events::event_feed_endpoint,
+    ),
+    tags(
+        (name = "transactions", description = "Transaction submission and listing"),
+        (name = "batches", description = "Batch creation, proofs and processing"),
+        (name = "state", description = "Counter state and contract data"),
+        (name = "system", description = "Health, metadata and documentation"),
+    )
+)]
+pub struct ApiDoc;
+
+/// List the documented operations as `EndpointInfo` entries
+fn endpoints_from_spec(spec: &utoipa::openapi::OpenApi) -> Vec<EndpointInfo> {
+    spec.paths
+        .paths
+        .iter()
+        .flat_map(|(path, item)| {
+            [
+                ("GET", &item.get),
+                ("POST", &item.post),
+                ("PUT", &item.put),
+                ("PATCH", &item.patch),
+                ("DELETE", &item.delete),
+            ]
+            .into_iter()
+            .filter_map(move |(method, operation)| {
+                operation.as_ref().map(|operation| EndpointInfo {
+                    method: method.to_string(),
+                    path: path.clone(),
+                    description: operation.summary.clone().unwrap_or_default(),
+                })
+            })
+        })
+        .collect()
 }
 
 // ============================================================================
@@ -236,6 +749,15 @@ pub fn create_router(state: ApiState) -> Router {
 // ============================================================================
 
 /// Health check endpoint
+#[utoipa::path(
+    get,
+    path = "/api/v2/health",
+    tag = "system",
+    summary = "Health check",
+    responses(
+        (status = 200, description = "Service and database status", body = HealthResponse),
+    )
+)]
 #[instrument(skip(state), level = "info")]
 async fn health_check(
     State(state): State<ApiState>,
@@ -251,18 +773,31 @@ async fn health_check(
         }
     };
 
+    let mut chains = Vec::with_capacity(state.chain_health.len());
+    for monitor in &state.chain_health {
+        chains.push(ChainHealthInfo::new(monitor, monitor.latest().await));
+    }
+    let chains_healthy = chains.iter().all(|chain| chain.healthy);
+
     let response = HealthResponse {
-        status: if db_connected {
+        status: if !db_connected {
+            "unhealthy".to_string()
+        } else if chains_healthy {
             "healthy".to_string()
         } else {
-            "unhealthy".to_string()
+            "degraded".to_string()
         },
         timestamp: Utc::now(),
         database_connected: db_connected,
+        chains,
     };
 
     if db_connected {
-        info!("✅ API: Health check passed");
+        if chains_healthy {
+            info!("✅ API: Health check passed");
+        } else {
+            warn!("⚠️ API: Health check degraded - settlement network issues");
+        }
         Ok(Json(response))
     } else {
         error!("❌ API: Health check failed - database not connected");
@@ -274,57 +809,18 @@ async fn health_check(
 }
 
 /// API information endpoint
+#[utoipa::path(
+    get,
+    path = "/api/v2/info",
+    tag = "system",
+    summary = "API information and endpoint list",
+    responses((status = 200, description = "Server metadata", body = ApiInfoResponse))
+)]
 #[instrument(skip(state), level = "info")]
 async fn api_info(State(state): State<ApiState>) -> Json<ApiInfoResponse> {
     info!("📋 API: API info requested");
 
-    let endpoints = vec![
-        EndpointInfo {
-            method: "GET".to_string(),
-            path: "/api/v2/health".to_string(),
-            description: "Health check".to_string(),
-        },
-        EndpointInfo {
-            method: "POST".to_string(),
-            path: "/api/v2/transactions".to_string(),
-            description: "Submit a new transaction".to_string(),
-        },
-        EndpointInfo {
-            method: "GET".to_string(),
-            path: "/api/v2/transactions/pending".to_string(),
-            description: "Get pending (unbatched) transactions".to_string(),
-        },
-        EndpointInfo {
-            method: "POST".to_string(),
-            path: "/api/v2/batches".to_string(),
-            description: "Create a new batch from pending transactions".to_string(),
-        },
-        EndpointInfo {
-            method: "GET".to_string(),
-            path: "/api/v2/batches".to_string(),
-            description: "List historical batches".to_string(),
-        },
-        EndpointInfo {
-            method: "GET".to_string(),
-            path: "/api/v2/batches/{batch_id}".to_string(),
-            description: "Get specific batch details".to_string(),
-        },
-        EndpointInfo {
-            method: "POST".to_string(),
-            path: "/api/v2/batches/{batch_id}/proof".to_string(),
-            description: "Update batch with ZK proof".to_string(),
-        },
-        EndpointInfo {
-            method: "GET".to_string(),
-            path: "/api/v2/state/current".to_string(),
-            description: "Get current counter state".to_string(),
-        },
-        EndpointInfo {
-            method: "GET".to_string(),
-            path: "/api/v2/state/{batch_id}/contract".to_string(),
-            description: "Get contract submission data (dry run)".to_string(),
-        },
-    ];
+    let endpoints = endpoints_from_spec(&ApiDoc::openapi());
 
     let response = ApiInfoResponse {
         server_name: state.config.server_name.clone(),
@@ -337,25 +833,88 @@ async fn api_info(State(state): State<ApiState>) -> Json<ApiInfoResponse> {
     Json(response)
 }
 
+/// OpenAPI document for this API
+#[utoipa::path(
+    get,
+    path = "/api/v2/openapi.json",
+    tag = "system",
+    summary = "OpenAPI 3.1 document (browsable at /api/v2/docs)",
+    responses((status = 200, description = "OpenAPI document", body = Object))
+)]
+async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
 /// Submit a new transaction
-#[instrument(skip(state), level = "info")]
+///
+/// An idempotency key may be supplied via the `Idempotency-Key` header or the
+/// `client_tx_id` body field. Resubmitting with a known key returns the original
+/// response without enqueueing the amount again; reusing a key with a different
+/// amount is rejected with 409.
+#[utoipa::path(
+    post,
+    path = "/api/v2/transactions",
+    tag = "transactions",
+    summary = "Submit a new transaction",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Client-supplied idempotency key"),
+    ),
+    request_body = SubmitTransactionRequest,
+    responses(
+        (status = 200, description = "Transaction enqueued (or replayed)", body = SubmitTransactionResponse),
+        (status = 400, description = "Malformed idempotency key", body = String),
+        (status = 409, description = "Idempotency key reused with a different amount", body = String),
+        (status = 500, description = "Database error", body = String),
+    )
+)]
+#[instrument(skip(state, headers), level = "info")]
 async fn submit_transaction_endpoint(
     State(state): State<ApiState>,
+    headers: HeaderMap,
     Json(request): Json<SubmitTransactionRequest>,
 ) -> Result<Json<SubmitTransactionResponse>, (StatusCode, String)> {
     info!("💰 API: Submitting transaction: amount={}", request.amount);
 
-    match submit_transaction(&state.pool, request.amount).await {
-        Ok(transaction) => {
-            let response = SubmitTransactionResponse {
+    let client_tx_id = resolve_idempotency_key(&headers, request.client_tx_id.as_deref())?;
+
+    match submit_transaction_idempotent(&state.pool, request.amount, client_tx_id.as_deref()).await
+    {
+        Ok(submitted) => {
+            let transaction = submitted.transaction;
+
+            if submitted.replayed {
+                if transaction.amount != request.amount {
+                    warn!(
+                        "Idempotency key reused with different amount: key={:?}, original={}, requested={}",
+                        submitted.client_tx_id, transaction.amount, request.amount
+                    );
+                    return Err((
+                        StatusCode::CONFLICT,
+                        format!(
+                            "Idempotency key already used for transaction {} with a different amount",
+                            transaction.id
+                        ),
+                    ));
+                }
+                info!(
+                    "♻️ API: Idempotent replay of transaction: id={}",
+                    transaction.id
+                );
+            } else {
+                info!("✅ API: Transaction submitted: id={}", transaction.id);
+                state.events.publish(ApiEventKind::TransactionSubmitted {
+                    transaction_id: transaction.id,
+                    amount: transaction.amount,
+                });
+            }
+
+            Ok(Json(SubmitTransactionResponse {
                 transaction_id: transaction.id,
                 amount: transaction.amount,
                 status: "pending".to_string(),
                 created_at: transaction.created_at,
-            };
-
-            info!("✅ API: Transaction submitted: id={}", transaction.id);
-            Ok(Json(response))
+                client_tx_id: submitted.client_tx_id,
+            }))
         }
         Err(e) => {
             error!("Failed to submit transaction: {}", e);
@@ -367,7 +926,307 @@ async fn submit_transaction_endpoint(
     }
 }
 
+/// Pick the idempotency key from the header or body, rejecting conflicting or malformed keys
+fn resolve_idempotency_key(
+    headers: &HeaderMap,
+    body_key: Option<&str>,
+) -> Result<Option<String>, (StatusCode, String)> {
+    let header_key = headers
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .map(|value| {
+            value.to_str().map_err(|_| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    "Idempotency-Key header must be visible ASCII".to_string(),
+                )
+            })
+        })
+        .transpose()?;
+
+    let key = match (header_key, body_key) {
+        (Some(header), Some(body)) if header != body => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Idempotency-Key header and client_tx_id differ".to_string(),
+            ));
+        }
+        (Some(key), _) | (None, Some(key)) => key.trim(),
+        (None, None) => return Ok(None),
+    };
+
+    if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Idempotency key must be 1-{} characters",
+                MAX_IDEMPOTENCY_KEY_LEN
+            ),
+        ));
+    }
+
+    Ok(Some(key.to_string()))
+}
+
+/// Submit many transactions in one request
+///
+/// The body is either a JSON array of `{amount, client_tx_id?}` objects or, with
+/// `Content-Type: application/x-ndjson`, one such object per line. All rows are
+/// inserted with a single statement inside one database transaction. In
+/// `atomic` mode (the default) any failed item rolls everything back and the
+/// response is 422; in `best_effort` mode the valid items are committed.
+#[utoipa::path(
+    post,
+    path = "/api/v2/transactions/bulk",
+    tag = "transactions",
+    summary = "Submit many transactions (JSON array or NDJSON)",
+    params(BulkSubmitQuery),
+    request_body(
+        description = "JSON array of items, or one item per line as NDJSON",
+        content(
+            (Vec<BulkTransactionItem> = "application/json"),
+            (BulkTransactionItem = "application/x-ndjson"),
+        )
+    ),
+    responses(
+        (status = 200, description = "Submission committed", body = BulkSubmitResponse),
+        (status = 400, description = "Unparseable or empty body", body = String),
+        (status = 413, description = "Too many items in one request", body = String),
+        (status = 422, description = "Atomic submission rejected and rolled back", body = BulkSubmitResponse),
+        (status = 429, description = "Rate limit exceeded", body = String),
+        (status = 500, description = "Database error", body = String),
+    )
+)]
+#[instrument(skip(state, headers, body), level = "info")]
+async fn submit_transactions_bulk_endpoint(
+    State(state): State<ApiState>,
+    Query(query): Query<BulkSubmitQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<BulkSubmitResponse>), (StatusCode, String)> {
+    let parsed = parse_bulk_body(&headers, &body)?;
+    let item_count = parsed.len();
+    info!(
+        "📦 API: Bulk submitting {} transactions (mode={:?})",
+        item_count, query.mode
+    );
+
+    if item_count == 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Bulk submission contains no transactions".to_string(),
+        ));
+    }
+    let max_items = state.config.validation.max_batch_size;
+    if item_count > max_items {
+        return Err((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!(
+                "Bulk submission of {} transactions exceeds the limit of {}",
+                item_count, max_items
+            ),
+        ));
+    }
+    if let Some(limiter) = &state.rate_limiter {
+        let client_id = client_id_from_headers(&headers);
+        if let Err(e) = limiter.check_rate_limit(&client_id, bulk_token_cost(item_count)) {
+            warn!("Bulk submission rate limited: client={}", e.client_id);
+            return Err((
+                StatusCode::TOO_MANY_REQUESTS,
+                format!(
+                    "Rate limit of {} per {}s exceeded; retry after {}s",
+                    e.limit, e.window_seconds, e.retry_after_seconds
+                ),
+            ));
+        }
+    }
+
+    // Validate items up front; only the valid ones reach the database.
+    let mut results: Vec<BulkItemResult> = Vec::with_capacity(item_count);
+    let mut valid: Vec<(usize, NewTransaction)> = Vec::with_capacity(item_count);
+    let mut seen_keys = HashSet::new();
+    for (index, item) in parsed.into_iter().enumerate() {
+        let outcome = item.and_then(|mut item| {
+            if let Some(key) = item.client_tx_id.take() {
+                let key = key.trim().to_string();
+                if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+                    return Err(format!(
+                        "client_tx_id must be 1-{} characters",
+                        MAX_IDEMPOTENCY_KEY_LEN
+                    ));
+                }
+                if !seen_keys.insert(key.clone()) {
+                    return Err(format!("duplicate client_tx_id {key} in request"));
+                }
+                item.client_tx_id = Some(key);
+            }
+            Ok(item)
+        });
+        results.push(BulkItemResult {
+            index,
+            transaction_id: None,
+            client_tx_id: outcome
+                .as_ref()
+                .ok()
+                .and_then(|item| item.client_tx_id.clone()),
+            replayed: false,
+            error: outcome.as_ref().err().cloned(),
+        });
+        if let Ok(item) = outcome {
+            valid.push((index, item));
+        }
+    }
+
+    let mut tx = state.pool.begin().await.map_err(|e| {
+        error!("Failed to begin bulk submission: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to submit transactions: {}", e),
+        )
+    })?;
+
+    let items: Vec<NewTransaction> = valid.iter().map(|(_, item)| item.clone()).collect();
+    let submitted = submit_transactions_bulk(&mut *tx, &items)
+        .await
+        .map_err(|e| {
+            error!("Failed to bulk submit transactions: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to submit transactions: {}", e),
+            )
+        })?;
+
+    let mut fresh = Vec::new();
+    for ((index, item), submitted) in valid.iter().zip(submitted) {
+        let result = &mut results[*index];
+        if submitted.replayed && submitted.transaction.amount != item.amount {
+            result.error = Some(format!(
+                "client_tx_id already used for transaction {} with a different amount",
+                submitted.transaction.id
+            ));
+            continue;
+        }
+        result.transaction_id = Some(submitted.transaction.id);
+        result.replayed = submitted.replayed;
+        if !submitted.replayed {
+            fresh.push((submitted.transaction.id, submitted.transaction.amount));
+        }
+    }
+
+    let failed = results.iter().filter(|r| r.error.is_some()).count();
+    let committed = failed == 0 || query.mode == BulkSubmitMode::BestEffort;
+    let finish = if committed {
+        tx.commit().await
+    } else {
+        tx.rollback().await
+    };
+    finish.map_err(|e| {
+        error!("Failed to finish bulk submission: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to submit transactions: {}", e),
+        )
+    })?;
+
+    if !committed {
+        // Nothing was written, so no item has a transaction to point at.
+        for result in &mut results {
+            result.transaction_id = None;
+            result.replayed = false;
+        }
+    }
+
+    let replayed = results.iter().filter(|r| r.replayed).count();
+    let response = BulkSubmitResponse {
+        mode: query.mode,
+        committed,
+        submitted: results
+            .iter()
+            .filter(|r| r.transaction_id.is_some() && !r.replayed)
+            .count(),
+        replayed,
+        failed,
+        results,
+    };
+
+    if committed {
+        for (transaction_id, amount) in fresh {
+            state.events.publish(ApiEventKind::TransactionSubmitted {
+                transaction_id,
+                amount,
+            });
+        }
+        info!(
+            "✅ API: Bulk submission committed: submitted={}, replayed={}, failed={}",
+            response.submitted, response.replayed, response.failed
+        );
+        Ok((StatusCode::OK, Json(response)))
+    } else {
+        warn!(
+            "Bulk submission rejected: {} of {} items failed",
+            failed, item_count
+        );
+        Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(response)))
+    }
+}
+
+/// Parse a bulk body into per-item results, keeping item-level parse errors
+fn parse_bulk_body(
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<Vec<Result<NewTransaction, String>>, (StatusCode, String)> {
+    let is_ndjson = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            value.starts_with("application/x-ndjson") || value.starts_with("application/jsonl")
+        });
+
+    if is_ndjson {
+        let text = std::str::from_utf8(body).map_err(|_| {
+            (
+                StatusCode::BAD_REQUEST,
+                "NDJSON body must be UTF-8".to_string(),
+            )
+        })?;
+        return Ok(text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                serde_json::from_str::<BulkTransactionItem>(line)
+                    .map(NewTransaction::from)
+                    .map_err(|e| format!("invalid item: {e}"))
+            })
+            .collect());
+    }
+
+    let values: Vec<serde_json::Value> = serde_json::from_slice(body).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Body must be a JSON array of transactions: {}", e),
+        )
+    })?;
+    Ok(values
+        .into_iter()
+        .map(|value| {
+            serde_json::from_value::<BulkTransactionItem>(value)
+                .map(NewTransaction::from)
+                .map_err(|e| format!("invalid item: {e}"))
+        })
+        .collect())
+}
+
 /// Get pending transactions
+#[utoipa::path(
+    get,
+    path = "/api/v2/transactions/pending",
+    tag = "transactions",
+    summary = "Get pending (unbatched) transactions",
+    responses(
+        (status = 200, description = "Pending transactions", body = PendingTransactionsResponse),
+        (status = 500, description = "Database error", body = String),
+    )
+)]
 #[instrument(skip(state), level = "info")]
 async fn get_pending_transactions_endpoint(
     State(state): State<ApiState>,
@@ -382,6 +1241,7 @@ async fn get_pending_transactions_endpoint(
                 .map(|t| TransactionInfo {
                     id: t.id,
                     amount: t.amount,
+                    included_in_batch_id: t.included_in_batch_id,
                     created_at: t.created_at,
                 })
                 .collect();
@@ -408,7 +1268,168 @@ async fn get_pending_transactions_endpoint(
     }
 }
 
+/// Prove that a transaction was included in a batch
+///
+/// The receipt holds the transaction's nullifier, its IMT membership proof
+/// against the batch's committed root, where to download the batch's proof
+/// bundle, and the batch's settlements. `cli verify-receipt` checks it.
+#[utoipa::path(
+    get,
+    path = "/api/v2/transactions/{transaction_id}/receipt",
+    tag = "transactions",
+    summary = "Get an inclusion receipt for a transaction",
+    params(("transaction_id" = i32, Path, description = "Transaction id")),
+    responses(
+        (status = 200, description = "Transaction receipt", body = Object),
+        (status = 404, description = "Transaction not found, or its batch predates receipts", body = String),
+        (status = 409, description = "Transaction is not batched yet", body = String),
+        (status = 500, description = "Database error or inconsistent proof", body = String),
+    )
+)]
+#[instrument(skip(state), level = "info")]
+async fn get_transaction_receipt_endpoint(
+    State(state): State<ApiState>,
+    Path(transaction_id): Path<i32>,
+) -> Result<Json<TransactionReceipt>, (StatusCode, String)> {
+    info!(
+        "🧾 API: Receipt requested for transaction: id={}",
+        transaction_id
+    );
+
+    let db_error = |e: SqlxError| {
+        error!(
+            "Failed to build receipt for transaction {}: {}",
+            transaction_id, e
+        );
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to build receipt: {}", e),
+        )
+    };
+
+    let transaction = get_transaction_by_id(&state.pool, transaction_id)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Transaction not found: {}", transaction_id),
+            )
+        })?;
+    let batch_id = transaction.included_in_batch_id.ok_or_else(|| {
+        (
+            StatusCode::CONFLICT,
+            format!("Transaction {} is not batched yet", transaction_id),
+        )
+    })?;
+
+    let inclusion = get_transaction_inclusion(&state.pool, transaction_id)
+        .await
+        .map_err(db_error)?
+        .filter(|inclusion| inclusion.batch_id == batch_id)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!(
+                    "No inclusion proof for transaction {} in batch {}",
+                    transaction_id, batch_id
+                ),
+            )
+        })?;
+
+    let batch = get_batch_by_id(&state.pool, batch_id)
+        .await
+        .map_err(db_error)?;
+    let submissions = get_batch_submissions(&state.pool, &[batch_id])
+        .await
+        .map_err(db_error)?;
+
+    let invalid = |what: &str| {
+        error!(
+            "Stored inclusion proof of transaction {} has an invalid {}",
+            transaction_id, what
+        );
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Stored inclusion proof has an invalid {}", what),
+        )
+    };
+    let siblings = inclusion
+        .siblings
+        .into_iter()
+        .map(<[u8; 32]>::try_from)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| invalid("sibling hash"))?;
+    let batch_root =
+        <[u8; 32]>::try_from(inclusion.merkle_root).map_err(|_| invalid("Merkle root"))?;
+
+    let membership = ImtMembershipProof {
+        leaf_index: inclusion.leaf_index,
+        value: inclusion.nullifier_value,
+        next_index: inclusion.next_index,
+        next_value: inclusion.next_value,
+        siblings,
+        path_indices: inclusion.path_indices,
+    };
+    let mut receipt = TransactionReceipt::new(
+        transaction.id,
+        transaction.amount,
+        transaction.created_at.timestamp(),
+        batch.id,
+        membership,
+        batch_root,
+        batch.final_counter_value,
+    );
+    if batch.proof_status == "proven" {
+        receipt.proof_bundle = Some(format!("/api/v2/batches/{}/proof-bundle", batch.id));
+    }
+    receipt.proof_status = batch.proof_status;
+    receipt.settlements = submissions
+        .into_iter()
+        .map(|s| ReceiptSettlement {
+            target: s.target,
+            chain_id: s.chain_id,
+            status: s.status,
+            transaction_hash: s.transaction_hash,
+            block_number: s.block_number,
+        })
+        .collect();
+
+    // Never hand out a receipt that does not check out
+    receipt.verify().map_err(|e| {
+        error!(
+            "Receipt for transaction {} is invalid: {}",
+            transaction_id, e
+        );
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!(
+                "Receipt for transaction {} is invalid: {}",
+                transaction_id, e
+            ),
+        )
+    })?;
+
+    info!(
+        "✅ API: Receipt for transaction {} in batch {} ({})",
+        transaction_id, batch_id, receipt.proof_status
+    );
+    Ok(Json(receipt))
+}
+
 /// Create a new batch using unified ADS-integrated workflow
+#[utoipa::path(
+    post,
+    path = "/api/v2/batches",
+    tag = "batches",
+    summary = "Create a new batch from pending transactions",
+    request_body = CreateBatchRequest,
+    responses(
+        (status = 200, description = "Batch created", body = CreateBatchResponse),
+        (status = 400, description = "No pending transactions", body = String),
+        (status = 500, description = "Batch creation failed", body = String),
+    )
+)]
 #[instrument(skip(state), level = "info")]
 async fn create_batch_endpoint(
     State(state): State<ApiState>,
@@ -456,24 +1477,10 @@ async fn create_batch_endpoint(
                 hex::encode(&result.merkle_root[..8])
             );
 
-            // Trigger proof generation for the newly created batch
-            if state.batch_processor.is_some() {
-                info!(
-                    "🚀 Triggering proof generation for batch {}",
-                    result.batch_id
-                );
-                tokio::spawn({
-                    let pool = state.pool.clone();
-                    let batch_id = result.batch_id;
-                    async move {
-                        if let Err(e) = crate::batch_processor::BackgroundBatchProcessor::generate_proof_for_batch(&pool, batch_id).await {
-                            error!("Failed to generate proof for unified batch {}: {}", batch_id, e);
-                        }
-                    }
-                });
-            } else {
-                warn!("Batch processor not available - proof generation skipped");
-            }
+            state.events.publish(ApiEventKind::BatchCreated {
+                batch_id: result.batch_id,
+                transaction_count: result.transaction_count,
+            });
 
             Ok(Json(response))
         }
@@ -494,34 +1501,128 @@ async fn create_batch_endpoint(
     }
 }
 
-/// Get all batches (paginated)
+/// List transactions with filters (cursor-paginated)
+#[utoipa::path(
+    get,
+    path = "/api/v2/transactions",
+    tag = "transactions",
+    summary = "List transactions (filtered, cursor-paginated)",
+    params(TransactionListQuery),
+    responses(
+        (status = 200, description = "One page of transactions", body = TransactionListResponse),
+        (status = 400, description = "Invalid filter or cursor", body = String),
+        (status = 500, description = "Database error", body = String),
+    )
+)]
+#[instrument(skip(state), level = "info")]
+async fn list_transactions_endpoint(
+    State(state): State<ApiState>,
+    Query(params): Query<TransactionListQuery>,
+) -> Result<Json<TransactionListResponse>, (StatusCode, String)> {
+    info!("📋 API: Listing transactions: {:?}", params);
+
+    let filter = TransactionListFilter {
+        limit: page_size(params.limit),
+        after_id: parse_cursor(params.cursor.as_deref())?,
+        order: params.order.unwrap_or_default(),
+        status: params.status,
+        created_after: params.since,
+        created_before: params.until,
+        min_amount: params.min_amount,
+        max_amount: params.max_amount,
+        batch_id: params.batch_id,
+    };
+
+    match list_transactions(&state.pool, &filter).await {
+        Ok(page) => {
+            let transactions: Vec<TransactionInfo> = page
+                .items
+                .into_iter()
+                .map(|t| TransactionInfo {
+                    id: t.id,
+                    amount: t.amount,
+                    included_in_batch_id: t.included_in_batch_id,
+                    created_at: t.created_at,
+                })
+                .collect();
+
+            let response = TransactionListResponse {
+                total_count: transactions.len(),
+                transactions,
+                next_cursor: page.next_cursor.map(|id| id.to_string()),
+            };
+
+            info!("✅ API: Found {} transactions", response.total_count);
+            Ok(Json(response))
+        }
+        Err(e) => {
+            error!("Failed to list transactions: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to list transactions: {}", e),
+            ))
+        }
+    }
+}
+
+/// List batches with filters (cursor-paginated)
+#[utoipa::path(
+    get,
+    path = "/api/v2/batches",
+    tag = "batches",
+    summary = "List historical batches (filtered, cursor-paginated)",
+    params(BatchListQuery),
+    responses(
+        (status = 200, description = "One page of batches", body = BatchListResponse),
+        (status = 400, description = "Invalid filter or cursor", body = String),
+        (status = 500, description = "Database error", body = String),
+    )
+)]
 #[instrument(skip(state), level = "info")]
 async fn get_batches_endpoint(
     State(state): State<ApiState>,
     Query(params): Query<BatchListQuery>,
 ) -> Result<Json<BatchListResponse>, (StatusCode, String)> {
-    let limit = params.limit.unwrap_or(20);
-    info!("📋 API: Getting batches with limit: {}", limit);
+    info!("📋 API: Listing batches: {:?}", params);
+
+    const ALLOWED_STATUSES: &[&str] = &["pending", "proven", "failed", "unwound"];
+    if let Some(status) = &params.status {
+        if !ALLOWED_STATUSES.contains(&status.as_str()) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Invalid status '{}'. Allowed values: {}",
+                    status,
+                    ALLOWED_STATUSES.join(", ")
+                ),
+            ));
+        }
+    }
+
+    let filter = BatchListFilter {
+        limit: page_size(params.limit),
+        after_id: parse_cursor(params.cursor.as_deref())?,
+        order: params.order.unwrap_or_default(),
+        proof_status: params.status,
+        created_after: params.since,
+        created_before: params.until,
+    };
 
-    match get_all_batches(&state.pool, Some(limit)).await {
-        Ok(batches) => {
-            let batch_infos: Vec<BatchInfo> = batches
+    match list_batches(&state.pool, &filter).await {
+        Ok(page) => {
+            let batch_ids: Vec<i32> = page.items.iter().map(|b| b.id).collect();
+            let submissions = load_submissions(&state.pool, &batch_ids).await?;
+
+            let batch_infos: Vec<BatchInfo> = page
+                .items
                 .into_iter()
-                .map(|b| BatchInfo {
-                    id: b.id,
-                    previous_counter_value: b.previous_counter_value,
-                    final_counter_value: b.final_counter_value,
-                    transaction_count: b.transaction_ids.len(),
-                    proof_status: b.proof_status,
-                    sindri_proof_id: b.sindri_proof_id,
-                    created_at: b.created_at,
-                    proven_at: b.proven_at,
-                })
+                .map(|b| BatchInfo::new(b, &submissions))
                 .collect();
 
             let response = BatchListResponse {
                 total_count: batch_infos.len(),
                 batches: batch_infos,
+                next_cursor: page.next_cursor.map(|id| id.to_string()),
             };
 
             info!("✅ API: Found {} batches", response.total_count);
@@ -537,7 +1638,50 @@ async fn get_batches_endpoint(
     }
 }
 
+/// Settlement rows for the given batches, as an API error on failure
+async fn load_submissions(
+    pool: &PgPool,
+    batch_ids: &[i32],
+) -> Result<Vec<BatchSubmission>, (StatusCode, String)> {
+    get_batch_submissions(pool, batch_ids).await.map_err(|e| {
+        error!("Failed to get batch submissions: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to get batch submissions: {}", e),
+        )
+    })
+}
+
+/// Clamp a requested page size to `[1, MAX_PAGE_SIZE]`
+fn page_size(limit: Option<i32>) -> i64 {
+    limit
+        .map_or(DEFAULT_PAGE_SIZE, i64::from)
+        .clamp(1, MAX_PAGE_SIZE)
+}
+
+/// Decode an opaque listing cursor
+fn parse_cursor(cursor: Option<&str>) -> Result<Option<i32>, (StatusCode, String)> {
+    cursor
+        .map(|c| {
+            c.parse::<i32>()
+                .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid cursor: {}", c)))
+        })
+        .transpose()
+}
+
 /// Get specific batch by ID
+#[utoipa::path(
+    get,
+    path = "/api/v2/batches/{batch_id}",
+    tag = "batches",
+    summary = "Get specific batch details",
+    params(("batch_id" = i32, Path, description = "Batch id")),
+    responses(
+        (status = 200, description = "Batch details", body = BatchInfo),
+        (status = 404, description = "Batch not found", body = String),
+        (status = 500, description = "Database error", body = String),
+    )
+)]
 #[instrument(skip(state), level = "info")]
 async fn get_batch_endpoint(
     State(state): State<ApiState>,
@@ -547,18 +1691,10 @@ async fn get_batch_endpoint(
 
     match get_batch_by_id(&state.pool, batch_id).await {
         Ok(batch) => {
-            let batch_info = BatchInfo {
-                id: batch.id,
-                previous_counter_value: batch.previous_counter_value,
-                final_counter_value: batch.final_counter_value,
-                transaction_count: batch.transaction_ids.len(),
-                proof_status: batch.proof_status,
-                sindri_proof_id: batch.sindri_proof_id,
-                created_at: batch.created_at,
-                proven_at: batch.proven_at,
-            };
+            let submissions = load_submissions(&state.pool, &[batch.id]).await?;
+            let batch_info = BatchInfo::new(batch, &submissions);
 
-            info!("✅ API: Found batch: id={}", batch.id);
+            info!("✅ API: Found batch: id={}", batch_info.id);
             Ok(Json(batch_info))
         }
         Err(SqlxError::RowNotFound) => {
@@ -585,6 +1721,19 @@ async fn get_batch_endpoint(
 /// - When status is "proven", merkle_root is required and must be exactly 32 bytes
 /// - When status is not "proven", merkle_root is optional but if provided must be valid hex and 32 bytes
 /// - Hex values can optionally start with "0x" prefix
+#[utoipa::path(
+    post,
+    path = "/api/v2/batches/{batch_id}/proof",
+    tag = "batches",
+    summary = "Update batch with ZK proof",
+    params(("batch_id" = i32, Path, description = "Batch id")),
+    request_body = UpdateBatchProofRequest,
+    responses(
+        (status = 200, description = "Updated batch", body = BatchInfo),
+        (status = 400, description = "Invalid status or merkle root", body = String),
+        (status = 500, description = "Database error", body = String),
+    )
+)]
 #[instrument(skip(state), level = "info")]
 async fn update_batch_proof_endpoint(
     State(state): State<ApiState>,
@@ -682,6 +1831,8 @@ async fn update_batch_proof_endpoint(
     .await
     {
         Ok(()) => {
+            state.events.publish_proof_status(batch_id, &request.status);
+
             // Store Merkle root if validated and provided
             if let Some(merkle_root) = validated_merkle_root {
                 if let Err(e) = store_ads_state_commit(&state.pool, batch_id, &merkle_root).await {
@@ -696,16 +1847,8 @@ async fn update_batch_proof_endpoint(
             // Return updated batch info
             match get_batch_by_id(&state.pool, batch_id).await {
                 Ok(batch) => {
-                    let batch_info = BatchInfo {
-                        id: batch.id,
-                        previous_counter_value: batch.previous_counter_value,
-                        final_counter_value: batch.final_counter_value,
-                        transaction_count: batch.transaction_ids.len(),
-                        proof_status: batch.proof_status,
-                        sindri_proof_id: batch.sindri_proof_id,
-                        created_at: batch.created_at,
-                        proven_at: batch.proven_at,
-                    };
+                    let submissions = load_submissions(&state.pool, &[batch.id]).await?;
+                    let batch_info = BatchInfo::new(batch, &submissions);
 
                     info!("✅ API: Batch proof updated: id={}", batch_id);
                     Ok(Json(batch_info))
@@ -730,6 +1873,16 @@ async fn update_batch_proof_endpoint(
 }
 
 /// Get current counter state
+#[utoipa::path(
+    get,
+    path = "/api/v2/state/current",
+    tag = "state",
+    summary = "Get current counter state",
+    responses(
+        (status = 200, description = "Current counter state", body = CurrentStateResponse),
+        (status = 500, description = "Database error", body = String),
+    )
+)]
 #[instrument(skip(state), level = "info")]
 async fn get_current_state_endpoint(
     State(state): State<ApiState>,
@@ -745,11 +1898,24 @@ async fn get_current_state_endpoint(
                 None
             };
 
+            // The live tree may be ahead of the last committed root
+            let commitment = match state.ads_service.read().await.get_state_commitment().await {
+                Ok(commitment) => Some(commitment),
+                Err(e) => {
+                    warn!("Failed to read IMT state: {}", e);
+                    None
+                }
+            };
+
             let response = CurrentStateResponse {
                 counter_value: state_info.counter_value,
                 has_merkle_root: state_info.merkle_root.is_some(),
                 last_batch_id: state_info.last_batch_id,
                 last_proven_batch_id,
+                imt_root: commitment
+                    .as_ref()
+                    .map(|c| format!("0x{}", hex::encode(c.root_hash))),
+                nullifier_count: commitment.map(|c| c.nullifier_count),
             };
 
             info!(
@@ -769,6 +1935,18 @@ async fn get_current_state_endpoint(
 }
 
 /// Get contract submission data (dry run)
+#[utoipa::path(
+    get,
+    path = "/api/v2/state/{batch_id}/contract",
+    tag = "state",
+    summary = "Get contract submission data (dry run)",
+    params(("batch_id" = i32, Path, description = "Batch id")),
+    responses(
+        (status = 200, description = "Public and private contract inputs", body = Object),
+        (status = 404, description = "Batch not found or not proven", body = String),
+        (status = 500, description = "Database error", body = String),
+    )
+)]
 #[instrument(skip(state), level = "info")]
 async fn get_contract_data_endpoint(
     State(state): State<ApiState>,
@@ -798,7 +1976,167 @@ async fn get_contract_data_endpoint(
     }
 }
 
+/// Download the proof of a batch as a portable proof bundle
+///
+/// Returns CBOR when the request accepts `application/cbor`, JSON otherwise.
+#[utoipa::path(
+    get,
+    path = "/api/v2/batches/{batch_id}/proof-bundle",
+    tag = "batches",
+    summary = "Download a batch proof bundle (JSON or CBOR)",
+    params(("batch_id" = i32, Path, description = "Batch id"), ProofBundleQuery),
+    responses(
+        (status = 200, description = "Proof bundle; CBOR with Accept: application/cbor", body = Object),
+        (status = 404, description = "Batch not found", body = String),
+        (status = 409, description = "Batch is not proven", body = String),
+        (status = 500, description = "Database error or inconsistent proof", body = String),
+        (status = 502, description = "Proof could not be fetched from Sindri", body = String),
+    )
+)]
+#[instrument(skip(state, headers), level = "info")]
+async fn get_proof_bundle_endpoint(
+    State(state): State<ApiState>,
+    Path(batch_id): Path<i32>,
+    Query(query): Query<ProofBundleQuery>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    info!("📦 API: Proof bundle requested for batch: id={}", batch_id);
+
+    let batch = match get_batch_by_id(&state.pool, batch_id).await {
+        Ok(batch) => batch,
+        Err(SqlxError::RowNotFound) => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                format!("Batch not found: {}", batch_id),
+            ))
+        }
+        Err(e) => {
+            error!("Failed to get batch {}: {}", batch_id, e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to get batch: {}", e),
+            ));
+        }
+    };
+
+    let proof_id = match (&batch.sindri_proof_id, batch.proof_status.as_str()) {
+        (Some(proof_id), "proven") => proof_id.clone(),
+        (_, status) => {
+            return Err((
+                StatusCode::CONFLICT,
+                format!("Batch {} is not proven (status: {})", batch_id, status),
+            ))
+        }
+    };
+
+    let contract_data = get_contract_submission_data(&state.pool, batch_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to get contract data for batch {}: {}", batch_id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to get batch roots: {}", e),
+            )
+        })?;
+    let parse_root = |root: &str| {
+        parse_hex_array::<32>(root).map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Invalid stored Merkle root {}: {}", root, e),
+            )
+        })
+    };
+    let prev_root = parse_root(&contract_data.public.prev_merkle_root)?;
+    let new_root = parse_root(&contract_data.public.new_merkle_root)?;
+
+    let proof_data = arithmetic_lib::proof::get_sindri_proof_data(&proof_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch proof {} from Sindri: {}", proof_id, e);
+            (
+                StatusCode::BAD_GATEWAY,
+                format!("Failed to fetch proof {}: {}", proof_id, e),
+            )
+        })?;
+
+    let mut bundle = proof_data
+        .into_bundle(batch_id, prev_root, new_root)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if query.include_transactions {
+        bundle = bundle.with_transactions(contract_data.private.transactions);
+    }
+
+    // Never hand out a bundle that does not describe this batch
+    let summary = bundle.validate().map_err(|e| {
+        error!("Proof bundle for batch {} is invalid: {}", batch_id, e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Proof bundle for batch {} is invalid: {}", batch_id, e),
+        )
+    })?;
+    if i64::from(summary.initial_balance) != batch.previous_counter_value
+        || i64::from(summary.final_balance) != batch.final_counter_value
+    {
+        error!(
+            "Proof {} for batch {} proves {} -> {}, batch is {} -> {}",
+            proof_id,
+            batch_id,
+            summary.initial_balance,
+            summary.final_balance,
+            batch.previous_counter_value,
+            batch.final_counter_value
+        );
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!(
+                "Proof {} does not match the balances of batch {}",
+                proof_id, batch_id
+            ),
+        ));
+    }
+
+    let wants_cbor = headers
+        .get(ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| accept.contains("application/cbor"));
+
+    let encoded = if wants_cbor {
+        bundle
+            .to_cbor()
+            .map(|body| ([(CONTENT_TYPE, "application/cbor")], body).into_response())
+    } else {
+        bundle
+            .to_json()
+            .map(|body| ([(CONTENT_TYPE, "application/json")], body).into_response())
+    };
+    let response = encoded.map_err(|e| {
+        error!("Failed to encode proof bundle: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to encode proof bundle: {}", e),
+        )
+    })?;
+
+    info!(
+        "✅ API: Proof bundle for batch {} served as {}",
+        batch_id,
+        if wants_cbor { "CBOR" } else { "JSON" }
+    );
+    Ok(response)
+}
+
 /// Manually trigger batch processing
+#[utoipa::path(
+    post,
+    path = "/api/v2/batches/trigger",
+    tag = "batches",
+    summary = "Manually trigger batch processing",
+    responses(
+        (status = 200, description = "Trigger accepted", body = TriggerBatchResponse),
+        (status = 500, description = "Trigger could not be delivered", body = String),
+        (status = 503, description = "Background processor not running", body = String),
+    )
+)]
 #[instrument(skip(state), level = "info")]
 async fn trigger_batch_endpoint(
     State(state): State<ApiState>,
@@ -831,7 +2169,182 @@ async fn trigger_batch_endpoint(
     }
 }
 
+/// Queue a new proof request for a failed batch
+///
+/// The batch keeps its transactions and place in the chain; the batch processor
+/// submits the proof on its next cycle.
+#[utoipa::path(
+    post,
+    path = "/api/v2/batches/{batch_id}/retry-proof",
+    tag = "batches",
+    summary = "Retry proof generation for a failed batch",
+    params(("batch_id" = i32, Path, description = "Failed batch id")),
+    responses(
+        (status = 200, description = "Batch queued for a new proof", body = BatchInfo),
+        (status = 404, description = "Batch not found", body = String),
+        (status = 409, description = "Batch proof has not failed", body = String),
+        (status = 500, description = "Database error", body = String),
+        (status = 503, description = "Background processor not running", body = String),
+    )
+)]
+#[instrument(skip(state), level = "info")]
+async fn retry_batch_proof_endpoint(
+    State(state): State<ApiState>,
+    Path(batch_id): Path<i32>,
+) -> Result<Json<BatchInfo>, (StatusCode, String)> {
+    info!("🔁 API: Retrying proof for batch {}", batch_id);
+
+    if state.batch_processor.is_none() {
+        warn!("Batch processor is not available");
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Background batch processor is not available".to_string(),
+        ));
+    }
+
+    reset_failed_batch_proof(&state.pool, batch_id)
+        .await
+        .map_err(|e| {
+            let status = match e {
+                DbError::NotFound(_) => StatusCode::NOT_FOUND,
+                DbError::InvalidState(_) => StatusCode::CONFLICT,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            error!("Failed to retry proof for batch {}: {}", batch_id, e);
+            (
+                status,
+                format!("Failed to retry proof for batch {}: {}", batch_id, e),
+            )
+        })?;
+
+    let batch = get_batch_by_id(&state.pool, batch_id).await.map_err(|e| {
+        error!("Failed to get batch {}: {}", batch_id, e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to get batch: {}", e),
+        )
+    })?;
+    let submissions = load_submissions(&state.pool, &[batch.id]).await?;
+
+    info!("✅ API: Batch {} queued for a new proof", batch_id);
+    Ok(Json(BatchInfo::new(batch, &submissions)))
+}
+
+/// Unwind a failed batch and its descendants, then re-batch their transactions
+#[utoipa::path(
+    post,
+    path = "/api/v2/batches/{batch_id}/recover",
+    tag = "batches",
+    summary = "Recover a failed batch",
+    params(("batch_id" = i32, Path, description = "Failed batch id")),
+    request_body = RecoverBatchRequest,
+    responses(
+        (status = 200, description = "Batches unwound and transactions re-batched", body = BatchRecoveryResponse),
+        (status = 400, description = "Recovery not confirmed", body = String),
+        (status = 404, description = "Batch not found", body = String),
+        (status = 409, description = "Batch has not failed or its lineage is already settled", body = String),
+        (status = 500, description = "Database error", body = String),
+    )
+)]
+#[instrument(skip(state), level = "info")]
+async fn recover_batch_endpoint(
+    State(state): State<ApiState>,
+    Path(batch_id): Path<i32>,
+    Json(request): Json<RecoverBatchRequest>,
+) -> Result<Json<BatchRecoveryResponse>, (StatusCode, String)> {
+    if !request.confirm {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Recovery must be confirmed with \"confirm\": true".to_string(),
+        ));
+    }
+
+    warn!("⏪ API: Recovering failed batch {}", batch_id);
+
+    // Hold the ADS lock so no batch inserts nullifiers while the tree is rolled back
+    let unwind = {
+        let _ads_guard = state.ads_service.write().await;
+        unwind_failed_batch(&state.pool, batch_id).await
+    }
+    .map_err(|e| {
+        let status = match e {
+            DbError::NotFound(_) => StatusCode::NOT_FOUND,
+            DbError::InvalidState(_) => StatusCode::CONFLICT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        error!("Failed to unwind batch {}: {}", batch_id, e);
+        (
+            status,
+            format!("Failed to unwind batch {}: {}", batch_id, e),
+        )
+    })?;
+
+    info!(
+        "✅ API: Unwound batches {:?}, {} transactions released",
+        unwind.unwound_batch_ids,
+        unwind.released_transaction_ids.len()
+    );
+
+    // Re-batch the released transactions on top of the parent
+    let unified_service = crate::unified_batch_service::UnifiedBatchService::new(
+        state.pool.clone(),
+        state.ads_service.clone(),
+        state.config.max_batch_size,
+    );
+    let mut rebatched_batch_ids = Vec::new();
+    let mut rebatched = 0;
+    while rebatched < unwind.released_transaction_ids.len() {
+        match unified_service
+            .create_batch_with_ads(None, "recovery")
+            .await
+        {
+            Ok(Some(result)) => {
+                state.events.publish(ApiEventKind::BatchCreated {
+                    batch_id: result.batch_id,
+                    transaction_count: result.transaction_count,
+                });
+                spawn_proof_generation(&state, result.batch_id);
+                rebatched += result.transaction_count;
+                rebatched_batch_ids.push(result.batch_id);
+            }
+            Ok(None) => break,
+            Err(e) => {
+                // The unwind stands; the batch processor picks the transactions up later
+                error!(
+                    "Failed to re-batch after recovering batch {}: {}",
+                    batch_id, e
+                );
+                break;
+            }
+        }
+    }
+
+    info!(
+        "✅ API: Batch {} recovered, re-batched into {:?}",
+        batch_id, rebatched_batch_ids
+    );
+
+    Ok(Json(BatchRecoveryResponse {
+        failed_batch_id: unwind.failed_batch_id,
+        parent_batch_id: unwind.parent_batch_id,
+        unwound_batch_ids: unwind.unwound_batch_ids,
+        released_transaction_ids: unwind.released_transaction_ids,
+        restored_root: format!("0x{}", hex::encode(&unwind.restored_root)),
+        rebatched_batch_ids,
+    }))
+}
+
 /// Get batch processor statistics
+#[utoipa::path(
+    get,
+    path = "/api/v2/batches/stats",
+    tag = "batches",
+    summary = "Get batch processor statistics",
+    responses(
+        (status = 200, description = "Processor counters", body = BatchProcessorStatsResponse),
+        (status = 503, description = "Background processor not running", body = String),
+    )
+)]
 #[instrument(skip(state), level = "info")]
 async fn get_batch_processor_stats_endpoint(
     State(state): State<ApiState>,
@@ -863,3 +2376,204 @@ async fn get_batch_processor_stats_endpoint(
         ))
     }
 }
+
+/// Rebuild the IMT from the transaction log
+#[utoipa::path(
+    post,
+    path = "/api/v2/state/rebuild",
+    tag = "state",
+    summary = "Replay batches into a fresh IMT and optionally swap it in",
+    request_body = StateRebuildRequest,
+    responses(
+        (status = 200, description = "Replay report, with the first divergence if any", body = StateRebuildResponse),
+        (status = 500, description = "Database error", body = String),
+    )
+)]
+#[instrument(skip(state), level = "info")]
+async fn rebuild_state_endpoint(
+    State(state): State<ApiState>,
+    Json(request): Json<StateRebuildRequest>,
+) -> Result<Json<StateRebuildResponse>, (StatusCode, String)> {
+    info!("🔁 API: State rebuild requested (swap: {})", request.swap);
+
+    let db_error = |e: DbError| {
+        error!("State rebuild failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("State rebuild failed: {}", e),
+        )
+    };
+
+    // A swap must not lose nullifiers inserted while the log is replayed
+    let ads_guard = if request.swap {
+        Some(state.ads_service.write().await)
+    } else {
+        None
+    };
+
+    let replay = replay_state(&state.pool).await.map_err(db_error)?;
+
+    let swapped = request.swap && replay.divergence.is_none();
+    if swapped {
+        swap_in_replayed_state(&state.pool, &replay)
+            .await
+            .map_err(db_error)?;
+        info!("✅ API: Rebuilt IMT swapped in");
+    } else if request.swap {
+        warn!("Replay diverged, the live IMT tables were left in place");
+    }
+    drop(ads_guard);
+
+    let hex_root = |root: &[u8]| format!("0x{}", hex::encode(root));
+    Ok(Json(StateRebuildResponse {
+        batches_replayed: replay.batches_replayed,
+        transactions_replayed: replay.transactions_replayed,
+        nullifier_count: replay.tree.len(),
+        rebuilt_root: hex_root(&replay.tree.root()[..]),
+        divergence: replay.divergence.map(|d| RebuildDivergence {
+            batch_id: d.batch_id,
+            committed_root: d.committed_root.as_deref().map(hex_root),
+            replayed_root: d.replayed_root.as_deref().map(hex_root),
+            reason: d.reason,
+        }),
+        live: replay.live.map(|live| LiveStateComparison {
+            live_root: hex_root(live.live_root.as_slice()),
+            root_matches: live.root_matches,
+            nullifier_mismatches: live.nullifier_mismatches,
+            node_mismatches: live.node_mismatches,
+        }),
+        swapped,
+    }))
+}
+
+/// Compare the settlement contracts with the database
+#[utoipa::path(
+    get,
+    path = "/api/v2/reconciliation",
+    tag = "state",
+    summary = "Compare on-chain state history with settlement records",
+    params(ReconciliationQuery),
+    responses(
+        (status = 200, description = "Discrepancies found", body = ReconciliationReport),
+        (status = 500, description = "Database error", body = String),
+        (status = 503, description = "Settlement is not configured", body = String),
+    )
+)]
+#[instrument(skip(state), level = "info")]
+async fn reconciliation_endpoint(
+    State(state): State<ApiState>,
+    Query(query): Query<ReconciliationQuery>,
+) -> Result<Json<ReconciliationReport>, (StatusCode, String)> {
+    info!("🔎 API: Reconciliation requested");
+    run_reconciliation(&state.pool, query.from_batch, false).await
+}
+
+/// Repair the discrepancies the database can be brought in line with
+#[utoipa::path(
+    post,
+    path = "/api/v2/reconciliation/repair",
+    tag = "state",
+    summary = "Repair settlement records and re-queue missing posts",
+    request_body = ReconciliationRepairRequest,
+    responses(
+        (status = 200, description = "Discrepancies found and repairs applied", body = ReconciliationReport),
+        (status = 400, description = "Repair not confirmed", body = String),
+        (status = 500, description = "Database error", body = String),
+        (status = 503, description = "Settlement is not configured", body = String),
+    )
+)]
+#[instrument(skip(state), level = "info")]
+async fn reconciliation_repair_endpoint(
+    State(state): State<ApiState>,
+    Json(request): Json<ReconciliationRepairRequest>,
+) -> Result<Json<ReconciliationReport>, (StatusCode, String)> {
+    if !request.confirm {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Repair must be confirmed with \"confirm\": true".to_string(),
+        ));
+    }
+
+    warn!("🔧 API: Reconciliation repair requested");
+    run_reconciliation(&state.pool, request.from_batch, true).await
+}
+
+async fn run_reconciliation(
+    pool: &PgPool,
+    from_batch: Option<i32>,
+    repair: bool,
+) -> Result<Json<ReconciliationReport>, (StatusCode, String)> {
+    let settlement = SettlementConfig::from_env().map_err(|e| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("Settlement is not configured: {}", e),
+        )
+    })?;
+
+    reconcile(pool, &settlement, from_batch, repair)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("Reconciliation failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Reconciliation failed: {}", e),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Legacy aliases of documented paths (`/api/v2/health`, `/api/v2/info`)
+    const UNDOCUMENTED_ALIASES: &[&str] = &["/health", "/"];
+
+    /// `(method, path)` of every route `create_router` registers
+    fn router_routes() -> Vec<(String, String)> {
+        api_routes()
+            .into_iter()
+            .map(|route| (route.method.to_string(), route.path.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn every_route_has_an_openapi_entry() {
+        let documented: Vec<(String, String)> = endpoints_from_spec(&ApiDoc::openapi())
+            .into_iter()
+            .map(|endpoint| (endpoint.method, endpoint.path))
+            .collect();
+
+        let routes = router_routes();
+        assert!(!routes.is_empty(), "no routes in api_routes");
+
+        let missing: Vec<String> = routes
+            .iter()
+            .filter(|(_, path)| !UNDOCUMENTED_ALIASES.contains(&path.as_str()))
+            .filter(|route| !documented.contains(route))
+            .map(|(method, path)| format!("{method} {path}"))
+            .collect();
+        assert!(
+            missing.is_empty(),
+            "routes without an OpenAPI entry: {missing:?}"
+        );
+    }
+
+    #[test]
+    fn every_openapi_entry_is_routed() {
+        let routes = router_routes();
+        let stale: Vec<String> = endpoints_from_spec(&ApiDoc::openapi())
+            .into_iter()
+            .filter(|endpoint| !routes.contains(&(endpoint.method.clone(), endpoint.path.clone())))
+            .map(|endpoint| format!("{} {}", endpoint.method, endpoint.path))
+            .collect();
+        assert!(stale.is_empty(), "documented but not routed: {stale:?}");
+    }
+
+    #[test]
+    fn spec_is_openapi_3_1() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        assert!(spec["openapi"].as_str().unwrap().starts_with("3.1"));
+        assert!(spec["components"]["schemas"]["BulkSubmitResponse"].is_object());
+    }
+}
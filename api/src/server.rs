@@ -13,11 +13,17 @@ use tower_http::{
     timeout::TimeoutLayer,
     trace::TraceLayer,
 };
-use tracing::{info, instrument};
+use tracing::{info, instrument, warn};
 
 use crate::batch_processor::{create_batch_processor_config, start_batch_processor};
+use crate::events::EventBus;
+use crate::middleware::RateLimiter;
 use crate::rest::{ApiConfig, ApiState};
 use arithmetic_db::{init_db, AdsConfig, AdsServiceFactory, IndexedMerkleTreeADS};
+use ethereum_client::{
+    ChainHealthMonitor, EthereumCache, EthereumClient, EventManager, HealthConfig,
+    SettlementConfig, SettlementTarget, VAppEventHandler,
+};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -104,8 +110,16 @@ impl ApiServer {
 
         // Start background batch processor
         let batch_processor_config = create_batch_processor_config(&config.api_config);
-        let batch_processor_handle =
-            start_batch_processor(pool.clone(), batch_processor_config, ads_service.clone()).await;
+        let events = EventBus::default();
+        let batch_processor_handle = start_batch_processor(
+            pool.clone(),
+            batch_processor_config,
+            ads_service.clone(),
+            events.clone(),
+        )
+        .await;
+        let chain_health = start_chain_health_monitors(&pool).await;
+        start_settlement_reconciliation(&pool).await;
 
         // Create API state
         let state = ApiState {
@@ -113,6 +127,9 @@ impl ApiServer {
             config: config.api_config.clone(),
             batch_processor: Some(batch_processor_handle),
             ads_service,
+            rate_limiter: Some(RateLimiter::new(config.rate_limit_per_minute)),
+            events,
+            chain_health,
         };
 
         let server = Self { config, state };
@@ -134,14 +151,25 @@ impl ApiServer {
 
         // Start background batch processor
         let batch_processor_config = create_batch_processor_config(&config.api_config);
-        let batch_processor_handle =
-            start_batch_processor(pool.clone(), batch_processor_config, ads_service.clone()).await;
+        let events = EventBus::default();
+        let batch_processor_handle = start_batch_processor(
+            pool.clone(),
+            batch_processor_config,
+            ads_service.clone(),
+            events.clone(),
+        )
+        .await;
+        let chain_health = start_chain_health_monitors(&pool).await;
+        start_settlement_reconciliation(&pool).await;
 
         let state = ApiState {
             pool,
             config: config.api_config.clone(),
             batch_processor: Some(batch_processor_handle),
             ads_service,
+            rate_limiter: Some(RateLimiter::new(config.rate_limit_per_minute)),
+            events,
+            chain_health,
         };
 
         Ok(Self { config, state })
@@ -255,6 +283,102 @@ impl ApiServer {
     }
 }
 
+/// Start a chain health monitor for every settlement network, recording
+/// samples in the database; none when Ethereum is not configured
+async fn start_chain_health_monitors(pool: &PgPool) -> Vec<ChainHealthMonitor> {
+    let (settlement, health) = match (SettlementConfig::from_env(), HealthConfig::from_env()) {
+        (Ok(settlement), Ok(health)) => (settlement, health),
+        (Err(e), _) | (_, Err(e)) => {
+            info!("⏭️ Chain health monitoring disabled: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let cache = EthereumCache::new(pool.clone());
+    let cache = match cache.initialize().await {
+        Ok(()) => Some(cache),
+        Err(e) => {
+            warn!("⚠️ Network stats will not be recorded: {}", e);
+            None
+        }
+    };
+
+    let mut monitors = Vec::new();
+    for target in &settlement.targets {
+        match target.to_config() {
+            Ok(config) => {
+                let monitor = ChainHealthMonitor::new(&config);
+                let monitor = match &cache {
+                    Some(cache) => monitor.with_cache(cache.clone()),
+                    None => monitor,
+                };
+                monitors.push(monitor.spawn(health.clone()));
+            }
+            Err(e) => warn!("⚠️ Not monitoring {}: {}", target.name, e),
+        }
+    }
+
+    monitors
+}
+
+/// Follow every settlement contract's events and record batches settled
+/// outside this process, when `EVENT_RECONCILIATION=true`
+async fn start_settlement_reconciliation(pool: &PgPool) {
+    if !std::env::var("EVENT_RECONCILIATION").is_ok_and(|v| v == "true") {
+        return;
+    }
+    let settlement = match SettlementConfig::from_env() {
+        Ok(settlement) => settlement,
+        Err(e) => {
+            info!("⏭️ Event reconciliation disabled: {}", e);
+            return;
+        }
+    };
+
+    let cache = EthereumCache::new(pool.clone());
+    if let Err(e) = cache.initialize().await {
+        warn!("⚠️ Event reconciliation disabled: {}", e);
+        return;
+    }
+
+    for target in settlement.targets {
+        let (pool, cache) = (pool.clone(), cache.clone());
+        tokio::spawn(async move {
+            if let Err(e) = reconcile_target(pool, cache, &target).await {
+                warn!("⚠️ Event reconciliation on {} stopped: {}", target.name, e);
+            }
+        });
+    }
+}
+
+/// Index `target`'s contract events and deliver them to a reconciling `VAppEventHandler`
+async fn reconcile_target(
+    pool: PgPool,
+    cache: EthereumCache,
+    target: &SettlementTarget,
+) -> ethereum_client::Result<()> {
+    let config = target.to_config()?;
+    let chain_id = config.network.chain_id;
+    let client = Arc::new(EthereumClient::new(config).await?.with_cache(cache)?);
+
+    let handler = VAppEventHandler::new(format!("reconcile-{}", target.name)).with_reconciliation(
+        pool,
+        target.name.clone(),
+        chain_id,
+    );
+    let mut manager = EventManager::new(Arc::clone(&client));
+    manager.register_handler(Arc::new(handler));
+
+    info!("🔁 Reconciling settlement events on {}", target.name);
+    let monitor = Arc::clone(&client);
+    tokio::spawn(async move {
+        if let Err(e) = monitor.start_event_monitoring().await {
+            warn!("⚠️ Event indexing stopped: {}", e);
+        }
+    });
+    manager.start_processing_events().await
+}
+
 // ============================================================================
 // HANDLERS
 // ============================================================================
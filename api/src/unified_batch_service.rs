@@ -4,16 +4,23 @@
 //! uses ADS integration, regardless of how the batch is triggered (API, timer, threshold).
 //!
 //! This replaces the dual workflow problem where some batches used ADS and others didn't.
+//!
+//! Several API replicas can share one database: each batch is created in a
+//! single database transaction holding the IMT write lock (a Postgres
+//! advisory lock), and pending transactions are claimed with
+//! `FOR UPDATE SKIP LOCKED`, so no transaction lands in two batches.
 
 use sqlx::PgPool;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{debug, error, info, instrument};
+use tracing::{debug, error, info, instrument, warn};
 
 use arithmetic_db::{
-    get_pending_transactions, store_ads_state_commit, AuthenticatedDataStructure,
-    IncomingTransaction, IndexedMerkleTreeADS, ProofBatch,
+    create_batch_in, get_batch_transactions, lock_for_write, set_batch_tree_start_index,
+    store_ads_state_commit, store_transaction_inclusions, IncomingTransaction,
+    IndexedMerkleTreeADS, TransactionInclusion,
 };
+use arithmetic_lib::receipt::{transaction_nullifier, ImtMembershipProof};
 
 // ============================================================================
 // UNIFIED BATCH SERVICE
@@ -54,6 +61,12 @@ impl UnifiedBatchService {
     /// Create a batch with full ADS integration
     ///
     /// This is the ONLY way batches should be created - all triggers use this method
+    ///
+    /// Everything happens in one database transaction that holds the IMT
+    /// write lock, so replicas sharing the database create batches one at a
+    /// time: claiming transactions, IMT insertions, the state commit, the
+    /// membership proofs and the proof request queued in the outbox all
+    /// commit or roll back together.
     #[instrument(skip(self), level = "info")]
     pub async fn create_batch_with_ads(
         &self,
@@ -62,44 +75,28 @@ impl UnifiedBatchService {
     ) -> Result<Option<BatchCreationResult>, String> {
         info!("🔄 UNIFIED: Creating batch via {} trigger", trigger_source);
 
-        // Step 1: Get pending transactions
-        let pending_transactions = match get_pending_transactions(&self.pool).await {
-            Ok(transactions) => transactions,
-            Err(e) => return Err(format!("Failed to get pending transactions: {}", e)),
-        };
-
-        if pending_transactions.is_empty() {
-            debug!("No pending transactions to batch");
-            return Ok(None);
-        }
-
-        // Step 2: Determine batch size
         let batch_size = requested_batch_size
-            .map(|s| s as usize)
-            .unwrap_or(pending_transactions.len())
-            .min(self.max_batch_size as usize)
-            .min(pending_transactions.len());
-
-        let batch_transactions = &pending_transactions[..batch_size];
-
-        info!(
-            "📦 UNIFIED: Processing {} transactions through ADS integration",
-            batch_size
-        );
+            .unwrap_or(self.max_batch_size as i32)
+            .clamp(1, self.max_batch_size as i32);
 
-        // Step 3: Start atomic database transaction
-        let db_tx = self
+        // Step 1: Start atomic database transaction
+        let mut db_tx = self
             .pool
             .begin()
             .await
             .map_err(|e| format!("Failed to begin database transaction: {}", e))?;
 
-        // Step 4: Create batch entry (using existing logic for now)
+        // Step 2: Wait for batches being created by other replicas
+        lock_for_write(&mut db_tx)
+            .await
+            .map_err(|e| format!("Failed to take the IMT write lock: {}", e))?;
+
+        // Step 3: Claim pending transactions
         info!("📋 UNIFIED: Creating batch entry in database");
-        let batch = match self.create_batch_entry(batch_transactions).await {
+        let batch = match create_batch_in(&mut db_tx, Some(batch_size)).await {
             Ok(Some(batch)) => batch,
             Ok(None) => {
-                debug!("Failed to create batch entry");
+                debug!("No pending transactions to batch");
                 db_tx.rollback().await.ok();
                 return Ok(None);
             }
@@ -109,6 +106,14 @@ impl UnifiedBatchService {
                 return Err(format!("Failed to create batch entry: {}", e));
             }
         };
+        let batch_transactions = match get_batch_transactions(&mut *db_tx, batch.id).await {
+            Ok(transactions) => transactions,
+            Err(e) => {
+                error!("Failed to load transactions of batch {}: {}", batch.id, e);
+                db_tx.rollback().await.ok();
+                return Err(format!("Failed to load batch transactions: {}", e));
+            }
+        };
 
         info!(
             "✅ UNIFIED: Batch entry created: id={}, transactions={}",
@@ -116,19 +121,18 @@ impl UnifiedBatchService {
             batch_transactions.len()
         );
 
-        // Step 5: Process transactions through ADS
+        // Step 4: Process transactions through ADS
         info!("🔐 UNIFIED: Processing transactions through ADS service");
-        let mut ads_guard = self.ads_service.write().await;
+        let ads_guard = self.ads_service.read().await;
 
         // Convert transactions to nullifiers
-        let mut nullifiers = Vec::new();
-        for tx in batch_transactions {
-            let nullifier_value = self.transaction_to_nullifier(tx);
-            nullifiers.push(nullifier_value);
-        }
+        let nullifiers: Vec<i64> = batch_transactions
+            .iter()
+            .map(|tx| self.transaction_to_nullifier(tx))
+            .collect();
 
         // Insert nullifiers through ADS
-        let state_transitions = match ads_guard.batch_insert(&nullifiers).await {
+        let state_transitions = match ads_guard.batch_insert_in(&mut db_tx, &nullifiers).await {
             Ok(transitions) => {
                 info!(
                     "✅ UNIFIED: Successfully processed {} nullifiers through ADS",
@@ -144,7 +148,7 @@ impl UnifiedBatchService {
             }
         };
 
-        // Step 6: Get the final merkle root
+        // Step 5: Get the final merkle root
         let merkle_root = if let Some(last_transition) = state_transitions.last() {
             last_transition.new_root
         } else {
@@ -154,13 +158,74 @@ impl UnifiedBatchService {
             return Err("No state transitions returned from ADS".to_string());
         };
 
-        drop(ads_guard); // Release ADS lock
+        // First leaf of this batch, where a rollback of the batch starts
+        let tree_start_index = state_transitions
+            .first()
+            .map(|t| t.insertion_proof.new_nullifier_proof.leaf_index);
+
+        // Paths against the batch root can't be rebuilt once later batches
+        // change the tree, so capture them for receipts before committing
+        let mut inclusions = Vec::with_capacity(batch_transactions.len());
+        for (tx, &nullifier_value) in batch_transactions.iter().zip(&nullifiers) {
+            match ads_guard
+                .inclusion_proof_in(&mut db_tx, nullifier_value)
+                .await
+            {
+                Ok((leaf, proof)) => {
+                    let membership = ImtMembershipProof {
+                        leaf_index: proof.leaf_index,
+                        value: leaf.value,
+                        next_index: leaf.next_index.unwrap_or(0),
+                        next_value: leaf.next_value,
+                        siblings: proof.siblings,
+                        path_indices: proof.path_indices,
+                    };
+                    if membership.compute_root().ok() != Some(merkle_root) {
+                        warn!(
+                            "UNIFIED: Membership proof of transaction {} does not lead to the batch root",
+                            tx.id
+                        );
+                        continue;
+                    }
+                    inclusions.push(TransactionInclusion {
+                        transaction_id: tx.id,
+                        batch_id: batch.id,
+                        nullifier_value,
+                        leaf_index: membership.leaf_index,
+                        next_index: membership.next_index,
+                        next_value: membership.next_value,
+                        siblings: membership.siblings.iter().map(|s| s.to_vec()).collect(),
+                        path_indices: membership.path_indices,
+                        merkle_root: merkle_root.to_vec(),
+                    });
+                }
+                Err(e) => warn!(
+                    "UNIFIED: No membership proof for transaction {}: {}",
+                    tx.id, e
+                ),
+            }
+        }
+
+        drop(ads_guard);
 
         info!("🌳 UNIFIED: Final merkle root: {:02x?}", &merkle_root[..8]);
 
-        // Step 7: Store merkle root atomically
+        if let Some(tree_start_index) = tree_start_index {
+            if let Err(e) =
+                set_batch_tree_start_index(&mut *db_tx, batch.id, tree_start_index).await
+            {
+                error!(
+                    "UNIFIED: Failed to record tree start index for batch {}: {}",
+                    batch.id, e
+                );
+                db_tx.rollback().await.ok();
+                return Err(format!("Failed to record tree start index: {}", e));
+            }
+        }
+
+        // Step 6: Store merkle root atomically
         info!("💾 UNIFIED: Storing merkle root for batch {}", batch.id);
-        match store_ads_state_commit(&self.pool, batch.id, &merkle_root).await {
+        match store_ads_state_commit(&mut *db_tx, batch.id, &merkle_root).await {
             Ok(_) => {
                 info!(
                     "✅ UNIFIED: Merkle root stored successfully for batch {}",
@@ -177,7 +242,16 @@ impl UnifiedBatchService {
             }
         }
 
-        // Step 8: Commit the transaction
+        // Receipts are served from these; a batch without them still settles.
+        // They are written in a savepoint, so a failure here keeps the batch.
+        if let Err(e) = store_transaction_inclusions(&mut *db_tx, &inclusions).await {
+            warn!(
+                "UNIFIED: Failed to store membership proofs for batch {}, its transactions have no receipts: {}",
+                batch.id, e
+            );
+        }
+
+        // Step 7: Commit the transaction, releasing the IMT write lock
         if let Err(e) = db_tx.commit().await {
             error!("UNIFIED: Failed to commit batch transaction: {}", e);
             return Err(format!("Failed to commit batch transaction: {}", e));
@@ -185,15 +259,15 @@ impl UnifiedBatchService {
 
         info!("🎉 UNIFIED: Atomic batch processing completed successfully!");
         info!("   📊 Batch ID: {}", batch.id);
-        info!("   🔢 Transactions: {}", batch_size);
+        info!("   🔢 Transactions: {}", batch_transactions.len());
         info!("   🔢 Nullifiers: {}", nullifiers.len());
-        info!("   🌳 Merkle Root: 0x{}", hex::encode(&merkle_root));
+        info!("   🌳 Merkle Root: 0x{}", hex::encode(merkle_root));
 
         let result = BatchCreationResult {
             batch_id: batch.id,
             previous_counter_value: batch.previous_counter_value,
             final_counter_value: batch.final_counter_value,
-            transaction_count: batch_size,
+            transaction_count: batch_transactions.len(),
             merkle_root: merkle_root.to_vec(),
             nullifier_count: nullifiers.len(),
         };
@@ -201,52 +275,16 @@ impl UnifiedBatchService {
         Ok(Some(result))
     }
 
-    /// Create batch entry in database (temporary - uses existing logic)
-    /// TODO: This should be replaced with pure database operations once SQL function is deprecated
-    async fn create_batch_entry(
-        &self,
-        transactions: &[IncomingTransaction],
-    ) -> Result<Option<ProofBatch>, String> {
-        // For now, use the existing create_batch logic but limit to exact transactions
-        match arithmetic_db::create_batch(&self.pool, Some(transactions.len() as i32)).await {
-            Ok(batch) => Ok(batch),
-            Err(e) => Err(format!("Database error: {}", e)),
-        }
-    }
-
     /// Convert a transaction to a nullifier value (deterministic hash)
+    ///
+    /// Receipts recompute it with the same function, see `arithmetic_lib::receipt`.
     fn transaction_to_nullifier(&self, transaction: &IncomingTransaction) -> i64 {
-        // Create a deterministic nullifier from the transaction data using blake3
-        // This ensures the same transaction always produces the same nullifier across deployments
-        let mut hasher = blake3::Hasher::new();
-
-        // Hash each field as its little-endian byte representation for deterministic results
-        hasher.update(&transaction.id.to_le_bytes());
-        hasher.update(&transaction.amount.to_le_bytes());
-        hasher.update(&transaction.created_at.timestamp().to_le_bytes());
-
-        // Get the first 8 bytes of the digest as a u64 (little-endian)
-        let digest = hasher.finalize();
-        let hash_bytes = digest.as_bytes();
-        let hash_u64 = u64::from_le_bytes([
-            hash_bytes[0],
-            hash_bytes[1],
-            hash_bytes[2],
-            hash_bytes[3],
-            hash_bytes[4],
-            hash_bytes[5],
-            hash_bytes[6],
-            hash_bytes[7],
-        ]);
-
-        // Convert to positive i64 (IMT requires positive nullifiers)
-        // Take modulo to ensure we stay in positive range, then add 1 to avoid zero
-        let nullifier = ((hash_u64 % (i64::MAX as u64)) as i64) + 1;
-
-        debug!(
-            "Transaction {} -> nullifier {} (from hash {})",
-            transaction.id, nullifier, hash_u64
+        let nullifier = transaction_nullifier(
+            transaction.id,
+            transaction.amount,
+            transaction.created_at.timestamp(),
         );
+        debug!("Transaction {} -> nullifier {}", transaction.id, nullifier);
         nullifier
     }
 
@@ -0,0 +1,88 @@
+//! Batch listing through the HTTP router
+//!
+//! Needs the Postgres from `docker-compose.yml`:
+//!
+//! ```bash
+//! cargo test -p api --test batch_listing
+//! ```
+
+use std::sync::Arc;
+
+use api::{create_router, ApiConfig, ApiState, BatchListResponse, EventBus, UnifiedBatchService};
+use arithmetic_db::test_utils::TestDatabase;
+use arithmetic_db::{
+    submit_transaction, unwind_failed_batch, update_batch_proof, AdsConfig, IndexedMerkleTreeADS,
+};
+use axum_test::TestServer;
+use tokio::sync::RwLock;
+
+#[tokio::test]
+async fn test_batches_can_be_listed_by_unwound_status() {
+    let test_db = TestDatabase::new()
+        .await
+        .expect("Failed to create test database");
+    let pool = test_db.pool.clone();
+
+    for amount in [5, 7] {
+        submit_transaction(&pool, amount)
+            .await
+            .expect("Failed to submit transaction");
+    }
+
+    let ads = IndexedMerkleTreeADS::new(pool.clone(), AdsConfig::default())
+        .await
+        .expect("Failed to create ADS service");
+    let ads_service = Arc::new(RwLock::new(ads));
+    let service = UnifiedBatchService::new(pool.clone(), ads_service.clone(), 10);
+
+    let mut batch_ids = Vec::new();
+    for _ in 0..2 {
+        let batch = service
+            .create_batch_with_ads(Some(1), "test")
+            .await
+            .expect("Failed to create batch")
+            .expect("No pending transactions to batch");
+        batch_ids.push(batch.batch_id);
+    }
+    let (kept, unwound) = (batch_ids[0], batch_ids[1]);
+
+    update_batch_proof(&pool, unwound, "failed-proof", "failed")
+        .await
+        .expect("Failed to mark batch failed");
+    unwind_failed_batch(&pool, unwound)
+        .await
+        .expect("Failed to unwind batch");
+
+    let server = TestServer::new(create_router(ApiState {
+        pool: pool.clone(),
+        config: ApiConfig::default(),
+        batch_processor: None,
+        ads_service,
+        rate_limiter: None,
+        events: EventBus::default(),
+        chain_health: Vec::new(),
+    }))
+    .expect("Failed to start test server");
+
+    let response = server
+        .get("/api/v2/batches")
+        .add_query_param("status", "unwound")
+        .await;
+    response.assert_status_ok();
+    let listed: BatchListResponse = response.json();
+    let ids: Vec<i32> = listed.batches.iter().map(|b| b.id).collect();
+    assert_eq!(ids, vec![unwound]);
+    assert!(listed.batches.iter().all(|b| b.proof_status == "unwound"));
+
+    let response = server
+        .get("/api/v2/batches")
+        .add_query_param("status", "pending")
+        .await;
+    response.assert_status_ok();
+    let listed: BatchListResponse = response.json();
+    let ids: Vec<i32> = listed.batches.iter().map(|b| b.id).collect();
+    assert_eq!(ids, vec![kept]);
+
+    drop(server);
+    test_db.drop_database().await;
+}
@@ -0,0 +1,141 @@
+//! Batch creation from several API replicas sharing one database
+//!
+//! Each replica gets its own pool and ADS service, like separate processes,
+//! and all of them create batches at once. Needs the Postgres from
+//! `docker-compose.yml`:
+//!
+//! ```bash
+//! cargo test -p api --test concurrent_batch_creation
+//! ```
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use api::UnifiedBatchService;
+use arithmetic_db::test_utils::TestDatabase;
+use arithmetic_db::{
+    init_db_with_url, replay_state, submit_transaction, AdsConfig, IndexedMerkleTreeADS,
+};
+use sqlx::Row;
+use tokio::sync::RwLock;
+
+const REPLICAS: usize = 4;
+const TRANSACTIONS: i32 = 40;
+const BATCH_SIZE: i32 = 3;
+
+async fn replica(url: &str) -> UnifiedBatchService {
+    let pool = init_db_with_url(url)
+        .await
+        .expect("Failed to initialize database");
+    let ads = IndexedMerkleTreeADS::new(pool.clone(), AdsConfig::default())
+        .await
+        .expect("Failed to create ADS service");
+    UnifiedBatchService::new(pool, Arc::new(RwLock::new(ads)), 10)
+}
+
+#[tokio::test]
+async fn test_replicas_never_batch_a_transaction_twice() {
+    let test_db = TestDatabase::new()
+        .await
+        .expect("Failed to create test database");
+    let pool = test_db.pool.clone();
+
+    let mut submitted = HashSet::new();
+    for amount in 1..=TRANSACTIONS {
+        let transaction = submit_transaction(&pool, amount)
+            .await
+            .expect("Failed to submit transaction");
+        submitted.insert(transaction.id);
+    }
+
+    let mut replicas = Vec::with_capacity(REPLICAS);
+    for _ in 0..REPLICAS {
+        replicas.push(replica(&test_db.url).await);
+    }
+
+    let tasks: Vec<_> = replicas
+        .into_iter()
+        .enumerate()
+        .map(|(i, service)| {
+            tokio::spawn(async move {
+                let trigger = format!("replica-{i}");
+                let mut batch_ids = Vec::new();
+                while let Some(result) = service
+                    .create_batch_with_ads(Some(BATCH_SIZE), &trigger)
+                    .await
+                    .expect("Batch creation failed")
+                {
+                    batch_ids.push(result.batch_id);
+                }
+                batch_ids
+            })
+        })
+        .collect();
+
+    let mut batch_ids = Vec::new();
+    for task in tasks {
+        batch_ids.extend(task.await.expect("Replica task panicked"));
+    }
+    assert_eq!(batch_ids.len(), (TRANSACTIONS / BATCH_SIZE + 1) as usize);
+
+    // Every transaction is in exactly one batch, and its row agrees
+    let batches = sqlx::query(
+        "SELECT id, transaction_ids, parent_batch_id, tree_start_index FROM proof_batches ORDER BY id",
+    )
+    .fetch_all(&pool)
+    .await
+    .expect("Failed to load batches");
+    let mut batched = HashSet::new();
+    let mut parent: Option<i32> = None;
+    let mut next_leaf = 1i64;
+    for row in &batches {
+        let id: i32 = row.get("id");
+        let transaction_ids: Vec<i32> = row.get("transaction_ids");
+        for transaction_id in &transaction_ids {
+            assert!(
+                batched.insert(*transaction_id),
+                "Transaction {transaction_id} is in more than one batch"
+            );
+        }
+
+        // Batches form one chain over consecutive IMT leaves
+        assert_eq!(row.get::<Option<i32>, _>("parent_batch_id"), parent);
+        assert_eq!(
+            row.get::<Option<i64>, _>("tree_start_index"),
+            Some(next_leaf)
+        );
+        parent = Some(id);
+        next_leaf += transaction_ids.len() as i64;
+    }
+    assert_eq!(batched, submitted);
+
+    let misassigned: i64 = sqlx::query_scalar(
+        r"
+        SELECT COUNT(*)
+        FROM incoming_transactions t
+        WHERE t.included_in_batch_id IS NULL
+           OR NOT EXISTS (
+               SELECT 1 FROM proof_batches pb
+               WHERE pb.id = t.included_in_batch_id AND t.id = ANY(pb.transaction_ids)
+           )
+        ",
+    )
+    .fetch_one(&pool)
+    .await
+    .expect("Failed to check transaction rows");
+    assert_eq!(misassigned, 0);
+
+    // One nullifier per transaction, and every committed root replays
+    let nullifiers: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM nullifiers WHERE tree_index > 0")
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to count nullifiers");
+    assert_eq!(nullifiers, i64::from(TRANSACTIONS));
+
+    let replay = replay_state(&pool).await.expect("Replay failed");
+    assert!(replay.divergence.is_none(), "{:?}", replay.divergence);
+    assert!(replay.live.expect("Live state was not compared").is_clean());
+
+    test_db.drop_database().await;
+}
@@ -11,33 +11,79 @@
 //! # Submit a transaction
 //! cli submit-transaction --amount 5
 //!
+//! # Submit many transactions from a CSV file (amount[,idempotency_key] per line)
+//! cli submit-transactions --file amounts.csv
+//!
 //! # View pending transactions
 //! cli view-pending
 //!
-//! # Trigger batch creation
-//! cli trigger-batch --verbose
+//! # Trigger batch creation and wait for its proof
+//! cli trigger-batch --verbose --wait
+//!
+//! # Wait until a batch is posted on-chain
+//! cli wait-for-batch --batch-id 3 --posted --timeout 600
+//!
+//! # Follow batch lifecycle events
+//! cli watch-events
+//!
+//! # Live operator dashboard
+//! cli dashboard --refresh 2
 //!
-//! # Download and verify proof
+//! # List proven batches created since a point in time
+//! cli list-batches --status proven --since 2025-01-01T00:00:00Z
+//!
+//! # List transactions included in a batch
+//! cli list-transactions --batch-id 3
+//!
+//! # Download a proof bundle and verify it offline
 //! cli download-proof --batch-id 1
+//! cli download-proof --batch-id 1 --format cbor --include-transactions
 //! cli verify-proof --proof-file proof_batch_1.json --expected-initial-balance 10 --expected-final-balance 22
 //!
+//! # Prove a transaction was included in a posted batch
+//! cli verify-receipt --transaction-id 7 --save receipt_7.json
+//! cli verify-receipt --receipt-file receipt_7.json --bundle-file proof_batch_1.json
+//!
 //! # Query smart contract verification key
 //! cli query-verification-key --verbose
 //!
 //! # Check API health
 //! cli health-check
+//!
+//! # Compare on-chain state history with the database, then repair after confirmation
+//! cli reconcile
+//! cli reconcile --repair
+//!
+//! # Rebuild the IMT from the transaction log and compare it with the live tables
+//! cli rebuild-state
+//! cli rebuild-state --swap
+//!
+//! # Export the state at a batch and load it into an empty database (uses DATABASE_URL)
+//! cli export-snapshot --batch-id 12 --output state_batch_12.cbor
+//! cli import-snapshot --file state_batch_12.cbor
 //! ```
 
-use clap::{Parser, Subcommand};
+#[path = "cli/dashboard.rs"]
+mod dashboard;
+
+use clap::{Parser, Subcommand, ValueEnum};
 use eyre::Result;
+use futures::StreamExt;
 use std::env;
 use std::fs;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tracing::error;
 
 // Import new batch processing API types
-use arithmetic_api::BatchApiClient;
-use ethereum_client::{config::Config, EthereumClient};
+use arithmetic_api::{
+    ApiClientError, ApiEventKind, BatchApiClient, BatchListParams, BulkTransactionItem,
+    RetryPolicy, SettlementInfo, TransactionListParams,
+};
+use arithmetic_db::{export_snapshot, import_snapshot, init_db_with_url, StateSnapshot};
+use arithmetic_lib::bundle::{parse_hex_array, verify_bundle, BundleExpectations, ProofBundle};
+use arithmetic_lib::receipt::TransactionReceipt;
+use arithmetic_lib::ProofSystem;
+use ethereum_client::{batch_state_id, batch_state_root, config::Config, EthereumClient};
 
 #[derive(Parser)]
 #[command(name = "cli")]
@@ -52,6 +98,18 @@ struct Cli {
     )]
     api_url: String,
 
+    /// API key sent as `x-api-key`
+    #[arg(long, env = "ARITHMETIC_API_KEY", hide_env_values = true)]
+    api_key: Option<String>,
+
+    /// Per-request timeout in seconds
+    #[arg(long, default_value = "30")]
+    timeout: u64,
+
+    /// Attempts per request for reads and idempotent submissions (1 disables retries)
+    #[arg(long, default_value = "3")]
+    retries: u32,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -63,6 +121,22 @@ enum Commands {
         /// Transaction amount to add to the counter
         #[arg(short, long)]
         amount: i32,
+        /// Idempotency key; resubmitting with the same key never enqueues twice
+        /// (a random key is generated when omitted)
+        #[arg(long)]
+        idempotency_key: Option<String>,
+    },
+    /// Submit many transactions from a CSV file of `amount[,idempotency_key]` lines
+    SubmitTransactions {
+        /// CSV file to read; a non-numeric first line is treated as a header
+        #[arg(short, long)]
+        file: String,
+        /// Commit valid rows even if some rows are rejected
+        #[arg(long)]
+        best_effort: bool,
+        /// Rows sent per request (must not exceed the server's bulk limit)
+        #[arg(long, default_value = "1000")]
+        chunk_size: usize,
     },
     /// View all pending (unbatched) transactions
     ViewPending,
@@ -76,29 +150,174 @@ enum Commands {
         /// Show detailed output including private information
         #[arg(short, long)]
         verbose: bool,
+        /// Wait for the batch proof before exiting
+        #[arg(long)]
+        wait: bool,
+        /// Seconds to wait for the proof with --wait
+        #[arg(long, default_value = "600")]
+        wait_timeout: u64,
+    },
+    /// Wait until a batch is proven (or posted on-chain with --posted)
+    WaitForBatch {
+        /// Batch ID
+        #[arg(long)]
+        batch_id: i32,
+        /// Wait for the on-chain posting instead of the proof
+        #[arg(long)]
+        posted: bool,
+        /// Seconds to wait before giving up
+        #[arg(long, default_value = "600")]
+        timeout: u64,
+    },
+    /// Follow the server's batch lifecycle event feed
+    WatchEvents {
+        /// Only events about this batch
+        #[arg(long)]
+        batch_id: Option<i32>,
+    },
+    /// Live dashboard of the queue, batches, on-chain posts and IMT
+    Dashboard {
+        /// Seconds between refreshes
+        #[arg(long, default_value = "2")]
+        refresh: u64,
+        /// Number of recent batches shown
+        #[arg(long, default_value = "20")]
+        batches: i32,
+    },
+    /// List historical batches, following pagination cursors
+    ListBatches {
+        /// Only batches with this proof status (pending, proven, failed, unwound)
+        #[arg(long)]
+        status: Option<String>,
+        /// Only batches created at or after this time (RFC 3339)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only batches created before this time (RFC 3339)
+        #[arg(long)]
+        until: Option<String>,
+        /// Sort direction by id (asc, desc)
+        #[arg(long, default_value = "desc")]
+        order: String,
+        /// Number of batches fetched per request
+        #[arg(long, default_value = "20")]
+        page_size: i32,
+        /// Maximum number of batches to show (all when omitted)
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// List transactions (pending and batched), following pagination cursors
+    ListTransactions {
+        /// Only transactions with this queue status (pending, batched)
+        #[arg(long)]
+        status: Option<String>,
+        /// Only transactions included in this batch
+        #[arg(long)]
+        batch_id: Option<i32>,
+        /// Only transactions created at or after this time (RFC 3339)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only transactions created before this time (RFC 3339)
+        #[arg(long)]
+        until: Option<String>,
+        /// Minimum amount (inclusive)
+        #[arg(long)]
+        min_amount: Option<i32>,
+        /// Maximum amount (inclusive)
+        #[arg(long)]
+        max_amount: Option<i32>,
+        /// Sort direction by id (asc, desc)
+        #[arg(long, default_value = "desc")]
+        order: String,
+        /// Number of transactions fetched per request
+        #[arg(long, default_value = "100")]
+        page_size: i32,
+        /// Maximum number of transactions to show (all when omitted)
+        #[arg(long)]
+        limit: Option<usize>,
     },
-    /// List all historical batches
-    ListBatches,
     /// Get details of a specific batch
     GetBatch {
         /// Batch ID
         #[arg(long)]
         batch_id: i32,
     },
-    /// Download raw proof data for local verification
+    /// Unwind a failed batch and every batch built on it, then re-batch their transactions
+    RecoverBatch {
+        /// ID of the failed batch
+        #[arg(long)]
+        batch_id: i32,
+        /// Recover without asking for confirmation
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Download a batch proof bundle for offline verification
     DownloadProof {
         /// Batch ID with associated proof
         #[arg(long)]
         batch_id: i32,
-        /// Output file path (optional, defaults to `proof_batch_<id>.json`)
+        /// Output file path (optional, defaults to `proof_batch_<id>.<format>`)
         #[arg(long)]
         output: Option<String>,
+        /// Bundle encoding
+        #[arg(long, value_enum, default_value_t = BundleFormat::Json)]
+        format: BundleFormat,
+        /// Include the batch transaction amounts (operator view)
+        #[arg(long)]
+        include_transactions: bool,
     },
     /// Check API server health
     HealthCheck,
+    /// Compare the settlement contracts' state history with the database
+    Reconcile {
+        /// First batch to check (all proven batches when omitted)
+        #[arg(long)]
+        from_batch: Option<i32>,
+        /// Repair the database records and re-queue missing posts
+        #[arg(long)]
+        repair: bool,
+        /// Repair without asking for confirmation
+        #[arg(long, requires = "repair")]
+        yes: bool,
+    },
+    /// Replay every batch into a fresh IMT and compare it with the live tables
+    RebuildState {
+        /// Replace the live IMT tables with the rebuilt tree
+        #[arg(long)]
+        swap: bool,
+        /// Swap without asking for confirmation
+        #[arg(long, requires = "swap")]
+        yes: bool,
+    },
+    /// Export the state at a batch to a snapshot file, read directly from the database
+    ExportSnapshot {
+        /// PostgreSQL URL of the database to export
+        #[arg(long, env = "DATABASE_URL", hide_env_values = true)]
+        database_url: String,
+        /// Last batch in the snapshot (latest batch when omitted)
+        #[arg(long)]
+        batch_id: Option<i32>,
+        /// Output file path (optional, defaults to `state_batch_<id>.cbor`)
+        #[arg(long)]
+        output: Option<String>,
+    },
+    /// Verify a snapshot file and load it into an empty database
+    ImportSnapshot {
+        /// PostgreSQL URL of the database to import into
+        #[arg(long, env = "DATABASE_URL", hide_env_values = true)]
+        database_url: Option<String>,
+        /// Snapshot file written by `export-snapshot`
+        #[arg(long)]
+        file: String,
+        /// Only verify the snapshot, without touching a database
+        #[arg(long)]
+        verify_only: bool,
+        /// Import without asking for confirmation
+        #[arg(long)]
+        yes: bool,
+    },
     /// Verify proof locally without network dependencies
     VerifyProof {
-        /// Path to the downloaded proof JSON file
+        /// Path to a proof bundle (JSON or CBOR) written by `download-proof`
         #[arg(long, group = "input")]
         proof_file: Option<String>,
         /// Hex-encoded proof data (alternative to --proof-file)
@@ -107,9 +326,15 @@ enum Commands {
         /// Hex-encoded public values (required when using --proof-data)
         #[arg(long, requires = "proof_data")]
         public_values: Option<String>,
-        /// Hex-encoded verifying key (required when using --proof-data)
+        /// Hex-encoded verifying key hash (required when using --proof-data)
         #[arg(long, requires = "proof_data")]
         verifying_key: Option<String>,
+        /// Proof system of --proof-data
+        #[arg(long, value_enum, default_value_t = ProofSystem::Groth16, requires = "proof_data")]
+        proof_system: ProofSystem,
+        /// Expected program verifying key hash (the key pinned by the contract)
+        #[arg(long)]
+        expected_vkey: Option<String>,
         /// Expected initial balance
         #[arg(long)]
         expected_initial_balance: i32,
@@ -120,6 +345,27 @@ enum Commands {
         #[arg(short, long)]
         verbose: bool,
     },
+    /// Verify a transaction receipt offline, then look up its batch on-chain
+    VerifyReceipt {
+        /// Fetch the receipt (and its batch's proof bundle) from the API
+        #[arg(long, group = "receipt")]
+        transaction_id: Option<i32>,
+        /// Path to a receipt saved with --save
+        #[arg(long, group = "receipt", requires = "bundle_file")]
+        receipt_file: Option<String>,
+        /// Path to the batch's proof bundle written by `download-proof`
+        #[arg(long)]
+        bundle_file: Option<String>,
+        /// Write the fetched receipt to this file
+        #[arg(long, requires = "transaction_id")]
+        save: Option<String>,
+        /// Expected program verifying key hash (the key pinned by the contract)
+        #[arg(long)]
+        expected_vkey: Option<String>,
+        /// Skip the on-chain state root lookup
+        #[arg(long)]
+        skip_onchain: bool,
+    },
     /// Query the current verification key from the smart contract
     QueryVerificationKey {
         /// Show detailed verification key information
@@ -128,6 +374,22 @@ enum Commands {
     },
 }
 
+/// Encoding of a downloaded proof bundle
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum BundleFormat {
+    Json,
+    Cbor,
+}
+
+impl BundleFormat {
+    const fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Cbor => "cbor",
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Setup logging
@@ -138,12 +400,33 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
 
     // Create API client using the new batch processing client
-    let client = BatchApiClient::new(&cli.api_url);
+    let mut builder = BatchApiClient::builder(&cli.api_url)
+        .timeout(Duration::from_secs(cli.timeout))
+        .retry_policy(RetryPolicy {
+            max_attempts: cli.retries,
+            ..RetryPolicy::default()
+        });
+    if let Some(api_key) = cli.api_key {
+        builder = builder.api_key(api_key);
+    }
+    let client = builder
+        .build()
+        .map_err(|e| eyre::eyre!("Failed to create API client: {}", e))?;
 
     // Execute command
     match cli.command {
-        Commands::SubmitTransaction { amount } => {
-            submit_transaction(&client, amount).await?;
+        Commands::SubmitTransaction {
+            amount,
+            idempotency_key,
+        } => {
+            submit_transaction(&client, amount, idempotency_key).await?;
+        }
+        Commands::SubmitTransactions {
+            file,
+            best_effort,
+            chunk_size,
+        } => {
+            submit_transactions(&client, &file, best_effort, chunk_size).await?;
         }
         Commands::ViewPending => {
             view_pending_transactions(&client).await?;
@@ -154,40 +437,189 @@ async fn main() -> Result<()> {
         Commands::TriggerBatch {
             batch_size,
             verbose,
+            wait,
+            wait_timeout,
+        } => {
+            let batch_id = trigger_batch(&client, batch_size, verbose).await?;
+            if let (true, Some(batch_id)) = (wait, batch_id) {
+                wait_for_batch(&client, batch_id, false, wait_timeout).await?;
+            }
+        }
+        Commands::WaitForBatch {
+            batch_id,
+            posted,
+            timeout,
+        } => {
+            wait_for_batch(&client, batch_id, posted, timeout).await?;
+        }
+        Commands::WatchEvents { batch_id } => {
+            watch_events(&client, batch_id).await?;
+        }
+        Commands::Dashboard { refresh, batches } => {
+            dashboard::run(
+                &client,
+                &cli.api_url,
+                Duration::from_secs(refresh.max(1)),
+                batches,
+            )
+            .await?;
+        }
+        Commands::ListBatches {
+            status,
+            since,
+            until,
+            order,
+            page_size,
+            limit,
         } => {
-            trigger_batch(&client, batch_size, verbose).await?;
+            let params = BatchListParams {
+                limit: Some(page_size),
+                cursor: None,
+                status,
+                since,
+                until,
+                order: Some(order),
+            };
+            list_batches(&client, params, limit).await?;
         }
-        Commands::ListBatches => {
-            list_batches(&client).await?;
+        Commands::ListTransactions {
+            status,
+            batch_id,
+            since,
+            until,
+            min_amount,
+            max_amount,
+            order,
+            page_size,
+            limit,
+        } => {
+            let params = TransactionListParams {
+                limit: Some(page_size),
+                cursor: None,
+                status,
+                since,
+                until,
+                min_amount,
+                max_amount,
+                batch_id,
+                order: Some(order),
+            };
+            list_transactions(&client, params, limit).await?;
         }
         Commands::GetBatch { batch_id } => {
             get_batch(&client, batch_id).await?;
         }
-        Commands::DownloadProof { batch_id, output } => {
-            download_proof(&client, batch_id, output).await?;
+        Commands::DownloadProof {
+            batch_id,
+            output,
+            format,
+            include_transactions,
+        } => {
+            download_proof(&client, batch_id, output, format, include_transactions).await?;
         }
         Commands::HealthCheck => {
             health_check(&client).await?;
         }
+        Commands::RecoverBatch { batch_id, yes } => {
+            recover_batch(&client, batch_id, yes).await?;
+        }
+        Commands::Reconcile {
+            from_batch,
+            repair,
+            yes,
+        } => {
+            reconcile(&client, from_batch, repair, yes).await?;
+        }
+        Commands::RebuildState { swap, yes } => {
+            rebuild_state(&client, swap, yes).await?;
+        }
+        Commands::ExportSnapshot {
+            database_url,
+            batch_id,
+            output,
+        } => {
+            export_state_snapshot(&database_url, batch_id, output).await?;
+        }
+        Commands::ImportSnapshot {
+            database_url,
+            file,
+            verify_only,
+            yes,
+        } => {
+            import_state_snapshot(database_url.as_deref(), &file, verify_only, yes).await?;
+        }
         Commands::VerifyProof {
             proof_file,
             proof_data,
             public_values,
             verifying_key,
+            proof_system,
+            expected_vkey,
             expected_initial_balance,
             expected_final_balance,
             verbose,
         } => {
-            verify_proof_local(
+            let bundle = load_proof_bundle(
                 proof_file,
                 proof_data,
                 public_values,
                 verifying_key,
+                proof_system,
+            )?;
+            verify_proof_local(
+                &bundle,
+                expected_vkey,
                 expected_initial_balance,
                 expected_final_balance,
                 verbose,
             )?;
         }
+        Commands::VerifyReceipt {
+            transaction_id,
+            receipt_file,
+            bundle_file,
+            save,
+            expected_vkey,
+            skip_onchain,
+        } => {
+            let receipt = match (transaction_id, receipt_file) {
+                (Some(transaction_id), _) => {
+                    let receipt = client.get_transaction_receipt(transaction_id).await?;
+                    if let Some(path) = save {
+                        fs::write(&path, receipt.to_json()?)?;
+                        println!("💾 Receipt saved to {}", path);
+                    }
+                    receipt
+                }
+                (None, Some(path)) => {
+                    let json = fs::read_to_string(&path)
+                        .map_err(|e| eyre::eyre!("Failed to read receipt '{}': {}", path, e))?;
+                    TransactionReceipt::from_json(&json)
+                        .map_err(|e| eyre::eyre!("Failed to parse receipt '{}': {}", path, e))?
+                }
+                (None, None) => {
+                    return Err(eyre::eyre!(
+                        "Either --transaction-id or --receipt-file is required"
+                    ))
+                }
+            };
+            let bundle = match bundle_file {
+                Some(path) => Some(load_proof_bundle(
+                    Some(path),
+                    None,
+                    None,
+                    None,
+                    ProofSystem::default(),
+                )?),
+                None if receipt.proof_bundle.is_some() => Some(
+                    client
+                        .download_proof_bundle(receipt.batch_id, false)
+                        .await?,
+                ),
+                None => None,
+            };
+            verify_receipt(&receipt, bundle.as_ref(), expected_vkey, skip_onchain).await?;
+        }
         Commands::QueryVerificationKey { verbose } => {
             query_verification_key(verbose).await?;
         }
@@ -197,14 +629,26 @@ async fn main() -> Result<()> {
 }
 
 /// Submit a new transaction to the batch processing queue
-async fn submit_transaction(client: &BatchApiClient, amount: i32) -> Result<()> {
-    match client.submit_transaction(amount).await {
+async fn submit_transaction(
+    client: &BatchApiClient,
+    amount: i32,
+    idempotency_key: Option<String>,
+) -> Result<()> {
+    let result = match idempotency_key {
+        Some(key) => client.submit_transaction_with_key(amount, &key).await,
+        None => client.submit_transaction(amount).await,
+    };
+
+    match result {
         Ok(response) => {
             println!("✅ Transaction submitted successfully!");
             println!("   Transaction ID: {}", response.transaction_id);
             println!("   Amount: {}", response.amount);
             println!("   Status: {}", response.status);
             println!("   Created: {}", response.created_at);
+            if let Some(ref key) = response.client_tx_id {
+                println!("   Idempotency Key: {}", key);
+            }
             println!();
             println!("💡 Use 'cli view-pending' to see all pending transactions");
             println!("💡 Use 'cli trigger-batch' to create a batch and generate proof");
@@ -217,6 +661,108 @@ async fn submit_transaction(client: &BatchApiClient, amount: i32) -> Result<()>
     Ok(())
 }
 
+/// Submit the transactions listed in a CSV file using the bulk endpoint
+async fn submit_transactions(
+    client: &BatchApiClient,
+    file: &str,
+    best_effort: bool,
+    chunk_size: usize,
+) -> Result<()> {
+    let content = fs::read_to_string(file)
+        .map_err(|e| eyre::eyre!("Failed to read transactions file {}: {}", file, e))?;
+    let items = parse_transactions_csv(&content)?;
+    if items.is_empty() {
+        println!("📭 No transactions found in {}", file);
+        return Ok(());
+    }
+
+    println!(
+        "📦 Submitting {} transactions from {} ({} mode)",
+        items.len(),
+        file,
+        if best_effort { "best-effort" } else { "atomic" }
+    );
+
+    let start = Instant::now();
+    let (mut submitted, mut replayed, mut failed) = (0, 0, 0);
+    for (chunk_index, chunk) in items.chunks(chunk_size.max(1)).enumerate() {
+        let offset = chunk_index * chunk_size.max(1);
+        let response = match client.submit_transactions_bulk(chunk, best_effort).await {
+            Ok(response) => response,
+            Err(e) => {
+                eprintln!(
+                    "❌ Failed to submit rows {}-{}: {}",
+                    offset + 1,
+                    offset + chunk.len(),
+                    e
+                );
+                break;
+            }
+        };
+
+        for result in &response.results {
+            if let Some(ref error) = result.error {
+                eprintln!("   ⚠️  Row {}: {}", offset + result.index + 1, error);
+            }
+        }
+        submitted += response.submitted;
+        replayed += response.replayed;
+        failed += response.failed;
+
+        if !response.committed {
+            eprintln!(
+                "❌ Rows {}-{} rejected; nothing from this chunk was enqueued",
+                offset + 1,
+                offset + chunk.len()
+            );
+            break;
+        }
+    }
+
+    println!("✅ Bulk submission finished in {:.2?}", start.elapsed());
+    println!("   Submitted: {}", submitted);
+    println!("   Replayed:  {}", replayed);
+    println!("   Failed:    {}", failed);
+
+    Ok(())
+}
+
+/// Parse `amount[,idempotency_key]` lines, skipping blanks, comments and a header row
+fn parse_transactions_csv(content: &str) -> Result<Vec<BulkTransactionItem>> {
+    let mut items = Vec::new();
+    for (line_index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.splitn(2, ',').map(str::trim);
+        let amount_field = fields.next().unwrap_or_default();
+        let amount = match amount_field.parse::<i32>() {
+            Ok(amount) => amount,
+            Err(_) if items.is_empty() && line_index == 0 => continue,
+            Err(e) => {
+                return Err(eyre::eyre!(
+                    "Invalid amount '{}' on line {}: {}",
+                    amount_field,
+                    line_index + 1,
+                    e
+                ))
+            }
+        };
+        let client_tx_id = fields
+            .next()
+            .filter(|key| !key.is_empty())
+            .map(str::to_string);
+
+        items.push(BulkTransactionItem {
+            amount,
+            client_tx_id,
+        });
+    }
+    Ok(items)
+}
+
 /// View all pending (unbatched) transactions
 async fn view_pending_transactions(client: &BatchApiClient) -> Result<()> {
     match client.get_pending_transactions().await {
@@ -285,11 +831,13 @@ async fn get_current_state(client: &BatchApiClient) -> Result<()> {
 }
 
 /// Trigger batch creation and get contract submission data
+///
+/// Returns the ID of the created batch, if one was created.
 async fn trigger_batch(
     client: &BatchApiClient,
     batch_size: Option<i32>,
     verbose: bool,
-) -> Result<()> {
+) -> Result<Option<i32>> {
     println!("🔄 Creating batch from pending transactions...");
 
     match client.create_batch(batch_size).await {
@@ -360,29 +908,130 @@ async fn trigger_batch(
                 "   • Use 'cli download-proof --batch-id {}' once proof is ready",
                 response.batch_id
             );
+            Ok(Some(response.batch_id))
         }
         Err(e) => {
             eprintln!("❌ Failed to create batch: {}", e);
+            Ok(None)
+        }
+    }
+}
+
+/// Block until a batch is proven, or posted to the contract when `posted` is set
+async fn wait_for_batch(
+    client: &BatchApiClient,
+    batch_id: i32,
+    posted: bool,
+    timeout_secs: u64,
+) -> Result<()> {
+    let timeout = Duration::from_secs(timeout_secs);
+    let target = if posted { "posted on-chain" } else { "proven" };
+    println!(
+        "⏳ Waiting up to {}s for batch {} to be {}...",
+        timeout_secs, batch_id, target
+    );
+
+    let started = Instant::now();
+    let result = if posted {
+        client.wait_for_batch_posted(batch_id, timeout).await
+    } else {
+        client.wait_for_batch_proven(batch_id, timeout).await
+    };
+
+    match result {
+        Ok(batch) => {
+            println!(
+                "✅ Batch {} is {} ({:.1}s)",
+                batch_id,
+                target,
+                started.elapsed().as_secs_f64()
+            );
+            if let Some(proof_id) = batch.sindri_proof_id {
+                println!("   Proof ID: {}", proof_id);
+            }
+            if let Some(posted_at) = batch.posted_to_contract_at {
+                println!("   Posted: {}", posted_at);
+            }
+            Ok(())
         }
+        Err(ApiClientError::WaitTimeout { waited, .. }) => Err(eyre::eyre!(
+            "Batch {} was not {} after {:.0}s",
+            batch_id,
+            target,
+            waited.as_secs_f64()
+        )),
+        Err(e) => Err(eyre::eyre!("Failed waiting for batch {}: {}", batch_id, e)),
     }
+}
+
+/// Print batch lifecycle events until the server closes the feed
+async fn watch_events(client: &BatchApiClient, batch_id: Option<i32>) -> Result<()> {
+    let mut events = client
+        .events(batch_id)
+        .await
+        .map_err(|e| eyre::eyre!("Failed to subscribe to events: {}", e))?;
+
+    println!("📡 Watching events (Ctrl-C to stop)...");
+    while let Some(event) = events.next().await {
+        let event = match event {
+            Ok(event) => event,
+            Err(e) => {
+                eprintln!("❌ Event stream error: {}", e);
+                break;
+            }
+        };
 
+        let description = match event.kind {
+            ApiEventKind::TransactionSubmitted {
+                transaction_id,
+                amount,
+            } => format!(
+                "Transaction {} submitted (amount {})",
+                transaction_id, amount
+            ),
+            ApiEventKind::BatchCreated {
+                batch_id,
+                transaction_count,
+            } => format!(
+                "Batch {} created with {} transactions",
+                batch_id, transaction_count
+            ),
+            ApiEventKind::BatchProven { batch_id } => format!("Batch {} proven", batch_id),
+            ApiEventKind::BatchFailed { batch_id } => {
+                format!("Batch {} proof generation failed", batch_id)
+            }
+            ApiEventKind::BatchPosted { batch_id } => {
+                format!("Batch {} posted to contract", batch_id)
+            }
+            ApiEventKind::Lagged { skipped } => {
+                format!("⚠ Fell behind, {} events skipped", skipped)
+            }
+        };
+        println!("[{}] {}", event.timestamp, description);
+    }
+
+    println!("Event feed closed");
     Ok(())
 }
 
-/// List all historical batches
-async fn list_batches(client: &BatchApiClient) -> Result<()> {
-    match client.get_batches(None).await {
-        Ok(response) => {
-            if response.batches.is_empty() {
+/// List historical batches, paging through all matching results
+async fn list_batches(
+    client: &BatchApiClient,
+    params: BatchListParams,
+    limit: Option<usize>,
+) -> Result<()> {
+    match client.list_all_batches(params, limit).await {
+        Ok(batches) => {
+            if batches.is_empty() {
                 println!("📭 No batches found.");
                 println!();
                 println!("💡 Create batches using: cli trigger-batch");
             } else {
                 println!("📋 Historical Batches:");
-                println!("   Total Count: {}", response.total_count);
+                println!("   Total Count: {}", batches.len());
                 println!();
 
-                for (i, batch) in response.batches.iter().enumerate() {
+                for (i, batch) in batches.iter().enumerate() {
                     println!("   {}. Batch ID: {}", i + 1, batch.id);
                     println!(
                         "      Counter: {} → {}",
@@ -397,6 +1046,7 @@ async fn list_batches(client: &BatchApiClient) -> Result<()> {
                     if let Some(ref proven_at) = batch.proven_at {
                         println!("      Proven: {}", proven_at);
                     }
+                    print_settlements(&batch.settlements, "      ");
                     println!();
                 }
             }
@@ -409,6 +1059,81 @@ async fn list_batches(client: &BatchApiClient) -> Result<()> {
     Ok(())
 }
 
+/// List transactions, paging through all matching results
+async fn list_transactions(
+    client: &BatchApiClient,
+    params: TransactionListParams,
+    limit: Option<usize>,
+) -> Result<()> {
+    match client.list_all_transactions(params, limit).await {
+        Ok(transactions) => {
+            if transactions.is_empty() {
+                println!("📭 No transactions found.");
+            } else {
+                println!("📋 Transactions:");
+                println!("   Total Count: {}", transactions.len());
+                println!(
+                    "   Total Amount: {}",
+                    transactions
+                        .iter()
+                        .map(|t| i64::from(t.amount))
+                        .sum::<i64>()
+                );
+                println!();
+
+                for (i, tx) in transactions.iter().enumerate() {
+                    let batch = tx
+                        .included_in_batch_id
+                        .map_or_else(|| "pending".to_string(), |id| format!("batch {}", id));
+                    println!(
+                        "   {}. Transaction ID: {} | Amount: {} | {} | Created: {}",
+                        i + 1,
+                        tx.id,
+                        tx.amount,
+                        batch,
+                        tx.created_at
+                    );
+                }
+            }
+        }
+        Err(e) => {
+            eprintln!("❌ Failed to list transactions: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the per-network settlement status of a batch
+fn print_settlements(settlements: &[SettlementInfo], indent: &str) {
+    if settlements.is_empty() {
+        return;
+    }
+
+    println!("{indent}Settlement:");
+    for settlement in settlements {
+        let icon = match settlement.status.as_str() {
+            "confirmed" => "✅",
+            "failed" => "❌",
+            "submitted" => "📤",
+            _ => "⏳",
+        };
+        println!(
+            "{indent}  {icon} {} (chain {}): {}",
+            settlement.target, settlement.chain_id, settlement.status
+        );
+        if let Some(ref tx_hash) = settlement.transaction_hash {
+            match settlement.block_number {
+                Some(block) => println!("{indent}     Tx: {tx_hash} (block {block})"),
+                None => println!("{indent}     Tx: {tx_hash}"),
+            }
+        }
+        if let Some(ref error) = settlement.error {
+            println!("{indent}     Error: {error}");
+        }
+    }
+}
+
 /// Get details of a specific batch
 async fn get_batch(client: &BatchApiClient, batch_id: i32) -> Result<()> {
     match client.get_batch(batch_id).await {
@@ -419,6 +1144,9 @@ async fn get_batch(client: &BatchApiClient, batch_id: i32) -> Result<()> {
             println!("   Final Counter: {}", batch.final_counter_value);
             println!("   Transaction Count: {}", batch.transaction_count);
             println!("   Proof Status: {}", batch.proof_status);
+            if let Some(parent_batch_id) = batch.parent_batch_id {
+                println!("   Parent Batch: {}", parent_batch_id);
+            }
 
             if let Some(ref proof_id) = batch.sindri_proof_id {
                 println!("   Sindri Proof ID: {}", proof_id);
@@ -428,6 +1156,7 @@ async fn get_batch(client: &BatchApiClient, batch_id: i32) -> Result<()> {
             if let Some(ref proven_at) = batch.proven_at {
                 println!("   Proven: {}", proven_at);
             }
+            print_settlements(&batch.settlements, "   ");
 
             println!();
             if batch.proof_status == "proven" {
@@ -435,6 +1164,11 @@ async fn get_batch(client: &BatchApiClient, batch_id: i32) -> Result<()> {
                     "💡 Proof is ready! Download using: cli download-proof --batch-id {}",
                     batch_id
                 );
+            } else if batch.proof_status == "failed" {
+                println!(
+                    "💡 Proof generation failed. Recover using: cli recover-batch --batch-id {}",
+                    batch_id
+                );
             } else {
                 println!(
                     "⏳ Proof is still being generated (status: {})",
@@ -453,12 +1187,13 @@ async fn get_batch(client: &BatchApiClient, batch_id: i32) -> Result<()> {
     Ok(())
 }
 
-/// Download proof data for local verification
-#[allow(clippy::too_many_lines)]
+/// Download a proof bundle for offline verification
 async fn download_proof(
     client: &BatchApiClient,
     batch_id: i32,
     output: Option<String>,
+    format: BundleFormat,
+    include_transactions: bool,
 ) -> Result<()> {
     // First check if the batch exists and has a proof
     match client.get_batch(batch_id).await {
@@ -475,86 +1210,51 @@ async fn download_proof(
                 return Ok(());
             }
 
-            if batch.sindri_proof_id.is_none() {
-                println!("❌ Batch {} has no associated proof ID", batch_id);
-                return Ok(());
-            }
+            println!("🔄 Downloading proof bundle for batch {}...", batch_id);
 
-            let proof_id = batch.sindri_proof_id.unwrap();
-            let filename = output.unwrap_or_else(|| format!("proof_batch_{}.json", batch_id));
-
-            println!("🔄 Downloading real proof data from Sindri...");
-            println!("   Proof ID: {}", proof_id);
-
-            // Fetch actual proof data from Sindri
-            match arithmetic_lib::proof::get_sindri_proof_data(&proof_id).await {
-                Ok(proof_data) => {
-                    let download_data = serde_json::json!({
-                        "batch_id": batch_id,
-                        "proof_id": proof_data.proof_id,
-                        "initial_balance": batch.previous_counter_value,
-                        "final_balance": batch.final_counter_value,
-                        "status": "proven",
-                        "proof_data": format!("0x{}", hex::encode(&proof_data.proof_bytes)),
-                        "public_values": format!("0x{}", hex::encode(&proof_data.public_values)),
-                        "verifying_key": format!("0x{}", hex::encode(&proof_data.verifying_key)),
-                        "note": "Real proof data retrieved from Sindri API"
-                    });
-
-                    fs::write(&filename, serde_json::to_string_pretty(&download_data)?)?;
-
-                    println!("✅ Real proof data downloaded!");
-                    println!("   File: {}", filename);
-                    println!("   Batch ID: {}", batch_id);
-                    println!("   Proof ID: {}", proof_data.proof_id);
-                    println!(
-                        "   Balance: {} → {}",
-                        batch.previous_counter_value, batch.final_counter_value
-                    );
-                    println!("   Proof Size: {} bytes", proof_data.proof_bytes.len());
-                    println!(
-                        "   Public Values Size: {} bytes",
-                        proof_data.public_values.len()
-                    );
-                    println!(
-                        "   Verifying Key Size: {} bytes",
-                        proof_data.verifying_key.len()
-                    );
-                    println!();
-                }
+            let bundle = match client
+                .download_proof_bundle(batch_id, include_transactions)
+                .await
+            {
+                Ok(bundle) => bundle,
                 Err(e) => {
-                    println!("❌ Failed to download proof data from Sindri: {}", e);
+                    println!("❌ Failed to download proof bundle: {}", e);
                     println!("💡 This might be because:");
                     println!("   • Proof is still being generated");
                     println!("   • Network connectivity issues");
                     println!("   • Sindri API error");
-                    println!();
-                    println!("⚠ Falling back to placeholder template...");
-
-                    // Fallback to placeholder
-                    let download_data = serde_json::json!({
-                        "batch_id": batch_id,
-                        "proof_id": proof_id,
-                        "initial_balance": batch.previous_counter_value,
-                        "final_balance": batch.final_counter_value,
-                        "status": "proven",
-                        "proof_data": "0x...",
-                        "public_values": "0x...",
-                        "verifying_key": "0x...",
-                        "error": format!("Failed to fetch real data: {}", e),
-                        "note": "Placeholder data - real proof fetch failed"
-                    });
-
-                    fs::write(&filename, serde_json::to_string_pretty(&download_data)?)?;
-
-                    println!("✅ Placeholder proof data saved!");
-                    println!("   File: {}", filename);
-                    println!("   Batch ID: {}", batch_id);
-                    println!("   Proof ID: {}", proof_id);
-                    println!();
+                    return Ok(());
                 }
+            };
+
+            let filename = output
+                .unwrap_or_else(|| format!("proof_batch_{}.{}", batch_id, format.extension()));
+            match format {
+                BundleFormat::Json => fs::write(&filename, bundle.to_json()?)?,
+                BundleFormat::Cbor => fs::write(&filename, bundle.to_cbor()?)?,
+            }
+
+            println!("✅ Proof bundle downloaded!");
+            println!("   File: {}", filename);
+            println!("   Batch ID: {}", bundle.batch_id);
+            println!(
+                "   Proof System: {}",
+                bundle.proof_system.to_sindri_scheme().to_uppercase()
+            );
+            println!(
+                "   Balance: {} → {}",
+                batch.previous_counter_value, batch.final_counter_value
+            );
+            println!("   Previous Root: 0x{}", hex::encode(bundle.prev_root));
+            println!("   New Root: 0x{}", hex::encode(bundle.new_root));
+            println!("   Verifying Key Hash: 0x{}", hex::encode(bundle.vkey_hash));
+            println!("   Proof Size: {} bytes", bundle.proof.len());
+            if let Some(transactions) = &bundle.transactions {
+                println!("   Transactions: {:?}", transactions);
             }
-            println!("💡 Once real proof data is available, verify with:");
+            println!("   Checksum: 0x{}", hex::encode(bundle.checksum));
+            println!();
+            println!("💡 Verify offline with:");
             println!("   cli verify-proof --proof-file {} \\", filename);
             println!(
                 "     --expected-initial-balance {} \\",
@@ -583,7 +1283,7 @@ async fn health_check(client: &BatchApiClient) -> Result<()> {
     match client.health_check().await {
         Ok(response) => {
             let duration = start.elapsed();
-            println!("✅ API server is healthy");
+            println!("✅ API server is reachable");
             println!("   Status: {}", response.status);
             println!(
                 "   Database: {}",
@@ -595,6 +1295,29 @@ async fn health_check(client: &BatchApiClient) -> Result<()> {
             );
             println!("   Response time: {:?}", duration);
             println!("   Timestamp: {}", response.timestamp);
+
+            for chain in &response.chains {
+                let icon = if chain.healthy { "✅" } else { "⚠️" };
+                println!(
+                    "   {icon} Chain {} (expected id {})",
+                    chain.network, chain.expected_chain_id
+                );
+                if let Some(block) = chain.block_number {
+                    let age = chain
+                        .head_age_seconds
+                        .map_or_else(String::new, |age| format!(", {age}s old"));
+                    println!("      Head: block {block}{age}");
+                }
+                if let Some(ref gas_price) = chain.gas_price {
+                    println!("      Gas price: {gas_price} wei");
+                }
+                for issue in &chain.issues {
+                    println!("      Issue: {issue}");
+                }
+                if let Some(ref error) = chain.error {
+                    println!("      Error: {error}");
+                }
+            }
         }
         Err(e) => {
             let duration = start.elapsed();
@@ -606,13 +1329,332 @@ async fn health_check(client: &BatchApiClient) -> Result<()> {
     Ok(())
 }
 
-/// Verify proof locally without requiring network access
-#[allow(clippy::too_many_lines)]
-fn verify_proof_local(
+/// Report on-chain/off-chain discrepancies and optionally repair them
+async fn recover_batch(client: &BatchApiClient, batch_id: i32, yes: bool) -> Result<()> {
+    if !yes {
+        print!(
+            "Unwind batch {batch_id} and every batch built on it, then re-batch their transactions? [y/N] "
+        );
+        std::io::Write::flush(&mut std::io::stdout())?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            println!("Aborted, nothing changed");
+            return Ok(());
+        }
+    }
+
+    let recovery = client.recover_batch(batch_id).await?;
+    println!(
+        "⏪ Unwound batch(es) {:?}, {} transaction(s) back in the queue",
+        recovery.unwound_batch_ids,
+        recovery.released_transaction_ids.len()
+    );
+    match recovery.parent_batch_id {
+        Some(parent) => println!("   Chain continues from batch {parent}"),
+        None => println!("   Chain restarts from the genesis state"),
+    }
+    println!("   Restored root: {}", recovery.restored_root);
+    if recovery.rebatched_batch_ids.is_empty() {
+        println!("⚠️ Nothing re-batched yet; the batch processor will pick the transactions up");
+    } else {
+        println!("📦 Re-batched into {:?}", recovery.rebatched_batch_ids);
+    }
+
+    Ok(())
+}
+
+async fn reconcile(
+    client: &BatchApiClient,
+    from_batch: Option<i32>,
+    repair: bool,
+    yes: bool,
+) -> Result<()> {
+    let report = client.reconcile(from_batch).await?;
+    print_reconciliation(&report);
+
+    if !repair {
+        return Ok(());
+    }
+    let repairable = report.discrepancies.iter().filter(|d| d.repairable).count();
+    if repairable == 0 {
+        println!("✅ Nothing to repair");
+        return Ok(());
+    }
+
+    if !yes {
+        print!("Apply {repairable} repair(s) to the database? [y/N] ");
+        std::io::Write::flush(&mut std::io::stdout())?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            println!("Aborted, nothing changed");
+            return Ok(());
+        }
+    }
+
+    let repaired = client.repair_reconciliation(from_batch).await?;
+    println!("🔧 Applied {} repair(s)", repaired.repairs.len());
+    for repair in &repaired.repairs {
+        println!("   {repair}");
+    }
+    for error in &repaired.errors {
+        println!("   ❌ {error}");
+    }
+
+    Ok(())
+}
+
+fn print_reconciliation(report: &arithmetic_api::ReconciliationReport) {
+    println!(
+        "🔎 Checked {} proven batches on {}",
+        report.checked_batches,
+        if report.targets.is_empty() {
+            "no targets".to_string()
+        } else {
+            report.targets.join(", ")
+        }
+    );
+
+    if report.discrepancies.is_empty() {
+        println!("✅ Contract state history matches the database");
+    }
+    for discrepancy in &report.discrepancies {
+        let icon = if discrepancy.repairable {
+            "🔧"
+        } else {
+            "⚠️"
+        };
+        let target = if discrepancy.target.is_empty() {
+            String::new()
+        } else {
+            format!(" on {}", discrepancy.target)
+        };
+        println!(
+            "{icon} Batch {}{target}: {} - {}",
+            discrepancy.batch_id, discrepancy.kind, discrepancy.detail
+        );
+        if !discrepancy.onchain_roots.is_empty() {
+            println!("      Expected root: {}", discrepancy.expected_root);
+            for root in &discrepancy.onchain_roots {
+                println!("      On-chain root: {root}");
+            }
+        }
+    }
+    for error in &report.errors {
+        println!("❌ {error}");
+    }
+}
+
+async fn rebuild_state(client: &BatchApiClient, swap: bool, yes: bool) -> Result<()> {
+    println!("🔁 Replaying the transaction log into a fresh IMT...");
+    let report = client.rebuild_state(false).await?;
+    print_rebuild(&report);
+
+    if !swap {
+        return Ok(());
+    }
+    if report.divergence.is_some() {
+        println!("❌ Not swapping: the replay does not reproduce every committed root");
+        std::process::exit(1);
+    }
+    if report.live.as_ref().is_some_and(|live| {
+        live.root_matches && live.nullifier_mismatches == 0 && live.node_mismatches == 0
+    }) {
+        println!("✅ Nothing to swap");
+        return Ok(());
+    }
+
+    if !yes {
+        print!("Replace the live IMT tables with the rebuilt tree? [y/N] ");
+        std::io::Write::flush(&mut std::io::stdout())?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            println!("Aborted, nothing changed");
+            return Ok(());
+        }
+    }
+
+    // Replayed again under the server's ADS lock, so batches created since are included
+    let swapped = client.rebuild_state(true).await?;
+    if swapped.swapped {
+        println!(
+            "🔁 Swapped in the rebuilt IMT: {} nullifiers, root {}",
+            swapped.nullifier_count, swapped.rebuilt_root
+        );
+    } else {
+        print_rebuild(&swapped);
+        println!("❌ The server did not swap the rebuilt tree in");
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn print_rebuild(report: &arithmetic_api::StateRebuildResponse) {
+    println!(
+        "   Replayed {} batches, {} transactions, {} nullifiers",
+        report.batches_replayed, report.transactions_replayed, report.nullifier_count
+    );
+    println!("   Rebuilt root: {}", report.rebuilt_root);
+
+    if let Some(divergence) = &report.divergence {
+        println!(
+            "⚠️ Batch {} diverges: {}",
+            divergence.batch_id, divergence.reason
+        );
+        println!(
+            "      Committed root: {}",
+            divergence.committed_root.as_deref().unwrap_or("none")
+        );
+        if let Some(root) = &divergence.replayed_root {
+            println!("      Replayed root:  {root}");
+        }
+    }
+
+    if let Some(live) = &report.live {
+        if live.root_matches && live.nullifier_mismatches == 0 && live.node_mismatches == 0 {
+            println!("✅ Live IMT tables match the transaction log");
+        } else {
+            println!("⚠️ Live IMT tables differ from the transaction log");
+            println!(
+                "      Live root: {}{}",
+                live.live_root,
+                if live.root_matches { "" } else { " (differs)" }
+            );
+            println!("      Mismatched nullifiers: {}", live.nullifier_mismatches);
+            println!("      Mismatched nodes: {}", live.node_mismatches);
+        }
+    }
+}
+
+async fn export_state_snapshot(
+    database_url: &str,
+    batch_id: Option<i32>,
+    output: Option<String>,
+) -> Result<()> {
+    let pool = init_db_with_url(database_url)
+        .await
+        .map_err(|e| eyre::eyre!("Failed to connect to the database: {}", e))?;
+
+    println!("📦 Exporting state snapshot...");
+    let snapshot = export_snapshot(&pool, batch_id).await?;
+    let path = output.unwrap_or_else(|| format!("state_batch_{}.cbor", snapshot.manifest.batch_id));
+    fs::write(&path, snapshot.to_cbor()?)?;
+
+    print_snapshot_manifest(&snapshot);
+    println!("💾 Snapshot saved to {}", path);
+    Ok(())
+}
+
+async fn import_state_snapshot(
+    database_url: Option<&str>,
+    file: &str,
+    verify_only: bool,
+    yes: bool,
+) -> Result<()> {
+    let bytes =
+        fs::read(file).map_err(|e| eyre::eyre!("Failed to read snapshot '{}': {}", file, e))?;
+    let snapshot = StateSnapshot::from_cbor(&bytes)?;
+    print_snapshot_manifest(&snapshot);
+
+    println!("🔁 Replaying the snapshot to check its roots...");
+    snapshot.verify()?;
+    println!("✅ Snapshot replays to its manifest root");
+    if verify_only {
+        return Ok(());
+    }
+
+    let database_url =
+        database_url.ok_or_else(|| eyre::eyre!("--database-url or DATABASE_URL is required"))?;
+    if !yes {
+        print!(
+            "Load batch {} into the database at {}? [y/N] ",
+            snapshot.manifest.batch_id,
+            database_url.split('@').next_back().unwrap_or(database_url)
+        );
+        std::io::Write::flush(&mut std::io::stdout())?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            println!("Aborted, nothing changed");
+            return Ok(());
+        }
+    }
+
+    let pool = init_db_with_url(database_url)
+        .await
+        .map_err(|e| eyre::eyre!("Failed to connect to the database: {}", e))?;
+    import_snapshot(&pool, &snapshot).await?;
+    println!(
+        "✅ Imported state at batch {}, counter {}",
+        snapshot.manifest.batch_id, snapshot.manifest.counter_value
+    );
+    Ok(())
+}
+
+fn print_snapshot_manifest(snapshot: &StateSnapshot) {
+    let manifest = &snapshot.manifest;
+    println!("   Batch: {}", manifest.batch_id);
+    println!("   Counter value: {}", manifest.counter_value);
+    println!("   IMT root: 0x{}", hex::encode(manifest.imt_root));
+    println!("   Exported at: {}", manifest.exported_at.to_rfc3339());
+    println!(
+        "   Rows: {} batches, {} transactions, {} commits, {} nullifiers, {} nodes",
+        manifest.batch_count,
+        manifest.transaction_count,
+        manifest.commit_count,
+        manifest.nullifier_count,
+        manifest.node_count
+    );
+    println!("   Checksum: 0x{}", hex::encode(manifest.checksum));
+}
+
+/// Read a proof bundle from a file, or assemble one from hex arguments
+///
+/// A bundle assembled from hex has no batch id or roots; only its proof and
+/// balances are verified.
+fn load_proof_bundle(
     proof_file: Option<String>,
     proof_data: Option<String>,
     public_values: Option<String>,
     verifying_key: Option<String>,
+    proof_system: ProofSystem,
+) -> Result<ProofBundle> {
+    if let Some(file_path) = proof_file {
+        let bytes = fs::read(&file_path)
+            .map_err(|e| eyre::eyre!("Failed to read proof file '{}': {}", file_path, e))?;
+        return ProofBundle::decode(&bytes)
+            .map_err(|e| eyre::eyre!("Failed to parse proof bundle '{}': {}", file_path, e));
+    }
+
+    let decode_hex = |name: &str, value: Option<String>| -> Result<Vec<u8>> {
+        let value = value.ok_or_else(|| eyre::eyre!("{} is required", name))?;
+        hex::decode(value.strip_prefix("0x").unwrap_or(&value))
+            .map_err(|e| eyre::eyre!("{} is not valid hex: {}", name, e))
+    };
+    let proof = decode_hex("Proof data", proof_data)?;
+    let public_values = decode_hex("Public values", public_values)?;
+    let verifying_key = verifying_key.ok_or_else(|| eyre::eyre!("Verifying key is required"))?;
+    let vkey_hash = parse_hex_array::<32>(&verifying_key)
+        .map_err(|e| eyre::eyre!("Verifying key is not a 32-byte hash: {}", e))?;
+
+    Ok(ProofBundle::new(
+        0,
+        proof_system,
+        vkey_hash,
+        public_values,
+        proof,
+        [0u8; 32],
+        [0u8; 32],
+    ))
+}
+
+/// Verify a proof bundle locally without requiring network access
+fn verify_proof_local(
+    bundle: &ProofBundle,
+    expected_vkey: Option<String>,
     expected_initial_balance: i32,
     expected_final_balance: i32,
     verbose: bool,
@@ -621,147 +1663,205 @@ fn verify_proof_local(
 
     println!("🔍 Starting local proof verification...");
 
-    let (proof_hex, public_values_hex, verifying_key_hex, batch_info) =
-        if let Some(file_path) = proof_file {
-            // Load from file
-            let file_content = fs::read_to_string(&file_path)
-                .map_err(|e| eyre::eyre!("Failed to read proof file '{}': {}", file_path, e))?;
-
-            let download_response: serde_json::Value = serde_json::from_str(&file_content)
-                .map_err(|e| eyre::eyre!("Failed to parse proof file: {}", e))?;
-
-            let proof_data = download_response
-                .get("proof_data")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| eyre::eyre!("Proof data not found in file"))?
-                .to_string();
-            let public_values = download_response
-                .get("public_values")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| eyre::eyre!("Public values not found in file"))?
-                .to_string();
-            let verifying_key = download_response
-                .get("verifying_key")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| eyre::eyre!("Verifying key not found in file"))?
-                .to_string();
-
-            let batch_id = i32::try_from(
-                download_response
-                    .get("batch_id")
-                    .and_then(serde_json::Value::as_i64)
-                    .unwrap_or(0),
-            )
-            .unwrap_or(0);
-            let initial_balance = i32::try_from(
-                download_response
-                    .get("initial_balance")
-                    .and_then(serde_json::Value::as_i64)
-                    .unwrap_or(0),
-            )
-            .unwrap_or(0);
-            let final_balance = i32::try_from(
-                download_response
-                    .get("final_balance")
-                    .and_then(serde_json::Value::as_i64)
-                    .unwrap_or(0),
-            )
-            .unwrap_or(0);
-
-            (
-                proof_data,
-                public_values,
-                verifying_key,
-                (batch_id, initial_balance, final_balance),
-            )
-        } else {
-            // Use provided hex data
-            let proof_data = proof_data.ok_or_else(|| eyre::eyre!("Proof data is required"))?;
-            let public_values =
-                public_values.ok_or_else(|| eyre::eyre!("Public values are required"))?;
-            let verifying_key =
-                verifying_key.ok_or_else(|| eyre::eyre!("Verifying key is required"))?;
-
-            (proof_data, public_values, verifying_key, (0, 0, 0))
-        };
+    let expected_vkey = expected_vkey
+        .map(|vkey| parse_hex_array::<32>(&vkey))
+        .transpose()
+        .map_err(|e| eyre::eyre!("Expected verifying key is not a 32-byte hash: {}", e))?;
 
     if verbose {
         println!("📊 Verification Details:");
+        println!("   Bundle version: {}", bundle.version);
+        println!("   Batch ID: {}", bundle.batch_id);
+        println!(
+            "   Proof system: {}",
+            bundle.proof_system.to_sindri_scheme().to_uppercase()
+        );
         println!("   Expected initial balance: {}", expected_initial_balance);
         println!("   Expected final balance: {}", expected_final_balance);
-        println!("   Proof data length: {} chars", proof_hex.len());
-        println!("   Public values length: {} chars", public_values_hex.len());
-        println!("   Verifying key length: {} chars", verifying_key_hex.len());
-        if batch_info.0 > 0 {
-            println!("   Batch ID: {}", batch_info.0);
-            println!("   File initial balance: {}", batch_info.1);
-            println!("   File final balance: {}", batch_info.2);
+        println!("   Proof size: {} bytes", bundle.proof.len());
+        println!(
+            "   Public values size: {} bytes",
+            bundle.public_values.len()
+        );
+        println!("   Verifying key hash: 0x{}", hex::encode(bundle.vkey_hash));
+        println!("   Previous root: 0x{}", hex::encode(bundle.prev_root));
+        println!("   New root: 0x{}", hex::encode(bundle.new_root));
+        if let Some(transactions) = &bundle.transactions {
+            println!("   Transactions: {:?}", transactions);
         }
         println!();
     }
 
-    // For now, we'll validate the structure and expected values
-    // In a real implementation, you'd verify the actual cryptographic proof
-    println!("🔓 Validating proof structure and values...");
+    if expected_vkey.is_none() {
+        println!(
+            "   ⚠ No --expected-vkey given: the proof is checked against the key in the bundle"
+        );
+    }
 
-    let balances_match = if batch_info.0 > 0 {
-        // Compare with file data
-        batch_info.1 == expected_initial_balance && batch_info.2 == expected_final_balance
-    } else {
-        // Can't validate without file data in this placeholder implementation
-        true
+    let expectations = BundleExpectations {
+        vkey_hash: expected_vkey,
+        initial_balance: Some(expected_initial_balance),
+        final_balance: Some(expected_final_balance),
+        ..BundleExpectations::default()
     };
 
-    if balances_match {
-        println!("   ✅ Balance transition matches expected values");
-    } else {
-        println!("   ❌ Balance transition mismatch");
-        if batch_info.0 > 0 {
+    println!("🔒 Verifying checksum, balances and SP1 proof...");
+    let result = verify_bundle(bundle, &expectations);
+    let verification_time = start_time.elapsed();
+
+    match result {
+        Ok(summary) => {
+            println!();
+            println!("📋 Verification Summary:");
+            println!(
+                "   Balance transition: {} → {} ✅",
+                summary.initial_balance, summary.final_balance
+            );
             println!(
-                "      Expected: {} → {}",
-                expected_initial_balance, expected_final_balance
+                "   {} proof: ✅ VALID",
+                summary.proof_system.to_sindri_scheme().to_uppercase()
             );
-            println!("      File contains: {} → {}", batch_info.1, batch_info.2);
+            if bundle.transactions.is_some() {
+                println!("   Transactions: ✅ match the balance transition");
+            }
+            println!("   Verification Time: {:?}", verification_time);
+            println!();
+            println!("🎉 Batch proof cryptographically verified!");
+            println!("   • Privacy: Individual transaction amounts remain hidden");
+            println!("   • Correctness: Balance transition verified");
+            println!("   • Integrity: Bundle checksum and proof are valid");
+            Ok(())
+        }
+        Err(e) => {
+            error!("❌ Proof verification failed: {}", e);
+            error!("   Verification Time: {:?}", verification_time);
+            std::process::exit(1);
         }
     }
+}
 
-    // Placeholder cryptographic verification
+/// Verify every link from a transaction to its batch's on-chain state root
+///
+/// Offline: the transaction's nullifier and Merkle path lead to the batch
+/// root, and the proof bundle proves that root and the batch's final balance.
+/// Then, unless skipped, the batch's state root is read from the contract.
+async fn verify_receipt(
+    receipt: &TransactionReceipt,
+    bundle: Option<&ProofBundle>,
+    expected_vkey: Option<String>,
+    skip_onchain: bool,
+) -> Result<()> {
+    fn fail(message: String) -> ! {
+        error!("❌ {}", message);
+        std::process::exit(1);
+    }
+
+    println!(
+        "🧾 Verifying receipt for transaction {}...",
+        receipt.transaction_id
+    );
+    println!("   Amount: {}", receipt.amount);
+    println!("   Batch ID: {}", receipt.batch_id);
+    println!("   Nullifier: {}", receipt.nullifier);
+    println!("   Leaf index: {}", receipt.membership.leaf_index);
+    println!("   Batch root: 0x{}", hex::encode(receipt.batch_root));
+    println!("   Proof status: {}", receipt.proof_status);
     println!();
-    println!("🔒 Cryptographic verification...");
-    println!("   ℹ Note: Full cryptographic proof verification requires SP1 verifier integration");
-    println!("   ℹ For now, validating structure and balance transitions only");
 
-    let verification_time = start_time.elapsed();
-    let overall_valid = balances_match;
+    if let Err(e) = receipt.verify() {
+        fail(format!("Inclusion check failed: {}", e));
+    }
+    println!("✅ Transaction hashes to the nullifier in the proven leaf");
+    println!(
+        "✅ Membership proof leads to the batch root ({} levels)",
+        receipt.membership.siblings.len()
+    );
 
-    println!();
-    println!("📋 Verification Summary:");
+    let Some(bundle) = bundle else {
+        fail(format!(
+            "Batch {} is not proven yet (status: {}), try again later",
+            receipt.batch_id, receipt.proof_status
+        ));
+    };
+
+    let expected_vkey = expected_vkey
+        .map(|vkey| parse_hex_array::<32>(&vkey))
+        .transpose()
+        .map_err(|e| eyre::eyre!("Expected verifying key is not a 32-byte hash: {}", e))?;
+    if expected_vkey.is_none() {
+        println!(
+            "   ⚠ No --expected-vkey given: the proof is checked against the key in the bundle"
+        );
+    }
+
+    println!("🔒 Verifying the batch proof bundle...");
+    let expectations = BundleExpectations {
+        batch_id: Some(receipt.batch_id),
+        vkey_hash: expected_vkey,
+        new_root: Some(receipt.batch_root),
+        ..BundleExpectations::default()
+    };
+    let summary = match verify_bundle(bundle, &expectations) {
+        Ok(summary) => summary,
+        Err(e) => fail(format!("Proof bundle verification failed: {}", e)),
+    };
+    if let Err(e) = receipt.check_bundle(&summary) {
+        fail(format!("Proof bundle does not match the receipt: {}", e));
+    }
     println!(
-        "   Balance validation: {}",
-        if balances_match {
-            "✅ PASS"
-        } else {
-            "❌ FAIL"
-        }
+        "✅ {} proof of batch {} is valid: balance {} → {}",
+        summary.proof_system.to_sindri_scheme().to_uppercase(),
+        summary.batch_id,
+        summary.initial_balance,
+        summary.final_balance
     );
-    println!("   Structure validation: ✅ PASS");
-    println!("   Verification Time: {:?}", verification_time);
-    println!();
 
-    if overall_valid {
-        println!("🎉 Batch proof structure successfully verified!");
-        println!("   • Privacy: Individual transaction amounts remain hidden");
-        println!("   • Correctness: Balance transition verified");
-        println!("   • Integrity: Proof structure is valid");
-        println!();
-        println!("⚠ Note: This is a structural verification only.");
-        println!("   Full cryptographic verification requires SP1 verifier integration.");
+    if skip_onchain {
+        println!("   ⚠ Skipping the on-chain lookup (--skip-onchain)");
     } else {
-        error!("❌ Proof verification failed!");
-        error!("   • The proof does not match expected values");
-        std::process::exit(1);
+        println!("🔗 Looking up the batch's state root on-chain...");
+        let ethereum_config = Config::from_env()
+            .map_err(|e| eyre::eyre!("Failed to load Ethereum configuration: {}", e))?;
+        let ethereum_client = EthereumClient::new_without_validation(ethereum_config)
+            .await
+            .map_err(|e| eyre::eyre!("Failed to connect to Ethereum network: {}", e))?;
+
+        let expected_root = batch_state_root(receipt.final_counter_value);
+        let onchain_root = ethereum_client
+            .get_state_root(batch_state_id(receipt.batch_id))
+            .await
+            .map_err(|e| eyre::eyre!("Failed to read the state root: {}", e))?;
+        if onchain_root.is_zero() {
+            fail(format!(
+                "Batch {} has not been posted to the contract yet",
+                receipt.batch_id
+            ));
+        }
+        if onchain_root != expected_root {
+            fail(format!(
+                "On-chain root {} is not the root of balance {} ({})",
+                onchain_root, receipt.final_counter_value, expected_root
+            ));
+        }
+        println!("✅ Contract holds the batch's state root {}", onchain_root);
+        for settlement in &receipt.settlements {
+            if let Some(hash) = &settlement.transaction_hash {
+                println!(
+                    "   {} (chain {}): {} {}",
+                    settlement.target, settlement.chain_id, settlement.status, hash
+                );
+            }
+        }
     }
 
+    println!();
+    println!(
+        "🎉 Transaction {} is included in batch {}",
+        receipt.transaction_id, receipt.batch_id
+    );
+    if !skip_onchain {
+        println!("   and the batch's final balance is settled on-chain");
+    }
     Ok(())
 }
 
@@ -0,0 +1,519 @@
+//! Live operator dashboard (`cli dashboard`)
+//!
+//! Polls the API and redraws a full-screen view of the batch pipeline: the
+//! pending queue, recent batches with their proof progress, recent on-chain
+//! posts and the IMT. Keys act on the selected batch.
+
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use arithmetic_api::{
+    BatchApiClient, BatchInfo, BatchListParams, ContractSubmissionData, CurrentStateResponse,
+};
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+use termion::screen::IntoAlternateScreen;
+use termion::{clear, color, cursor, style};
+
+/// How often the key loop wakes up to check for input
+const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Number of recent posts listed
+const RECENT_POSTS: usize = 5;
+
+/// Everything shown on the overview, fetched in one refresh
+struct Snapshot {
+    fetched_at: DateTime<Utc>,
+    queue_depth: usize,
+    queue_amount: i32,
+    oldest_pending: Option<DateTime<Utc>>,
+    state: CurrentStateResponse,
+    /// Most recent batches, newest first
+    batches: Vec<BatchInfo>,
+}
+
+enum View {
+    Overview,
+    Contract {
+        batch_id: i32,
+        data: Result<Option<ContractSubmissionData>, String>,
+    },
+}
+
+struct Dashboard<'a> {
+    client: &'a BatchApiClient,
+    api_url: String,
+    refresh: Duration,
+    batch_window: i32,
+    snapshot: Option<Snapshot>,
+    /// Error of the last refresh; the previous snapshot stays on screen
+    refresh_error: Option<String>,
+    selected: usize,
+    view: View,
+    /// Outcome of the last key action
+    message: Option<String>,
+}
+
+/// Run the dashboard until the operator quits
+pub async fn run(
+    client: &BatchApiClient,
+    api_url: &str,
+    refresh: Duration,
+    batch_window: i32,
+) -> Result<()> {
+    let mut dashboard = Dashboard {
+        client,
+        api_url: api_url.to_string(),
+        refresh,
+        batch_window,
+        snapshot: None,
+        refresh_error: None,
+        selected: 0,
+        view: View::Overview,
+        message: None,
+    };
+
+    // Raw mode and the alternate screen are restored when these are dropped
+    let mut keys = termion::async_stdin().keys();
+    let mut screen =
+        cursor::HideCursor::from(io::stdout().into_raw_mode()?.into_alternate_screen()?);
+
+    let mut next_refresh = Instant::now();
+    let mut dirty = true;
+    loop {
+        if Instant::now() >= next_refresh {
+            dashboard.refresh().await;
+            next_refresh = Instant::now() + dashboard.refresh;
+            dirty = true;
+        }
+
+        for key in keys.by_ref() {
+            if !dashboard.handle_key(key?).await {
+                return Ok(());
+            }
+            dirty = true;
+        }
+
+        if dirty {
+            dashboard.draw(&mut screen)?;
+            dirty = false;
+        }
+        tokio::time::sleep(INPUT_POLL_INTERVAL).await;
+    }
+}
+
+impl Dashboard<'_> {
+    async fn refresh(&mut self) {
+        match self.fetch().await {
+            Ok(snapshot) => {
+                self.selected = self.selected.min(snapshot.batches.len().saturating_sub(1));
+                self.snapshot = Some(snapshot);
+                self.refresh_error = None;
+            }
+            Err(e) => self.refresh_error = Some(e.to_string()),
+        }
+    }
+
+    async fn fetch(&self) -> Result<Snapshot> {
+        let pending = self.client.get_pending_transactions().await?;
+        let state = self.client.get_current_state().await?;
+        let batches = self
+            .client
+            .list_batches(&BatchListParams {
+                limit: Some(self.batch_window),
+                order: Some("desc".to_string()),
+                ..Default::default()
+            })
+            .await?
+            .batches;
+
+        Ok(Snapshot {
+            fetched_at: Utc::now(),
+            queue_depth: pending.total_count,
+            queue_amount: pending.total_amount,
+            oldest_pending: pending
+                .transactions
+                .iter()
+                .filter_map(|tx| parse_time(&tx.created_at))
+                .min(),
+            state,
+            batches,
+        })
+    }
+
+    fn selected_batch(&self) -> Option<&BatchInfo> {
+        self.snapshot.as_ref()?.batches.get(self.selected)
+    }
+
+    /// Apply a key press; returns `false` when the operator quits
+    async fn handle_key(&mut self, key: Key) -> bool {
+        if let View::Contract { .. } = self.view {
+            match key {
+                Key::Char('q') | Key::Ctrl('c') => return false,
+                Key::Esc | Key::Backspace | Key::Char('\n') => self.view = View::Overview,
+                _ => {}
+            }
+            return true;
+        }
+
+        match key {
+            Key::Char('q') | Key::Esc | Key::Ctrl('c') => return false,
+            Key::Up | Key::Char('k') => self.selected = self.selected.saturating_sub(1),
+            Key::Down | Key::Char('j') => {
+                let count = self.snapshot.as_ref().map_or(0, |s| s.batches.len());
+                self.selected = (self.selected + 1).min(count.saturating_sub(1));
+            }
+            Key::Char('u') => self.refresh().await,
+            Key::Char('t') => {
+                self.message = Some(match self.client.trigger_batch().await {
+                    Ok(response) => format!("✅ {}", response.message),
+                    Err(e) => format!("❌ Failed to trigger batch: {e}"),
+                });
+                self.refresh().await;
+            }
+            Key::Char('r') => {
+                self.message = Some(self.retry_selected().await);
+                self.refresh().await;
+            }
+            Key::Char('\n') | Key::Char('c') => {
+                if let Some(batch_id) = self.selected_batch().map(|b| b.id) {
+                    let data = self
+                        .client
+                        .get_contract_data(batch_id)
+                        .await
+                        .map_err(|e| e.to_string());
+                    self.view = View::Contract { batch_id, data };
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    async fn retry_selected(&self) -> String {
+        let Some(batch) = self.selected_batch() else {
+            return "No batch selected".to_string();
+        };
+        if batch.proof_status != "failed" {
+            return format!(
+                "Batch {} is {}, only failed proofs can be retried",
+                batch.id, batch.proof_status
+            );
+        }
+        match self.client.retry_batch_proof(batch.id).await {
+            Ok(batch) => format!("🔁 Batch {} queued for a new proof", batch.id),
+            Err(e) => format!("❌ Failed to retry batch {}: {e}", batch.id),
+        }
+    }
+
+    fn draw(&self, out: &mut impl Write) -> Result<()> {
+        let (width, height) = termion::terminal_size().unwrap_or((100, 30));
+        let lines = match &self.view {
+            View::Overview => self.overview_lines(),
+            View::Contract { batch_id, data } => contract_lines(*batch_id, data),
+        };
+
+        write!(out, "{}", clear::All)?;
+        let mut row = 1;
+        write!(
+            out,
+            "{}{}{} Batch pipeline — {}{}",
+            cursor::Goto(1, row),
+            style::Bold,
+            color::Fg(color::Cyan),
+            self.api_url,
+            style::Reset
+        )?;
+        if let Some(snapshot) = &self.snapshot {
+            let updated = format!(
+                "updated {} (every {}s)",
+                snapshot.fetched_at.format("%H:%M:%S"),
+                self.refresh.as_secs()
+            );
+            let column = width.saturating_sub(updated.len() as u16).max(1);
+            write!(out, "{}{}", cursor::Goto(column, row), updated)?;
+        }
+        row += 2;
+
+        if let Some(error) = &self.refresh_error {
+            write!(
+                out,
+                "{}{}⚠ Refresh failed: {}{}",
+                cursor::Goto(1, row),
+                color::Fg(color::Red),
+                error,
+                style::Reset
+            )?;
+            row += 2;
+        }
+
+        // Keep the footer on the last two rows
+        let body_rows = height.saturating_sub(row + 2);
+        for line in lines.iter().take(usize::from(body_rows)) {
+            write!(out, "{}{}", cursor::Goto(1, row), line)?;
+            row += 1;
+        }
+
+        let help = match self.view {
+            View::Overview => "[↑/↓] select  [t] trigger batch  [r] retry failed proof  [enter] contract data  [u] refresh  [q] quit",
+            View::Contract { .. } => "[esc] back  [q] quit",
+        };
+        write!(
+            out,
+            "{}{}{}{}",
+            cursor::Goto(1, height.saturating_sub(1).max(1)),
+            style::Faint,
+            help,
+            style::Reset
+        )?;
+        if let Some(message) = &self.message {
+            write!(out, "{}{}", cursor::Goto(1, height), message)?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    fn overview_lines(&self) -> Vec<String> {
+        let Some(snapshot) = &self.snapshot else {
+            return vec!["Loading...".to_string()];
+        };
+        let now = snapshot.fetched_at;
+        let mut lines = Vec::new();
+
+        // Queue
+        let oldest = snapshot
+            .oldest_pending
+            .map_or_else(String::new, |t| format!(", oldest {}", format_age(now - t)));
+        lines.push(format!(
+            "{}QUEUE{}    {} pending (Σ {}){}",
+            style::Bold,
+            style::Reset,
+            snapshot.queue_depth,
+            snapshot.queue_amount,
+            oldest
+        ));
+
+        // IMT
+        let state = &snapshot.state;
+        lines.push(format!(
+            "{}IMT{}      root {}, {} nullifiers, counter {}",
+            style::Bold,
+            style::Reset,
+            state
+                .imt_root
+                .as_deref()
+                .map_or("unknown".to_string(), short_hash),
+            state
+                .nullifier_count
+                .map_or("?".to_string(), |count| count.to_string()),
+            state.counter_value
+        ));
+
+        // Batches by status
+        let count = |status: &str| {
+            snapshot
+                .batches
+                .iter()
+                .filter(|b| batch_stage(b) == status)
+                .count()
+        };
+        let posted = snapshot
+            .batches
+            .iter()
+            .filter(|b| b.posted_to_contract_at.is_some())
+            .count();
+        lines.push(format!(
+            "{}BATCHES{}  last {}: {} queued · {} proving · {} proven · {}{} failed{} · {} unwound · {} posted",
+            style::Bold,
+            style::Reset,
+            snapshot.batches.len(),
+            count("queued"),
+            count("proving"),
+            count("proven"),
+            color::Fg(color::Red),
+            count("failed"),
+            style::Reset,
+            count("unwound"),
+            posted
+        ));
+        lines.push(String::new());
+
+        // Recent batches
+        lines.push(format!(
+            "{}   {:<7} {:<9} {:>4}  {:<17} {:<9} {}{}",
+            style::Bold,
+            "ID",
+            "STAGE",
+            "TXS",
+            "BALANCE",
+            "AGE",
+            "SETTLEMENT",
+            style::Reset
+        ));
+        for (index, batch) in snapshot.batches.iter().enumerate() {
+            let marker = if index == self.selected { ">" } else { " " };
+            let stage = batch_stage(batch);
+            let stage_color = match stage {
+                "failed" => color::Fg(color::Red).to_string(),
+                "queued" | "proving" => color::Fg(color::Yellow).to_string(),
+                "unwound" => style::Faint.to_string(),
+                _ => color::Fg(color::Green).to_string(),
+            };
+            let age = match (parse_time(&batch.created_at), batch.proven_at.as_deref()) {
+                // Proof time for proven batches, waiting time otherwise
+                (Some(created), Some(proven)) => parse_time(proven)
+                    .map_or_else(String::new, |proven| format_age(proven - created)),
+                (Some(created), None) => format_age(now - created),
+                (None, _) => String::new(),
+            };
+            let balance = format!(
+                "{} → {}",
+                batch.previous_counter_value, batch.final_counter_value
+            );
+            let line = format!(
+                "{} {:<7} {}{:<9}{} {:>4}  {:<17} {:<9} {}",
+                marker,
+                batch.id,
+                stage_color,
+                stage,
+                style::Reset,
+                batch.transaction_count,
+                balance,
+                age,
+                settlement_summary(batch)
+            );
+            if index == self.selected {
+                lines.push(format!("{}{}{}", style::Invert, line, style::Reset));
+            } else {
+                lines.push(line);
+            }
+        }
+        lines.push(String::new());
+
+        // Recent posts
+        lines.push(format!("{}RECENT POSTS{}", style::Bold, style::Reset));
+        let mut posts: Vec<_> = snapshot
+            .batches
+            .iter()
+            .flat_map(|batch| {
+                batch
+                    .settlements
+                    .iter()
+                    .filter(|s| s.transaction_hash.is_some())
+                    .map(move |s| (batch.id, s))
+            })
+            .collect();
+        posts.sort_by(|a, b| b.1.updated_at.cmp(&a.1.updated_at));
+        if posts.is_empty() {
+            lines.push("   none in the last batches".to_string());
+        }
+        for (batch_id, settlement) in posts.into_iter().take(RECENT_POSTS) {
+            lines.push(format!(
+                "   batch {:<6} {:<12} {:<10} block {:<10} {}  {}",
+                batch_id,
+                settlement.target,
+                settlement.status,
+                settlement
+                    .block_number
+                    .map_or("-".to_string(), |block| block.to_string()),
+                settlement
+                    .transaction_hash
+                    .as_deref()
+                    .map_or(String::new(), short_hash),
+                parse_time(&settlement.updated_at)
+                    .map_or_else(String::new, |t| format!("{} ago", format_age(now - t)))
+            ));
+        }
+
+        lines
+    }
+}
+
+fn contract_lines(
+    batch_id: i32,
+    data: &Result<Option<ContractSubmissionData>, String>,
+) -> Vec<String> {
+    let mut lines = vec![
+        format!(
+            "{}CONTRACT DATA — batch {}{}",
+            style::Bold,
+            batch_id,
+            style::Reset
+        ),
+        String::new(),
+    ];
+    match data {
+        Ok(Some(data)) => {
+            lines.push(format!("Previous root:  {}", data.public.prev_merkle_root));
+            lines.push(format!("New root:       {}", data.public.new_merkle_root));
+            lines.push(format!("Proof:          {}", data.public.zk_proof));
+            lines.push(String::new());
+            lines.push(format!(
+                "Counter:        {} → {}",
+                data.private.prev_counter_value, data.private.new_counter_value
+            ));
+            lines.push(format!("Transactions:   {:?}", data.private.transactions));
+        }
+        Ok(None) => lines.push(format!("Batch {batch_id} is not proven yet")),
+        Err(e) => lines.push(format!(
+            "{}Failed to load contract data: {}{}",
+            color::Fg(color::Red),
+            e,
+            style::Reset
+        )),
+    }
+    lines
+}
+
+/// Pipeline stage of a batch; pending batches are split by whether their proof
+/// request has been submitted
+fn batch_stage(batch: &BatchInfo) -> &str {
+    match batch.proof_status.as_str() {
+        "pending" if batch.sindri_proof_id.is_some() => "proving",
+        "pending" => "queued",
+        status => status,
+    }
+}
+
+/// Settlement status on the only target, or how many targets have confirmed
+fn settlement_summary(batch: &BatchInfo) -> String {
+    match batch.settlements.as_slice() {
+        [] => "-".to_string(),
+        [settlement] => format!("{} {}", settlement.target, settlement.status),
+        settlements => {
+            let confirmed = settlements
+                .iter()
+                .filter(|s| s.status == "confirmed")
+                .count();
+            format!("{}/{} confirmed", confirmed, settlements.len())
+        }
+    }
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Compact age such as `45s`, `3m 12s` or `2h 05m`
+fn format_age(age: chrono::Duration) -> String {
+    let seconds = age.num_seconds().max(0);
+    match seconds {
+        0..=59 => format!("{seconds}s"),
+        60..=3599 => format!("{}m {:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}h {:02}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
+
+/// `0x1234abcd…9876` for long hex values
+fn short_hash(value: &str) -> String {
+    if value.len() <= 18 {
+        value.to_string()
+    } else {
+        format!("{}…{}", &value[..10], &value[value.len() - 6..])
+    }
+}
@@ -0,0 +1,17 @@
+-- Add client-supplied idempotency keys to incoming transactions
+--
+-- A client retrying POST /api/v2/transactions after a timeout could enqueue the
+-- same amount twice, permanently skewing the counter. Clients may now send an
+-- Idempotency-Key header (or client_tx_id body field); the key is stored with the
+-- transaction and a retry with the same key returns the original row instead of
+-- inserting again.
+
+ALTER TABLE incoming_transactions
+ADD COLUMN IF NOT EXISTS client_tx_id VARCHAR(255);
+
+-- Partial unique index: keys are optional, but must be unique when present
+CREATE UNIQUE INDEX IF NOT EXISTS idx_incoming_transactions_client_tx_id
+ON incoming_transactions(client_tx_id)
+WHERE client_tx_id IS NOT NULL;
+
+COMMENT ON COLUMN incoming_transactions.client_tx_id IS 'Client-supplied idempotency key; a resubmission with the same key returns the original transaction';
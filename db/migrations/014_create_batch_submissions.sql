@@ -0,0 +1,28 @@
+-- Per-network settlement status of proven batches
+--
+-- A proven batch is posted to every configured settlement target (for example
+-- Sepolia and Base Sepolia). Each target gets one row here that moves from
+-- pending to submitted once the transaction is mined, then to confirmed once it
+-- is buried under the target's confirmation depth. proof_batches.posted_to_contract
+-- is only set after every target has confirmed.
+
+CREATE TABLE IF NOT EXISTS batch_submissions (
+    batch_id INTEGER NOT NULL REFERENCES proof_batches(id) ON DELETE CASCADE,
+    target VARCHAR(64) NOT NULL,
+    chain_id BIGINT NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'submitted', 'confirmed', 'failed')),
+    transaction_hash VARCHAR(66),
+    block_number BIGINT,
+    error TEXT,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (batch_id, target)
+);
+
+CREATE INDEX IF NOT EXISTS idx_batch_submissions_target_status
+ON batch_submissions(target, status);
+
+COMMENT ON TABLE batch_submissions IS 'Settlement status of each proven batch on each target network';
+COMMENT ON COLUMN batch_submissions.target IS 'Settlement target name from the settlement config';
+COMMENT ON COLUMN batch_submissions.status IS 'Status: pending, submitted, confirmed, failed';
+COMMENT ON COLUMN batch_submissions.error IS 'Why the last attempt on this target failed';
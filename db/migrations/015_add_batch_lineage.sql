@@ -0,0 +1,136 @@
+-- Explicit batch lineage and failed-batch recovery
+--
+-- Since migration 011 every batch builds on the latest batch regardless of its
+-- proof status, but the link was only implied by matching counter values. This
+-- migration records it: parent_batch_id names the batch whose final state a
+-- batch starts from, and tree_start_index is the first IMT leaf its nullifiers
+-- were written to.
+--
+-- When a batch can never be proven, it and every batch built on it are marked
+-- 'unwound': their transactions go back to the queue and the IMT is rolled back
+-- to tree_start_index of the failed batch. Unwound batches are kept for audit
+-- but are no longer part of the chain.
+--
+-- Rolling back needs the state each insertion overwrote, so every insertion now
+-- journals the low nullifier's pointers and leaf hash in nullifier_undo_log.
+
+ALTER TABLE proof_batches
+ADD COLUMN IF NOT EXISTS parent_batch_id INTEGER REFERENCES proof_batches(id);
+
+ALTER TABLE proof_batches
+ADD COLUMN IF NOT EXISTS tree_start_index BIGINT;
+
+-- Until now each batch built on the batch created right before it
+UPDATE proof_batches pb
+SET parent_batch_id = (
+    SELECT MAX(prev.id) FROM proof_batches prev WHERE prev.id < pb.id
+)
+WHERE pb.parent_batch_id IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_proof_batches_parent_batch_id
+ON proof_batches(parent_batch_id);
+
+-- The chain never forks: a batch has at most one live child
+CREATE UNIQUE INDEX IF NOT EXISTS idx_proof_batches_single_child
+ON proof_batches(parent_batch_id)
+WHERE proof_status IS DISTINCT FROM 'unwound';
+
+-- One row per inserted leaf: what the insertion overwrote in its low nullifier
+CREATE TABLE IF NOT EXISTS nullifier_undo_log (
+    tree_index BIGINT PRIMARY KEY,           -- Leaf written by the insertion
+    low_tree_index BIGINT NOT NULL,          -- Leaf of the low nullifier it updated
+    low_next_index BIGINT,                   -- Low nullifier pointers before the update
+    low_next_value BIGINT NOT NULL DEFAULT 0,
+    low_leaf_hash BYTEA CHECK (low_leaf_hash IS NULL OR length(low_leaf_hash) = 32),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- The head of the chain is the latest batch that has not been unwound
+DROP FUNCTION IF EXISTS get_current_counter_value();
+
+CREATE OR REPLACE FUNCTION get_current_counter_value()
+RETURNS BIGINT AS $$
+DECLARE
+    current_value BIGINT := 0;
+BEGIN
+    SELECT pb.final_counter_value
+    INTO current_value
+    FROM proof_batches pb
+    WHERE pb.proof_status IS DISTINCT FROM 'unwound'
+    ORDER BY pb.id DESC
+    LIMIT 1;
+
+    RETURN COALESCE(current_value, 0);
+END;
+$$ LANGUAGE plpgsql;
+
+-- Same as migration 010, but the new batch records the head it builds on
+DROP FUNCTION IF EXISTS create_batch(INTEGER);
+
+CREATE OR REPLACE FUNCTION create_batch(batch_size INTEGER DEFAULT 10)
+RETURNS INTEGER AS $$
+DECLARE
+    new_batch_id INTEGER;
+    parent_id INTEGER;
+    previous_counter BIGINT;
+    final_counter BIGINT;
+    transaction_total BIGINT;
+    transaction_id_array INTEGER[];
+    claimed_count INTEGER;
+BEGIN
+    -- Build on the head of the chain
+    SELECT pb.id, pb.final_counter_value
+    INTO parent_id, previous_counter
+    FROM proof_batches pb
+    WHERE pb.proof_status IS DISTINCT FROM 'unwound'
+    ORDER BY pb.id DESC
+    LIMIT 1;
+
+    previous_counter := COALESCE(previous_counter, 0);
+
+    -- Atomically select and lock unbatched transactions
+    WITH locked_transactions AS (
+        SELECT id, amount
+        FROM incoming_transactions
+        WHERE included_in_batch_id IS NULL
+        ORDER BY id ASC
+        LIMIT batch_size
+        FOR UPDATE SKIP LOCKED
+    )
+    SELECT
+        ARRAY_AGG(id ORDER BY id),
+        SUM(amount),
+        COUNT(*)
+    INTO transaction_id_array, transaction_total, claimed_count
+    FROM locked_transactions;
+
+    IF transaction_id_array IS NULL OR claimed_count = 0 THEN
+        RETURN 0;
+    END IF;
+
+    final_counter := previous_counter + transaction_total;
+
+    INSERT INTO proof_batches (
+        previous_counter_value,
+        final_counter_value,
+        transaction_ids,
+        parent_batch_id
+    ) VALUES (
+        previous_counter,
+        final_counter,
+        transaction_id_array,
+        parent_id
+    ) RETURNING id INTO new_batch_id;
+
+    UPDATE incoming_transactions
+    SET included_in_batch_id = new_batch_id
+    WHERE id = ANY(transaction_id_array);
+
+    RETURN new_batch_id;
+END;
+$$ LANGUAGE plpgsql;
+
+COMMENT ON COLUMN proof_batches.parent_batch_id IS 'Batch whose final state this batch builds on, NULL for the first batch';
+COMMENT ON COLUMN proof_batches.tree_start_index IS 'First IMT leaf index used by this batch''s nullifiers';
+COMMENT ON TABLE nullifier_undo_log IS 'Low nullifier state overwritten by each IMT insertion, used to roll the tree back';
+COMMENT ON COLUMN proof_batches.proof_status IS 'Status: pending, proven, failed, unwound';
@@ -0,0 +1,29 @@
+-- IMT membership proofs for transaction receipts
+--
+-- When a batch is created, every transaction's nullifier is inserted into the
+-- IMT and the batch commits to the resulting root. Later batches change the
+-- tree, so a path against the batch's root can't be rebuilt afterwards; it is
+-- captured here right after the batch's insertions, while the root is still
+-- the batch's committed root.
+--
+-- Rows of unwound batches are deleted. When the released transactions are
+-- batched again their rows are replaced.
+
+CREATE TABLE IF NOT EXISTS transaction_inclusions (
+    transaction_id INTEGER PRIMARY KEY REFERENCES incoming_transactions(id),
+    batch_id INTEGER NOT NULL REFERENCES proof_batches(id) ON DELETE CASCADE,
+    nullifier_value BIGINT NOT NULL,
+    leaf_index BIGINT NOT NULL,
+    next_index BIGINT NOT NULL,              -- Leaf pointers when the batch committed
+    next_value BIGINT NOT NULL,
+    siblings BYTEA[] NOT NULL,               -- Sibling hashes from the leaf level up
+    path_indices BOOLEAN[] NOT NULL,         -- TRUE where the path node is a right child
+    merkle_root BYTEA NOT NULL CHECK (length(merkle_root) = 32),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_transaction_inclusions_batch_id
+ON transaction_inclusions(batch_id);
+
+COMMENT ON TABLE transaction_inclusions IS 'Membership proof of each batched transaction''s nullifier against its batch root';
+COMMENT ON COLUMN transaction_inclusions.merkle_root IS 'Root the path leads to, equal to the batch''s committed root';
@@ -0,0 +1,53 @@
+-- Transactional outbox for proof requests and contract posts
+--
+-- A batch's proof request is queued here in the same transaction that creates
+-- the batch, and its contract post in the same transaction that marks it
+-- proven. Workers claim pending jobs with FOR UPDATE SKIP LOCKED and keep them
+-- locked until the outcome is recorded, so a job is dispatched by one worker
+-- at a time no matter how many API replicas run, and nothing is lost if a
+-- process dies before dispatching.
+--
+-- Each batch has at most one job of each kind. Queueing a job that already
+-- exists puts it back to pending, which is how a failed proof is retried and
+-- a reconciled batch is posted again. Jobs of unwound batches are deleted.
+
+CREATE TABLE IF NOT EXISTS outbox_jobs (
+    id BIGSERIAL PRIMARY KEY,
+    batch_id INTEGER NOT NULL REFERENCES proof_batches(id) ON DELETE CASCADE,
+    kind VARCHAR(32) NOT NULL CHECK (kind IN ('proof_request', 'contract_post')),
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'done')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    available_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    last_error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (batch_id, kind)
+);
+
+CREATE INDEX IF NOT EXISTS idx_outbox_jobs_pending
+ON outbox_jobs(kind, available_at)
+WHERE status = 'pending';
+
+-- Work the batch monitor used to find by scanning proof_batches
+INSERT INTO outbox_jobs (batch_id, kind)
+SELECT id, 'proof_request'
+FROM proof_batches
+WHERE proof_status IS DISTINCT FROM 'unwound'
+  AND (sindri_proof_id IS NULL
+       OR sindri_proof_id = ''
+       OR sindri_proof_id LIKE 'failed_%'
+       OR sindri_proof_id LIKE 'error_%')
+ON CONFLICT (batch_id, kind) DO NOTHING;
+
+INSERT INTO outbox_jobs (batch_id, kind)
+SELECT id, 'contract_post'
+FROM proof_batches
+WHERE proof_status = 'proven' AND posted_to_contract = FALSE
+ON CONFLICT (batch_id, kind) DO NOTHING;
+
+COMMENT ON TABLE outbox_jobs IS 'Proof and posting work queued in the transaction that made it due';
+COMMENT ON COLUMN outbox_jobs.kind IS 'Kind: proof_request, contract_post';
+COMMENT ON COLUMN outbox_jobs.status IS 'Status: pending, done';
+COMMENT ON COLUMN outbox_jobs.attempts IS 'Times a worker claimed the job';
+COMMENT ON COLUMN outbox_jobs.available_at IS 'Earliest time a worker may claim the job again';
+COMMENT ON COLUMN outbox_jobs.last_error IS 'Why the last dispatch failed';
@@ -0,0 +1,51 @@
+-- One genesis root for every code path
+--
+-- Migration 012 recorded the genesis tree's root as 32 zero bytes, while
+-- MerkleTree32::initialize wrote the empty tree's root. The genesis tree is
+-- the genesis nullifier's leaf (32 zero bytes) at index 0 with every other
+-- subtree empty, and its root is the value below, the same one
+-- merkle_tree_32::genesis_root() computes. Trees still at genesis are moved
+-- over to it; trees with insertions already carry a computed root.
+
+UPDATE tree_state
+SET root_hash = '\xb3496ac277db8e304997228d44542a27422379552813adc40c4bb65072403956',
+    updated_at = NOW()
+WHERE root_hash = '\x0000000000000000000000000000000000000000000000000000000000000000'
+  AND next_available_index <= 1;
+
+-- Same function as in 012, creating a missing tree_state row at the genesis root
+CREATE OR REPLACE FUNCTION fix_tree_state_consistency()
+RETURNS VOID AS $$
+DECLARE
+    actual_nullifier_count BIGINT;
+    actual_next_index BIGINT;
+    current_count BIGINT;
+    current_next BIGINT;
+BEGIN
+    -- Get the actual data from nullifiers table
+    SELECT COUNT(*) INTO actual_nullifier_count
+    FROM nullifiers WHERE is_active = true;
+
+    SELECT COALESCE(MAX(tree_index), -1) + 1 INTO actual_next_index
+    FROM nullifiers WHERE is_active = true;
+
+    -- Ensure the row exists first
+    INSERT INTO tree_state (tree_id, root_hash, next_available_index, tree_height, total_nullifiers)
+    VALUES ('default', '\xb3496ac277db8e304997228d44542a27422379552813adc40c4bb65072403956', 0, 32, 0)
+    ON CONFLICT (tree_id) DO NOTHING;
+
+    -- Get current values
+    SELECT total_nullifiers, next_available_index INTO current_count, current_next
+    FROM tree_state WHERE tree_id = 'default';
+
+    -- Update with correct values only if they differ
+    IF current_count != actual_nullifier_count OR current_next != actual_next_index THEN
+        UPDATE tree_state
+        SET
+            total_nullifiers = actual_nullifier_count,
+            next_available_index = actual_next_index,
+            updated_at = NOW()
+        WHERE tree_id = 'default';
+    END IF;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,22 @@
+-- Leased outbox claims
+--
+-- Workers used to hold a claimed job's row lock until the outcome was
+-- recorded, which kept a transaction open across the Sindri request or the
+-- contract submission. A claim now marks the job in_flight until
+-- lease_expires_at and commits; the outcome is recorded in a new transaction.
+-- A job whose lease ran out, because its worker died or stalled, is claimed
+-- again.
+
+ALTER TABLE outbox_jobs DROP CONSTRAINT IF EXISTS outbox_jobs_status_check;
+ALTER TABLE outbox_jobs
+ADD CONSTRAINT outbox_jobs_status_check CHECK (status IN ('pending', 'in_flight', 'done'));
+
+ALTER TABLE outbox_jobs
+ADD COLUMN IF NOT EXISTS lease_expires_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX IF NOT EXISTS idx_outbox_jobs_in_flight
+ON outbox_jobs(kind, lease_expires_at)
+WHERE status = 'in_flight';
+
+COMMENT ON COLUMN outbox_jobs.status IS 'Status: pending, in_flight, done';
+COMMENT ON COLUMN outbox_jobs.lease_expires_at IS 'When the claim of an in_flight job runs out';
@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 use std::collections::HashMap;
 use std::sync::Arc;
 use thiserror::Error;
@@ -10,8 +10,10 @@ use tracing::{debug, error, info, instrument, warn};
 
 use crate::error::DbError;
 use crate::merkle_tree::{
-    AlgorithmInsertionResult, IndexedMerkleTree, InsertionProof, MerkleProof,
+    lock_for_write, AlgorithmInsertionResult, IndexedMerkleTree, InsertionProof, Nullifier,
+    NullifierDb,
 };
+use crate::merkle_tree_32::MerkleProof;
 
 // ============================================================================
 // AUTHENTICATED DATA STRUCTURE SERVICE LAYER
@@ -481,6 +483,30 @@ impl IndexedMerkleTreeADS {
         root_after: [u8; 32],
         block_height: u64,
         metadata: serde_json::Value,
+    ) -> Result<(), AdsError> {
+        let mut conn = self.pool.acquire().await?;
+        self.record_audit_event_in(
+            &mut conn,
+            nullifier_value,
+            event_type,
+            root_before,
+            root_after,
+            block_height,
+            metadata,
+        )
+        .await
+    }
+
+    /// `record_audit_event` on `conn`
+    async fn record_audit_event_in(
+        &self,
+        conn: &mut PgConnection,
+        nullifier_value: i64,
+        event_type: AuditEventType,
+        root_before: [u8; 32],
+        root_after: [u8; 32],
+        block_height: u64,
+        metadata: serde_json::Value,
     ) -> Result<(), AdsError> {
         if !self.config.audit_enabled {
             return Ok(());
@@ -518,7 +544,7 @@ impl IndexedMerkleTreeADS {
             event.operator,
             event.metadata,
         )
-        .execute(&self.pool)
+        .execute(&mut *conn)
         .await?;
 
         // Update in-memory audit trail
@@ -620,114 +646,9 @@ impl AuthenticatedDataStructure for IndexedMerkleTreeADS {
 
     #[instrument(skip(self), level = "info")]
     async fn insert(&mut self, value: i64) -> Result<StateTransition, AdsError> {
-        info!("🔄 Inserting nullifier: {}", value);
-        let start_time = std::time::Instant::now();
-
-        // Get exclusive access to tree for modification
-        let mut tree_guard = self.tree.write().await;
-
-        // Check if nullifier already exists
-        if tree_guard.db.nullifiers.exists(value).await? {
-            return Err(AdsError::NullifierExists(value));
-        }
-
-        // Perform insertion using 7-step algorithm
-        let insertion_result = tree_guard
-            .insert_nullifier(value)
-            .await
-            .map_err(|e| AdsError::InsertionFailed(e.to_string()))?;
-
-        drop(tree_guard); // Release lock early
-
-        // Generate witness data for ZK circuits
-        let witnesses = self.generate_witness_data(&insertion_result);
-
-        // Get current block height
-        let block_height = self.get_current_block_height()?;
-
-        // Create state transition
-        let transition_id = Self::generate_transaction_id();
-        let state_transition = StateTransition {
-            id: transition_id.clone(),
-            old_root: insertion_result.old_root,
-            new_root: insertion_result.new_root,
-            nullifier_value: value,
-            insertion_proof: insertion_result.insertion_proof.clone(),
-            block_height,
-            timestamp: Utc::now(),
-            gas_estimate: self.calculate_gas_estimate(&StateTransition {
-                id: transition_id,
-                old_root: insertion_result.old_root,
-                new_root: insertion_result.new_root,
-                nullifier_value: value,
-                insertion_proof: insertion_result.insertion_proof.clone(),
-                block_height,
-                timestamp: Utc::now(),
-                gas_estimate: 0,
-                witnesses: witnesses.clone(),
-            }),
-            witnesses,
-        };
-
-        // Update state cache
-        let nullifier_count = self.get_nullifier_count().await?;
-        let new_commitment = StateCommitment {
-            root_hash: insertion_result.new_root,
-            nullifier_count,
-            tree_height: 32,
-            last_updated: Utc::now(),
-            commitment_hash: self
-                .calculate_commitment_hash(&insertion_result.new_root, nullifier_count),
-            settlement_data: SettlementData {
-                contract_address: self.config.settlement_contract.clone(),
-                chain_id: self.config.chain_id,
-                nonce: nullifier_count,
-                gas_price: self.config.gas_price,
-            },
-        };
-
-        {
-            let mut cache = self.state_cache.write().await;
-            cache.insert(insertion_result.new_root, new_commitment);
-
-            // Limit cache size
-            if cache.len() > self.config.cache_size_limit {
-                // Remove oldest entries (simple LRU would be better)
-                let keys_to_remove: Vec<_> = cache
-                    .keys()
-                    .take(cache.len() - self.config.cache_size_limit)
-                    .cloned()
-                    .collect();
-                for key in keys_to_remove {
-                    cache.remove(&key);
-                }
-            }
-        }
-
-        // Record audit event
-        self.record_audit_event(
-            value,
-            AuditEventType::Insertion,
-            insertion_result.old_root,
-            insertion_result.new_root,
-            block_height,
-            serde_json::json!({
-                "transition_id": state_transition.id,
-                "constraints": insertion_result.operations_count.constraints_count,
-                "hash_operations": insertion_result.operations_count.hash_operations,
-                "gas_estimate": state_transition.gas_estimate,
-            }),
-        )
-        .await?;
-
-        // Update metrics
-        let duration_ms = start_time.elapsed().as_millis() as f64;
-        self.update_metrics("insertion", duration_ms).await?;
-
-        info!(
-            "✅ Nullifier {} inserted successfully in {:.2}ms",
-            value, duration_ms
-        );
+        let mut tx = self.pool.begin().await?;
+        let state_transition = self.insert_in(&mut tx, value).await?;
+        tx.commit().await?;
         Ok(state_transition)
     }
 
@@ -1092,6 +1013,192 @@ impl IndexedMerkleTreeADS {
         hasher.finalize().into()
     }
 
+    /// Insert a nullifier on `conn`, inside the caller's transaction
+    ///
+    /// Takes the IMT write lock, so the caller's other writes in the same
+    /// transaction are serialized with every IMT writer.
+    #[instrument(skip(self, conn), level = "info")]
+    pub async fn insert_in(
+        &self,
+        conn: &mut PgConnection,
+        value: i64,
+    ) -> Result<StateTransition, AdsError> {
+        info!("🔄 Inserting nullifier: {}", value);
+        let start_time = std::time::Instant::now();
+
+        // Other writers, in this process or another, wait for our transaction
+        lock_for_write(conn).await?;
+        let tree_guard = self.tree.read().await;
+
+        // Check if nullifier already exists
+        if NullifierDb::exists_in(conn, value).await? {
+            return Err(AdsError::NullifierExists(value));
+        }
+
+        // Perform insertion using 7-step algorithm
+        let insertion_result = tree_guard
+            .insert_nullifier_in(conn, value)
+            .await
+            .map_err(|e| AdsError::InsertionFailed(e.to_string()))?;
+
+        drop(tree_guard); // Release lock early
+
+        // Generate witness data for ZK circuits
+        let witnesses = self.generate_witness_data(&insertion_result);
+
+        // Get current block height
+        let block_height = self.get_current_block_height()?;
+
+        // Create state transition
+        let transition_id = Self::generate_transaction_id();
+        let state_transition = StateTransition {
+            id: transition_id.clone(),
+            old_root: insertion_result.old_root,
+            new_root: insertion_result.new_root,
+            nullifier_value: value,
+            insertion_proof: insertion_result.insertion_proof.clone(),
+            block_height,
+            timestamp: Utc::now(),
+            gas_estimate: self.calculate_gas_estimate(&StateTransition {
+                id: transition_id,
+                old_root: insertion_result.old_root,
+                new_root: insertion_result.new_root,
+                nullifier_value: value,
+                insertion_proof: insertion_result.insertion_proof.clone(),
+                block_height,
+                timestamp: Utc::now(),
+                gas_estimate: 0,
+                witnesses: witnesses.clone(),
+            }),
+            witnesses,
+        };
+
+        // Update state cache
+        let nullifier_count = self.get_nullifier_count().await?;
+        let new_commitment = StateCommitment {
+            root_hash: insertion_result.new_root,
+            nullifier_count,
+            tree_height: 32,
+            last_updated: Utc::now(),
+            commitment_hash: self
+                .calculate_commitment_hash(&insertion_result.new_root, nullifier_count),
+            settlement_data: SettlementData {
+                contract_address: self.config.settlement_contract.clone(),
+                chain_id: self.config.chain_id,
+                nonce: nullifier_count,
+                gas_price: self.config.gas_price,
+            },
+        };
+
+        {
+            let mut cache = self.state_cache.write().await;
+            cache.insert(insertion_result.new_root, new_commitment);
+
+            // Limit cache size
+            if cache.len() > self.config.cache_size_limit {
+                // Remove oldest entries (simple LRU would be better)
+                let keys_to_remove: Vec<_> = cache
+                    .keys()
+                    .take(cache.len() - self.config.cache_size_limit)
+                    .cloned()
+                    .collect();
+                for key in keys_to_remove {
+                    cache.remove(&key);
+                }
+            }
+        }
+
+        // Record audit event
+        self.record_audit_event_in(
+            conn,
+            value,
+            AuditEventType::Insertion,
+            insertion_result.old_root,
+            insertion_result.new_root,
+            block_height,
+            serde_json::json!({
+                "transition_id": state_transition.id,
+                "constraints": insertion_result.operations_count.constraints_count,
+                "hash_operations": insertion_result.operations_count.hash_operations,
+                "gas_estimate": state_transition.gas_estimate,
+            }),
+        )
+        .await?;
+
+        // Update metrics
+        let duration_ms = start_time.elapsed().as_millis() as f64;
+        self.update_metrics("insertion", duration_ms).await?;
+
+        info!(
+            "✅ Nullifier {} inserted successfully in {:.2}ms",
+            value, duration_ms
+        );
+        Ok(state_transition)
+    }
+
+    /// Insert several nullifiers on `conn`, inside the caller's transaction
+    #[instrument(skip(self, conn, values), level = "info")]
+    pub async fn batch_insert_in(
+        &self,
+        conn: &mut PgConnection,
+        values: &[i64],
+    ) -> Result<Vec<StateTransition>, AdsError> {
+        info!("📦 Batch inserting {} nullifiers", values.len());
+
+        if values.len() > self.config.batch_size_limit {
+            return Err(AdsError::InvalidRange(format!(
+                "Batch size {} exceeds limit {}",
+                values.len(),
+                self.config.batch_size_limit
+            )));
+        }
+
+        let mut transitions = Vec::with_capacity(values.len());
+        for &value in values {
+            transitions.push(self.insert_in(conn, value).await?);
+        }
+        Ok(transitions)
+    }
+
+    /// Leaf of an inserted nullifier and its Merkle path to the current root
+    ///
+    /// Unlike `prove_membership` this includes the leaf's pointers and records
+    /// no audit event; transaction receipts are built from it.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn inclusion_proof(&self, value: i64) -> Result<(Nullifier, MerkleProof), AdsError> {
+        let tree_guard = self.tree.read().await;
+
+        let nullifier = tree_guard
+            .db
+            .nullifiers
+            .get_by_value(value)
+            .await?
+            .ok_or(AdsError::NullifierNotFound(value))?;
+        let merkle_proof = tree_guard
+            .generate_merkle_proof(nullifier.tree_index)
+            .await?;
+
+        Ok((nullifier, merkle_proof))
+    }
+
+    /// `inclusion_proof` on `conn`, against the caller's uncommitted root
+    pub async fn inclusion_proof_in(
+        &self,
+        conn: &mut PgConnection,
+        value: i64,
+    ) -> Result<(Nullifier, MerkleProof), AdsError> {
+        let tree_guard = self.tree.read().await;
+
+        let nullifier = NullifierDb::get_by_value_in(conn, value)
+            .await?
+            .ok_or(AdsError::NullifierNotFound(value))?;
+        let merkle_proof = tree_guard
+            .generate_merkle_proof_in(conn, nullifier.tree_index)
+            .await?;
+
+        Ok((nullifier, merkle_proof))
+    }
+
     /// Get current performance metrics
     #[instrument(skip(self), level = "info")]
     pub async fn get_metrics(&self) -> Result<AdsMetrics, AdsError> {
@@ -1,10 +1,12 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgExecutor, PgPool, Postgres};
 use std::env;
 use std::str::FromStr;
 use tracing::debug;
 
+use crate::outbox::{queue_outbox_job, OUTBOX_CONTRACT_POST, OUTBOX_PROOF_REQUEST};
+
 #[cfg(all(not(target_env = "msvc"), feature = "tikv-jemallocator"))]
 use tikv_jemallocator::Jemalloc;
 
@@ -25,6 +27,25 @@ pub struct IncomingTransaction {
     pub created_at: DateTime<Utc>,
 }
 
+/// Result of an idempotent transaction submission
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubmittedTransaction {
+    pub transaction: IncomingTransaction,
+    /// Idempotency key stored with the transaction, if any
+    pub client_tx_id: Option<String>,
+    /// True when the key was already known and the original row was returned
+    pub replayed: bool,
+}
+
+/// One transaction in a bulk submission
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NewTransaction {
+    pub amount: i32,
+    /// Optional idempotency key, see `submit_transaction_idempotent`
+    #[serde(default)]
+    pub client_tx_id: Option<String>,
+}
+
 /// Batch of transactions with ZK proof
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ProofBatch {
@@ -33,9 +54,29 @@ pub struct ProofBatch {
     pub final_counter_value: i64,
     pub transaction_ids: Vec<i32>,
     pub sindri_proof_id: Option<String>,
-    pub proof_status: String, // pending, proven, failed
+    pub proof_status: String, // pending, proven, failed, unwound
     pub created_at: DateTime<Utc>,
     pub proven_at: Option<DateTime<Utc>>,
+    /// Set once the batch has been posted to the settlement contract
+    #[serde(default)]
+    pub posted_to_contract_at: Option<DateTime<Utc>>,
+    /// Batch whose final state this batch builds on, `None` for the first batch
+    #[serde(default)]
+    pub parent_batch_id: Option<i32>,
+}
+
+/// Outcome of unwinding a failed batch, see `unwind_failed_batch`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchUnwind {
+    pub failed_batch_id: i32,
+    /// Batch the chain continues from, `None` when the first batch failed
+    pub parent_batch_id: Option<i32>,
+    /// The failed batch and every batch built on it, oldest first
+    pub unwound_batch_ids: Vec<i32>,
+    /// Transactions returned to the pending queue
+    pub released_transaction_ids: Vec<i32>,
+    /// IMT root after the rollback, equal to the parent's committed root
+    pub restored_root: Vec<u8>,
 }
 
 /// ADS/Merkle tree state commitment for smart contract
@@ -55,6 +96,49 @@ pub struct CounterState {
     pub last_batch_id: Option<i32>,
 }
 
+/// Settlement status of a batch on one target network
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct BatchSubmission {
+    pub batch_id: i32,
+    pub target: String,
+    pub chain_id: i64,
+    pub status: String, // pending, submitted, confirmed, failed
+    pub transaction_hash: Option<String>,
+    pub block_number: Option<i64>,
+    pub error: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// New settlement status for a batch on one target, see `record_batch_submission`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchSubmissionUpdate {
+    pub batch_id: i32,
+    pub target: String,
+    pub chain_id: i64,
+    pub status: String,
+    pub transaction_hash: Option<String>,
+    pub block_number: Option<i64>,
+    pub error: Option<String>,
+}
+
+/// Membership proof of a batched transaction's nullifier against its batch root
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TransactionInclusion {
+    pub transaction_id: i32,
+    pub batch_id: i32,
+    pub nullifier_value: i64,
+    pub leaf_index: i64,
+    /// Leaf pointers when the batch committed (0 for the largest nullifier)
+    pub next_index: i64,
+    pub next_value: i64,
+    /// Sibling hashes from the leaf level up
+    pub siblings: Vec<Vec<u8>>,
+    /// `true` where the path node is a right child
+    pub path_indices: Vec<bool>,
+    /// Root the path leads to, the batch's committed root
+    pub merkle_root: Vec<u8>,
+}
+
 /// Contract submission data (public/private split)
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ContractSubmissionData {
@@ -76,6 +160,66 @@ pub struct ContractPrivateData {
     pub transactions: Vec<i32>,
 }
 
+// ============================================================================
+// LISTING / PAGINATION TYPES
+// ============================================================================
+
+/// Sort direction for keyset-paginated listings (ordered by id)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Queue status of an incoming transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionStatus {
+    /// Not yet included in any batch
+    Pending,
+    /// Included in a batch
+    Batched,
+}
+
+/// Filters for listing batches
+///
+/// `after_id` is the keyset cursor: the id of the last batch on the previous page.
+#[derive(Debug, Clone, Default)]
+pub struct BatchListFilter {
+    pub limit: i64,
+    pub after_id: Option<i32>,
+    pub order: SortOrder,
+    pub proof_status: Option<String>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+}
+
+/// Filters for listing incoming transactions
+///
+/// `after_id` is the keyset cursor: the id of the last transaction on the previous page.
+#[derive(Debug, Clone, Default)]
+pub struct TransactionListFilter {
+    pub limit: i64,
+    pub after_id: Option<i32>,
+    pub order: SortOrder,
+    pub status: Option<TransactionStatus>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub min_amount: Option<i32>,
+    pub max_amount: Option<i32>,
+    pub batch_id: Option<i32>,
+}
+
+/// A single page of a keyset-paginated listing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Id of the last item when more results are available
+    pub next_cursor: Option<i32>,
+}
+
 // ============================================================================
 // DATABASE CONNECTION FUNCTIONS
 // ============================================================================
@@ -162,6 +306,198 @@ pub async fn submit_transaction(
     Ok(transaction)
 }
 
+/// Submit a transaction with an optional client-supplied idempotency key
+///
+/// When `client_tx_id` was already used, nothing is inserted and the original
+/// transaction is returned with `replayed = true`. Callers should compare the
+/// returned amount against the request to detect key reuse with a different payload.
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn submit_transaction_idempotent(
+    pool: &PgPool,
+    amount: i32,
+    client_tx_id: Option<&str>,
+) -> Result<SubmittedTransaction, sqlx::Error> {
+    let Some(key) = client_tx_id else {
+        let transaction = submit_transaction(pool, amount).await?;
+        return Ok(SubmittedTransaction {
+            transaction,
+            client_tx_id: None,
+            replayed: false,
+        });
+    };
+
+    debug!("Submitting transaction: amount={amount}, client_tx_id={key}");
+
+    let inserted = sqlx::query_as::<_, IncomingTransaction>(
+        r"
+        INSERT INTO incoming_transactions (amount, client_tx_id)
+        VALUES ($1, $2)
+        ON CONFLICT (client_tx_id) WHERE client_tx_id IS NOT NULL DO NOTHING
+        RETURNING id, amount, included_in_batch_id, created_at
+        ",
+    )
+    .bind(amount)
+    .bind(key)
+    .fetch_optional(pool)
+    .await?;
+
+    let (transaction, replayed) = match inserted {
+        Some(transaction) => (transaction, false),
+        None => {
+            let existing = sqlx::query_as::<_, IncomingTransaction>(
+                r"
+                SELECT id, amount, included_in_batch_id, created_at
+                FROM incoming_transactions
+                WHERE client_tx_id = $1
+                ",
+            )
+            .bind(key)
+            .fetch_one(pool)
+            .await?;
+            debug!(
+                "Idempotency key {key} already used by transaction {}",
+                existing.id
+            );
+            (existing, true)
+        }
+    };
+
+    Ok(SubmittedTransaction {
+        transaction,
+        client_tx_id: Some(key.to_string()),
+        replayed,
+    })
+}
+
+/// Insert many transactions with a single multi-row statement
+///
+/// Runs on the caller's connection so the insert can share a transaction with
+/// whatever validation the caller does around it; roll back to discard the
+/// whole batch. Results are returned in input order. Keys that already exist
+/// are not re-inserted and come back with `replayed = true`. Keys must be
+/// unique within `items`.
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn submit_transactions_bulk(
+    conn: &mut sqlx::PgConnection,
+    items: &[NewTransaction],
+) -> Result<Vec<SubmittedTransaction>, sqlx::Error> {
+    use sqlx::Row;
+    use std::collections::HashMap;
+
+    if items.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    debug!("Bulk submitting {} transactions", items.len());
+
+    let amounts: Vec<i32> = items.iter().map(|item| item.amount).collect();
+    let keys: Vec<Option<String>> = items.iter().map(|item| item.client_tx_id.clone()).collect();
+
+    // ORDER BY ordinality makes ids follow input order, which is how unkeyed
+    // rows are matched back to their items below.
+    let rows = sqlx::query(
+        r"
+        INSERT INTO incoming_transactions (amount, client_tx_id)
+        SELECT t.amount, t.client_tx_id
+        FROM UNNEST($1::INTEGER[], $2::VARCHAR[]) WITH ORDINALITY AS t(amount, client_tx_id, ord)
+        ORDER BY t.ord
+        ON CONFLICT (client_tx_id) WHERE client_tx_id IS NOT NULL DO NOTHING
+        RETURNING id, amount, included_in_batch_id, created_at, client_tx_id
+        ",
+    )
+    .bind(&amounts)
+    .bind(&keys)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut inserted = rows
+        .iter()
+        .map(|row| {
+            Ok((
+                IncomingTransaction {
+                    id: row.try_get("id")?,
+                    amount: row.try_get("amount")?,
+                    included_in_batch_id: row.try_get("included_in_batch_id")?,
+                    created_at: row.try_get("created_at")?,
+                },
+                row.try_get::<Option<String>, _>("client_tx_id")?,
+            ))
+        })
+        .collect::<Result<Vec<_>, sqlx::Error>>()?;
+    inserted.sort_by_key(|(transaction, _)| transaction.id);
+
+    let mut unkeyed = Vec::new();
+    let mut fresh_by_key = HashMap::new();
+    for (transaction, key) in inserted {
+        match key {
+            Some(key) => {
+                fresh_by_key.insert(key, transaction);
+            }
+            None => unkeyed.push(transaction),
+        }
+    }
+
+    let replayed_keys: Vec<String> = keys
+        .iter()
+        .flatten()
+        .filter(|key| !fresh_by_key.contains_key(*key))
+        .cloned()
+        .collect();
+    let mut existing_by_key = HashMap::new();
+    if !replayed_keys.is_empty() {
+        let rows = sqlx::query(
+            r"
+            SELECT id, amount, included_in_batch_id, created_at, client_tx_id
+            FROM incoming_transactions
+            WHERE client_tx_id = ANY($1)
+            ",
+        )
+        .bind(&replayed_keys)
+        .fetch_all(&mut *conn)
+        .await?;
+        for row in rows {
+            let key: String = row.try_get("client_tx_id")?;
+            existing_by_key.insert(
+                key,
+                IncomingTransaction {
+                    id: row.try_get("id")?,
+                    amount: row.try_get("amount")?,
+                    included_in_batch_id: row.try_get("included_in_batch_id")?,
+                    created_at: row.try_get("created_at")?,
+                },
+            );
+        }
+    }
+
+    let mut unkeyed = unkeyed.into_iter();
+    items
+        .iter()
+        .map(|item| {
+            let (transaction, replayed) = match &item.client_tx_id {
+                Some(key) => match fresh_by_key.remove(key) {
+                    Some(transaction) => (transaction, false),
+                    None => (
+                        existing_by_key
+                            .remove(key)
+                            .ok_or(sqlx::Error::RowNotFound)?,
+                        true,
+                    ),
+                },
+                None => (unkeyed.next().ok_or(sqlx::Error::RowNotFound)?, false),
+            };
+            Ok(SubmittedTransaction {
+                transaction,
+                client_tx_id: item.client_tx_id.clone(),
+                replayed,
+            })
+        })
+        .collect()
+}
+
 /// Get pending transactions (not yet batched)
 ///
 /// # Errors
@@ -191,6 +527,77 @@ pub async fn get_pending_transactions(
     Ok(transactions)
 }
 
+/// Get a single incoming transaction, pending or batched
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn get_transaction_by_id(
+    pool: &PgPool,
+    transaction_id: i32,
+) -> Result<Option<IncomingTransaction>, sqlx::Error> {
+    sqlx::query_as::<_, IncomingTransaction>(
+        "SELECT id, amount, included_in_batch_id, created_at FROM incoming_transactions WHERE id = $1",
+    )
+    .bind(transaction_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// List incoming transactions (pending and batched) with filters and keyset pagination
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn list_transactions(
+    pool: &PgPool,
+    filter: &TransactionListFilter,
+) -> Result<Page<IncomingTransaction>, sqlx::Error> {
+    debug!("Listing transactions: {filter:?}");
+
+    let mut query = sqlx::QueryBuilder::<sqlx::Postgres>::new(
+        "SELECT id, amount, included_in_batch_id, created_at FROM incoming_transactions WHERE TRUE",
+    );
+
+    push_keyset_cursor(&mut query, filter.after_id, filter.order);
+
+    match filter.status {
+        Some(TransactionStatus::Pending) => {
+            query.push(" AND included_in_batch_id IS NULL");
+        }
+        Some(TransactionStatus::Batched) => {
+            query.push(" AND included_in_batch_id IS NOT NULL");
+        }
+        None => {}
+    }
+    if let Some(batch_id) = filter.batch_id {
+        query
+            .push(" AND included_in_batch_id = ")
+            .push_bind(batch_id);
+    }
+    if let Some(created_after) = filter.created_after {
+        query.push(" AND created_at >= ").push_bind(created_after);
+    }
+    if let Some(created_before) = filter.created_before {
+        query.push(" AND created_at < ").push_bind(created_before);
+    }
+    if let Some(min_amount) = filter.min_amount {
+        query.push(" AND amount >= ").push_bind(min_amount);
+    }
+    if let Some(max_amount) = filter.max_amount {
+        query.push(" AND amount <= ").push_bind(max_amount);
+    }
+
+    push_keyset_order_and_limit(&mut query, filter.order, filter.limit);
+
+    let transactions = query
+        .build_query_as::<IncomingTransaction>()
+        .fetch_all(pool)
+        .await?;
+
+    let page = into_page(transactions, filter.limit, |t| t.id);
+    debug!("Found {} transactions", page.items.len());
+    Ok(page)
+}
+
 // ============================================================================
 // BATCH FUNCTIONS
 // ============================================================================
@@ -202,12 +609,30 @@ pub async fn get_pending_transactions(
 pub async fn create_batch(
     pool: &PgPool,
     batch_size: Option<i32>,
+) -> Result<Option<ProofBatch>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let batch = create_batch_in(&mut tx, batch_size).await?;
+    tx.commit().await?;
+    Ok(batch)
+}
+
+/// `create_batch` on `conn`, inside the caller's transaction
+///
+/// The claimed transactions stay row-locked until the caller's transaction
+/// ends; other connections skip them rather than wait. The batch's proof
+/// request is queued in the outbox on the same transaction.
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn create_batch_in(
+    conn: &mut PgConnection,
+    batch_size: Option<i32>,
 ) -> Result<Option<ProofBatch>, sqlx::Error> {
     let size = batch_size.unwrap_or(10);
     debug!("Creating batch with size: {size}");
 
     let batch_id: i32 = sqlx::query_scalar!("SELECT create_batch($1)", size)
-        .fetch_one(pool)
+        .fetch_one(&mut *conn)
         .await?
         .unwrap_or(0);
 
@@ -216,27 +641,54 @@ pub async fn create_batch(
         return Ok(None);
     }
 
+    queue_outbox_job(&mut *conn, batch_id, OUTBOX_PROOF_REQUEST).await?;
+
     // Get the created batch details
-    get_batch_by_id(pool, batch_id).await.map(Some)
+    get_batch_by_id(&mut *conn, batch_id).await.map(Some)
+}
+
+/// Transactions included in a batch, by id
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn get_batch_transactions(
+    executor: impl PgExecutor<'_>,
+    batch_id: i32,
+) -> Result<Vec<IncomingTransaction>, sqlx::Error> {
+    sqlx::query_as::<_, IncomingTransaction>(
+        r"
+        SELECT id, amount, included_in_batch_id, created_at
+        FROM incoming_transactions
+        WHERE included_in_batch_id = $1
+        ORDER BY id ASC
+        ",
+    )
+    .bind(batch_id)
+    .fetch_all(executor)
+    .await
 }
 
 /// Get batch by ID
 ///
 /// # Errors
 /// Returns error if database operation fails or batch not found
-pub async fn get_batch_by_id(pool: &PgPool, batch_id: i32) -> Result<ProofBatch, sqlx::Error> {
+pub async fn get_batch_by_id(
+    executor: impl PgExecutor<'_>,
+    batch_id: i32,
+) -> Result<ProofBatch, sqlx::Error> {
     debug!("Getting batch: id={batch_id}");
 
     let row = sqlx::query!(
         r"
         SELECT id, previous_counter_value, final_counter_value, transaction_ids, 
-               sindri_proof_id, proof_status, created_at, proven_at
+               sindri_proof_id, proof_status, created_at, proven_at, posted_to_contract_at,
+               parent_batch_id
         FROM proof_batches 
         WHERE id = $1
         ",
         batch_id
     )
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await?;
 
     let batch = ProofBatch {
@@ -248,6 +700,8 @@ pub async fn get_batch_by_id(pool: &PgPool, batch_id: i32) -> Result<ProofBatch,
         proof_status: row.proof_status.unwrap_or_else(|| "pending".to_string()),
         created_at: row.created_at.unwrap_or_else(|| Utc::now()),
         proven_at: row.proven_at,
+        posted_to_contract_at: row.posted_to_contract_at,
+        parent_batch_id: row.parent_batch_id,
     };
 
     debug!(
@@ -271,7 +725,8 @@ pub async fn get_all_batches(
     let rows = sqlx::query!(
         r"
         SELECT id, previous_counter_value, final_counter_value, transaction_ids,
-               sindri_proof_id, proof_status, created_at, proven_at
+               sindri_proof_id, proof_status, created_at, proven_at, posted_to_contract_at,
+               parent_batch_id
         FROM proof_batches 
         ORDER BY id DESC 
         LIMIT $1
@@ -292,6 +747,8 @@ pub async fn get_all_batches(
             proof_status: row.proof_status.unwrap_or_else(|| "pending".to_string()),
             created_at: row.created_at.unwrap_or_else(|| Utc::now()),
             proven_at: row.proven_at,
+            posted_to_contract_at: row.posted_to_contract_at,
+            parent_batch_id: row.parent_batch_id,
         })
         .collect();
 
@@ -299,6 +756,109 @@ pub async fn get_all_batches(
     Ok(batches)
 }
 
+/// List batches with filters and keyset pagination
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn list_batches(
+    pool: &PgPool,
+    filter: &BatchListFilter,
+) -> Result<Page<ProofBatch>, sqlx::Error> {
+    use sqlx::Row;
+
+    debug!("Listing batches: {filter:?}");
+
+    let mut query = sqlx::QueryBuilder::<sqlx::Postgres>::new(
+        r"SELECT id, previous_counter_value, final_counter_value, transaction_ids,
+               sindri_proof_id, proof_status, created_at, proven_at, posted_to_contract_at,
+               parent_batch_id
+        FROM proof_batches WHERE TRUE",
+    );
+
+    push_keyset_cursor(&mut query, filter.after_id, filter.order);
+
+    if let Some(status) = &filter.proof_status {
+        query.push(" AND proof_status = ").push_bind(status.clone());
+    }
+    if let Some(created_after) = filter.created_after {
+        query.push(" AND created_at >= ").push_bind(created_after);
+    }
+    if let Some(created_before) = filter.created_before {
+        query.push(" AND created_at < ").push_bind(created_before);
+    }
+
+    push_keyset_order_and_limit(&mut query, filter.order, filter.limit);
+
+    let rows = query.build().fetch_all(pool).await?;
+
+    let batches = rows
+        .into_iter()
+        .map(|row| -> Result<ProofBatch, sqlx::Error> {
+            Ok(ProofBatch {
+                id: row.try_get("id")?,
+                previous_counter_value: row.try_get("previous_counter_value")?,
+                final_counter_value: row.try_get("final_counter_value")?,
+                transaction_ids: row.try_get("transaction_ids")?,
+                sindri_proof_id: row.try_get("sindri_proof_id")?,
+                proof_status: row
+                    .try_get::<Option<String>, _>("proof_status")?
+                    .unwrap_or_else(|| "pending".to_string()),
+                created_at: row
+                    .try_get::<Option<DateTime<Utc>>, _>("created_at")?
+                    .unwrap_or_else(|| Utc::now()),
+                proven_at: row.try_get("proven_at")?,
+                posted_to_contract_at: row.try_get("posted_to_contract_at")?,
+                parent_batch_id: row.try_get("parent_batch_id")?,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let page = into_page(batches, filter.limit, |b| b.id);
+    debug!("Found {} batches", page.items.len());
+    Ok(page)
+}
+
+/// Append the keyset cursor condition for an id-ordered listing
+fn push_keyset_cursor(
+    query: &mut sqlx::QueryBuilder<'_, sqlx::Postgres>,
+    after_id: Option<i32>,
+    order: SortOrder,
+) {
+    if let Some(after_id) = after_id {
+        match order {
+            SortOrder::Asc => query.push(" AND id > "),
+            SortOrder::Desc => query.push(" AND id < "),
+        };
+        query.push_bind(after_id);
+    }
+}
+
+/// Append ORDER BY and LIMIT, fetching one extra row to detect a following page
+fn push_keyset_order_and_limit(
+    query: &mut sqlx::QueryBuilder<'_, sqlx::Postgres>,
+    order: SortOrder,
+    limit: i64,
+) {
+    match order {
+        SortOrder::Asc => query.push(" ORDER BY id ASC"),
+        SortOrder::Desc => query.push(" ORDER BY id DESC"),
+    };
+    query.push(" LIMIT ").push_bind(limit.max(1) + 1);
+}
+
+/// Trim the extra lookahead row and derive the next cursor
+fn into_page<T>(mut items: Vec<T>, limit: i64, id_of: impl Fn(&T) -> i32) -> Page<T> {
+    let limit = limit.max(1) as usize;
+    let next_cursor = if items.len() > limit {
+        items.truncate(limit);
+        items.last().map(id_of)
+    } else {
+        None
+    };
+
+    Page { items, next_cursor }
+}
+
 /// Update batch with Sindri proof ID and status
 ///
 /// # Errors
@@ -308,6 +868,24 @@ pub async fn update_batch_proof(
     batch_id: i32,
     proof_id: &str,
     status: &str,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    update_batch_proof_in(&mut tx, batch_id, proof_id, status).await?;
+    tx.commit().await
+}
+
+/// `update_batch_proof` on `conn`, inside the caller's transaction
+///
+/// A batch that becomes `proven` gets its contract post queued in the outbox
+/// on the same transaction.
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn update_batch_proof_in(
+    conn: &mut PgConnection,
+    batch_id: i32,
+    proof_id: &str,
+    status: &str,
 ) -> Result<(), sqlx::Error> {
     debug!("Updating batch {batch_id} with proof {proof_id}, status: {status}");
 
@@ -317,24 +895,264 @@ pub async fn update_batch_proof(
         None
     };
 
-    sqlx::query!(
+    let updated = sqlx::query!(
         r"
         UPDATE proof_batches 
         SET sindri_proof_id = $1, proof_status = $2, proven_at = $3
-        WHERE id = $4
+        WHERE id = $4 AND proof_status IS DISTINCT FROM 'unwound'
         ",
         proof_id,
         status,
         proven_at,
         batch_id
     )
-    .execute(pool)
+    .execute(&mut *conn)
     .await?;
 
+    if status == "proven" && updated.rows_affected() == 1 {
+        queue_outbox_job(&mut *conn, batch_id, OUTBOX_CONTRACT_POST).await?;
+    }
+
     debug!("Batch updated successfully");
     Ok(())
 }
 
+/// Record the first IMT leaf a batch's nullifiers were written to
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn set_batch_tree_start_index(
+    executor: impl PgExecutor<'_>,
+    batch_id: i32,
+    tree_start_index: i64,
+) -> Result<(), sqlx::Error> {
+    debug!("Batch {batch_id} starts at IMT leaf {tree_start_index}");
+
+    sqlx::query("UPDATE proof_batches SET tree_start_index = $1 WHERE id = $2")
+        .bind(tree_start_index)
+        .bind(batch_id)
+        .execute(executor)
+        .await?;
+
+    Ok(())
+}
+
+/// Put a failed batch back in the proof queue
+///
+/// Clears the proof id, marks the batch `pending` and queues a fresh proof
+/// request in the outbox. The batch keeps its transactions and IMT state,
+/// unlike `unwind_failed_batch`.
+///
+/// # Errors
+/// Returns `DbError::NotFound` if the batch doesn't exist, `DbError::InvalidState`
+/// if it has not failed, `DbError::Database` if a query fails
+pub async fn reset_failed_batch_proof(
+    pool: &PgPool,
+    batch_id: i32,
+) -> Result<(), crate::error::DbError> {
+    use crate::error::DbError;
+
+    debug!("Resetting proof of failed batch {batch_id}");
+
+    let mut tx = pool.begin().await?;
+    let reset = sqlx::query(
+        r"
+        UPDATE proof_batches
+        SET proof_status = 'pending', sindri_proof_id = NULL, proven_at = NULL
+        WHERE id = $1 AND proof_status = 'failed'
+        ",
+    )
+    .bind(batch_id)
+    .execute(&mut *tx)
+    .await?;
+
+    if reset.rows_affected() == 0 {
+        let status: Option<Option<String>> =
+            sqlx::query_scalar("SELECT proof_status FROM proof_batches WHERE id = $1")
+                .bind(batch_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        return Err(match status {
+            None => DbError::NotFound(format!("batch {batch_id}")),
+            Some(status) => DbError::InvalidState(format!(
+                "Batch {batch_id} is {}, only failed proofs can be retried",
+                status.as_deref().unwrap_or("pending")
+            )),
+        });
+    }
+
+    queue_outbox_job(&mut *tx, batch_id, OUTBOX_PROOF_REQUEST).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Unwind a failed batch and every batch built on it
+///
+/// In one database transaction the batches are marked `unwound`, their
+/// transactions go back to the pending queue, their settlement rows and
+/// outbox jobs are dropped and the IMT is rolled back to the failed batch's
+/// first leaf. The caller re-batches the released transactions on top of the
+/// parent.
+///
+/// # Errors
+/// Returns `DbError::NotFound` if the batch doesn't exist, `DbError::InvalidState`
+/// if it has not failed, part of the lineage is already settled, the batch
+/// predates lineage tracking or the rolled back root doesn't match the
+/// parent's committed root, `DbError::Database` if a query fails
+pub async fn unwind_failed_batch(
+    pool: &PgPool,
+    batch_id: i32,
+) -> Result<BatchUnwind, crate::error::DbError> {
+    use crate::error::DbError;
+    use sqlx::Row;
+
+    debug!("Unwinding failed batch {batch_id}");
+
+    let mut tx = pool.begin().await?;
+    // Before any row lock, in the same order as batch creation
+    crate::merkle_tree::lock_for_write(&mut tx).await?;
+
+    let batch = sqlx::query(
+        r"
+        SELECT proof_status, parent_batch_id, tree_start_index
+        FROM proof_batches
+        WHERE id = $1
+        FOR UPDATE
+        ",
+    )
+    .bind(batch_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| DbError::NotFound(format!("batch {batch_id}")))?;
+
+    let status: Option<String> = batch.try_get("proof_status")?;
+    if status.as_deref() != Some("failed") {
+        return Err(DbError::InvalidState(format!(
+            "Batch {batch_id} is {}, only failed batches can be unwound",
+            status.as_deref().unwrap_or("pending")
+        )));
+    }
+    let parent_batch_id: Option<i32> = batch.try_get("parent_batch_id")?;
+    let tree_start_index: i64 = batch
+        .try_get::<Option<i64>, _>("tree_start_index")?
+        .ok_or_else(|| {
+            DbError::InvalidState(format!(
+                "Batch {batch_id} predates lineage tracking and has no tree start index"
+            ))
+        })?;
+
+    let lineage = sqlx::query(
+        r"
+        WITH RECURSIVE lineage AS (
+            SELECT id FROM proof_batches WHERE id = $1
+            UNION ALL
+            SELECT child.id
+            FROM proof_batches child
+            JOIN lineage ON child.parent_batch_id = lineage.id
+            WHERE child.proof_status IS DISTINCT FROM 'unwound'
+        )
+        SELECT pb.id,
+               pb.posted_to_contract,
+               EXISTS (
+                   SELECT 1 FROM batch_submissions bs
+                   WHERE bs.batch_id = pb.id AND bs.status IN ('submitted', 'confirmed')
+               ) AS settled
+        FROM proof_batches pb
+        JOIN lineage ON lineage.id = pb.id
+        ORDER BY pb.id
+        FOR UPDATE OF pb
+        ",
+    )
+    .bind(batch_id)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut unwound_batch_ids = Vec::with_capacity(lineage.len());
+    for row in &lineage {
+        let id: i32 = row.try_get("id")?;
+        let posted: Option<bool> = row.try_get("posted_to_contract")?;
+        let settled: Option<bool> = row.try_get("settled")?;
+        if posted.unwrap_or(false) || settled.unwrap_or(false) {
+            return Err(DbError::InvalidState(format!(
+                "Batch {id} built on batch {batch_id} is already settled on chain"
+            )));
+        }
+        unwound_batch_ids.push(id);
+    }
+
+    sqlx::query("UPDATE proof_batches SET proof_status = 'unwound' WHERE id = ANY($1)")
+        .bind(&unwound_batch_ids)
+        .execute(&mut *tx)
+        .await?;
+
+    let mut released_transaction_ids: Vec<i32> = sqlx::query_scalar(
+        r"
+        UPDATE incoming_transactions
+        SET included_in_batch_id = NULL
+        WHERE included_in_batch_id = ANY($1)
+        RETURNING id
+        ",
+    )
+    .bind(&unwound_batch_ids)
+    .fetch_all(&mut *tx)
+    .await?;
+    released_transaction_ids.sort_unstable();
+
+    sqlx::query("DELETE FROM batch_submissions WHERE batch_id = ANY($1)")
+        .bind(&unwound_batch_ids)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM transaction_inclusions WHERE batch_id = ANY($1)")
+        .bind(&unwound_batch_ids)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM outbox_jobs WHERE batch_id = ANY($1)")
+        .bind(&unwound_batch_ids)
+        .execute(&mut *tx)
+        .await?;
+
+    let restored_root = crate::merkle_tree::IndexedMerkleTree::new(pool.clone())
+        .rollback_to_size(&mut *tx, tree_start_index)
+        .await?;
+
+    let expected_root: Vec<u8> = match parent_batch_id {
+        Some(parent_id) => sqlx::query_scalar(
+            "SELECT merkle_root FROM ads_state_commits WHERE batch_id = $1 ORDER BY id ASC LIMIT 1",
+        )
+        .bind(parent_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| {
+            DbError::InvalidState(format!("Parent batch {parent_id} has no committed root"))
+        })?,
+        None => crate::merkle_tree_32::genesis_root().to_vec(),
+    };
+    if expected_root != restored_root {
+        return Err(DbError::InvalidState(format!(
+            "Rolled back root 0x{} does not match the parent's root 0x{}",
+            hex::encode(restored_root),
+            hex::encode(&expected_root)
+        )));
+    }
+
+    tx.commit().await?;
+
+    debug!(
+        "Unwound batches {unwound_batch_ids:?}, released {} transactions",
+        released_transaction_ids.len()
+    );
+    Ok(BatchUnwind {
+        failed_batch_id: batch_id,
+        parent_batch_id,
+        unwound_batch_ids,
+        released_transaction_ids,
+        restored_root: restored_root.to_vec(),
+    })
+}
+
 // ============================================================================
 // STATE FUNCTIONS
 // ============================================================================
@@ -405,7 +1223,7 @@ pub async fn get_current_state(pool: &PgPool) -> Result<CounterState, sqlx::Erro
 /// # Errors
 /// Returns error if database operation fails
 pub async fn store_ads_state_commit(
-    pool: &PgPool,
+    executor: impl PgExecutor<'_>,
     batch_id: i32,
     merkle_root: &[u8],
 ) -> Result<AdsStateCommit, sqlx::Error> {
@@ -420,7 +1238,7 @@ pub async fn store_ads_state_commit(
         batch_id,
         merkle_root
     )
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await?;
 
     let commit = AdsStateCommit {
@@ -434,6 +1252,42 @@ pub async fn store_ads_state_commit(
     Ok(commit)
 }
 
+/// ADS state commits of the given batches, ordered by batch
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn get_ads_state_commits(
+    pool: &PgPool,
+    batch_ids: &[i32],
+) -> Result<Vec<AdsStateCommit>, sqlx::Error> {
+    use sqlx::Row;
+
+    let rows = sqlx::query(
+        r"
+        SELECT id, batch_id, merkle_root, created_at
+        FROM ads_state_commits
+        WHERE batch_id = ANY($1)
+        ORDER BY batch_id, id
+        ",
+    )
+    .bind(batch_ids)
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            Ok(AdsStateCommit {
+                id: row.try_get("id")?,
+                batch_id: row.try_get("batch_id")?,
+                merkle_root: row.try_get("merkle_root")?,
+                created_at: row
+                    .try_get::<Option<DateTime<Utc>>, _>("created_at")?
+                    .unwrap_or_else(|| Utc::now()),
+            })
+        })
+        .collect()
+}
+
 /// Get contract submission data for a batch (public/private split)
 ///
 /// # Errors
@@ -448,29 +1302,29 @@ pub async fn get_contract_submission_data(
     let batch = get_batch_by_id(pool, batch_id).await?;
 
     // Get previous and new Merkle roots
-    let prev_root = if batch.previous_counter_value == 0 {
+    let prev_root = match batch.parent_batch_id {
         // Genesis state - use empty root
-        "0x0000000000000000000000000000000000000000000000000000000000000000".to_string()
-    } else {
-        // Get previous batch's Merkle root
-        let prev_row = sqlx::query!(
-            r"
-            SELECT ads.merkle_root
-            FROM proof_batches pb
-            JOIN ads_state_commits ads ON pb.id = ads.batch_id
-            WHERE pb.final_counter_value = $1 AND pb.proof_status = 'proven'
-            ORDER BY pb.id DESC
-            LIMIT 1
-            ",
-            batch.previous_counter_value
-        )
-        .fetch_optional(pool)
-        .await?;
-
-        match prev_row {
-            Some(row) => format!("0x{}", hex::encode(row.merkle_root)),
-            None => {
-                "0x0000000000000000000000000000000000000000000000000000000000000000".to_string()
+        None => "0x0000000000000000000000000000000000000000000000000000000000000000".to_string(),
+        Some(parent_id) => {
+            // The parent batch's Merkle root is the state this batch starts from
+            let prev_root: Option<Vec<u8>> = sqlx::query_scalar(
+                r"
+                SELECT merkle_root
+                FROM ads_state_commits
+                WHERE batch_id = $1
+                ORDER BY id ASC
+                LIMIT 1
+                ",
+            )
+            .bind(parent_id)
+            .fetch_optional(pool)
+            .await?;
+
+            match prev_root {
+                Some(root) => format!("0x{}", hex::encode(root)),
+                None => {
+                    "0x0000000000000000000000000000000000000000000000000000000000000000".to_string()
+                }
             }
         }
     };
@@ -520,44 +1374,72 @@ pub async fn get_contract_submission_data(
 
 /// Get proven batches that haven't been posted to the smart contract yet
 ///
+/// Batches are posted strictly along the lineage: a batch is only returned
+/// once its parent is posted or returned before it, so a batch stuck behind an
+/// unproven or failed parent waits until that parent is proven or unwound.
+///
 /// # Errors
 /// Returns error if database operation fails
 pub async fn get_proven_unposted_batches(
     pool: &PgPool,
     limit: Option<i32>,
 ) -> Result<Vec<ProofBatch>, sqlx::Error> {
+    use sqlx::Row;
+
     let limit = limit.unwrap_or(10);
     debug!("Getting proven unposted batches with limit: {limit}");
 
-    let rows = sqlx::query!(
+    let rows = sqlx::query(
         r"
+        WITH RECURSIVE postable AS (
+            SELECT pb.*
+            FROM proof_batches pb
+            LEFT JOIN proof_batches parent ON parent.id = pb.parent_batch_id
+            WHERE pb.proof_status = 'proven'
+              AND pb.posted_to_contract = FALSE
+              AND pb.sindri_proof_id IS NOT NULL
+              AND (pb.parent_batch_id IS NULL OR parent.posted_to_contract = TRUE)
+            UNION ALL
+            SELECT child.*
+            FROM proof_batches child
+            JOIN postable ON child.parent_batch_id = postable.id
+            WHERE child.proof_status = 'proven'
+              AND child.posted_to_contract = FALSE
+              AND child.sindri_proof_id IS NOT NULL
+        )
         SELECT id, previous_counter_value, final_counter_value, transaction_ids,
-               sindri_proof_id, proof_status, created_at, proven_at
-        FROM proof_batches
-        WHERE proof_status = 'proven' 
-          AND posted_to_contract = FALSE
-          AND sindri_proof_id IS NOT NULL
+               sindri_proof_id, proof_status, created_at, proven_at, posted_to_contract_at,
+               parent_batch_id
+        FROM postable
         ORDER BY id ASC
         LIMIT $1
         ",
-        limit as i64
     )
+    .bind(limit as i64)
     .fetch_all(pool)
     .await?;
 
-    let batches: Vec<ProofBatch> = rows
+    let batches = rows
         .into_iter()
-        .map(|row| ProofBatch {
-            id: row.id,
-            previous_counter_value: row.previous_counter_value,
-            final_counter_value: row.final_counter_value,
-            transaction_ids: row.transaction_ids,
-            sindri_proof_id: row.sindri_proof_id,
-            proof_status: row.proof_status.unwrap_or_else(|| "pending".to_string()),
-            created_at: row.created_at.unwrap_or_else(|| Utc::now()),
-            proven_at: row.proven_at,
+        .map(|row| -> Result<ProofBatch, sqlx::Error> {
+            Ok(ProofBatch {
+                id: row.try_get("id")?,
+                previous_counter_value: row.try_get("previous_counter_value")?,
+                final_counter_value: row.try_get("final_counter_value")?,
+                transaction_ids: row.try_get("transaction_ids")?,
+                sindri_proof_id: row.try_get("sindri_proof_id")?,
+                proof_status: row
+                    .try_get::<Option<String>, _>("proof_status")?
+                    .unwrap_or_else(|| "pending".to_string()),
+                created_at: row
+                    .try_get::<Option<DateTime<Utc>>, _>("created_at")?
+                    .unwrap_or_else(|| Utc::now()),
+                proven_at: row.try_get("proven_at")?,
+                posted_to_contract_at: row.try_get("posted_to_contract_at")?,
+                parent_batch_id: row.try_get("parent_batch_id")?,
+            })
         })
-        .collect();
+        .collect::<Result<Vec<_>, _>>()?;
 
     debug!("Found {} proven unposted batches", batches.len());
     Ok(batches)
@@ -568,7 +1450,7 @@ pub async fn get_proven_unposted_batches(
 /// # Errors
 /// Returns error if database operation fails
 pub async fn mark_batch_posted_to_contract(
-    pool: &PgPool,
+    executor: impl PgExecutor<'_>,
     batch_id: i32,
 ) -> Result<(), sqlx::Error> {
     debug!("Marking batch {batch_id} as posted to contract");
@@ -582,7 +1464,7 @@ pub async fn mark_batch_posted_to_contract(
         ",
         batch_id
     )
-    .execute(pool)
+    .execute(executor)
     .await?;
 
     if result.rows_affected() != 1 {
@@ -592,3 +1474,172 @@ pub async fn mark_batch_posted_to_contract(
     debug!("Successfully marked batch {batch_id} as posted to contract");
     Ok(())
 }
+
+/// Clear the posted flag of a batch and queue its contract post again
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn unmark_batch_posted_to_contract(
+    pool: &PgPool,
+    batch_id: i32,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    unmark_batch_posted_to_contract_in(&mut tx, batch_id).await?;
+    tx.commit().await
+}
+
+/// `unmark_batch_posted_to_contract` on `conn`, inside the caller's transaction
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn unmark_batch_posted_to_contract_in(
+    conn: &mut PgConnection,
+    batch_id: i32,
+) -> Result<(), sqlx::Error> {
+    debug!("Clearing posted flag of batch {batch_id}");
+
+    sqlx::query(
+        r"
+        UPDATE proof_batches
+        SET posted_to_contract = FALSE,
+            posted_to_contract_at = NULL
+        WHERE id = $1
+        ",
+    )
+    .bind(batch_id)
+    .execute(&mut *conn)
+    .await?;
+
+    queue_outbox_job(&mut *conn, batch_id, OUTBOX_CONTRACT_POST).await
+}
+
+/// Insert or overwrite the settlement status of a batch on one target
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn record_batch_submission(
+    executor: impl PgExecutor<'_>,
+    update: &BatchSubmissionUpdate,
+) -> Result<BatchSubmission, sqlx::Error> {
+    debug!(
+        "Recording batch {} on {} as {}",
+        update.batch_id, update.target, update.status
+    );
+
+    sqlx::query_as::<_, BatchSubmission>(
+        r"
+        INSERT INTO batch_submissions
+            (batch_id, target, chain_id, status, transaction_hash, block_number, error)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (batch_id, target) DO UPDATE
+        SET chain_id = EXCLUDED.chain_id,
+            status = EXCLUDED.status,
+            transaction_hash = EXCLUDED.transaction_hash,
+            block_number = EXCLUDED.block_number,
+            error = EXCLUDED.error,
+            updated_at = NOW()
+        RETURNING batch_id, target, chain_id, status, transaction_hash, block_number, error, updated_at
+        ",
+    )
+    .bind(update.batch_id)
+    .bind(&update.target)
+    .bind(update.chain_id)
+    .bind(&update.status)
+    .bind(&update.transaction_hash)
+    .bind(update.block_number)
+    .bind(&update.error)
+    .fetch_one(executor)
+    .await
+}
+
+/// Settlement status of the given batches on every target, ordered by batch and target
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn get_batch_submissions(
+    pool: &PgPool,
+    batch_ids: &[i32],
+) -> Result<Vec<BatchSubmission>, sqlx::Error> {
+    sqlx::query_as::<_, BatchSubmission>(
+        r"
+        SELECT batch_id, target, chain_id, status, transaction_hash, block_number, error, updated_at
+        FROM batch_submissions
+        WHERE batch_id = ANY($1)
+        ORDER BY batch_id, target
+        ",
+    )
+    .bind(batch_ids)
+    .fetch_all(pool)
+    .await
+}
+
+/// Store the membership proofs captured when a batch was created
+///
+/// A transaction that already has a proof (from a batch that was since
+/// unwound) gets it replaced. Given a connection inside a transaction, the
+/// proofs are written in a savepoint of it.
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn store_transaction_inclusions<'c>(
+    conn: impl sqlx::Acquire<'c, Database = Postgres>,
+    inclusions: &[TransactionInclusion],
+) -> Result<(), sqlx::Error> {
+    debug!("Storing {} transaction inclusion proofs", inclusions.len());
+
+    let mut tx = conn.begin().await?;
+    for inclusion in inclusions {
+        sqlx::query(
+            r"
+            INSERT INTO transaction_inclusions
+                (transaction_id, batch_id, nullifier_value, leaf_index, next_index,
+                 next_value, siblings, path_indices, merkle_root)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (transaction_id) DO UPDATE
+            SET batch_id = EXCLUDED.batch_id,
+                nullifier_value = EXCLUDED.nullifier_value,
+                leaf_index = EXCLUDED.leaf_index,
+                next_index = EXCLUDED.next_index,
+                next_value = EXCLUDED.next_value,
+                siblings = EXCLUDED.siblings,
+                path_indices = EXCLUDED.path_indices,
+                merkle_root = EXCLUDED.merkle_root,
+                created_at = NOW()
+            ",
+        )
+        .bind(inclusion.transaction_id)
+        .bind(inclusion.batch_id)
+        .bind(inclusion.nullifier_value)
+        .bind(inclusion.leaf_index)
+        .bind(inclusion.next_index)
+        .bind(inclusion.next_value)
+        .bind(&inclusion.siblings)
+        .bind(&inclusion.path_indices)
+        .bind(&inclusion.merkle_root)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await
+}
+
+/// Membership proof of a transaction, `None` if it was never batched or its
+/// batch predates receipts
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn get_transaction_inclusion(
+    pool: &PgPool,
+    transaction_id: i32,
+) -> Result<Option<TransactionInclusion>, sqlx::Error> {
+    sqlx::query_as::<_, TransactionInclusion>(
+        r"
+        SELECT transaction_id, batch_id, nullifier_value, leaf_index, next_index,
+               next_value, siblings, path_indices, merkle_root
+        FROM transaction_inclusions
+        WHERE transaction_id = $1
+        ",
+    )
+    .bind(transaction_id)
+    .fetch_optional(pool)
+    .await
+}
@@ -51,6 +51,10 @@ pub enum DbError {
     #[error("Invalid state: {0}")]
     InvalidState(String),
 
+    /// State snapshot that is malformed or does not verify
+    #[error("Invalid snapshot: {0}")]
+    InvalidSnapshot(String),
+
     /// Migration error
     #[error("Migration error: {0}")]
     MigrationError(String),
@@ -107,6 +111,7 @@ impl DbError {
             Self::MigrationError(_) => "MIGRATION_ERROR",
             Self::ConfigError(_) => "CONFIG_ERROR",
             Self::InvalidState(_) => "INVALID_STATE",
+            Self::InvalidSnapshot(_) => "INVALID_SNAPSHOT",
         }
     }
 }
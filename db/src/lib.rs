@@ -38,6 +38,9 @@ pub mod ads_service;
 pub mod background_processor;
 pub mod merkle_tree;
 pub mod merkle_tree_32;
+pub mod outbox;
+pub mod replay;
+pub mod snapshot;
 pub mod vapp_integration;
 
 pub mod db;
@@ -47,8 +50,12 @@ pub mod error;
 pub use db::{
     // Batch functions
     create_batch,
+    create_batch_in,
+    get_ads_state_commits,
     get_all_batches,
     get_batch_by_id,
+    get_batch_submissions,
+    get_batch_transactions,
     get_contract_submission_data,
     // State functions
     get_current_counter_value,
@@ -57,25 +64,52 @@ pub use db::{
     get_pending_transactions,
 
     get_proven_unposted_batches,
+    get_transaction_by_id,
+    get_transaction_inclusion,
     // Database connection
     init_db,
     init_db_with_url,
 
+    // Listing functions
+    list_batches,
+    list_transactions,
+
     mark_batch_posted_to_contract,
+    record_batch_submission,
+    reset_failed_batch_proof,
+    set_batch_tree_start_index,
     // ADS/Merkle functions
     store_ads_state_commit,
+    store_transaction_inclusions,
     // Transaction functions
     submit_transaction,
+    submit_transaction_idempotent,
+    submit_transactions_bulk,
+    unmark_batch_posted_to_contract,
+    unmark_batch_posted_to_contract_in,
+    unwind_failed_batch,
     update_batch_proof,
+    update_batch_proof_in,
 
     // Types
     AdsStateCommit,
+    BatchListFilter,
+    BatchSubmission,
+    BatchSubmissionUpdate,
+    BatchUnwind,
     ContractPrivateData,
     ContractPublicData,
     ContractSubmissionData,
     CounterState,
     IncomingTransaction,
+    NewTransaction,
+    Page,
     ProofBatch,
+    SortOrder,
+    SubmittedTransaction,
+    TransactionInclusion,
+    TransactionListFilter,
+    TransactionStatus,
 };
 
 // Re-export essential error types
@@ -90,19 +124,34 @@ pub use ads_service::{
 
 pub use background_processor::{BackgroundProcessor, ProcessorBuilder, ProcessorConfig};
 pub use merkle_tree::{
-    AlgorithmInsertionResult, IndexedMerkleTree, InsertionMetrics, InsertionProof, InsertionResult,
-    LowNullifier, MerkleNode, MerkleNodeDb, MerkleProof, MerkleTreeDb, Nullifier, NullifierDb,
-    TreeState, TreeStateDb, TreeStats,
+    lock_for_write, AlgorithmInsertionResult, IndexedMerkleTree, InsertionMetrics, InsertionProof,
+    InsertionResult, LowNullifier, MerkleNode, MerkleNodeDb, MerkleTreeDb, Nullifier, NullifierDb,
+    TreeState, TreeStateDb, TreeStats, IMT_WRITE_LOCK,
+};
+pub use merkle_tree_32::{
+    genesis_root, zero_hashes, BatchUpdate, MerkleProof, MerkleTree32, Tree32Stats, TreeMetrics,
+    GENESIS_LEAF, NODE_CACHE_CAPACITY, NODE_CACHE_MIN_LEVEL, TREE_HEIGHT,
+};
+pub use outbox::{
+    claim_outbox_jobs, complete_outbox_job, get_outbox_jobs, queue_outbox_job,
+    queue_outstanding_outbox_jobs, retry_outbox_job, OutboxJob, OUTBOX_CONTRACT_POST,
+    OUTBOX_PROOF_REQUEST,
+};
+pub use replay::{
+    replay_state, swap_in_replayed_state, LiveStateDiff, ReplayDivergence, ReplayTree, StateReplay,
+};
+pub use snapshot::{
+    export_snapshot, import_snapshot, SnapshotManifest, StateSnapshot, STATE_SNAPSHOT_FORMAT,
+    STATE_SNAPSHOT_VERSION,
 };
-pub use merkle_tree_32::{BatchUpdate, MerkleProof32, MerkleTree32, Tree32Stats, TreeMetrics};
 pub use vapp_integration::{
     ComplianceError, ComplianceResult, Environment, ProofError, ProofType, SettlementError,
     SettlementResult, VAppAdsIntegration, VAppBatchResponse, VAppConfig, VAppError,
     VAppInsertionResponse, VAppProofResponse, ZkProof,
 };
 
-#[cfg(test)]
-mod test_utils;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 
 #[cfg(test)]
 mod tests;
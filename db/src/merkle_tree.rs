@@ -1,10 +1,12 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use sqlx::{FromRow, PgPool};
+use sqlx::{FromRow, PgConnection, PgPool};
+use std::collections::BTreeMap;
 use tracing::{debug, error, info, instrument, warn};
 
 use crate::error::DbError;
+use crate::merkle_tree_32::{genesis_root, MerkleProof, MerkleTree32, GENESIS_LEAF, TREE_HEIGHT};
 
 // ============================================================================
 // CORE DATA STRUCTURES
@@ -66,14 +68,6 @@ pub struct TreeStats {
 // INDEXED MERKLE TREE ALGORITHM - 7-STEP INSERTION
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MerkleProof {
-    pub leaf_index: i64,
-    pub leaf_hash: [u8; 32],
-    pub siblings: Vec<[u8; 32]>, // 32-level tree = 32 siblings max
-    pub path_indices: Vec<bool>, // true = right, false = left
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InsertionProof {
     pub low_nullifier_proof: MerkleProof,
@@ -117,6 +111,15 @@ impl NullifierDb {
     pub async fn find_low_nullifier(
         &self,
         new_value: i64,
+    ) -> Result<Option<LowNullifier>, DbError> {
+        let mut conn = self.pool.acquire().await?;
+        Self::find_low_nullifier_in(&mut conn, new_value).await
+    }
+
+    /// `find_low_nullifier` on `conn`
+    pub async fn find_low_nullifier_in(
+        conn: &mut PgConnection,
+        new_value: i64,
     ) -> Result<Option<LowNullifier>, DbError> {
         debug!("Finding low nullifier for value: {}", new_value);
 
@@ -128,7 +131,7 @@ impl NullifierDb {
             "#,
             new_value
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *conn)
         .await
         .map_err(DbError::Database)?;
 
@@ -149,7 +152,7 @@ impl NullifierDb {
                 // Check if this is an empty tree (first insertion)
                 let nullifier_count =
                     sqlx::query_scalar!("SELECT COUNT(*) FROM nullifiers WHERE is_active = true")
-                        .fetch_one(&self.pool)
+                        .fetch_one(&mut *conn)
                         .await
                         .map_err(DbError::Database)?
                         .unwrap_or(0);
@@ -174,11 +177,17 @@ impl NullifierDb {
 
     #[instrument(skip(self), level = "debug")]
     pub async fn exists(&self, value: i64) -> Result<bool, DbError> {
+        let mut conn = self.pool.acquire().await?;
+        Self::exists_in(&mut conn, value).await
+    }
+
+    /// `exists` on `conn`
+    pub async fn exists_in(conn: &mut PgConnection, value: i64) -> Result<bool, DbError> {
         let count: Option<i64> = sqlx::query_scalar!(
             "SELECT COUNT(*) FROM nullifiers WHERE value = $1 AND is_active = true",
             value
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *conn)
         .await
         .map_err(DbError::Database)?;
 
@@ -195,17 +204,51 @@ impl NullifierDb {
         new_value: i64,
         new_tree_index: i64,
         low_nullifier: &LowNullifier,
+    ) -> Result<Nullifier, DbError> {
+        let mut tx = self.pool.begin().await.map_err(DbError::Database)?;
+        let new_nullifier =
+            Self::insert_with_update_in(&mut tx, new_value, new_tree_index, low_nullifier).await?;
+        tx.commit().await.map_err(DbError::Database)?;
+        Ok(new_nullifier)
+    }
+
+    /// `insert_with_update` on `conn`, inside the caller's transaction
+    pub async fn insert_with_update_in(
+        conn: &mut PgConnection,
+        new_value: i64,
+        new_tree_index: i64,
+        low_nullifier: &LowNullifier,
     ) -> Result<Nullifier, DbError> {
         info!(
             "Inserting nullifier: value={}, tree_index={}, low_nullifier_value={}",
             new_value, new_tree_index, low_nullifier.value
         );
 
-        let mut tx = self.pool.begin().await.map_err(DbError::Database)?;
-
         // Check if this is an empty tree insertion (virtual low nullifier)
         let is_empty_tree = low_nullifier.value == 0 && low_nullifier.tree_index == 0;
 
+        // Journal what this insertion overwrites so the tree can be rolled back
+        sqlx::query(
+            r#"
+            INSERT INTO nullifier_undo_log (tree_index, low_tree_index, low_next_index, low_next_value, low_leaf_hash)
+            SELECT $1, n.tree_index, n.next_index, COALESCE(n.next_value, 0), leaf.hash_value
+            FROM nullifiers n
+            LEFT JOIN merkle_nodes leaf ON leaf.tree_level = 0 AND leaf.node_index = n.tree_index
+            WHERE n.value = $2
+            ON CONFLICT (tree_index) DO UPDATE SET
+                low_tree_index = EXCLUDED.low_tree_index,
+                low_next_index = EXCLUDED.low_next_index,
+                low_next_value = EXCLUDED.low_next_value,
+                low_leaf_hash = EXCLUDED.low_leaf_hash,
+                created_at = NOW()
+            "#,
+        )
+        .bind(new_tree_index)
+        .bind(low_nullifier.value)
+        .execute(&mut *conn)
+        .await
+        .map_err(DbError::Database)?;
+
         if is_empty_tree {
             info!("📝 Skipping low nullifier update for empty tree insertion");
         } else {
@@ -220,7 +263,7 @@ impl NullifierDb {
                 new_value,
                 low_nullifier.value
             )
-            .execute(&mut *tx)
+            .execute(&mut *conn)
             .await
             .map_err(DbError::Database)?;
 
@@ -251,12 +294,10 @@ impl NullifierDb {
             next_value,
             new_tree_index
         )
-        .fetch_one(&mut *tx)
+        .fetch_one(&mut *conn)
         .await
         .map_err(DbError::Database)?;
 
-        tx.commit().await.map_err(DbError::Database)?;
-
         info!(
             "Successfully inserted nullifier with id: {}",
             new_nullifier.id
@@ -342,6 +383,15 @@ impl NullifierDb {
 
     #[instrument(skip(self), level = "debug")]
     pub async fn get_by_value(&self, value: i64) -> Result<Option<Nullifier>, DbError> {
+        let mut conn = self.pool.acquire().await?;
+        Self::get_by_value_in(&mut conn, value).await
+    }
+
+    /// `get_by_value` on `conn`
+    pub async fn get_by_value_in(
+        conn: &mut PgConnection,
+        value: i64,
+    ) -> Result<Option<Nullifier>, DbError> {
         let nullifier = sqlx::query_as!(
             Nullifier,
             r#"
@@ -351,7 +401,7 @@ impl NullifierDb {
             "#,
             value
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *conn)
         .await
         .map_err(DbError::Database)?;
 
@@ -704,10 +754,40 @@ impl MerkleTreeDb {
 // INDEXED MERKLE TREE - 7-STEP INSERTION ALGORITHM IMPLEMENTATION
 // ============================================================================
 
+/// Leaf hash of a nullifier: SHA-256 over its value, next index (0 when it
+/// is the maximum) and next value, all big-endian
+pub(crate) fn leaf_hash(value: i64, next_index: Option<i64>, next_value: i64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(value.to_be_bytes());
+    hasher.update(next_index.unwrap_or(0).to_be_bytes());
+    hasher.update(next_value.to_be_bytes());
+    hasher.finalize().into()
+}
+
+/// Indexed Merkle tree: nullifier linked list in `nullifiers`, leaves and
+/// paths kept by the `MerkleTree32` engine
 #[derive(Clone)]
 pub struct IndexedMerkleTree {
     pub db: MerkleTreeDb,
+    pub merkle: MerkleTree32,
     pub tree_height: usize, // Exactly 32 levels
+    pool: PgPool,
+}
+
+/// Advisory lock key held by every IMT writer until its transaction ends
+pub const IMT_WRITE_LOCK: i64 = 0x494d_545f_5752_4954; // "IMT_WRIT"
+
+/// Take the IMT write lock for the rest of `conn`'s transaction
+///
+/// Serializes IMT writes, and whatever else the caller does in the same
+/// transaction, across connections and processes. Taking it again on the
+/// same connection doesn't block.
+pub async fn lock_for_write(conn: &mut PgConnection) -> Result<(), DbError> {
+    sqlx::query("SELECT pg_advisory_xact_lock($1)")
+        .bind(IMT_WRITE_LOCK)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
 }
 
 impl IndexedMerkleTree {
@@ -715,7 +795,9 @@ impl IndexedMerkleTree {
     pub fn new(pool: PgPool) -> Self {
         Self {
             db: MerkleTreeDb::new(pool.clone()),
-            tree_height: 32, // Fixed at 32 levels per spec
+            merkle: MerkleTree32::new(pool.clone()),
+            tree_height: TREE_HEIGHT,
+            pool,
         }
     }
 
@@ -763,6 +845,11 @@ impl IndexedMerkleTree {
             info!("✅ Nullifier chain integrity verified");
         }
 
+        // Trees from before the shared engine hashed missing siblings as zero bytes
+        if self.merkle.rebuild_stale_nodes().await? {
+            info!("✅ Merkle nodes rebuilt with the current empty-subtree hashes");
+        }
+
         // Verify that the current root matches what we expect
         let current_root = self.get_root().await?;
         if current_root != state.root_hash.as_slice() {
@@ -783,11 +870,30 @@ impl IndexedMerkleTree {
     }
 
     /// Implements the exact 7-step nullifier insertion algorithm from transparency dictionaries paper
+    ///
+    /// Runs in its own database transaction, see `insert_nullifier_in`.
     #[instrument(skip(self), level = "info")]
     pub async fn insert_nullifier(
         &mut self,
         new_nullifier: i64,
     ) -> Result<AlgorithmInsertionResult, DbError> {
+        let mut tx = self.pool.begin().await?;
+        let result = self.insert_nullifier_in(&mut tx, new_nullifier).await?;
+        tx.commit().await?;
+        Ok(result)
+    }
+
+    /// `insert_nullifier` on `conn`, inside the caller's transaction
+    ///
+    /// Takes the IMT write lock first, so insertions from other connections
+    /// and processes wait until the caller's transaction ends.
+    #[instrument(skip(self, conn), level = "info")]
+    pub async fn insert_nullifier_in(
+        &self,
+        conn: &mut PgConnection,
+        new_nullifier: i64,
+    ) -> Result<AlgorithmInsertionResult, DbError> {
+        lock_for_write(conn).await?;
         info!(
             "🚀 Starting 7-step nullifier insertion for value: {}",
             new_nullifier
@@ -801,7 +907,7 @@ impl IndexedMerkleTree {
 
         // Get current root before changes
         metrics.database_rounds += 1;
-        let old_root = self.get_root().await?;
+        let old_root = Self::root_in(conn).await?;
         info!("📊 Old root: {:02x?}", &old_root[..8]);
 
         // STEP 1: Find low_nullifier
@@ -810,10 +916,7 @@ impl IndexedMerkleTree {
             new_nullifier
         );
         metrics.database_rounds += 1;
-        let low_nullifier = self
-            .db
-            .nullifiers
-            .find_low_nullifier(new_nullifier)
+        let low_nullifier = NullifierDb::find_low_nullifier_in(conn, new_nullifier)
             .await?
             .ok_or_else(|| DbError::NotFound("low nullifier".to_string()))?;
 
@@ -829,7 +932,7 @@ impl IndexedMerkleTree {
             info!("📝 Skipping membership check for empty tree insertion");
         } else {
             metrics.database_rounds += 1;
-            if !self.db.nullifiers.exists(low_nullifier.value).await? {
+            if !NullifierDb::exists_in(conn, low_nullifier.value).await? {
                 error!("Low nullifier {} not found in tree", low_nullifier.value);
                 return Err(DbError::NotFound(format!(
                     "Low nullifier {}",
@@ -891,7 +994,11 @@ impl IndexedMerkleTree {
 
         // Get next available tree index
         metrics.database_rounds += 1;
-        let new_tree_index = self.db.state.get_next_index(None).await?;
+        let new_tree_index: i64 =
+            sqlx::query_scalar::<_, Option<i64>>("SELECT get_next_tree_index()")
+                .fetch_one(&mut *conn)
+                .await?
+                .unwrap_or(0);
         info!(
             "📍 Assigned tree index {} for new nullifier",
             new_tree_index
@@ -910,11 +1017,9 @@ impl IndexedMerkleTree {
 
         // Execute the atomic insertion
         metrics.database_rounds += 1;
-        let new_nullifier_entry = self
-            .db
-            .nullifiers
-            .insert_with_update(new_nullifier, new_tree_index, &low_nullifier)
-            .await?;
+        let new_nullifier_entry =
+            NullifierDb::insert_with_update_in(conn, new_nullifier, new_tree_index, &low_nullifier)
+                .await?;
 
         info!(
             "✅ Successfully inserted nullifier with ID: {}",
@@ -924,6 +1029,7 @@ impl IndexedMerkleTree {
         // Update Merkle tree with both changes (hash operations counted here)
         let tree_update_metrics = self
             .update_tree_for_insertion(
+                conn,
                 &low_nullifier_before,
                 &low_nullifier_after,
                 &new_nullifier_entry,
@@ -933,12 +1039,13 @@ impl IndexedMerkleTree {
 
         // Get new root after changes
         metrics.database_rounds += 1;
-        let new_root = self.get_root().await?;
+        let new_root = Self::root_in(conn).await?;
         info!("📊 New root: {:02x?}", &new_root[..8]);
 
         // Generate proofs (additional hash operations)
         let (insertion_proof, proof_metrics) = self
             .generate_insertion_proof(
+                conn,
                 &low_nullifier_before,
                 &low_nullifier_after,
                 &new_nullifier_entry,
@@ -964,11 +1071,7 @@ impl IndexedMerkleTree {
     /// Generate leaf hash from nullifier data (as per algorithm specification)
     #[instrument(skip(self, nullifier), level = "debug")]
     fn hash_nullifier_leaf(&self, nullifier: &Nullifier) -> [u8; 32] {
-        let mut hasher = Sha256::new();
-        hasher.update(nullifier.value.to_be_bytes());
-        hasher.update(nullifier.next_index.unwrap_or(0).to_be_bytes());
-        hasher.update(nullifier.next_value.to_be_bytes());
-        let result: [u8; 32] = hasher.finalize().into();
+        let result = leaf_hash(nullifier.value, nullifier.next_index, nullifier.next_value);
         debug!(
             "Hashed nullifier leaf: value={}, hash={:02x?}",
             nullifier.value,
@@ -980,11 +1083,11 @@ impl IndexedMerkleTree {
     /// Generate leaf hash for LowNullifier state
     #[instrument(skip(self, low_nullifier), level = "debug")]
     fn hash_low_nullifier_leaf(&self, low_nullifier: &LowNullifier) -> [u8; 32] {
-        let mut hasher = Sha256::new();
-        hasher.update(low_nullifier.value.to_be_bytes());
-        hasher.update(low_nullifier.next_index.unwrap_or(0).to_be_bytes());
-        hasher.update(low_nullifier.next_value.to_be_bytes());
-        let result: [u8; 32] = hasher.finalize().into();
+        let result = leaf_hash(
+            low_nullifier.value,
+            low_nullifier.next_index,
+            low_nullifier.next_value,
+        );
         debug!(
             "Hashed low nullifier leaf: value={}, hash={:02x?}",
             low_nullifier.value,
@@ -993,23 +1096,15 @@ impl IndexedMerkleTree {
         result
     }
 
-    /// Hash two child nodes to create parent node
-    #[instrument(skip(self, left, right), level = "debug")]
-    fn hash_internal_node(&self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
-        let mut hasher = Sha256::new();
-        hasher.update(left);
-        hasher.update(right);
-        hasher.finalize().into()
-    }
-
     /// Efficiently update tree for both low_nullifier and new_nullifier
     /// Returns the number of hash operations performed
     #[instrument(
-        skip(self, low_nullifier_before, low_nullifier_after, new_nullifier),
+        skip(self, conn, low_nullifier_before, low_nullifier_after, new_nullifier),
         level = "debug"
     )]
     async fn update_tree_for_insertion(
         &self,
+        conn: &mut PgConnection,
         low_nullifier_before: &LowNullifier,
         low_nullifier_after: &LowNullifier,
         new_nullifier: &Nullifier,
@@ -1022,7 +1117,8 @@ impl IndexedMerkleTree {
         hash_count += 1;
         let updated_low_hash = self.hash_low_nullifier_leaf(low_nullifier_after);
 
-        self.update_leaf(low_nullifier_before.tree_index as usize, updated_low_hash)
+        self.merkle
+            .update_leaf_in(conn, low_nullifier_before.tree_index, updated_low_hash)
             .await?;
         debug!(
             "Updated low nullifier leaf at index {}",
@@ -1033,8 +1129,11 @@ impl IndexedMerkleTree {
         hash_count += 1;
         let new_nullifier_hash = self.hash_nullifier_leaf(new_nullifier);
 
-        self.update_leaf(new_nullifier.tree_index as usize, new_nullifier_hash)
+        let root = self
+            .merkle
+            .update_leaf_in(conn, new_nullifier.tree_index, new_nullifier_hash)
             .await?;
+        MerkleTree32::set_root_in(conn, &root).await?;
         debug!(
             "Inserted new nullifier leaf at index {}",
             new_nullifier.tree_index
@@ -1051,71 +1150,160 @@ impl IndexedMerkleTree {
         Ok(hash_count)
     }
 
-    /// Update a specific leaf and propagate changes to root
-    #[instrument(skip(self, leaf_hash), level = "debug")]
-    async fn update_leaf(&self, leaf_index: usize, leaf_hash: [u8; 32]) -> Result<(), DbError> {
-        debug!(
-            "Updating leaf at index {} with hash {:02x?}",
-            leaf_index,
-            &leaf_hash[..8]
-        );
+    /// Remove every nullifier at tree index `tree_size` or above and restore
+    /// the tree to the root it had when it held `tree_size` leaves
+    ///
+    /// Runs on `conn` so the caller can roll it back together with its own
+    /// changes. Leaves are only ever appended, so the removed nullifiers are the
+    /// most recent insertions; `nullifier_undo_log` is replayed newest first to
+    /// give their low nullifiers back the pointers and leaf hashes they had.
+    ///
+    /// # Errors
+    /// Returns `DbError::InvalidState` when `tree_size` would remove the
+    /// genesis nullifier or a removed insertion was never journaled,
+    /// `DbError::Database` if a query fails
+    #[instrument(skip(self, conn), level = "info")]
+    pub async fn rollback_to_size(
+        &self,
+        conn: &mut PgConnection,
+        tree_size: i64,
+    ) -> Result<[u8; 32], DbError> {
+        use sqlx::Row;
 
-        // Store leaf node (level 0)
-        self.db
-            .nodes
-            .upsert_node(0, leaf_index as i64, &leaf_hash)
+        if tree_size < 1 {
+            return Err(DbError::InvalidState(
+                "Cannot roll back past the genesis nullifier".into(),
+            ));
+        }
+        lock_for_write(conn).await?;
+        info!("⏪ Rolling IMT back to {} leaves", tree_size);
+
+        let undo = sqlx::query(
+            r"
+            SELECT tree_index, low_tree_index, low_next_index, low_next_value, low_leaf_hash
+            FROM nullifier_undo_log
+            WHERE tree_index >= $1
+            ORDER BY tree_index DESC
+            ",
+        )
+        .bind(tree_size)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let removed: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM nullifiers WHERE tree_index >= $1")
+                .bind(tree_size)
+                .fetch_one(&mut *conn)
+                .await?;
+        if removed != undo.len() as i64 {
+            return Err(DbError::InvalidState(format!(
+                "{} nullifiers above leaf {} but only {} journaled insertions",
+                removed,
+                tree_size,
+                undo.len()
+            )));
+        }
+
+        // Newest first, so each low nullifier ends with its oldest overwritten state
+        let mut restored: BTreeMap<i64, [u8; 32]> = BTreeMap::new();
+        for row in &undo {
+            let low_tree_index: i64 = row.try_get("low_tree_index")?;
+            if low_tree_index >= tree_size {
+                continue;
+            }
+            sqlx::query(
+                "UPDATE nullifiers SET next_index = $1, next_value = $2 WHERE tree_index = $3",
+            )
+            .bind(row.try_get::<Option<i64>, _>("low_next_index")?)
+            .bind(row.try_get::<i64, _>("low_next_value")?)
+            .bind(low_tree_index)
+            .execute(&mut *conn)
             .await?;
 
-        // Propagate changes up the tree
-        let mut current_hash = leaf_hash;
-        let mut current_index = leaf_index;
-
-        for level in 1..=self.tree_height {
-            let parent_index = current_index / 2;
-            let is_right_child = current_index % 2 == 1;
-            let sibling_index = if is_right_child {
-                current_index - 1
-            } else {
-                current_index + 1
-            };
-
-            // Get sibling hash (or use zero hash if sibling doesn't exist)
-            let sibling_hash = match self
-                .db
-                .nodes
-                .get_node(level as i32 - 1, sibling_index as i64)
-                .await?
-            {
-                Some(node) => {
-                    let mut hash = [0u8; 32];
-                    hash.copy_from_slice(&node.hash_value);
-                    hash
-                }
-                None => [0u8; 32], // Zero hash for missing siblings
-            };
-
-            // Calculate parent hash
-            let parent_hash = if is_right_child {
-                self.hash_internal_node(&sibling_hash, &current_hash)
-            } else {
-                self.hash_internal_node(&current_hash, &sibling_hash)
-            };
-
-            // Store parent node
-            self.db
-                .nodes
-                .upsert_node(level as i32, parent_index as i64, &parent_hash)
+            let mut leaf_hash = [0u8; 32];
+            if let Some(hash) = row.try_get::<Option<Vec<u8>>, _>("low_leaf_hash")? {
+                leaf_hash.copy_from_slice(&hash);
+            }
+            restored.insert(low_tree_index, leaf_hash);
+        }
+
+        sqlx::query("DELETE FROM nullifiers WHERE tree_index >= $1")
+            .bind(tree_size)
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query("DELETE FROM nullifier_undo_log WHERE tree_index >= $1")
+            .bind(tree_size)
+            .execute(&mut *conn)
+            .await?;
+
+        // Drop every node that only covers removed leaves; missing nodes are empty subtrees
+        sqlx::query("DELETE FROM merkle_nodes WHERE (node_index << tree_level) >= $1")
+            .bind(tree_size)
+            .execute(&mut *conn)
+            .await?;
+
+        let root = if tree_size == 1 {
+            // Back to the genesis state written by migrations 012 and 018
+            sqlx::query("DELETE FROM merkle_nodes WHERE tree_level > 0")
+                .execute(&mut *conn)
                 .await?;
+            sqlx::query(
+                r"
+                INSERT INTO merkle_nodes (tree_level, node_index, hash_value)
+                VALUES (0, 0, $1)
+                ON CONFLICT (tree_level, node_index)
+                DO UPDATE SET hash_value = EXCLUDED.hash_value, updated_at = NOW()
+                ",
+            )
+            .bind(GENESIS_LEAF.as_slice())
+            .execute(&mut *conn)
+            .await?;
+            genesis_root()
+        } else {
+            for (leaf_index, leaf_hash) in &restored {
+                self.merkle
+                    .update_leaf_in(conn, *leaf_index, *leaf_hash)
+                    .await?;
+            }
 
-            current_hash = parent_hash;
-            current_index = parent_index;
-        }
+            // The last remaining leaf lost its right-hand neighbours
+            let last_leaf: Vec<u8> = sqlx::query_scalar(
+                "SELECT hash_value FROM merkle_nodes WHERE tree_level = 0 AND node_index = $1",
+            )
+            .bind(tree_size - 1)
+            .fetch_optional(&mut *conn)
+            .await?
+            .ok_or_else(|| DbError::NotFound(format!("leaf {}", tree_size - 1)))?;
+            let mut leaf_hash = [0u8; 32];
+            leaf_hash.copy_from_slice(&last_leaf);
+            self.merkle
+                .update_leaf_in(conn, tree_size - 1, leaf_hash)
+                .await?
+        };
 
-        // Update root in tree state
-        self.db.state.update_root(&current_hash, None).await?;
-        debug!("Propagated changes to root: {:02x?}", &current_hash[..8]);
+        sqlx::query(
+            r"
+            UPDATE tree_state
+            SET root_hash = $1,
+                next_available_index = $2,
+                total_nullifiers = (SELECT COUNT(*) FROM nullifiers WHERE is_active = true),
+                updated_at = NOW()
+            WHERE tree_id = 'default'
+            ",
+        )
+        .bind(root.as_slice())
+        .bind(tree_size)
+        .execute(&mut *conn)
+        .await?;
+        self.merkle.invalidate_cache();
 
-        Ok(())
+        info!(
+            "✅ IMT rolled back: {} nullifiers removed, {} leaves restored, root {:02x?}",
+            removed,
+            restored.len(),
+            &root[..8]
+        );
+        Ok(root)
     }
 
     /// Generate Merkle proof for a specific leaf
@@ -1127,60 +1315,34 @@ impl IndexedMerkleTree {
     pub async fn generate_merkle_proof(&self, leaf_index: i64) -> Result<MerkleProof, DbError> {
         debug!("Generating Merkle proof for leaf index {}", leaf_index);
 
-        let leaf_node = self
-            .db
-            .nodes
-            .get_node(0, leaf_index)
+        // Unlike the engine, only leaves that hold a nullifier are proven
+        self.merkle
+            .generate_stored_proof(leaf_index)
             .await?
-            .ok_or_else(|| DbError::NotFound(format!("leaf at index {}", leaf_index)))?;
-
-        let mut leaf_hash = [0u8; 32];
-        leaf_hash.copy_from_slice(&leaf_node.hash_value);
-
-        let mut siblings = Vec::with_capacity(self.tree_height);
-        let mut path_indices = Vec::with_capacity(self.tree_height);
-        let mut current_index = leaf_index;
-
-        for level in 0..self.tree_height {
-            let is_right_child = current_index % 2 == 1;
-            let sibling_index = if is_right_child {
-                current_index - 1
-            } else {
-                current_index + 1
-            };
-
-            // Get sibling hash
-            let sibling_hash = match self.db.nodes.get_node(level as i32, sibling_index).await? {
-                Some(node) => {
-                    let mut hash = [0u8; 32];
-                    hash.copy_from_slice(&node.hash_value);
-                    hash
-                }
-                None => [0u8; 32], // Zero hash for missing siblings
-            };
-
-            siblings.push(sibling_hash);
-            path_indices.push(is_right_child);
-
-            current_index = current_index / 2;
-        }
+            .ok_or_else(|| DbError::NotFound(format!("leaf at index {}", leaf_index)))
+    }
 
-        Ok(MerkleProof {
-            leaf_index,
-            leaf_hash,
-            siblings,
-            path_indices,
-        })
+    /// `generate_merkle_proof` on `conn`, seeing the caller's uncommitted writes
+    pub async fn generate_merkle_proof_in(
+        &self,
+        conn: &mut PgConnection,
+        leaf_index: i64,
+    ) -> Result<MerkleProof, DbError> {
+        self.merkle
+            .generate_stored_proof_in(conn, leaf_index)
+            .await?
+            .ok_or_else(|| DbError::NotFound(format!("leaf at index {}", leaf_index)))
     }
 
     /// Generate complete insertion proof with both low_nullifier and new_nullifier proofs
     /// Returns (proof, hash_operations_count)
     #[instrument(
-        skip(self, low_nullifier_before, low_nullifier_after, new_nullifier),
+        skip(self, conn, low_nullifier_before, low_nullifier_after, new_nullifier),
         level = "debug"
     )]
     async fn generate_insertion_proof(
         &self,
+        conn: &mut PgConnection,
         low_nullifier_before: &LowNullifier,
         low_nullifier_after: &LowNullifier,
         new_nullifier: &Nullifier,
@@ -1189,9 +1351,11 @@ impl IndexedMerkleTree {
 
         // Generate proofs (each proof generation involves tree_height hash operations for verification)
         let low_nullifier_proof = self
-            .generate_merkle_proof(low_nullifier_before.tree_index)
+            .generate_merkle_proof_in(conn, low_nullifier_before.tree_index)
+            .await?;
+        let new_nullifier_proof = self
+            .generate_merkle_proof_in(conn, new_nullifier.tree_index)
             .await?;
-        let new_nullifier_proof = self.generate_merkle_proof(new_nullifier.tree_index).await?;
 
         // Hash operations for proof generation: 2 * tree_height = 2 * 32 = 64 operations
         // But these are typically done by the verifier, not counted in our constraint budget
@@ -1208,6 +1372,18 @@ impl IndexedMerkleTree {
         Ok((insertion_proof, proof_hash_operations))
     }
 
+    /// Root recorded in `tree_state`, read on `conn`
+    async fn root_in(conn: &mut PgConnection) -> Result<[u8; 32], DbError> {
+        let root: Vec<u8> =
+            sqlx::query_scalar("SELECT root_hash FROM tree_state WHERE tree_id = 'default'")
+                .fetch_optional(&mut *conn)
+                .await?
+                .ok_or_else(|| DbError::NotFound("tree state".to_string()))?;
+        root.as_slice()
+            .try_into()
+            .map_err(|_| DbError::InvalidHashLength(root.len()))
+    }
+
     /// Get current root hash from tree state
     #[instrument(skip(self), level = "debug")]
     pub async fn get_root(&self) -> Result<[u8; 32], DbError> {
@@ -1241,13 +1417,13 @@ impl IndexedMerkleTree {
     #[instrument(skip(self, proof), level = "debug")]
     pub fn verify_insertion_proof(&self, proof: &InsertionProof, root: &[u8; 32]) -> bool {
         // Verify low nullifier proof
-        if !self.verify_merkle_proof(&proof.low_nullifier_proof, root) {
+        if !proof.low_nullifier_proof.verify(root) {
             warn!("Low nullifier proof verification failed");
             return false;
         }
 
         // Verify new nullifier proof
-        if !self.verify_merkle_proof(&proof.new_nullifier_proof, root) {
+        if !proof.new_nullifier_proof.verify(root) {
             warn!("New nullifier proof verification failed");
             return false;
         }
@@ -1262,22 +1438,6 @@ impl IndexedMerkleTree {
         true
     }
 
-    /// Verify a single Merkle proof
-    #[instrument(skip(self, proof, root), level = "debug")]
-    fn verify_merkle_proof(&self, proof: &MerkleProof, root: &[u8; 32]) -> bool {
-        let mut current_hash = proof.leaf_hash;
-
-        for (sibling, is_right) in proof.siblings.iter().zip(proof.path_indices.iter()) {
-            current_hash = if *is_right {
-                self.hash_internal_node(sibling, &current_hash)
-            } else {
-                self.hash_internal_node(&current_hash, sibling)
-            };
-        }
-
-        current_hash == *root
-    }
-
     /// Get tree statistics
     #[instrument(skip(self), level = "debug")]
     pub async fn get_stats(&self) -> Result<TreeStats, DbError> {
@@ -1,12 +1,80 @@
+//! Merkle tree engine behind the IMT
+//!
+//! `MerkleTree32` owns `merkle_nodes` and the root in `tree_state`: leaf
+//! updates, batch updates, paths and proofs. `IndexedMerkleTree` layers the
+//! low-nullifier logic on top of it and `ReplayTree` mirrors it in memory.
+//!
+//! A node that was never written is the root of an empty subtree and hashes
+//! to `zero_hashes()[level]`, starting from SHA-256 of an all-zero leaf, so
+//! any two trees with the same written leaves have the same root.
+//!
+//! A path is read in one query for all of its `(level, index)` pairs. Nodes
+//! at `NODE_CACHE_MIN_LEVEL` and above are shared by most paths, so proofs
+//! keep them in a bounded in-process cache. The cache is tagged with
+//! `tree_state.updated_at`, which every writer of `merkle_nodes` bumps in the
+//! same transaction; a read that sees another tag drops the cache, and this
+//! engine's own writes drop it right away.
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use sqlx::{PgPool, Postgres, Transaction};
+use sqlx::{PgConnection, PgPool, Postgres, Row, Transaction};
 use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
 use tracing::{debug, info, instrument, warn};
 
 use crate::error::DbError;
 
+/// Number of levels above the leaves
+pub const TREE_HEIGHT: usize = 32;
+
+/// Lowest level whose nodes are kept in the node cache
+pub const NODE_CACHE_MIN_LEVEL: usize = 16;
+
+/// Most nodes the node cache holds before it starts over
+pub const NODE_CACHE_CAPACITY: usize = 4096;
+
+static ZERO_HASHES: LazyLock<[[u8; 32]; TREE_HEIGHT + 1]> = LazyLock::new(|| {
+    let mut zero_hashes = [[0u8; 32]; TREE_HEIGHT + 1];
+    zero_hashes[0] = Sha256::digest([0u8; 32]).into();
+    for level in 1..=TREE_HEIGHT {
+        zero_hashes[level] = node_hash(&zero_hashes[level - 1], &zero_hashes[level - 1]);
+    }
+    zero_hashes
+});
+
+/// Hash of an empty subtree at each level, from an empty leaf (level 0) to
+/// an empty tree's root (level 32)
+pub fn zero_hashes() -> &'static [[u8; 32]; TREE_HEIGHT + 1] {
+    &ZERO_HASHES
+}
+
+/// Leaf hash of the genesis nullifier at index 0 before the first insertion
+pub const GENESIS_LEAF: [u8; 32] = [0u8; 32];
+
+static GENESIS_ROOT: LazyLock<[u8; 32]> = LazyLock::new(|| {
+    let mut root = GENESIS_LEAF;
+    for zero_hash in &ZERO_HASHES[..TREE_HEIGHT] {
+        root = node_hash(&root, zero_hash);
+    }
+    root
+});
+
+/// Root of the genesis tree: `GENESIS_LEAF` at index 0, every other subtree
+/// empty. Migrations 012 and 018, `ReplayTree::genesis` and a rollback to
+/// the genesis nullifier all start from it.
+pub fn genesis_root() -> [u8; 32] {
+    *GENESIS_ROOT
+}
+
+/// Internal node hash: SHA-256 over the left and right child
+pub fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
 // ============================================================================
 // 32-LEVEL MERKLE TREE OPTIMIZED FOR ZK CONSTRAINTS
 // ============================================================================
@@ -14,19 +82,31 @@ use crate::error::DbError;
 /// Optimized Merkle tree with exactly 32 levels for ZK circuit efficiency
 /// Capacity: 2^32 = ~4.3 billion entries
 /// Constraint reduction: 96 hashes vs 768 for traditional 256-level trees
+///
+/// Clones share one node cache.
 #[derive(Clone)]
 pub struct MerkleTree32 {
     pool: PgPool,
-    height: usize,              // Always 32
-    zero_hashes: Vec<[u8; 32]>, // Precomputed zero hashes for each level
+    cache: Arc<Mutex<NodeCache>>,
 }
 
-/// Merkle proof for 32-level tree
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MerkleProof32 {
-    pub leaf_index: usize,
-    pub proof_hashes: Vec<[u8; 32]>, // Length = 32
+/// Upper-level nodes read at one `tree_state.updated_at`
+#[derive(Default)]
+struct NodeCache {
+    tag: Option<DateTime<Utc>>,
+    nodes: HashMap<(usize, i64), [u8; 32]>,
+}
+
+/// Stored hash per `(level, index)`, `None` where the node was never written
+type NodeReads = HashMap<(usize, i64), Option<[u8; 32]>>;
+
+/// Merkle path from a leaf to the root, the single proof type of the tree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    pub leaf_index: i64,
     pub leaf_hash: [u8; 32],
+    pub siblings: Vec<[u8; 32]>, // 32-level tree = 32 siblings
+    pub path_indices: Vec<bool>, // true = right, false = left
 }
 
 /// Batch update operation for performance optimization
@@ -56,73 +136,61 @@ pub struct Tree32Stats {
     pub last_updated: DateTime<Utc>,
 }
 
+const UPSERT_NODE: &str = r"
+    INSERT INTO merkle_nodes (tree_level, node_index, hash_value)
+    VALUES ($1, $2, $3)
+    ON CONFLICT (tree_level, node_index)
+    DO UPDATE SET hash_value = EXCLUDED.hash_value, updated_at = NOW()
+    ";
+
+const UPSERT_NODES: &str = r"
+    INSERT INTO merkle_nodes (tree_level, node_index, hash_value)
+    SELECT * FROM UNNEST($1::INTEGER[], $2::BIGINT[], $3::BYTEA[])
+    ON CONFLICT (tree_level, node_index)
+    DO UPDATE SET hash_value = EXCLUDED.hash_value, updated_at = NOW()
+    ";
+
+const READ_NODES: &str = r"
+    SELECT p.tree_level, p.node_index, n.hash_value,
+           (SELECT updated_at FROM tree_state WHERE tree_id = 'default') AS tag
+    FROM UNNEST($1::INTEGER[], $2::BIGINT[]) AS p(tree_level, node_index)
+    LEFT JOIN merkle_nodes n
+        ON n.tree_level = p.tree_level AND n.node_index = p.node_index
+    ";
+
 impl MerkleTree32 {
-    /// Create new 32-level Merkle tree with precomputed zero hashes
-    #[instrument(skip(pool), level = "info")]
+    /// Create the engine over `merkle_nodes` and `tree_state`
     pub fn new(pool: PgPool) -> Self {
-        info!("🌳 Initializing 32-level Merkle tree");
-        let tree_height = 32;
-        let zero_hashes = Self::compute_zero_hashes(tree_height);
-
-        debug!(
-            "Precomputed {} zero hashes for tree optimization",
-            zero_hashes.len()
-        );
-        debug!(
-            "Tree capacity: 2^{} = {} leaves",
-            tree_height,
-            (1u64 << tree_height)
-        );
-
         Self {
             pool,
-            height: tree_height,
-            zero_hashes,
+            cache: Arc::default(),
         }
     }
 
-    /// Get the tree height (always 32)
-    pub fn height(&self) -> usize {
-        self.height
+    /// Drop every cached node
+    pub fn invalidate_cache(&self) {
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.tag = None;
+        cache.nodes.clear();
     }
 
-    /// Get a reference to the precomputed zero hashes
-    pub fn zero_hashes(&self) -> &Vec<[u8; 32]> {
-        &self.zero_hashes
+    /// Number of nodes in the node cache
+    pub fn cached_nodes(&self) -> usize {
+        self.cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .nodes
+            .len()
     }
 
-    /// Precompute zero hashes for empty subtrees at each level
-    /// This eliminates database lookups for empty nodes, significantly improving performance
-    #[instrument(level = "debug")]
-    fn compute_zero_hashes(tree_height: usize) -> Vec<[u8; 32]> {
-        info!("🔢 Computing zero hashes for {} levels", tree_height);
-        let mut zero_hashes = Vec::with_capacity(tree_height + 1);
-
-        // Level 0: hash of empty leaf (zero bytes)
-        let mut hasher = Sha256::new();
-        hasher.update(&[0u8; 32]); // Empty leaf value
-        let level0_hash: [u8; 32] = hasher.finalize().into();
-        zero_hashes.push(level0_hash);
-
-        debug!("Level 0 zero hash: {:02x?}", &level0_hash[..8]);
-
-        // Each level: hash of two zero hashes from level below
-        for level in 1..=tree_height {
-            let mut hasher = Sha256::new();
-            hasher.update(&zero_hashes[level - 1]);
-            hasher.update(&zero_hashes[level - 1]);
-            let current_level_hash: [u8; 32] = hasher.finalize().into();
-            zero_hashes.push(current_level_hash);
-
-            debug!(
-                "Level {} zero hash: {:02x?}",
-                level,
-                &current_level_hash[..8]
-            );
-        }
+    /// Get the tree height (always 32)
+    pub fn height(&self) -> usize {
+        TREE_HEIGHT
+    }
 
-        info!("✅ Zero hash precomputation complete");
-        zero_hashes
+    /// Get the precomputed zero hashes, see `zero_hashes()`
+    pub fn zero_hashes(&self) -> &'static [[u8; 32]] {
+        zero_hashes()
     }
 
     /// Initialize tree state in database if not exists
@@ -130,8 +198,8 @@ impl MerkleTree32 {
     pub async fn initialize(&self) -> Result<(), DbError> {
         info!("🚀 Initializing tree state in database");
 
-        // Insert default tree state with root being the zero hash for level 32
-        let root_hash = self.zero_hashes[self.height];
+        // Insert default tree state at the genesis root
+        let root_hash = genesis_root();
 
         sqlx::query!(
             r#"
@@ -167,17 +235,18 @@ impl MerkleTree32 {
         );
 
         // Validate leaf index is within bounds
-        if leaf_index >= (1 << self.height) {
+        if leaf_index >= (1 << TREE_HEIGHT) {
             return Err(DbError::InvalidTreeParameter(format!(
                 "Leaf index {} exceeds tree capacity 2^{}",
-                leaf_index, self.height
+                leaf_index, TREE_HEIGHT
             )));
         }
 
         let mut tx = self.pool.begin().await.map_err(DbError::Database)?;
         let root_hash = self
-            .update_leaf_internal(leaf_index, new_leaf_value, &mut tx)
+            .update_leaf_in(&mut tx, leaf_index as i64, new_leaf_value)
             .await?;
+        Self::set_root_in(&mut tx, &root_hash).await?;
         tx.commit().await.map_err(DbError::Database)?;
 
         info!(
@@ -187,222 +256,246 @@ impl MerkleTree32 {
         Ok(root_hash)
     }
 
-    /// Internal leaf update implementation with transaction
-    #[instrument(skip(self, new_leaf_value, tx), level = "debug")]
-    async fn update_leaf_internal(
+    /// Write a leaf and the nodes on its path on `conn`; returns the new root
+    ///
+    /// Reads the siblings in one query and writes the 33 path nodes in
+    /// another. Leaves `tree_state` alone so the caller can combine several
+    /// updates before recording the root.
+    #[instrument(skip(self, conn, leaf_hash), level = "debug")]
+    pub async fn update_leaf_in(
         &self,
-        leaf_index: usize,
-        new_leaf_value: [u8; 32],
-        tx: &mut Transaction<'_, Postgres>,
+        conn: &mut PgConnection,
+        leaf_index: i64,
+        leaf_hash: [u8; 32],
     ) -> Result<[u8; 32], DbError> {
-        let mut hash_operations = 0u32;
-        let mut current_hash = new_leaf_value;
-        let mut current_index = leaf_index;
-
-        // Update leaf node (level 0)
-        sqlx::query!(
-            r#"
-            INSERT INTO merkle_nodes (tree_level, node_index, hash_value)
-            VALUES (0, $1, $2)
-            ON CONFLICT (tree_level, node_index)
-            DO UPDATE SET hash_value = $2, updated_at = NOW()
-            "#,
-            current_index as i64,
-            current_hash.as_slice()
-        )
-        .execute(&mut **tx)
-        .await
-        .map_err(DbError::Database)?;
-
-        debug!("Stored leaf node at index {} level 0", current_index);
+        let positions = sibling_positions(leaf_index);
+        let (stored, _) = Self::read_nodes_in(conn, &positions).await?;
 
-        // Propagate changes up the tree
-        for level in 1..=self.height {
-            let is_right_child = (current_index % 2) == 1;
-            let sibling_index = if is_right_child {
-                current_index - 1
-            } else {
-                current_index + 1
-            };
-
-            // Get sibling hash (or use zero hash if doesn't exist)
-            let sibling_hash = self
-                .get_node_hash(level - 1, sibling_index, tx)
-                .await?
-                .unwrap_or(self.zero_hashes[level - 1]);
+        let mut levels = Vec::with_capacity(TREE_HEIGHT + 1);
+        let mut indices = Vec::with_capacity(TREE_HEIGHT + 1);
+        let mut hashes = Vec::with_capacity(TREE_HEIGHT + 1);
+        levels.push(0i32);
+        indices.push(leaf_index);
+        hashes.push(leaf_hash.to_vec());
 
-            // Compute parent hash (hash operation counted)
-            hash_operations += 1;
-            current_hash = if is_right_child {
-                self.hash_pair(&sibling_hash, &current_hash)
+        let mut current_hash = leaf_hash;
+        let mut current_index = leaf_index;
+        for (level, position) in positions.iter().enumerate() {
+            let sibling_hash = stored[position].unwrap_or(zero_hashes()[level]);
+            current_hash = if current_index % 2 == 1 {
+                node_hash(&sibling_hash, &current_hash)
             } else {
-                self.hash_pair(&current_hash, &sibling_hash)
+                node_hash(&current_hash, &sibling_hash)
             };
+            current_index /= 2;
 
-            // Update parent index
-            current_index = current_index / 2;
-
-            // Store parent node
-            sqlx::query!(
-                r#"
-                INSERT INTO merkle_nodes (tree_level, node_index, hash_value)
-                VALUES ($1, $2, $3)
-                ON CONFLICT (tree_level, node_index)
-                DO UPDATE SET hash_value = $3, updated_at = NOW()
-                "#,
-                level as i32,
-                current_index as i64,
-                current_hash.as_slice()
-            )
-            .execute(&mut **tx)
-            .await
-            .map_err(DbError::Database)?;
-
-            debug!(
-                "Updated level {} node {} with hash {:02x?}",
-                level,
-                current_index,
-                &current_hash[..8]
-            );
+            levels.push(level as i32 + 1);
+            indices.push(current_index);
+            hashes.push(current_hash.to_vec());
         }
 
-        // Update tree state with new root
-        sqlx::query!(
-            r#"
-            UPDATE tree_state
-            SET root_hash = $1, updated_at = NOW()
-            WHERE tree_id = 'default'
-            "#,
-            current_hash.as_slice()
-        )
-        .execute(&mut **tx)
-        .await
-        .map_err(DbError::Database)?;
+        sqlx::query(UPSERT_NODES)
+            .bind(levels)
+            .bind(indices)
+            .bind(hashes)
+            .execute(&mut *conn)
+            .await?;
+        self.invalidate_cache();
 
         debug!(
-            "Tree propagation complete: {} hash operations",
-            hash_operations
+            "Propagated leaf {} to root {:02x?}",
+            leaf_index,
+            &current_hash[..8]
         );
         Ok(current_hash)
     }
 
-    /// Get node hash from database or return None if doesn't exist
-    #[instrument(skip(self, tx), level = "debug")]
-    async fn get_node_hash(
-        &self,
-        level: usize,
-        index: usize,
-        tx: &mut Transaction<'_, Postgres>,
-    ) -> Result<Option<[u8; 32]>, DbError> {
-        let result: Option<Vec<u8>> = sqlx::query_scalar!(
-            "SELECT hash_value FROM merkle_nodes WHERE tree_level = $1 AND node_index = $2",
-            level as i32,
-            index as i64
+    /// Record `root` in `tree_state` on `conn`
+    pub(crate) async fn set_root_in(
+        conn: &mut PgConnection,
+        root: &[u8; 32],
+    ) -> Result<(), DbError> {
+        sqlx::query(
+            "UPDATE tree_state SET root_hash = $1, updated_at = NOW() WHERE tree_id = 'default'",
         )
-        .fetch_optional(&mut **tx)
-        .await
-        .map_err(DbError::Database)?;
-
-        Ok(result.map(|v| {
-            let mut arr = [0u8; 32];
-            if v.len() >= 32 {
-                arr.copy_from_slice(&v[..32]);
-            }
-            arr
-        }))
+        .bind(root.as_slice())
+        .execute(&mut *conn)
+        .await?;
+        Ok(())
     }
 
-    /// Hash two 32-byte values using SHA-256
-    #[instrument(skip(self, left, right), level = "debug")]
-    fn hash_pair(&self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
-        let mut hasher = Sha256::new();
-        hasher.update(left);
-        hasher.update(right);
-        hasher.finalize().into()
+    /// Node hash on `conn`, the zero hash of its level if it was never written
+    async fn node_in(
+        conn: &mut PgConnection,
+        level: usize,
+        index: i64,
+    ) -> Result<[u8; 32], DbError> {
+        let stored: Option<Vec<u8>> = sqlx::query_scalar(
+            "SELECT hash_value FROM merkle_nodes WHERE tree_level = $1 AND node_index = $2",
+        )
+        .bind(level as i32)
+        .bind(index)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        match stored {
+            Some(hash) => hash
+                .as_slice()
+                .try_into()
+                .map_err(|_| DbError::InvalidHashLength(hash.len())),
+            None => Ok(zero_hashes()[level]),
+        }
     }
 
-    /// Generate Merkle proof for a leaf (32 sibling hashes)
-    #[instrument(skip(self), level = "info")]
-    pub async fn generate_proof(&self, leaf_index: usize) -> Result<MerkleProof32, DbError> {
-        info!("🔐 Generating proof for leaf {}", leaf_index);
+    /// Stored hashes at `positions` in one query on `conn`, with the
+    /// `tree_state.updated_at` they were read at
+    async fn read_nodes_in(
+        conn: &mut PgConnection,
+        positions: &[(usize, i64)],
+    ) -> Result<(NodeReads, Option<DateTime<Utc>>), DbError> {
+        let (levels, indices): (Vec<i32>, Vec<i64>) = positions
+            .iter()
+            .map(|&(level, index)| (level as i32, index))
+            .unzip();
+
+        let rows = sqlx::query(READ_NODES)
+            .bind(levels)
+            .bind(indices)
+            .fetch_all(&mut *conn)
+            .await?;
 
-        if leaf_index >= (1 << self.height) {
-            return Err(DbError::InvalidTreeParameter(format!(
-                "Leaf index {} exceeds tree capacity",
-                leaf_index
-            )));
+        let mut tag: Option<DateTime<Utc>> = None;
+        let mut nodes = HashMap::with_capacity(rows.len());
+        for row in rows {
+            tag = row.try_get("tag")?;
+            let level: i32 = row.try_get("tree_level")?;
+            let index: i64 = row.try_get("node_index")?;
+            let hash = row
+                .try_get::<Option<Vec<u8>>, _>("hash_value")?
+                .map(|hash| {
+                    <[u8; 32]>::try_from(hash.as_slice())
+                        .map_err(|_| DbError::InvalidHashLength(hash.len()))
+                })
+                .transpose()?;
+            nodes.insert((level as usize, index), hash);
         }
+        Ok((nodes, tag))
+    }
 
-        let mut proof_hashes = Vec::with_capacity(self.height);
-        let mut current_index = leaf_index;
-
-        // Get the leaf hash first
-        let leaf_hash = self
-            .get_node_hash_public(0, leaf_index)
-            .await?
-            .unwrap_or(self.zero_hashes[0]);
-
-        // Collect sibling hashes from leaf to root
-        for level in 0..self.height {
-            let is_right_child = (current_index % 2) == 1;
-            let sibling_index = if is_right_child {
-                current_index - 1
-            } else {
-                current_index + 1
-            };
+    /// Stored leaf hash and the 32 siblings of `leaf_index`, leaf to root
+    ///
+    /// One query for the nodes the cache does not hold. If the tree changed
+    /// since the cached nodes were read, the whole path is read again.
+    async fn read_path(
+        &self,
+        leaf_index: i64,
+    ) -> Result<(Option<[u8; 32]>, Vec<[u8; 32]>), DbError> {
+        let leaf = (0, leaf_index);
+        let siblings = sibling_positions(leaf_index);
+
+        let (cached, cached_tag) = {
+            let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+            let cached: HashMap<_, _> = siblings[NODE_CACHE_MIN_LEVEL..]
+                .iter()
+                .filter_map(|position| cache.nodes.get(position).map(|hash| (*position, *hash)))
+                .collect();
+            (cached, cache.tag)
+        };
+        let uncached: Vec<_> = std::iter::once(leaf)
+            .chain(siblings.iter().copied())
+            .filter(|position| !cached.contains_key(position))
+            .collect();
+
+        let mut conn = self.pool.acquire().await?;
+        let (mut stored, mut tag) = Self::read_nodes_in(&mut conn, &uncached).await?;
+        if cached.is_empty() || tag == cached_tag {
+            stored.extend(
+                cached
+                    .into_iter()
+                    .map(|(position, hash)| (position, Some(hash))),
+            );
+        } else {
+            debug!("Tree changed since nodes were cached, reading full path");
+            let all: Vec<_> = std::iter::once(leaf)
+                .chain(siblings.iter().copied())
+                .collect();
+            (stored, tag) = Self::read_nodes_in(&mut conn, &all).await?;
+        }
 
-            // Get sibling hash (use zero hash if doesn't exist)
-            let sibling_hash = self
-                .get_node_hash_public(level, sibling_index)
-                .await?
-                .unwrap_or(self.zero_hashes[level]);
+        let path: Vec<[u8; 32]> = siblings
+            .iter()
+            .enumerate()
+            .map(|(level, position)| stored[position].unwrap_or(zero_hashes()[level]))
+            .collect();
+
+        {
+            let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+            if cache.tag != tag {
+                cache.nodes.clear();
+                cache.tag = tag;
+            }
+            if cache.nodes.len() + TREE_HEIGHT - NODE_CACHE_MIN_LEVEL > NODE_CACHE_CAPACITY {
+                cache.nodes.clear();
+            }
+            for (position, hash) in siblings.iter().zip(&path).skip(NODE_CACHE_MIN_LEVEL) {
+                cache.nodes.insert(*position, *hash);
+            }
+        }
 
-            proof_hashes.push(sibling_hash);
-            current_index = current_index / 2;
+        Ok((stored[&leaf], path))
+    }
 
-            debug!(
-                "Level {} sibling {}: {:02x?}",
-                level,
-                sibling_index,
-                &sibling_hash[..8]
-            );
-        }
+    /// Generate Merkle proof for a leaf (32 sibling hashes)
+    ///
+    /// A leaf that was never written is proven as the empty leaf.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn generate_proof(&self, leaf_index: i64) -> Result<MerkleProof, DbError> {
+        debug!("🔐 Generating proof for leaf {}", leaf_index);
 
-        let proof = MerkleProof32 {
+        check_leaf_index(leaf_index)?;
+        let (leaf_hash, siblings) = self.read_path(leaf_index).await?;
+        Ok(MerkleProof::new(
             leaf_index,
-            proof_hashes,
-            leaf_hash,
-        };
-
-        info!(
-            "✅ Proof generated with {} sibling hashes",
-            proof.proof_hashes.len()
-        );
-        Ok(proof)
+            leaf_hash.unwrap_or(zero_hashes()[0]),
+            siblings,
+        ))
     }
 
-    /// Get node hash from database (public version without transaction)
+    /// Proof for a leaf that was written, `None` if it never was
     #[instrument(skip(self), level = "debug")]
-    async fn get_node_hash_public(
+    pub async fn generate_stored_proof(
         &self,
-        level: usize,
-        index: usize,
-    ) -> Result<Option<[u8; 32]>, DbError> {
-        let result: Option<Vec<u8>> = sqlx::query_scalar!(
-            "SELECT hash_value FROM merkle_nodes WHERE tree_level = $1 AND node_index = $2",
-            level as i32,
-            index as i64
-        )
-        .fetch_optional(&self.pool)
-        .await
-        .map_err(DbError::Database)?;
+        leaf_index: i64,
+    ) -> Result<Option<MerkleProof>, DbError> {
+        check_leaf_index(leaf_index)?;
+        let (leaf_hash, siblings) = self.read_path(leaf_index).await?;
+        Ok(leaf_hash.map(|leaf_hash| MerkleProof::new(leaf_index, leaf_hash, siblings)))
+    }
 
-        Ok(result.map(|v| {
-            let mut arr = [0u8; 32];
-            if v.len() >= 32 {
-                arr.copy_from_slice(&v[..32]);
-            }
-            arr
+    /// `generate_stored_proof` on `conn`
+    ///
+    /// Bypasses the node cache, so the proof includes the caller's
+    /// uncommitted writes.
+    pub async fn generate_stored_proof_in(
+        &self,
+        conn: &mut PgConnection,
+        leaf_index: i64,
+    ) -> Result<Option<MerkleProof>, DbError> {
+        check_leaf_index(leaf_index)?;
+        let leaf = (0, leaf_index);
+        let siblings = sibling_positions(leaf_index);
+        let positions: Vec<_> = std::iter::once(leaf)
+            .chain(siblings.iter().copied())
+            .collect();
+        let (stored, _) = Self::read_nodes_in(conn, &positions).await?;
+
+        Ok(stored[&leaf].map(|leaf_hash| {
+            let path = siblings
+                .iter()
+                .enumerate()
+                .map(|(level, position)| stored[position].unwrap_or(zero_hashes()[level]))
+                .collect();
+            MerkleProof::new(leaf_index, leaf_hash, path)
         }))
     }
 
@@ -433,7 +526,7 @@ impl MerkleTree32 {
 
         // Validate all indices first
         for update in updates {
-            if update.leaf_index >= (1 << self.height) {
+            if update.leaf_index >= (1 << TREE_HEIGHT) {
                 return Err(DbError::InvalidTreeParameter(format!(
                     "Leaf index {} exceeds tree capacity",
                     update.leaf_index
@@ -452,19 +545,12 @@ impl MerkleTree32 {
             );
 
             // Store leaf directly without propagation yet
-            sqlx::query!(
-                r#"
-                INSERT INTO merkle_nodes (tree_level, node_index, hash_value)
-                VALUES (0, $1, $2)
-                ON CONFLICT (tree_level, node_index)
-                DO UPDATE SET hash_value = $2, updated_at = NOW()
-                "#,
-                update.leaf_index as i64,
-                update.new_value.as_slice()
-            )
-            .execute(&mut *tx)
-            .await
-            .map_err(DbError::Database)?;
+            sqlx::query(UPSERT_NODE)
+                .bind(0i32)
+                .bind(update.leaf_index as i64)
+                .bind(update.new_value.as_slice())
+                .execute(&mut *tx)
+                .await?;
         }
 
         // Collect all affected paths and recompute from bottom up
@@ -474,19 +560,10 @@ impl MerkleTree32 {
             .await?;
 
         // Update tree state
-        sqlx::query!(
-            r#"
-            UPDATE tree_state
-            SET root_hash = $1, updated_at = NOW()
-            WHERE tree_id = 'default'
-            "#,
-            final_root.as_slice()
-        )
-        .execute(&mut *tx)
-        .await
-        .map_err(DbError::Database)?;
+        Self::set_root_in(&mut tx, &final_root).await?;
 
         tx.commit().await.map_err(DbError::Database)?;
+        self.invalidate_cache();
 
         info!(
             "✅ Batch update complete, new root: {:02x?}",
@@ -503,7 +580,7 @@ impl MerkleTree32 {
             let mut current_index = update.leaf_index;
 
             // Mark all parents as affected
-            for level in 1..=self.height {
+            for level in 1..=TREE_HEIGHT {
                 current_index = current_index / 2;
                 affected.insert((level, current_index), ());
             }
@@ -519,47 +596,26 @@ impl MerkleTree32 {
         affected_nodes: &HashMap<(usize, usize), ()>,
         tx: &mut Transaction<'_, Postgres>,
     ) -> Result<[u8; 32], DbError> {
+        let mut root = zero_hashes()[TREE_HEIGHT];
+
         // Process levels from bottom to top
-        for level in 1..=self.height {
+        for level in 1..=TREE_HEIGHT {
             let level_nodes: Vec<usize> = affected_nodes
                 .keys()
                 .filter_map(|(l, idx)| if *l == level { Some(*idx) } else { None })
                 .collect();
 
             for &node_index in &level_nodes {
-                let left_child = node_index * 2;
-                let right_child = node_index * 2 + 1;
-
-                // Get left child hash
-                let left_hash = self
-                    .get_node_hash(level - 1, left_child, tx)
-                    .await?
-                    .unwrap_or(self.zero_hashes[level - 1]);
-
-                // Get right child hash
-                let right_hash = self
-                    .get_node_hash(level - 1, right_child, tx)
-                    .await?
-                    .unwrap_or(self.zero_hashes[level - 1]);
-
-                // Compute parent hash
-                let parent_hash = self.hash_pair(&left_hash, &right_hash);
-
-                // Store updated parent
-                sqlx::query!(
-                    r#"
-                    INSERT INTO merkle_nodes (tree_level, node_index, hash_value)
-                    VALUES ($1, $2, $3)
-                    ON CONFLICT (tree_level, node_index)
-                    DO UPDATE SET hash_value = $3, updated_at = NOW()
-                    "#,
-                    level as i32,
-                    node_index as i64,
-                    parent_hash.as_slice()
-                )
-                .execute(&mut **tx)
-                .await
-                .map_err(DbError::Database)?;
+                let left_hash = Self::node_in(tx, level - 1, (node_index * 2) as i64).await?;
+                let right_hash = Self::node_in(tx, level - 1, (node_index * 2 + 1) as i64).await?;
+                let parent_hash = node_hash(&left_hash, &right_hash);
+
+                sqlx::query(UPSERT_NODE)
+                    .bind(level as i32)
+                    .bind(node_index as i64)
+                    .bind(parent_hash.as_slice())
+                    .execute(&mut **tx)
+                    .await?;
 
                 debug!(
                     "Recomputed level {} node {}: {:02x?}",
@@ -567,18 +623,102 @@ impl MerkleTree32 {
                     node_index,
                     &parent_hash[..8]
                 );
+                root = parent_hash;
+            }
+        }
+
+        // The last level holds only the root
+        Ok(root)
+    }
+
+    /// Recompute every internal node from the stored leaves if the stored
+    /// root doesn't match them; returns whether the tree was rebuilt
+    ///
+    /// Nodes written before the engine was shared treated a missing sibling
+    /// as 32 zero bytes. Paths written since use `zero_hashes()`, so a tree
+    /// carried over from then would mix both schemes on its next insertion.
+    /// Takes the IMT write lock, so concurrent starts rebuild at most once.
+    #[instrument(skip(self), level = "info")]
+    pub async fn rebuild_stale_nodes(&self) -> Result<bool, DbError> {
+        let mut tx = self.pool.begin().await?;
+        crate::merkle_tree::lock_for_write(&mut tx).await?;
+
+        let rows =
+            sqlx::query("SELECT node_index, hash_value FROM merkle_nodes WHERE tree_level = 0")
+                .fetch_all(&mut *tx)
+                .await?;
+        if rows.is_empty() {
+            return Ok(false);
+        }
+
+        let mut level_nodes: HashMap<i64, [u8; 32]> = HashMap::with_capacity(rows.len());
+        for row in &rows {
+            let hash: Vec<u8> = row.try_get("hash_value")?;
+            let hash = <[u8; 32]>::try_from(hash.as_slice())
+                .map_err(|_| DbError::InvalidHashLength(hash.len()))?;
+            level_nodes.insert(row.try_get("node_index")?, hash);
+        }
 
-                // If this is the root level, return the root hash
-                if level == self.height && node_index == 0 {
-                    return Ok(parent_hash);
+        let mut levels = Vec::new();
+        let mut indices = Vec::new();
+        let mut hashes = Vec::new();
+        for level in 1..=TREE_HEIGHT {
+            let mut parents = HashMap::with_capacity(level_nodes.len() / 2 + 1);
+            for &index in level_nodes.keys() {
+                let parent = index / 2;
+                if parents.contains_key(&parent) {
+                    continue;
                 }
+                let child = |index: i64| {
+                    level_nodes
+                        .get(&index)
+                        .copied()
+                        .unwrap_or(zero_hashes()[level - 1])
+                };
+                parents.insert(
+                    parent,
+                    node_hash(&child(parent * 2), &child(parent * 2 + 1)),
+                );
+            }
+            for (&index, hash) in &parents {
+                levels.push(level as i32);
+                indices.push(index);
+                hashes.push(hash.to_vec());
             }
+            level_nodes = parents;
+        }
+        let root = level_nodes[&0];
+
+        let stored_node = Self::node_in(&mut tx, TREE_HEIGHT, 0).await?;
+        let stored_root: Option<Vec<u8>> =
+            sqlx::query_scalar("SELECT root_hash FROM tree_state WHERE tree_id = 'default'")
+                .fetch_optional(&mut *tx)
+                .await?;
+        if stored_node == root && stored_root.as_deref() == Some(root.as_slice()) {
+            return Ok(false);
         }
 
-        // Fallback: get root from database
-        self.get_node_hash(self.height, 0, tx)
-            .await?
-            .ok_or_else(|| DbError::NotFound("root node after recomputation".to_string()))
+        warn!(
+            "🔁 Stored root {:02x?} doesn't match the {} stored leaves, rebuilding {} nodes to root {:02x?}",
+            &stored_node[..8],
+            rows.len(),
+            hashes.len(),
+            &root[..8]
+        );
+        sqlx::query("DELETE FROM merkle_nodes WHERE tree_level > 0")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(UPSERT_NODES)
+            .bind(levels)
+            .bind(indices)
+            .bind(hashes)
+            .execute(&mut *tx)
+            .await?;
+        Self::set_root_in(&mut tx, &root).await?;
+        tx.commit().await?;
+        self.invalidate_cache();
+
+        Ok(true)
     }
 
     /// Get comprehensive tree statistics
@@ -615,7 +755,7 @@ impl MerkleTree32 {
             non_zero_nodes += count;
 
             // Estimate zero hash usage (nodes that could exist but don't)
-            let max_nodes_at_level = 1u64 << (self.height - level);
+            let max_nodes_at_level = 1u64 << (TREE_HEIGHT - level);
             zero_hash_usage.insert(level, max_nodes_at_level - count);
         }
 
@@ -626,7 +766,7 @@ impl MerkleTree32 {
                 .map_err(DbError::Database)?;
 
         let stats = Tree32Stats {
-            height: self.height,
+            height: TREE_HEIGHT,
             total_leaves,
             non_zero_nodes,
             root_hash,
@@ -645,84 +785,93 @@ impl MerkleTree32 {
     /// Calculate performance metrics for operations
     pub fn calculate_metrics(&self, operations: u32) -> TreeMetrics {
         TreeMetrics {
-            hash_operations: operations * 32,     // 32 hash ops per path to root
-            database_operations: operations * 33, // 32 internal nodes + 1 leaf + root update
-            proof_size: 32 * 32,                  // 32 hashes * 32 bytes = 1024 bytes
+            hash_operations: operations * 32,    // 32 hash ops per path to root
+            database_operations: operations * 3, // path read + path write + root update
+            proof_size: 32 * 32,                 // 32 hashes * 32 bytes = 1024 bytes
             constraint_count: operations * 32 * 8, // Poseidon constraints: 8 per hash
         }
     }
 }
 
-impl MerkleProof32 {
-    /// Verify proof against a root hash
-    #[instrument(skip(self, root), level = "debug")]
-    pub fn verify(&self, root: &[u8; 32]) -> bool {
-        debug!("🔍 Verifying proof for leaf {}", self.leaf_index);
+/// `(level, index)` of the sibling at each level on the path of `leaf_index`
+fn sibling_positions(leaf_index: i64) -> Vec<(usize, i64)> {
+    (0..TREE_HEIGHT)
+        .map(|level| (level, (leaf_index >> level) ^ 1))
+        .collect()
+}
 
-        if self.proof_hashes.len() != 32 {
-            warn!(
-                "Invalid proof: expected 32 hashes, got {}",
-                self.proof_hashes.len()
-            );
-            return false;
-        }
+fn check_leaf_index(leaf_index: i64) -> Result<(), DbError> {
+    if !(0..1 << TREE_HEIGHT).contains(&leaf_index) {
+        return Err(DbError::InvalidTreeParameter(format!(
+            "Leaf index {} exceeds tree capacity",
+            leaf_index
+        )));
+    }
+    Ok(())
+}
 
-        let mut current_hash = self.leaf_hash;
-        let mut current_index = self.leaf_index;
+impl MerkleProof {
+    /// Proof of `leaf_hash` at `leaf_index` from its siblings, leaf to root
+    fn new(leaf_index: i64, leaf_hash: [u8; 32], siblings: Vec<[u8; 32]>) -> Self {
+        let path_indices = (0..siblings.len())
+            .map(|level| (leaf_index >> level) & 1 == 1)
+            .collect();
+        Self {
+            leaf_index,
+            leaf_hash,
+            siblings,
+            path_indices,
+        }
+    }
 
-        for (level, sibling_hash) in self.proof_hashes.iter().enumerate() {
-            let is_right_child = (current_index % 2) == 1;
+    /// Root the leaf hashes up to, `None` if the proof is not a well-formed
+    /// 32-level path for `leaf_index`
+    pub fn compute_root(&self) -> Option<[u8; 32]> {
+        if self.siblings.len() != TREE_HEIGHT
+            || self.path_indices.len() != TREE_HEIGHT
+            || !(0..1 << TREE_HEIGHT).contains(&self.leaf_index)
+        {
+            return None;
+        }
 
-            let mut hasher = Sha256::new();
-            if is_right_child {
-                hasher.update(sibling_hash);
-                hasher.update(&current_hash);
-            } else {
-                hasher.update(&current_hash);
-                hasher.update(sibling_hash);
+        let mut current_hash = self.leaf_hash;
+        for (level, (sibling, is_right)) in self.siblings.iter().zip(&self.path_indices).enumerate()
+        {
+            if *is_right != ((self.leaf_index >> level) & 1 == 1) {
+                return None;
             }
-            current_hash = hasher.finalize().into();
-            current_index = current_index / 2;
-
-            debug!("Level {}: hash {:02x?}", level, &current_hash[..8]);
+            current_hash = if *is_right {
+                node_hash(sibling, &current_hash)
+            } else {
+                node_hash(&current_hash, sibling)
+            };
         }
+        Some(current_hash)
+    }
 
-        let verified = current_hash == *root;
-        if verified {
-            debug!("✅ Proof verification successful");
-        } else {
-            warn!("❌ Proof verification failed");
+    /// Verify proof against a root hash
+    #[instrument(skip(self, root), level = "debug")]
+    pub fn verify(&self, root: &[u8; 32]) -> bool {
+        match self.compute_root() {
+            Some(computed) if computed == *root => true,
+            Some(_) => {
+                debug!("Proof for leaf {} leads to another root", self.leaf_index);
+                false
+            }
+            None => {
+                warn!(
+                    "Malformed proof for leaf {}: {} siblings, {} path positions",
+                    self.leaf_index,
+                    self.siblings.len(),
+                    self.path_indices.len()
+                );
+                false
+            }
         }
-
-        verified
     }
 
     /// Get the size of this proof in bytes
     pub fn size_bytes(&self) -> usize {
-        32 * 32 + 32 + std::mem::size_of::<usize>() // 32 siblings + leaf + index
-    }
-
-    /// Verify proof with custom leaf hash (for external leaf values)
-    #[instrument(skip(self, custom_leaf_hash, root), level = "debug")]
-    pub fn verify_with_leaf(&self, custom_leaf_hash: &[u8; 32], root: &[u8; 32]) -> bool {
-        let mut current_hash = *custom_leaf_hash;
-        let mut current_index = self.leaf_index;
-
-        for sibling_hash in &self.proof_hashes {
-            let is_right_child = (current_index % 2) == 1;
-
-            let mut hasher = Sha256::new();
-            if is_right_child {
-                hasher.update(sibling_hash);
-                hasher.update(&current_hash);
-            } else {
-                hasher.update(&current_hash);
-                hasher.update(sibling_hash);
-            }
-            current_hash = hasher.finalize().into();
-            current_index = current_index / 2;
-        }
-
-        current_hash == *root
+        32 * self.siblings.len() + 32 + std::mem::size_of::<i64>() // siblings + leaf + index
     }
 }
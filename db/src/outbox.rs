@@ -0,0 +1,259 @@
+//! Transactional outbox for proof requests and contract posts
+//!
+//! Work that follows from a state change is queued in `outbox_jobs` in the
+//! same database transaction as the change: a batch's proof request when the
+//! batch is created, its contract post when it is marked proven. A crash right
+//! after the commit loses nothing, and no in-memory task is needed to kick the
+//! work off.
+//!
+//! A worker claims jobs with `claim_outbox_jobs`, which marks them
+//! `in_flight` under a lease and commits right away. The external call
+//! (Sindri, the settlement contracts) is made outside any transaction, and the
+//! outcome is recorded afterwards in a new one with `complete_outbox_job` or
+//! `retry_outbox_job`. Other workers skip a job while its lease runs; once it
+//! expires, say because the worker died mid-dispatch, the job is claimed
+//! again. A job can therefore be dispatched twice, so workers check the
+//! batch's state (a stored proof id, a submission on the target) before
+//! calling out again.
+//!
+//! Every claim bumps the job's `attempts`, which identifies the claim: a
+//! worker records the outcome with the job it claimed, and once its lease
+//! ran out and another worker claimed the job, the late outcome is dropped.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgExecutor};
+use tracing::debug;
+
+/// Job kind: submit the batch's proof request to Sindri
+pub const OUTBOX_PROOF_REQUEST: &str = "proof_request";
+
+/// Job kind: post the proven batch to every settlement target
+pub const OUTBOX_CONTRACT_POST: &str = "contract_post";
+
+/// Queued proof or posting work for one batch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct OutboxJob {
+    pub id: i64,
+    pub batch_id: i32,
+    pub kind: String,   // proof_request, contract_post
+    pub status: String, // pending, in_flight, done
+    /// Times a worker claimed the job, identifying the current claim
+    pub attempts: i32,
+    /// Earliest time a worker may claim the job again
+    pub available_at: DateTime<Utc>,
+    /// When the lease of an `in_flight` job runs out
+    pub lease_expires_at: Option<DateTime<Utc>>,
+    /// Why the last dispatch failed
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Queue `kind` work for a batch
+///
+/// Call it on the transaction that makes the work due. A job the batch
+/// already has is put back to pending and becomes available right away.
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn queue_outbox_job(
+    executor: impl PgExecutor<'_>,
+    batch_id: i32,
+    kind: &str,
+) -> Result<(), sqlx::Error> {
+    debug!("Queueing {kind} job for batch {batch_id}");
+
+    sqlx::query(
+        r"
+        INSERT INTO outbox_jobs (batch_id, kind)
+        VALUES ($1, $2)
+        ON CONFLICT (batch_id, kind) DO UPDATE
+        SET status = 'pending',
+            available_at = NOW(),
+            lease_expires_at = NULL,
+            last_error = NULL,
+            updated_at = NOW()
+        ",
+    )
+    .bind(batch_id)
+    .bind(kind)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Queue the jobs of batches that still need a proof or a contract post
+///
+/// For batches written without going through the outbox, such as imported
+/// ones. Existing jobs are left alone. Returns the number of jobs queued.
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn queue_outstanding_outbox_jobs(conn: &mut PgConnection) -> Result<u64, sqlx::Error> {
+    let proofs = sqlx::query(
+        r"
+        INSERT INTO outbox_jobs (batch_id, kind)
+        SELECT id, 'proof_request'
+        FROM proof_batches
+        WHERE proof_status IS DISTINCT FROM 'unwound'
+          AND (sindri_proof_id IS NULL
+               OR sindri_proof_id = ''
+               OR sindri_proof_id LIKE 'failed_%'
+               OR sindri_proof_id LIKE 'error_%')
+        ON CONFLICT (batch_id, kind) DO NOTHING
+        ",
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let posts = sqlx::query(
+        r"
+        INSERT INTO outbox_jobs (batch_id, kind)
+        SELECT id, 'contract_post'
+        FROM proof_batches
+        WHERE proof_status = 'proven' AND posted_to_contract = FALSE
+        ON CONFLICT (batch_id, kind) DO NOTHING
+        ",
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let queued = proofs.rows_affected() + posts.rows_affected();
+    debug!("Queued {queued} outstanding outbox jobs");
+    Ok(queued)
+}
+
+/// Claim up to `limit` available `kind` jobs for `lease`, oldest batch first
+///
+/// With `batch_ids`, only jobs of those batches are considered. A job is
+/// available when it is pending and due, or in flight with an expired lease.
+/// The claimed jobs are `in_flight` once the statement commits; jobs another
+/// worker is claiming at the same time are skipped, not waited for.
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn claim_outbox_jobs(
+    executor: impl PgExecutor<'_>,
+    kind: &str,
+    batch_ids: Option<&[i32]>,
+    limit: i64,
+    lease: Duration,
+) -> Result<Vec<OutboxJob>, sqlx::Error> {
+    let mut jobs = sqlx::query_as::<_, OutboxJob>(
+        r"
+        UPDATE outbox_jobs
+        SET status = 'in_flight',
+            attempts = attempts + 1,
+            lease_expires_at = NOW() + $4 * INTERVAL '1 millisecond',
+            updated_at = NOW()
+        WHERE id IN (
+            SELECT id
+            FROM outbox_jobs
+            WHERE kind = $1
+              AND ((status = 'pending' AND available_at <= NOW())
+                   OR (status = 'in_flight' AND lease_expires_at <= NOW()))
+              AND ($2::INTEGER[] IS NULL OR batch_id = ANY($2))
+            ORDER BY batch_id
+            LIMIT $3
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id, batch_id, kind, status, attempts, available_at, lease_expires_at,
+                  last_error, created_at, updated_at
+        ",
+    )
+    .bind(kind)
+    .bind(batch_ids)
+    .bind(limit)
+    .bind(lease.as_millis() as f64)
+    .fetch_all(executor)
+    .await?;
+    jobs.sort_by_key(|job| job.batch_id);
+
+    debug!("Claimed {} {kind} jobs", jobs.len());
+    Ok(jobs)
+}
+
+/// Mark the job claimed as `job` as done
+///
+/// Returns whether the claim still held. A job queued again or claimed by
+/// another worker since is left alone.
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn complete_outbox_job(
+    executor: impl PgExecutor<'_>,
+    job: &OutboxJob,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r"
+        UPDATE outbox_jobs
+        SET status = 'done', lease_expires_at = NULL, last_error = NULL, updated_at = NOW()
+        WHERE id = $1 AND status = 'in_flight' AND attempts = $2
+        ",
+    )
+    .bind(job.id)
+    .bind(job.attempts)
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Put the job claimed as `job` back to pending, available again after `delay`
+///
+/// Returns whether the claim still held, see `complete_outbox_job`.
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn retry_outbox_job(
+    executor: impl PgExecutor<'_>,
+    job: &OutboxJob,
+    delay: Duration,
+    error: Option<&str>,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r"
+        UPDATE outbox_jobs
+        SET status = 'pending',
+            available_at = NOW() + $2 * INTERVAL '1 millisecond',
+            lease_expires_at = NULL,
+            last_error = COALESCE($3, last_error),
+            updated_at = NOW()
+        WHERE id = $1 AND status = 'in_flight' AND attempts = $4
+        ",
+    )
+    .bind(job.id)
+    .bind(delay.as_millis() as f64)
+    .bind(error)
+    .bind(job.attempts)
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Outbox jobs of the given batches, ordered by batch and kind
+///
+/// # Errors
+/// Returns error if database operation fails
+pub async fn get_outbox_jobs(
+    executor: impl PgExecutor<'_>,
+    batch_ids: &[i32],
+) -> Result<Vec<OutboxJob>, sqlx::Error> {
+    sqlx::query_as::<_, OutboxJob>(
+        r"
+        SELECT id, batch_id, kind, status, attempts, available_at, lease_expires_at,
+               last_error, created_at, updated_at
+        FROM outbox_jobs
+        WHERE batch_id = ANY($1)
+        ORDER BY batch_id, kind
+        ",
+    )
+    .bind(batch_ids)
+    .fetch_all(executor)
+    .await
+}
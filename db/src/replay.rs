@@ -0,0 +1,668 @@
+//! Deterministic rebuild of the IMT from the transaction log
+//!
+//! Every batch that has not been unwound inserted the nullifiers of its
+//! transactions, in id order, on top of its parent's tree and committed the
+//! resulting root to `ads_state_commits`. Replaying that log into an in-memory
+//! tree reproduces `nullifiers`, `merkle_nodes`, `nullifier_undo_log` and
+//! `tree_state` without reading any of them, so corrupted IMT tables can be
+//! detected and rebuilt.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool, Row};
+use tracing::{debug, info, instrument, warn};
+
+use arithmetic_lib::receipt::transaction_nullifier;
+
+use crate::db::IncomingTransaction;
+use crate::error::DbError;
+use crate::merkle_tree::{leaf_hash, lock_for_write};
+use crate::merkle_tree_32::{genesis_root, node_hash, zero_hashes, GENESIS_LEAF, TREE_HEIGHT};
+
+/// Rows per statement when writing the rebuilt tables
+const SWAP_CHUNK_SIZE: usize = 10_000;
+
+// ============================================================================
+// IN-MEMORY TREE
+// ============================================================================
+
+/// Nullifier row of the replayed tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayedNullifier {
+    pub value: i64,
+    pub next_index: Option<i64>,
+    pub next_value: i64,
+    pub tree_index: i64,
+}
+
+/// `nullifier_undo_log` row of one replayed insertion
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayedUndo {
+    pub tree_index: i64,
+    pub low_tree_index: i64,
+    pub low_next_index: Option<i64>,
+    pub low_next_value: i64,
+    pub low_leaf_hash: Option<[u8; 32]>,
+}
+
+/// In-memory indexed Merkle tree that makes the same insertions as
+/// `IndexedMerkleTree`, including its quirk that the genesis nullifier's row
+/// is never relinked; nodes that were never written hash like `MerkleTree32`'s
+/// empty subtrees
+#[derive(Debug, Clone)]
+pub struct ReplayTree {
+    /// Rows by tree index
+    nullifiers: BTreeMap<i64, ReplayedNullifier>,
+    /// Tree index by value
+    by_value: BTreeMap<i64, i64>,
+    nodes: HashMap<(i32, i64), [u8; 32]>,
+    undo_log: Vec<ReplayedUndo>,
+    root: [u8; 32],
+    next_index: i64,
+}
+
+impl ReplayTree {
+    /// The tree as migration 012 creates it: the genesis nullifier 0 at leaf 0
+    /// with `GENESIS_LEAF` as its hash, under the genesis root
+    #[must_use]
+    pub fn genesis() -> Self {
+        let genesis = ReplayedNullifier {
+            value: 0,
+            next_index: None,
+            next_value: 0,
+            tree_index: 0,
+        };
+        Self {
+            nullifiers: BTreeMap::from([(0, genesis)]),
+            by_value: BTreeMap::from([(0, 0)]),
+            nodes: HashMap::from([((0, 0), GENESIS_LEAF)]),
+            undo_log: Vec::new(),
+            root: genesis_root(),
+            next_index: 1,
+        }
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.root
+    }
+
+    /// Tree index the next insertion gets
+    pub fn next_index(&self) -> i64 {
+        self.next_index
+    }
+
+    /// Number of nullifiers, genesis included
+    pub fn len(&self) -> usize {
+        self.nullifiers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nullifiers.is_empty()
+    }
+
+    /// Nullifier rows in tree index order
+    pub fn nullifiers(&self) -> impl Iterator<Item = &ReplayedNullifier> {
+        self.nullifiers.values()
+    }
+
+    /// Every written node as `((tree_level, node_index), hash)`
+    pub fn nodes(&self) -> &HashMap<(i32, i64), [u8; 32]> {
+        &self.nodes
+    }
+
+    /// Undo journal of every insertion, oldest first
+    pub fn undo_log(&self) -> &[ReplayedUndo] {
+        &self.undo_log
+    }
+
+    /// Leave the leaves below `tree_index` unused, like an index allocated by
+    /// an insertion that failed afterwards
+    pub fn skip_to(&mut self, tree_index: i64) {
+        self.next_index = self.next_index.max(tree_index);
+    }
+
+    /// Insert a nullifier; returns its tree index
+    ///
+    /// # Errors
+    /// Returns `DbError::InvalidNullifierValue` for values below 1,
+    /// `DbError::NullifierExists` if the value is already in the tree
+    pub fn insert(&mut self, value: i64) -> Result<i64, DbError> {
+        if value <= 0 {
+            return Err(DbError::InvalidNullifierValue(format!(
+                "Nullifier must be positive, got {value}"
+            )));
+        }
+        if self.by_value.contains_key(&value) {
+            return Err(DbError::NullifierExists(value));
+        }
+
+        // Same choice as find_low_nullifier(): the largest smaller value whose
+        // next value is above the new one. Genesis always qualifies.
+        let low = self
+            .by_value
+            .range(..value)
+            .rev()
+            .map(|(_, tree_index)| self.nullifiers[tree_index])
+            .find(|n| n.next_value == 0 || n.next_value > value)
+            .ok_or_else(|| DbError::NotFound("low nullifier".to_string()))?;
+
+        let tree_index = self.next_index;
+        self.next_index += 1;
+
+        self.undo_log.push(ReplayedUndo {
+            tree_index,
+            low_tree_index: low.tree_index,
+            low_next_index: low.next_index,
+            low_next_value: low.next_value,
+            low_leaf_hash: self.nodes.get(&(0, low.tree_index)).copied(),
+        });
+
+        let is_genesis_low = low.value == 0 && low.tree_index == 0;
+        let (next_index, next_value) = if is_genesis_low {
+            (None, 0)
+        } else {
+            self.nullifiers.insert(
+                low.tree_index,
+                ReplayedNullifier {
+                    next_index: Some(tree_index),
+                    next_value: value,
+                    ..low
+                },
+            );
+            (low.next_index, low.next_value)
+        };
+        self.nullifiers.insert(
+            tree_index,
+            ReplayedNullifier {
+                value,
+                next_index,
+                next_value,
+                tree_index,
+            },
+        );
+        self.by_value.insert(value, tree_index);
+
+        // The low leaf is rehashed with the new pointers even for genesis
+        self.update_leaf(
+            low.tree_index,
+            leaf_hash(low.value, Some(tree_index), value),
+        );
+        self.update_leaf(tree_index, leaf_hash(value, next_index, next_value));
+
+        Ok(tree_index)
+    }
+
+    fn update_leaf(&mut self, leaf_index: i64, hash: [u8; 32]) {
+        self.nodes.insert((0, leaf_index), hash);
+
+        let mut current_hash = hash;
+        let mut current_index = leaf_index;
+        for level in 1..=TREE_HEIGHT {
+            let sibling = self
+                .nodes
+                .get(&(level as i32 - 1, current_index ^ 1))
+                .copied()
+                .unwrap_or(zero_hashes()[level - 1]);
+            current_hash = if current_index % 2 == 1 {
+                node_hash(&sibling, &current_hash)
+            } else {
+                node_hash(&current_hash, &sibling)
+            };
+            current_index /= 2;
+            self.nodes
+                .insert((level as i32, current_index), current_hash);
+        }
+
+        self.root = current_hash;
+    }
+}
+
+// ============================================================================
+// REPLAY
+// ============================================================================
+
+/// First batch whose replay disagrees with the recorded state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayDivergence {
+    pub batch_id: i32,
+    /// Root committed for the batch in `ads_state_commits`
+    pub committed_root: Option<Vec<u8>>,
+    /// Root of the replayed tree after the batch, when it could be replayed
+    pub replayed_root: Option<Vec<u8>>,
+    pub reason: String,
+}
+
+/// How the live IMT tables compare with the replayed tree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LiveStateDiff {
+    /// Root in `tree_state`
+    pub live_root: Vec<u8>,
+    pub root_matches: bool,
+    /// Nullifier rows missing, extra or with different pointers
+    pub nullifier_mismatches: usize,
+    /// Merkle nodes missing, extra or with a different hash
+    pub node_mismatches: usize,
+}
+
+impl LiveStateDiff {
+    pub fn is_clean(&self) -> bool {
+        self.root_matches && self.nullifier_mismatches == 0 && self.node_mismatches == 0
+    }
+}
+
+/// Outcome of `replay_state`
+#[derive(Debug, Clone)]
+pub struct StateReplay {
+    pub batches_replayed: usize,
+    pub transactions_replayed: usize,
+    /// `None` when every batch reproduced its committed root
+    pub divergence: Option<ReplayDivergence>,
+    /// Comparison with the live tables, only made when nothing diverged
+    pub live: Option<LiveStateDiff>,
+    /// Replayed tree, including what was inserted for a diverging batch
+    pub tree: ReplayTree,
+}
+
+/// Replay every batch that has not been unwound into a fresh in-memory tree
+///
+/// Each batch's transactions are turned into nullifiers with the same function
+/// the batch service uses and inserted in id order. The tree after every batch
+/// is checked against the batch's first committed root, and replay stops at the
+/// first batch that doesn't reproduce it. Only reads the log tables.
+///
+/// # Errors
+/// Returns `DbError::Database` if a query fails
+#[instrument(skip(pool), level = "info")]
+pub async fn replay_state(pool: &PgPool) -> Result<StateReplay, DbError> {
+    info!("🔁 Replaying batches from the transaction log");
+
+    let batches = sqlx::query(
+        r"
+        SELECT id, transaction_ids, tree_start_index
+        FROM proof_batches
+        WHERE proof_status IS DISTINCT FROM 'unwound'
+        ORDER BY id
+        ",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let committed_roots: HashMap<i32, Vec<u8>> = sqlx::query(
+        r"
+        SELECT DISTINCT ON (batch_id) batch_id, merkle_root
+        FROM ads_state_commits
+        ORDER BY batch_id, id
+        ",
+    )
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(|row| Ok((row.try_get("batch_id")?, row.try_get("merkle_root")?)))
+    .collect::<Result<_, sqlx::Error>>()?;
+
+    let mut replay = StateReplay {
+        batches_replayed: 0,
+        transactions_replayed: 0,
+        divergence: None,
+        live: None,
+        tree: ReplayTree::genesis(),
+    };
+
+    for row in &batches {
+        let batch_id: i32 = row.try_get("id")?;
+        let transaction_ids: Vec<i32> = row.try_get("transaction_ids")?;
+        let tree_start_index: Option<i64> = row.try_get("tree_start_index")?;
+
+        let transactions = sqlx::query_as::<_, IncomingTransaction>(
+            r"
+            SELECT id, amount, included_in_batch_id, created_at
+            FROM incoming_transactions
+            WHERE id = ANY($1)
+            ORDER BY id
+            ",
+        )
+        .bind(&transaction_ids)
+        .fetch_all(pool)
+        .await?;
+
+        if let Err(divergence) = replay_batch(
+            &mut replay.tree,
+            batch_id,
+            &transaction_ids,
+            &transactions,
+            tree_start_index,
+            committed_roots.get(&batch_id).map(Vec::as_slice),
+        ) {
+            replay.divergence = Some(divergence);
+            break;
+        }
+
+        replay.batches_replayed += 1;
+        replay.transactions_replayed += transactions.len();
+        debug!(
+            "Batch {batch_id} replayed, root {:02x?}",
+            &replay.tree.root()[..8]
+        );
+    }
+
+    match &replay.divergence {
+        Some(divergence) => warn!(
+            "Replay diverged at batch {}: {}",
+            divergence.batch_id, divergence.reason
+        ),
+        None => {
+            let live = compare_live_state(pool, &replay.tree).await?;
+            info!(
+                "✅ Replayed {} batches ({} transactions), live tables {}",
+                replay.batches_replayed,
+                replay.transactions_replayed,
+                if live.is_clean() { "match" } else { "differ" }
+            );
+            replay.live = Some(live);
+        }
+    }
+
+    Ok(replay)
+}
+
+/// Replay one batch on top of `tree` and check its committed root
+///
+/// `transactions` are the batch's rows in id order; any of `transaction_ids`
+/// missing from them is a divergence.
+pub(crate) fn replay_batch(
+    tree: &mut ReplayTree,
+    batch_id: i32,
+    transaction_ids: &[i32],
+    transactions: &[IncomingTransaction],
+    tree_start_index: Option<i64>,
+    committed_root: Option<&[u8]>,
+) -> Result<(), ReplayDivergence> {
+    let diverge = |tree: &ReplayTree, reason: String, replayed: bool| ReplayDivergence {
+        batch_id,
+        committed_root: committed_root.map(<[u8]>::to_vec),
+        replayed_root: replayed.then(|| tree.root().to_vec()),
+        reason,
+    };
+
+    if transactions.len() != transaction_ids.len() {
+        return Err(diverge(
+            tree,
+            format!(
+                "{} of the batch's {} transactions are missing from the log",
+                transaction_ids.len().saturating_sub(transactions.len()),
+                transaction_ids.len()
+            ),
+            false,
+        ));
+    }
+
+    if let Some(start) = tree_start_index {
+        if start < tree.next_index() {
+            return Err(diverge(
+                tree,
+                format!(
+                    "Batch starts at leaf {start} but the replayed tree is already at leaf {}",
+                    tree.next_index()
+                ),
+                false,
+            ));
+        }
+        tree.skip_to(start);
+    }
+
+    for transaction in transactions {
+        let nullifier = transaction_nullifier(
+            transaction.id,
+            transaction.amount,
+            transaction.created_at.timestamp(),
+        );
+        if let Err(e) = tree.insert(nullifier) {
+            return Err(diverge(
+                tree,
+                format!(
+                    "Transaction {} (nullifier {nullifier}) cannot be inserted: {e}",
+                    transaction.id
+                ),
+                false,
+            ));
+        }
+    }
+
+    match committed_root {
+        None => Err(diverge(
+            tree,
+            "Batch has no committed root".to_string(),
+            true,
+        )),
+        Some(root) if root != tree.root() => Err(diverge(
+            tree,
+            "Replayed root differs from the committed root".to_string(),
+            true,
+        )),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Compare the live IMT tables with a replayed tree
+///
+/// # Errors
+/// Returns `DbError::Database` if a query fails
+pub async fn compare_live_state(
+    pool: &PgPool,
+    tree: &ReplayTree,
+) -> Result<LiveStateDiff, DbError> {
+    let live_root: Vec<u8> =
+        sqlx::query_scalar("SELECT root_hash FROM tree_state WHERE tree_id = 'default'")
+            .fetch_optional(pool)
+            .await?
+            .unwrap_or_default();
+
+    let live_nullifiers = sqlx::query(
+        r"
+        SELECT value, next_index, COALESCE(next_value, 0) AS next_value, tree_index
+        FROM nullifiers
+        WHERE is_active = true
+        ",
+    )
+    .fetch_all(pool)
+    .await?;
+    let mut nullifier_mismatches = 0;
+    let mut matched = 0;
+    for row in &live_nullifiers {
+        let live = ReplayedNullifier {
+            value: row.try_get("value")?,
+            next_index: row.try_get("next_index")?,
+            next_value: row.try_get("next_value")?,
+            tree_index: row.try_get("tree_index")?,
+        };
+        if tree.nullifiers.get(&live.tree_index) == Some(&live) {
+            matched += 1;
+        } else {
+            nullifier_mismatches += 1;
+        }
+    }
+    nullifier_mismatches += tree.len() - matched;
+
+    let live_nodes = sqlx::query("SELECT tree_level, node_index, hash_value FROM merkle_nodes")
+        .fetch_all(pool)
+        .await?;
+    let mut node_mismatches = 0;
+    let mut matched = 0;
+    for row in &live_nodes {
+        let key: (i32, i64) = (row.try_get("tree_level")?, row.try_get("node_index")?);
+        let hash: Vec<u8> = row.try_get("hash_value")?;
+        match tree.nodes.get(&key) {
+            Some(replayed) if replayed.as_slice() == hash => matched += 1,
+            _ => node_mismatches += 1,
+        }
+    }
+    node_mismatches += tree.nodes.len() - matched;
+
+    Ok(LiveStateDiff {
+        root_matches: live_root == tree.root(),
+        live_root,
+        nullifier_mismatches,
+        node_mismatches,
+    })
+}
+
+/// Replace the IMT tables with a replayed tree
+///
+/// In one database transaction `nullifiers`, `merkle_nodes` and
+/// `nullifier_undo_log` are emptied and rewritten from the tree and
+/// `tree_state` is reset to its root and next index. Nullifiers keep their
+/// `created_at` where the value was already present. The caller must keep
+/// batches from inserting nullifiers meanwhile.
+///
+/// # Errors
+/// Returns `DbError::InvalidState` if the replay diverged, `DbError::Database`
+/// if a query fails
+#[instrument(skip(pool, replay), level = "info")]
+pub async fn swap_in_replayed_state(pool: &PgPool, replay: &StateReplay) -> Result<(), DbError> {
+    if let Some(divergence) = &replay.divergence {
+        return Err(DbError::InvalidState(format!(
+            "Replay diverged at batch {}, refusing to swap in a partial tree",
+            divergence.batch_id
+        )));
+    }
+    let tree = &replay.tree;
+    warn!(
+        "🔁 Swapping in the replayed IMT: {} nullifiers, {} nodes",
+        tree.len(),
+        tree.nodes.len()
+    );
+
+    let mut tx = pool.begin().await?;
+    write_tree(&mut tx, tree).await?;
+    tx.commit().await?;
+
+    info!(
+        "✅ Replayed IMT swapped in, root {:02x?}",
+        &tree.root()[..8]
+    );
+    Ok(())
+}
+
+/// Rewrite `nullifiers`, `merkle_nodes`, `nullifier_undo_log` and `tree_state`
+/// from `tree` inside the caller's transaction
+pub(crate) async fn write_tree(conn: &mut PgConnection, tree: &ReplayTree) -> Result<(), DbError> {
+    // Same order as IMT writers: the write lock first, then the tables
+    lock_for_write(conn).await?;
+    sqlx::query(
+        "LOCK TABLE nullifiers, merkle_nodes, nullifier_undo_log, tree_state IN EXCLUSIVE MODE",
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let created_at: HashMap<i64, DateTime<Utc>> =
+        sqlx::query("SELECT value, created_at FROM nullifiers WHERE created_at IS NOT NULL")
+            .fetch_all(&mut *conn)
+            .await?
+            .iter()
+            .map(|row| Ok((row.try_get("value")?, row.try_get("created_at")?)))
+            .collect::<Result<_, sqlx::Error>>()?;
+
+    sqlx::query("DELETE FROM nullifiers")
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("DELETE FROM merkle_nodes")
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("DELETE FROM nullifier_undo_log")
+        .execute(&mut *conn)
+        .await?;
+
+    let nullifiers: Vec<&ReplayedNullifier> = tree.nullifiers().collect();
+    for chunk in nullifiers.chunks(SWAP_CHUNK_SIZE) {
+        let now = Utc::now();
+        sqlx::query(
+            r"
+            INSERT INTO nullifiers (value, next_index, next_value, tree_index, created_at)
+            SELECT * FROM UNNEST($1::BIGINT[], $2::BIGINT[], $3::BIGINT[], $4::BIGINT[], $5::TIMESTAMPTZ[])
+            ",
+        )
+        .bind(chunk.iter().map(|n| n.value).collect::<Vec<_>>())
+        .bind(chunk.iter().map(|n| n.next_index).collect::<Vec<_>>())
+        .bind(chunk.iter().map(|n| n.next_value).collect::<Vec<_>>())
+        .bind(chunk.iter().map(|n| n.tree_index).collect::<Vec<_>>())
+        .bind(
+            chunk
+                .iter()
+                .map(|n| created_at.get(&n.value).copied().unwrap_or(now))
+                .collect::<Vec<_>>(),
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    let nodes: Vec<(&(i32, i64), &[u8; 32])> = tree.nodes.iter().collect();
+    for chunk in nodes.chunks(SWAP_CHUNK_SIZE) {
+        sqlx::query(
+            r"
+            INSERT INTO merkle_nodes (tree_level, node_index, hash_value)
+            SELECT * FROM UNNEST($1::INTEGER[], $2::BIGINT[], $3::BYTEA[])
+            ",
+        )
+        .bind(
+            chunk
+                .iter()
+                .map(|((level, _), _)| *level)
+                .collect::<Vec<_>>(),
+        )
+        .bind(
+            chunk
+                .iter()
+                .map(|((_, index), _)| *index)
+                .collect::<Vec<_>>(),
+        )
+        .bind(
+            chunk
+                .iter()
+                .map(|(_, hash)| hash.to_vec())
+                .collect::<Vec<_>>(),
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    for chunk in tree.undo_log.chunks(SWAP_CHUNK_SIZE) {
+        sqlx::query(
+            r"
+            INSERT INTO nullifier_undo_log (tree_index, low_tree_index, low_next_index, low_next_value, low_leaf_hash)
+            SELECT * FROM UNNEST($1::BIGINT[], $2::BIGINT[], $3::BIGINT[], $4::BIGINT[], $5::BYTEA[])
+            ",
+        )
+        .bind(chunk.iter().map(|u| u.tree_index).collect::<Vec<_>>())
+        .bind(chunk.iter().map(|u| u.low_tree_index).collect::<Vec<_>>())
+        .bind(chunk.iter().map(|u| u.low_next_index).collect::<Vec<_>>())
+        .bind(chunk.iter().map(|u| u.low_next_value).collect::<Vec<_>>())
+        .bind(
+            chunk
+                .iter()
+                .map(|u| u.low_leaf_hash.map(|hash| hash.to_vec()))
+                .collect::<Vec<_>>(),
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    sqlx::query(
+        r"
+        UPDATE tree_state
+        SET root_hash = $1,
+            next_available_index = $2,
+            total_nullifiers = $3,
+            updated_at = NOW()
+        WHERE tree_id = 'default'
+        ",
+    )
+    .bind(tree.root().as_slice())
+    .bind(tree.next_index())
+    .bind(tree.len() as i64)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,742 @@
+//! Portable snapshots of the vApp state at a batch
+//!
+//! A `StateSnapshot` is a single CBOR archive of everything needed to stand up
+//! the service at a given batch: the transactions and batches up to it, their
+//! ADS commits, and the IMT (`nullifiers`, `merkle_nodes`,
+//! `nullifier_undo_log`, `tree_state`). The manifest names the batch, its
+//! counter value and IMT root, the row counts, and a SHA-256 checksum of the
+//! contents.
+//!
+//! The IMT rows are not copied from the live tables: export replays the log
+//! (see `replay`) and fails if any batch diverges, and import replays the
+//! snapshot again and checks every row and root before committing. Pending
+//! transactions, batch submissions, outbox jobs and transaction inclusions
+//! are not part of a snapshot; import queues proof and posting work for the
+//! imported batches that still need it.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Row};
+use tracing::{info, instrument, warn};
+
+use arithmetic_lib::bundle::{byte_vec, bytes32};
+
+use crate::db::IncomingTransaction;
+use crate::error::DbError;
+use crate::outbox::queue_outstanding_outbox_jobs;
+use crate::replay::{replay_batch, write_tree, ReplayDivergence, ReplayTree, ReplayedNullifier};
+
+/// Value of the manifest `format` field, identifies a file as a state snapshot
+pub const STATE_SNAPSHOT_FORMAT: &str = "arithmetic-state-snapshot";
+
+/// Snapshot layout version written by this crate
+pub const STATE_SNAPSHOT_VERSION: u32 = 1;
+
+/// Rows per statement when importing
+const IMPORT_CHUNK_SIZE: usize = 10_000;
+
+// ============================================================================
+// SNAPSHOT FORMAT
+// ============================================================================
+
+/// What a snapshot holds, checked before anything else is read
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SnapshotManifest {
+    /// Always `STATE_SNAPSHOT_FORMAT`
+    pub format: String,
+    pub version: u32,
+    /// Last batch in the snapshot
+    pub batch_id: i32,
+    /// Counter value after `batch_id`
+    pub counter_value: i64,
+    /// IMT root after `batch_id`, as committed to `ads_state_commits`
+    #[serde(with = "bytes32")]
+    pub imt_root: [u8; 32],
+    pub exported_at: DateTime<Utc>,
+    pub transaction_count: usize,
+    pub batch_count: usize,
+    pub commit_count: usize,
+    pub nullifier_count: usize,
+    pub node_count: usize,
+    /// SHA-256 over the CBOR encoding of the contents
+    #[serde(with = "bytes32")]
+    pub checksum: [u8; 32],
+}
+
+/// `incoming_transactions` row
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotTransaction {
+    pub id: i32,
+    pub amount: i32,
+    /// Batch of the snapshot that holds the transaction, `None` if it was only
+    /// in unwound batches
+    pub included_in_batch_id: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub client_tx_id: Option<String>,
+}
+
+/// `proof_batches` row
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotBatch {
+    pub id: i32,
+    pub previous_counter_value: i64,
+    pub final_counter_value: i64,
+    pub transaction_ids: Vec<i32>,
+    pub sindri_proof_id: Option<String>,
+    pub proof_status: String,
+    pub created_at: DateTime<Utc>,
+    pub proven_at: Option<DateTime<Utc>>,
+    pub posted_to_contract: bool,
+    pub posted_to_contract_at: Option<DateTime<Utc>>,
+    pub parent_batch_id: Option<i32>,
+    pub tree_start_index: Option<i64>,
+}
+
+impl SnapshotBatch {
+    fn is_unwound(&self) -> bool {
+        self.proof_status == "unwound"
+    }
+}
+
+/// `ads_state_commits` row; ids are reassigned on import, order is kept
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotCommit {
+    pub batch_id: i32,
+    #[serde(with = "byte_vec")]
+    pub merkle_root: Vec<u8>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// `merkle_nodes` row
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotNode {
+    pub tree_level: i32,
+    pub node_index: i64,
+    #[serde(with = "bytes32")]
+    pub hash: [u8; 32],
+}
+
+/// `nullifier_undo_log` row
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotUndo {
+    pub tree_index: i64,
+    pub low_tree_index: i64,
+    pub low_next_index: Option<i64>,
+    pub low_next_value: i64,
+    /// Zero when the low leaf had never been written
+    #[serde(with = "bytes32")]
+    pub low_leaf_hash: [u8; 32],
+}
+
+/// `tree_state` row
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotTreeState {
+    #[serde(with = "bytes32")]
+    pub root: [u8; 32],
+    pub next_available_index: i64,
+    pub total_nullifiers: i64,
+}
+
+/// Table rows of a snapshot, each in primary key order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SnapshotContents {
+    pub transactions: Vec<SnapshotTransaction>,
+    pub batches: Vec<SnapshotBatch>,
+    pub commits: Vec<SnapshotCommit>,
+    pub nullifiers: Vec<ReplayedNullifier>,
+    pub nodes: Vec<SnapshotNode>,
+    pub undo_log: Vec<SnapshotUndo>,
+    pub tree_state: SnapshotTreeState,
+}
+
+/// Manifest and contents of one snapshot archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StateSnapshot {
+    pub manifest: SnapshotManifest,
+    pub contents: SnapshotContents,
+}
+
+impl SnapshotContents {
+    /// SHA-256 over the CBOR encoding
+    ///
+    /// # Errors
+    /// Returns `DbError::InvalidSnapshot` if the contents cannot be encoded
+    pub fn checksum(&self) -> Result<[u8; 32], DbError> {
+        let mut buffer = Vec::new();
+        ciborium::into_writer(self, &mut buffer)
+            .map_err(|e| DbError::InvalidSnapshot(format!("CBOR encoding failed: {e}")))?;
+        Ok(Sha256::digest(&buffer).into())
+    }
+
+    /// Replay the batches into a fresh tree, checking every committed root
+    fn replay(&self) -> Result<ReplayTree, ReplayDivergence> {
+        let transactions: HashMap<i32, IncomingTransaction> = self
+            .transactions
+            .iter()
+            .map(|t| {
+                (
+                    t.id,
+                    IncomingTransaction {
+                        id: t.id,
+                        amount: t.amount,
+                        included_in_batch_id: t.included_in_batch_id,
+                        created_at: t.created_at,
+                    },
+                )
+            })
+            .collect();
+
+        let mut committed_roots: HashMap<i32, &[u8]> = HashMap::new();
+        for commit in &self.commits {
+            committed_roots
+                .entry(commit.batch_id)
+                .or_insert(&commit.merkle_root);
+        }
+
+        let mut tree = ReplayTree::genesis();
+        for batch in self.batches.iter().filter(|b| !b.is_unwound()) {
+            let mut transaction_ids = batch.transaction_ids.clone();
+            transaction_ids.sort_unstable();
+            let batch_transactions: Vec<IncomingTransaction> = transaction_ids
+                .iter()
+                .filter_map(|id| transactions.get(id).cloned())
+                .collect();
+
+            replay_batch(
+                &mut tree,
+                batch.id,
+                &batch.transaction_ids,
+                &batch_transactions,
+                batch.tree_start_index,
+                committed_roots.get(&batch.id).copied(),
+            )?;
+        }
+        Ok(tree)
+    }
+}
+
+/// IMT rows of a replayed tree, in the order a snapshot stores them
+fn tree_rows(
+    tree: &ReplayTree,
+) -> (
+    Vec<ReplayedNullifier>,
+    Vec<SnapshotNode>,
+    Vec<SnapshotUndo>,
+    SnapshotTreeState,
+) {
+    let nullifiers = tree.nullifiers().copied().collect();
+
+    let mut nodes: Vec<SnapshotNode> = tree
+        .nodes()
+        .iter()
+        .map(|(&(tree_level, node_index), &hash)| SnapshotNode {
+            tree_level,
+            node_index,
+            hash,
+        })
+        .collect();
+    nodes.sort_unstable_by_key(|n| (n.tree_level, n.node_index));
+
+    let undo_log = tree
+        .undo_log()
+        .iter()
+        .map(|u| SnapshotUndo {
+            tree_index: u.tree_index,
+            low_tree_index: u.low_tree_index,
+            low_next_index: u.low_next_index,
+            low_next_value: u.low_next_value,
+            low_leaf_hash: u.low_leaf_hash.unwrap_or([0u8; 32]),
+        })
+        .collect();
+
+    let tree_state = SnapshotTreeState {
+        root: tree.root(),
+        next_available_index: tree.next_index(),
+        total_nullifiers: tree.len() as i64,
+    };
+
+    (nullifiers, nodes, undo_log, tree_state)
+}
+
+impl StateSnapshot {
+    /// CBOR encoding
+    ///
+    /// # Errors
+    /// Returns `DbError::InvalidSnapshot` if serialization fails
+    pub fn to_cbor(&self) -> Result<Vec<u8>, DbError> {
+        let mut buffer = Vec::new();
+        ciborium::into_writer(self, &mut buffer)
+            .map_err(|e| DbError::InvalidSnapshot(format!("CBOR encoding failed: {e}")))?;
+        Ok(buffer)
+    }
+
+    /// Decode a CBOR snapshot and check its format and version
+    ///
+    /// # Errors
+    /// Returns `DbError::InvalidSnapshot` if the bytes are not a supported snapshot
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, DbError> {
+        let snapshot: Self = ciborium::from_reader(bytes)
+            .map_err(|e| DbError::InvalidSnapshot(format!("Not a state snapshot: {e}")))?;
+        snapshot.check_header()?;
+        Ok(snapshot)
+    }
+
+    /// Check everything that needs no database
+    ///
+    /// Checks the header, checksum and row counts, then replays the batches
+    /// and requires the replayed IMT rows, tree state and root to be exactly
+    /// the ones in the snapshot. Returns the replayed tree.
+    ///
+    /// # Errors
+    /// Returns `DbError::InvalidSnapshot` describing the first problem found
+    pub fn verify(&self) -> Result<ReplayTree, DbError> {
+        let manifest = &self.manifest;
+        let contents = &self.contents;
+        self.check_header()?;
+
+        let checksum = contents.checksum()?;
+        if checksum != manifest.checksum {
+            return Err(DbError::InvalidSnapshot(format!(
+                "Checksum mismatch: manifest says 0x{}, contents hash to 0x{}",
+                hex::encode(manifest.checksum),
+                hex::encode(checksum)
+            )));
+        }
+
+        let counts = [
+            (
+                "transaction",
+                manifest.transaction_count,
+                contents.transactions.len(),
+            ),
+            ("batch", manifest.batch_count, contents.batches.len()),
+            ("commit", manifest.commit_count, contents.commits.len()),
+            (
+                "nullifier",
+                manifest.nullifier_count,
+                contents.nullifiers.len(),
+            ),
+            ("node", manifest.node_count, contents.nodes.len()),
+        ];
+        for (rows, expected, actual) in counts {
+            if expected != actual {
+                return Err(DbError::InvalidSnapshot(format!(
+                    "Manifest lists {expected} {rows} rows, snapshot has {actual}"
+                )));
+            }
+        }
+
+        if !contents.batches.windows(2).all(|w| w[0].id < w[1].id) {
+            return Err(DbError::InvalidSnapshot(
+                "Batches are not in id order".to_string(),
+            ));
+        }
+        match contents.batches.last() {
+            Some(last) if last.id == manifest.batch_id && !last.is_unwound() => {
+                if last.final_counter_value != manifest.counter_value {
+                    return Err(DbError::InvalidSnapshot(format!(
+                        "Batch {} ends at counter {}, manifest says {}",
+                        last.id, last.final_counter_value, manifest.counter_value
+                    )));
+                }
+            }
+            _ => {
+                return Err(DbError::InvalidSnapshot(format!(
+                    "Snapshot does not end with batch {}",
+                    manifest.batch_id
+                )))
+            }
+        }
+
+        let tree = contents.replay().map_err(|divergence| {
+            DbError::InvalidSnapshot(format!(
+                "Batch {} does not replay: {}",
+                divergence.batch_id, divergence.reason
+            ))
+        })?;
+        if tree.root() != manifest.imt_root {
+            return Err(DbError::InvalidSnapshot(format!(
+                "Replayed root 0x{} differs from the manifest root 0x{}",
+                hex::encode(tree.root()),
+                hex::encode(manifest.imt_root)
+            )));
+        }
+
+        let (nullifiers, nodes, undo_log, tree_state) = tree_rows(&tree);
+        let tables = [
+            ("nullifiers", nullifiers == contents.nullifiers),
+            ("merkle_nodes", nodes == contents.nodes),
+            ("nullifier_undo_log", undo_log == contents.undo_log),
+            ("tree_state", tree_state == contents.tree_state),
+        ];
+        if let Some((table, _)) = tables.iter().find(|(_, matches)| !matches) {
+            return Err(DbError::InvalidSnapshot(format!(
+                "{table} rows differ from the replayed tree"
+            )));
+        }
+
+        Ok(tree)
+    }
+
+    fn check_header(&self) -> Result<(), DbError> {
+        if self.manifest.format != STATE_SNAPSHOT_FORMAT {
+            return Err(DbError::InvalidSnapshot(format!(
+                "Not a state snapshot: format is {:?}",
+                self.manifest.format
+            )));
+        }
+        if self.manifest.version != STATE_SNAPSHOT_VERSION {
+            return Err(DbError::InvalidSnapshot(format!(
+                "Unsupported snapshot version {} (supported: {STATE_SNAPSHOT_VERSION})",
+                self.manifest.version
+            )));
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// EXPORT / IMPORT
+// ============================================================================
+
+/// Export the state at `batch_id`, or at the latest batch that was not unwound
+///
+/// Reads every table in one repeatable-read transaction, so concurrent batches
+/// don't leak into the snapshot. Batches up to `batch_id` are included with
+/// their transactions and commits, and the IMT is rebuilt by replaying them.
+///
+/// # Errors
+/// Returns `DbError::NotFound` if there is no such batch,
+/// `DbError::InvalidState` if it was unwound or the log does not replay,
+/// `DbError::Database` if a query fails
+#[instrument(skip(pool), level = "info")]
+pub async fn export_snapshot(
+    pool: &PgPool,
+    batch_id: Option<i32>,
+) -> Result<StateSnapshot, DbError> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+        .execute(&mut *tx)
+        .await?;
+
+    let batch_id = match batch_id {
+        Some(id) => id,
+        None => sqlx::query_scalar::<_, Option<i32>>(
+            "SELECT MAX(id) FROM proof_batches WHERE proof_status IS DISTINCT FROM 'unwound'",
+        )
+        .fetch_one(&mut *tx)
+        .await?
+        .ok_or_else(|| DbError::NotFound("batches to snapshot".to_string()))?,
+    };
+    info!("📦 Exporting state snapshot at batch {batch_id}");
+
+    let batches: Vec<SnapshotBatch> = sqlx::query(
+        r"
+        SELECT id, previous_counter_value, final_counter_value, transaction_ids,
+               sindri_proof_id, proof_status, created_at, proven_at,
+               posted_to_contract, posted_to_contract_at, parent_batch_id, tree_start_index
+        FROM proof_batches
+        WHERE id <= $1
+        ORDER BY id
+        ",
+    )
+    .bind(batch_id)
+    .fetch_all(&mut *tx)
+    .await?
+    .iter()
+    .map(|row| {
+        Ok(SnapshotBatch {
+            id: row.try_get("id")?,
+            previous_counter_value: row.try_get("previous_counter_value")?,
+            final_counter_value: row.try_get("final_counter_value")?,
+            transaction_ids: row.try_get("transaction_ids")?,
+            sindri_proof_id: row.try_get("sindri_proof_id")?,
+            proof_status: row
+                .try_get::<Option<String>, _>("proof_status")?
+                .unwrap_or_else(|| "pending".to_string()),
+            created_at: row.try_get("created_at")?,
+            proven_at: row.try_get("proven_at")?,
+            posted_to_contract: row.try_get("posted_to_contract")?,
+            posted_to_contract_at: row.try_get("posted_to_contract_at")?,
+            parent_batch_id: row.try_get("parent_batch_id")?,
+            tree_start_index: row.try_get("tree_start_index")?,
+        })
+    })
+    .collect::<Result<_, sqlx::Error>>()?;
+
+    let last = batches
+        .last()
+        .filter(|b| b.id == batch_id)
+        .ok_or_else(|| DbError::NotFound(format!("batch {batch_id}")))?;
+    if last.is_unwound() {
+        return Err(DbError::InvalidState(format!(
+            "Batch {batch_id} was unwound, its state is not part of the chain"
+        )));
+    }
+    let counter_value = last.final_counter_value;
+
+    // A transaction belongs to the live batch holding it; one that was only in
+    // unwound batches was pending at `batch_id`
+    let holders: HashMap<i32, i32> = batches
+        .iter()
+        .filter(|b| !b.is_unwound())
+        .flat_map(|b| b.transaction_ids.iter().map(move |&id| (id, b.id)))
+        .collect();
+    let mut transaction_ids: Vec<i32> = batches
+        .iter()
+        .flat_map(|b| b.transaction_ids.iter().copied())
+        .collect();
+    transaction_ids.sort_unstable();
+    transaction_ids.dedup();
+
+    let transactions: Vec<SnapshotTransaction> = sqlx::query(
+        r"
+        SELECT id, amount, created_at, client_tx_id
+        FROM incoming_transactions
+        WHERE id = ANY($1)
+        ORDER BY id
+        ",
+    )
+    .bind(&transaction_ids)
+    .fetch_all(&mut *tx)
+    .await?
+    .iter()
+    .map(|row| {
+        let id: i32 = row.try_get("id")?;
+        Ok(SnapshotTransaction {
+            id,
+            amount: row.try_get("amount")?,
+            included_in_batch_id: holders.get(&id).copied(),
+            created_at: row.try_get("created_at")?,
+            client_tx_id: row.try_get("client_tx_id")?,
+        })
+    })
+    .collect::<Result<_, sqlx::Error>>()?;
+
+    let commits: Vec<SnapshotCommit> = sqlx::query(
+        r"
+        SELECT batch_id, merkle_root, created_at
+        FROM ads_state_commits
+        WHERE batch_id <= $1
+        ORDER BY id
+        ",
+    )
+    .bind(batch_id)
+    .fetch_all(&mut *tx)
+    .await?
+    .iter()
+    .map(|row| {
+        Ok(SnapshotCommit {
+            batch_id: row.try_get("batch_id")?,
+            merkle_root: row.try_get("merkle_root")?,
+            created_at: row.try_get("created_at")?,
+        })
+    })
+    .collect::<Result<_, sqlx::Error>>()?;
+
+    tx.commit().await?;
+
+    let mut contents = SnapshotContents {
+        transactions,
+        batches,
+        commits,
+        nullifiers: Vec::new(),
+        nodes: Vec::new(),
+        undo_log: Vec::new(),
+        tree_state: SnapshotTreeState {
+            root: [0u8; 32],
+            next_available_index: 0,
+            total_nullifiers: 0,
+        },
+    };
+    let tree = contents.replay().map_err(|divergence| {
+        DbError::InvalidState(format!(
+            "State at batch {batch_id} cannot be exported, batch {} does not replay: {}",
+            divergence.batch_id, divergence.reason
+        ))
+    })?;
+    (
+        contents.nullifiers,
+        contents.nodes,
+        contents.undo_log,
+        contents.tree_state,
+    ) = tree_rows(&tree);
+
+    let manifest = SnapshotManifest {
+        format: STATE_SNAPSHOT_FORMAT.to_string(),
+        version: STATE_SNAPSHOT_VERSION,
+        batch_id,
+        counter_value,
+        imt_root: tree.root(),
+        exported_at: Utc::now(),
+        transaction_count: contents.transactions.len(),
+        batch_count: contents.batches.len(),
+        commit_count: contents.commits.len(),
+        nullifier_count: contents.nullifiers.len(),
+        node_count: contents.nodes.len(),
+        checksum: contents.checksum()?,
+    };
+
+    info!(
+        "✅ Snapshot at batch {batch_id}: {} batches, {} transactions, root {:02x?}",
+        manifest.batch_count,
+        manifest.transaction_count,
+        &manifest.imt_root[..8]
+    );
+    Ok(StateSnapshot { manifest, contents })
+}
+
+/// Load a snapshot into an empty database
+///
+/// The snapshot is verified with `StateSnapshot::verify` first. In one
+/// transaction the batches, transactions and commits are inserted with their
+/// original ids, the IMT tables are replaced by the replayed tree and the id
+/// sequences are moved past the imported rows. The root in `tree_state` and the
+/// last batch's committed root are checked against the manifest before commit.
+///
+/// # Errors
+/// Returns `DbError::InvalidSnapshot` if the snapshot fails verification,
+/// `DbError::InvalidState` if the database already has batches or
+/// transactions, `DbError::Database` if a query fails
+#[instrument(skip(pool, snapshot), fields(batch_id = snapshot.manifest.batch_id), level = "info")]
+pub async fn import_snapshot(pool: &PgPool, snapshot: &StateSnapshot) -> Result<(), DbError> {
+    let tree = snapshot.verify()?;
+    let manifest = &snapshot.manifest;
+    let contents = &snapshot.contents;
+    info!(
+        "📦 Importing state snapshot at batch {}: {} batches, {} transactions",
+        manifest.batch_id, manifest.batch_count, manifest.transaction_count
+    );
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "LOCK TABLE incoming_transactions, proof_batches, ads_state_commits IN EXCLUSIVE MODE",
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let occupied: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM proof_batches) OR EXISTS (SELECT 1 FROM incoming_transactions)",
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    if occupied {
+        return Err(DbError::InvalidState(
+            "Target database already has batches or transactions".to_string(),
+        ));
+    }
+
+    // Parents come first since batches are in id order
+    for batch in &contents.batches {
+        sqlx::query(
+            r"
+            INSERT INTO proof_batches (
+                id, previous_counter_value, final_counter_value, transaction_ids,
+                sindri_proof_id, proof_status, created_at, proven_at,
+                posted_to_contract, posted_to_contract_at, parent_batch_id, tree_start_index
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            ",
+        )
+        .bind(batch.id)
+        .bind(batch.previous_counter_value)
+        .bind(batch.final_counter_value)
+        .bind(&batch.transaction_ids)
+        .bind(&batch.sindri_proof_id)
+        .bind(&batch.proof_status)
+        .bind(batch.created_at)
+        .bind(batch.proven_at)
+        .bind(batch.posted_to_contract)
+        .bind(batch.posted_to_contract_at)
+        .bind(batch.parent_batch_id)
+        .bind(batch.tree_start_index)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for chunk in contents.transactions.chunks(IMPORT_CHUNK_SIZE) {
+        sqlx::query(
+            r"
+            INSERT INTO incoming_transactions (id, amount, included_in_batch_id, created_at, client_tx_id)
+            SELECT * FROM UNNEST($1::INTEGER[], $2::INTEGER[], $3::INTEGER[], $4::TIMESTAMPTZ[], $5::TEXT[])
+            ",
+        )
+        .bind(chunk.iter().map(|t| t.id).collect::<Vec<_>>())
+        .bind(chunk.iter().map(|t| t.amount).collect::<Vec<_>>())
+        .bind(chunk.iter().map(|t| t.included_in_batch_id).collect::<Vec<_>>())
+        .bind(chunk.iter().map(|t| t.created_at).collect::<Vec<_>>())
+        .bind(chunk.iter().map(|t| t.client_tx_id.clone()).collect::<Vec<_>>())
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    // WITH ORDINALITY keeps the commit order, so the first commit of each
+    // batch stays the one replay checks
+    for chunk in contents.commits.chunks(IMPORT_CHUNK_SIZE) {
+        sqlx::query(
+            r"
+            INSERT INTO ads_state_commits (batch_id, merkle_root, created_at)
+            SELECT batch_id, merkle_root, created_at
+            FROM UNNEST($1::INTEGER[], $2::BYTEA[], $3::TIMESTAMPTZ[])
+                WITH ORDINALITY AS c(batch_id, merkle_root, created_at, position)
+            ORDER BY position
+            ",
+        )
+        .bind(chunk.iter().map(|c| c.batch_id).collect::<Vec<_>>())
+        .bind(
+            chunk
+                .iter()
+                .map(|c| c.merkle_root.clone())
+                .collect::<Vec<_>>(),
+        )
+        .bind(chunk.iter().map(|c| c.created_at).collect::<Vec<_>>())
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    write_tree(&mut tx, &tree).await?;
+    queue_outstanding_outbox_jobs(&mut tx).await?;
+
+    for table in ["incoming_transactions", "proof_batches"] {
+        sqlx::query(&format!(
+            "SELECT setval(pg_get_serial_sequence('{table}', 'id'), COALESCE(MAX(id), 1), MAX(id) IS NOT NULL) FROM {table}"
+        ))
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    let live_root: Vec<u8> =
+        sqlx::query_scalar("SELECT root_hash FROM tree_state WHERE tree_id = 'default'")
+            .fetch_one(&mut *tx)
+            .await?;
+    let committed_root: Option<Vec<u8>> = sqlx::query_scalar(
+        "SELECT merkle_root FROM ads_state_commits WHERE batch_id = $1 ORDER BY id LIMIT 1",
+    )
+    .bind(manifest.batch_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    if live_root != manifest.imt_root || committed_root.as_deref() != Some(&manifest.imt_root[..]) {
+        warn!("Imported roots differ from the manifest, rolling back");
+        return Err(DbError::InvalidSnapshot(format!(
+            "Imported tree root 0x{} does not match the manifest root 0x{}",
+            hex::encode(&live_root),
+            hex::encode(manifest.imt_root)
+        )));
+    }
+
+    tx.commit().await?;
+
+    info!(
+        "✅ Snapshot imported at batch {}, root {:02x?}",
+        manifest.batch_id,
+        &manifest.imt_root[..8]
+    );
+    Ok(())
+}
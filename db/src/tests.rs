@@ -664,3 +664,1665 @@ mod integration_tests {
         }
     }
 }
+
+#[cfg(test)]
+mod listing_tests {
+    use super::*;
+    use crate::db::{
+        create_batch, list_batches, list_transactions, BatchListFilter, SortOrder,
+        TransactionListFilter, TransactionStatus,
+    };
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_list_transactions_keyset_pagination() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+
+        let mut submitted_ids = vec![];
+        for amount in 1..=7 {
+            let transaction = submit_transaction(&test_db.pool, amount)
+                .await
+                .expect("Failed to submit transaction");
+            submitted_ids.push(transaction.id);
+        }
+
+        // Walk all pages oldest-first, three at a time
+        let mut filter = TransactionListFilter {
+            limit: 3,
+            order: SortOrder::Asc,
+            ..Default::default()
+        };
+        let mut seen_ids = vec![];
+        let mut pages = 0;
+        loop {
+            let page = list_transactions(&test_db.pool, &filter)
+                .await
+                .expect("Failed to list transactions");
+            pages += 1;
+            seen_ids.extend(page.items.iter().map(|t| t.id));
+            match page.next_cursor {
+                Some(cursor) => filter.after_id = Some(cursor),
+                None => break,
+            }
+        }
+
+        assert_eq!(pages, 3);
+        assert_eq!(seen_ids, submitted_ids);
+
+        // Descending order returns newest first
+        let page = list_transactions(
+            &test_db.pool,
+            &TransactionListFilter {
+                limit: 2,
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("Failed to list transactions");
+        assert_eq!(
+            page.items.iter().map(|t| t.id).collect::<Vec<_>>(),
+            vec![submitted_ids[6], submitted_ids[5]]
+        );
+        assert_eq!(page.next_cursor, Some(submitted_ids[5]));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_list_transactions_filters() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+
+        for amount in [5, 10, 15, 20, 25] {
+            submit_transaction(&test_db.pool, amount)
+                .await
+                .expect("Failed to submit transaction");
+        }
+
+        // Batch the two oldest transactions
+        let batch = create_batch(&test_db.pool, Some(2))
+            .await
+            .expect("Failed to create batch")
+            .expect("Expected a batch");
+
+        let batched = list_transactions(
+            &test_db.pool,
+            &TransactionListFilter {
+                limit: 10,
+                status: Some(TransactionStatus::Batched),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("Failed to list batched transactions");
+        assert_eq!(batched.items.len(), 2);
+        assert!(batched
+            .items
+            .iter()
+            .all(|t| t.included_in_batch_id == Some(batch.id)));
+
+        let by_batch = list_transactions(
+            &test_db.pool,
+            &TransactionListFilter {
+                limit: 10,
+                batch_id: Some(batch.id),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("Failed to list transactions by batch");
+        assert_eq!(by_batch.items, batched.items);
+
+        let pending_in_range = list_transactions(
+            &test_db.pool,
+            &TransactionListFilter {
+                limit: 10,
+                status: Some(TransactionStatus::Pending),
+                min_amount: Some(16),
+                max_amount: Some(25),
+                order: SortOrder::Asc,
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("Failed to list pending transactions by amount");
+        let amounts: Vec<i32> = pending_in_range.items.iter().map(|t| t.amount).collect();
+        assert_eq!(amounts, vec![20, 25]);
+        assert!(pending_in_range.next_cursor.is_none());
+
+        let in_future = list_transactions(
+            &test_db.pool,
+            &TransactionListFilter {
+                limit: 10,
+                created_after: Some(chrono::Utc::now() + chrono::Duration::hours(1)),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("Failed to list transactions by time");
+        assert!(in_future.items.is_empty());
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_list_batches_status_filter_and_cursor() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+
+        for amount in 1..=3 {
+            submit_transaction(&test_db.pool, amount)
+                .await
+                .expect("Failed to submit transaction");
+        }
+        let mut batch_ids = vec![];
+        for _ in 0..3 {
+            let batch = create_batch(&test_db.pool, Some(1))
+                .await
+                .expect("Failed to create batch")
+                .expect("Expected a batch");
+            batch_ids.push(batch.id);
+        }
+
+        crate::db::update_batch_proof(&test_db.pool, batch_ids[1], "proof_1", "proven")
+            .await
+            .expect("Failed to update batch proof");
+
+        let proven = list_batches(
+            &test_db.pool,
+            &BatchListFilter {
+                limit: 10,
+                proof_status: Some("proven".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("Failed to list proven batches");
+        assert_eq!(proven.items.len(), 1);
+        assert_eq!(proven.items[0].id, batch_ids[1]);
+
+        let first_page = list_batches(
+            &test_db.pool,
+            &BatchListFilter {
+                limit: 2,
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("Failed to list batches");
+        assert_eq!(first_page.items.len(), 2);
+        assert_eq!(first_page.items[0].id, batch_ids[2]);
+
+        let second_page = list_batches(
+            &test_db.pool,
+            &BatchListFilter {
+                limit: 2,
+                after_id: first_page.next_cursor,
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("Failed to list second page of batches");
+        assert_eq!(second_page.items.len(), 1);
+        assert_eq!(second_page.items[0].id, batch_ids[0]);
+        assert!(second_page.next_cursor.is_none());
+    }
+}
+
+#[cfg(test)]
+mod idempotency_tests {
+    use super::*;
+    use crate::db::submit_transaction_idempotent;
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_resubmission_with_same_key_returns_original() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+
+        let first = submit_transaction_idempotent(&test_db.pool, 42, Some("retry-key-1"))
+            .await
+            .expect("Failed to submit transaction");
+        assert!(!first.replayed);
+        assert_eq!(first.client_tx_id.as_deref(), Some("retry-key-1"));
+
+        let retry = submit_transaction_idempotent(&test_db.pool, 42, Some("retry-key-1"))
+            .await
+            .expect("Failed to resubmit transaction");
+        assert!(retry.replayed);
+        assert_eq!(retry.transaction, first.transaction);
+
+        // Only one row was enqueued
+        let pending = get_pending_transactions(&test_db.pool)
+            .await
+            .expect("Failed to retrieve pending transactions");
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, first.transaction.id);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_distinct_and_missing_keys_insert_separately() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+
+        submit_transaction_idempotent(&test_db.pool, 1, Some("key-a"))
+            .await
+            .expect("Failed to submit transaction");
+        submit_transaction_idempotent(&test_db.pool, 1, Some("key-b"))
+            .await
+            .expect("Failed to submit transaction");
+        submit_transaction_idempotent(&test_db.pool, 1, None)
+            .await
+            .expect("Failed to submit transaction");
+        submit_transaction_idempotent(&test_db.pool, 1, None)
+            .await
+            .expect("Failed to submit transaction");
+
+        let pending = get_pending_transactions(&test_db.pool)
+            .await
+            .expect("Failed to retrieve pending transactions");
+        assert_eq!(pending.len(), 4);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_concurrent_retries_insert_once() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+
+        let mut tasks = vec![];
+        for _ in 0..8 {
+            let pool = test_db.pool.clone();
+            tasks.push(tokio::spawn(async move {
+                submit_transaction_idempotent(&pool, 7, Some("concurrent-key"))
+                    .await
+                    .expect("Failed to submit transaction")
+            }));
+        }
+
+        let mut ids = vec![];
+        let mut fresh_inserts = 0;
+        for task in tasks {
+            let submitted = task.await.expect("Task failed");
+            ids.push(submitted.transaction.id);
+            if !submitted.replayed {
+                fresh_inserts += 1;
+            }
+        }
+
+        ids.dedup();
+        assert_eq!(ids.len(), 1);
+        assert_eq!(fresh_inserts, 1);
+    }
+}
+
+#[cfg(test)]
+mod bulk_submission_tests {
+    use super::*;
+    use crate::db::{submit_transaction_idempotent, submit_transactions_bulk, NewTransaction};
+    use tracing_test::traced_test;
+
+    fn item(amount: i32, key: Option<&str>) -> NewTransaction {
+        NewTransaction {
+            amount,
+            client_tx_id: key.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_bulk_results_follow_input_order() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+
+        let items = vec![
+            item(10, None),
+            item(20, Some("bulk-a")),
+            item(30, None),
+            item(40, Some("bulk-b")),
+        ];
+
+        let mut conn = test_db.pool.acquire().await.expect("Failed to acquire");
+        let results = submit_transactions_bulk(&mut *conn, &items)
+            .await
+            .expect("Failed to bulk submit");
+
+        assert_eq!(results.len(), items.len());
+        for (result, item) in results.iter().zip(&items) {
+            assert_eq!(result.transaction.amount, item.amount);
+            assert_eq!(result.client_tx_id, item.client_tx_id);
+            assert!(!result.replayed);
+        }
+        assert!(results
+            .windows(2)
+            .all(|pair| pair[0].transaction.id < pair[1].transaction.id));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_bulk_replays_existing_keys() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+
+        let original = submit_transaction_idempotent(&test_db.pool, 7, Some("bulk-existing"))
+            .await
+            .expect("Failed to submit transaction");
+
+        let mut conn = test_db.pool.acquire().await.expect("Failed to acquire");
+        let results = submit_transactions_bulk(
+            &mut *conn,
+            &[item(7, Some("bulk-existing")), item(8, Some("bulk-new"))],
+        )
+        .await
+        .expect("Failed to bulk submit");
+
+        assert!(results[0].replayed);
+        assert_eq!(results[0].transaction, original.transaction);
+        assert!(!results[1].replayed);
+
+        let pending = get_pending_transactions(&test_db.pool)
+            .await
+            .expect("Failed to retrieve pending transactions");
+        assert_eq!(pending.len(), 2);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_bulk_rollback_discards_all_rows() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+
+        let mut tx = test_db.pool.begin().await.expect("Failed to begin");
+        let results = submit_transactions_bulk(&mut *tx, &[item(1, None), item(2, None)])
+            .await
+            .expect("Failed to bulk submit");
+        assert_eq!(results.len(), 2);
+        tx.rollback().await.expect("Failed to roll back");
+
+        let pending = get_pending_transactions(&test_db.pool)
+            .await
+            .expect("Failed to retrieve pending transactions");
+        assert!(pending.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod batch_submission_tests {
+    use super::*;
+    use crate::db::{
+        create_batch, get_batch_submissions, record_batch_submission, BatchSubmissionUpdate,
+    };
+    use tracing_test::traced_test;
+
+    fn update(batch_id: i32, target: &str, status: &str) -> BatchSubmissionUpdate {
+        BatchSubmissionUpdate {
+            batch_id,
+            target: target.to_string(),
+            chain_id: 11_155_111,
+            status: status.to_string(),
+            transaction_hash: None,
+            block_number: None,
+            error: None,
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_submissions_are_tracked_per_target() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+
+        submit_transaction(&test_db.pool, 5)
+            .await
+            .expect("Failed to submit transaction");
+        let batch = create_batch(&test_db.pool, Some(1))
+            .await
+            .expect("Failed to create batch")
+            .expect("Expected a batch");
+
+        record_batch_submission(&test_db.pool, &update(batch.id, "sepolia", "submitted"))
+            .await
+            .expect("Failed to record submission");
+        record_batch_submission(&test_db.pool, &update(batch.id, "base-sepolia", "failed"))
+            .await
+            .expect("Failed to record submission");
+
+        // A later status for the same target overwrites the earlier one
+        let confirmed = record_batch_submission(
+            &test_db.pool,
+            &BatchSubmissionUpdate {
+                transaction_hash: Some(format!("0x{}", "ab".repeat(32))),
+                block_number: Some(42),
+                ..update(batch.id, "sepolia", "confirmed")
+            },
+        )
+        .await
+        .expect("Failed to record submission");
+        assert_eq!(confirmed.status, "confirmed");
+        assert_eq!(confirmed.block_number, Some(42));
+
+        let submissions = get_batch_submissions(&test_db.pool, &[batch.id])
+            .await
+            .expect("Failed to get submissions");
+        let statuses: Vec<_> = submissions
+            .iter()
+            .map(|s| (s.target.as_str(), s.status.as_str()))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![("base-sepolia", "failed"), ("sepolia", "confirmed")]
+        );
+
+        assert!(get_batch_submissions(&test_db.pool, &[batch.id + 1])
+            .await
+            .expect("Failed to get submissions")
+            .is_empty());
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_unknown_submission_status_is_rejected() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+
+        submit_transaction(&test_db.pool, 5)
+            .await
+            .expect("Failed to submit transaction");
+        let batch = create_batch(&test_db.pool, Some(1))
+            .await
+            .expect("Failed to create batch")
+            .expect("Expected a batch");
+
+        assert!(
+            record_batch_submission(&test_db.pool, &update(batch.id, "sepolia", "posted"))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_posted_flag_can_be_cleared_for_reposting() {
+        use crate::db::{
+            get_ads_state_commits, get_batch_by_id, mark_batch_posted_to_contract,
+            store_ads_state_commit, unmark_batch_posted_to_contract,
+        };
+
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+
+        submit_transaction(&test_db.pool, 5)
+            .await
+            .expect("Failed to submit transaction");
+        let batch = create_batch(&test_db.pool, Some(1))
+            .await
+            .expect("Failed to create batch")
+            .expect("Expected a batch");
+        store_ads_state_commit(&test_db.pool, batch.id, &[7u8; 32])
+            .await
+            .expect("Failed to store state commit");
+
+        mark_batch_posted_to_contract(&test_db.pool, batch.id)
+            .await
+            .expect("Failed to mark batch posted");
+        unmark_batch_posted_to_contract(&test_db.pool, batch.id)
+            .await
+            .expect("Failed to clear posted flag");
+
+        let reloaded = get_batch_by_id(&test_db.pool, batch.id)
+            .await
+            .expect("Failed to get batch");
+        assert!(reloaded.posted_to_contract_at.is_none());
+
+        // Cleared batches can be marked again
+        mark_batch_posted_to_contract(&test_db.pool, batch.id)
+            .await
+            .expect("Failed to mark batch posted again");
+
+        let commits = get_ads_state_commits(&test_db.pool, &[batch.id, batch.id + 1])
+            .await
+            .expect("Failed to get state commits");
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].merkle_root, vec![7u8; 32]);
+    }
+}
+
+#[cfg(test)]
+mod lineage_tests {
+    use super::*;
+    use crate::db::{
+        create_batch, get_batch_by_id, get_proven_unposted_batches, get_transaction_inclusion,
+        mark_batch_posted_to_contract, reset_failed_batch_proof, set_batch_tree_start_index,
+        store_ads_state_commit, store_transaction_inclusions, unwind_failed_batch,
+        update_batch_proof, ProofBatch, TransactionInclusion,
+    };
+    use crate::error::DbError;
+    use crate::merkle_tree::{IndexedMerkleTree, NullifierDb, TreeStateDb};
+    use tracing_test::traced_test;
+
+    /// Create a batch of `amounts`, insert `nullifiers` into the IMT for it and
+    /// commit the resulting root, like the unified batch service does
+    async fn batch_with_nullifiers(
+        test_db: &TestDatabase,
+        tree: &mut IndexedMerkleTree,
+        amounts: &[i32],
+        nullifiers: &[i64],
+    ) -> (ProofBatch, [u8; 32]) {
+        for amount in amounts {
+            submit_transaction(&test_db.pool, *amount)
+                .await
+                .expect("Failed to submit transaction");
+        }
+        let batch = create_batch(&test_db.pool, Some(amounts.len() as i32))
+            .await
+            .expect("Failed to create batch")
+            .expect("Expected a batch");
+
+        // get_next_index() would allocate the index, read it instead
+        let start = TreeStateDb::new(test_db.pool.clone())
+            .get_state(None)
+            .await
+            .expect("Failed to get tree state")
+            .expect("Expected tree state")
+            .next_available_index;
+        set_batch_tree_start_index(&test_db.pool, batch.id, start)
+            .await
+            .expect("Failed to record tree start index");
+
+        for nullifier in nullifiers {
+            tree.insert_nullifier(*nullifier)
+                .await
+                .expect("Failed to insert nullifier");
+        }
+        let root = tree.get_root().await.expect("Failed to get root");
+        store_ads_state_commit(&test_db.pool, batch.id, &root)
+            .await
+            .expect("Failed to store state commit");
+
+        (batch, root)
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_posting_follows_lineage() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+
+        let mut batches = Vec::new();
+        for amount in [5, 7, 9] {
+            submit_transaction(&test_db.pool, amount)
+                .await
+                .expect("Failed to submit transaction");
+            batches.push(
+                create_batch(&test_db.pool, Some(1))
+                    .await
+                    .expect("Failed to create batch")
+                    .expect("Expected a batch"),
+            );
+        }
+        assert_eq!(batches[0].parent_batch_id, None);
+        assert_eq!(batches[1].parent_batch_id, Some(batches[0].id));
+        assert_eq!(batches[2].parent_batch_id, Some(batches[1].id));
+
+        // Proven children wait for their unproven parent
+        for batch in &batches[1..] {
+            update_batch_proof(&test_db.pool, batch.id, "proof", "proven")
+                .await
+                .expect("Failed to update batch");
+        }
+        assert!(get_proven_unposted_batches(&test_db.pool, None)
+            .await
+            .expect("Failed to get postable batches")
+            .is_empty());
+
+        update_batch_proof(&test_db.pool, batches[0].id, "proof", "proven")
+            .await
+            .expect("Failed to update batch");
+        let postable: Vec<i32> = get_proven_unposted_batches(&test_db.pool, None)
+            .await
+            .expect("Failed to get postable batches")
+            .iter()
+            .map(|b| b.id)
+            .collect();
+        assert_eq!(postable, batches.iter().map(|b| b.id).collect::<Vec<_>>());
+
+        mark_batch_posted_to_contract(&test_db.pool, batches[0].id)
+            .await
+            .expect("Failed to mark batch posted");
+        let postable = get_proven_unposted_batches(&test_db.pool, Some(1))
+            .await
+            .expect("Failed to get postable batches");
+        assert_eq!(postable[0].id, batches[1].id);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_unwind_restores_parent_root_and_releases_transactions() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+        let mut tree = IndexedMerkleTree::new(test_db.pool.clone());
+
+        let (parent, parent_root) =
+            batch_with_nullifiers(&test_db, &mut tree, &[5, 7], &[400, 100]).await;
+        let (failed, failed_root) =
+            batch_with_nullifiers(&test_db, &mut tree, &[1, 2], &[50, 250]).await;
+        let (child, _) = batch_with_nullifiers(&test_db, &mut tree, &[3], &[900]).await;
+        assert_eq!(child.parent_batch_id, Some(failed.id));
+
+        update_batch_proof(&test_db.pool, failed.id, "failed_proof", "failed")
+            .await
+            .expect("Failed to update batch");
+
+        let unwind = unwind_failed_batch(&test_db.pool, failed.id)
+            .await
+            .expect("Failed to unwind batch");
+        assert_eq!(unwind.parent_batch_id, Some(parent.id));
+        assert_eq!(unwind.unwound_batch_ids, vec![failed.id, child.id]);
+        let mut released = failed.transaction_ids.clone();
+        released.extend(&child.transaction_ids);
+        assert_eq!(unwind.released_transaction_ids, released);
+        assert_eq!(unwind.restored_root, parent_root.to_vec());
+        assert_eq!(
+            tree.get_root().await.expect("Failed to get root"),
+            parent_root
+        );
+
+        let nullifiers = NullifierDb::new(test_db.pool.clone());
+        for value in [50, 250, 900] {
+            assert!(!nullifiers.exists(value).await.expect("Failed to check"));
+        }
+        assert!(nullifiers.exists(400).await.expect("Failed to check"));
+
+        let pending = get_pending_transactions(&test_db.pool)
+            .await
+            .expect("Failed to retrieve pending transactions");
+        assert_eq!(pending.len(), 3);
+        let reloaded = get_batch_by_id(&test_db.pool, child.id)
+            .await
+            .expect("Failed to get batch");
+        assert_eq!(reloaded.proof_status, "unwound");
+
+        // Re-batching builds on the parent and reproduces the same tree
+        let rebatch = create_batch(&test_db.pool, Some(2))
+            .await
+            .expect("Failed to create batch")
+            .expect("Expected a batch");
+        assert_eq!(rebatch.parent_batch_id, Some(parent.id));
+        assert_eq!(rebatch.previous_counter_value, parent.final_counter_value);
+        for nullifier in [50, 250] {
+            tree.insert_nullifier(nullifier)
+                .await
+                .expect("Failed to insert nullifier");
+        }
+        assert_eq!(
+            tree.get_root().await.expect("Failed to get root"),
+            failed_root
+        );
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_unwind_refuses_unfailed_and_settled_lineage() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+        let mut tree = IndexedMerkleTree::new(test_db.pool.clone());
+
+        let (first, _) = batch_with_nullifiers(&test_db, &mut tree, &[5], &[10]).await;
+        let (second, _) = batch_with_nullifiers(&test_db, &mut tree, &[6], &[20]).await;
+
+        assert!(matches!(
+            unwind_failed_batch(&test_db.pool, first.id).await,
+            Err(DbError::InvalidState(_))
+        ));
+        assert!(matches!(
+            unwind_failed_batch(&test_db.pool, second.id + 1).await,
+            Err(DbError::NotFound(_))
+        ));
+
+        update_batch_proof(&test_db.pool, first.id, "failed_proof", "failed")
+            .await
+            .expect("Failed to update batch");
+        mark_batch_posted_to_contract(&test_db.pool, second.id)
+            .await
+            .expect("Failed to mark batch posted");
+        assert!(matches!(
+            unwind_failed_batch(&test_db.pool, first.id).await,
+            Err(DbError::InvalidState(_))
+        ));
+
+        // Nothing was changed by the refused unwinds
+        let reloaded = get_batch_by_id(&test_db.pool, second.id)
+            .await
+            .expect("Failed to get batch");
+        assert_eq!(reloaded.proof_status, "pending");
+        assert!(NullifierDb::new(test_db.pool.clone())
+            .exists(20)
+            .await
+            .expect("Failed to check"));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_reset_failed_batch_proof() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+
+        submit_transaction(&test_db.pool, 5)
+            .await
+            .expect("Failed to submit transaction");
+        let batch = create_batch(&test_db.pool, Some(1))
+            .await
+            .expect("Failed to create batch")
+            .expect("Expected a batch");
+
+        assert!(matches!(
+            reset_failed_batch_proof(&test_db.pool, batch.id).await,
+            Err(DbError::InvalidState(_))
+        ));
+        assert!(matches!(
+            reset_failed_batch_proof(&test_db.pool, batch.id + 1).await,
+            Err(DbError::NotFound(_))
+        ));
+
+        update_batch_proof(&test_db.pool, batch.id, "failed_proof", "failed")
+            .await
+            .expect("Failed to update batch");
+        reset_failed_batch_proof(&test_db.pool, batch.id)
+            .await
+            .expect("Failed to reset proof");
+
+        let reloaded = get_batch_by_id(&test_db.pool, batch.id)
+            .await
+            .expect("Failed to get batch");
+        assert_eq!(reloaded.proof_status, "pending");
+        assert_eq!(reloaded.sindri_proof_id, None);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_transaction_inclusions_follow_batch_lineage() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+        let mut tree = IndexedMerkleTree::new(test_db.pool.clone());
+
+        let (parent, _) = batch_with_nullifiers(&test_db, &mut tree, &[5], &[400]).await;
+        let (failed, failed_root) = batch_with_nullifiers(&test_db, &mut tree, &[7], &[100]).await;
+
+        let mut inclusions = Vec::new();
+        for (batch, value) in [(&parent, 400), (&failed, 100)] {
+            let nullifier = NullifierDb::new(test_db.pool.clone())
+                .get_by_value(value)
+                .await
+                .expect("Failed to get nullifier")
+                .expect("Expected a nullifier");
+            let proof = tree
+                .generate_merkle_proof(nullifier.tree_index)
+                .await
+                .expect("Failed to generate proof");
+            inclusions.push(TransactionInclusion {
+                transaction_id: batch.transaction_ids[0],
+                batch_id: batch.id,
+                nullifier_value: value,
+                leaf_index: proof.leaf_index,
+                next_index: nullifier.next_index.unwrap_or(0),
+                next_value: nullifier.next_value,
+                siblings: proof.siblings.iter().map(|s| s.to_vec()).collect(),
+                path_indices: proof.path_indices,
+                merkle_root: failed_root.to_vec(),
+            });
+        }
+        store_transaction_inclusions(&test_db.pool, &inclusions)
+            .await
+            .expect("Failed to store inclusions");
+        // Storing again replaces the rows
+        store_transaction_inclusions(&test_db.pool, &inclusions)
+            .await
+            .expect("Failed to store inclusions");
+
+        let stored = get_transaction_inclusion(&test_db.pool, failed.transaction_ids[0])
+            .await
+            .expect("Failed to get inclusion")
+            .expect("Expected an inclusion");
+        assert_eq!(stored.batch_id, failed.id);
+        assert_eq!(stored.nullifier_value, 100);
+        assert_eq!(stored.siblings, inclusions[1].siblings);
+        assert_eq!(stored.path_indices, inclusions[1].path_indices);
+        assert_eq!(stored.merkle_root, failed_root.to_vec());
+
+        update_batch_proof(&test_db.pool, failed.id, "failed_proof", "failed")
+            .await
+            .expect("Failed to update batch");
+        unwind_failed_batch(&test_db.pool, failed.id)
+            .await
+            .expect("Failed to unwind batch");
+
+        assert!(
+            get_transaction_inclusion(&test_db.pool, failed.transaction_ids[0])
+                .await
+                .expect("Failed to get inclusion")
+                .is_none()
+        );
+        assert!(
+            get_transaction_inclusion(&test_db.pool, parent.transaction_ids[0])
+                .await
+                .expect("Failed to get inclusion")
+                .is_some()
+        );
+    }
+}
+
+#[cfg(test)]
+mod replay_tests {
+    use super::*;
+    use crate::db::{
+        create_batch, get_transaction_by_id, set_batch_tree_start_index, store_ads_state_commit,
+        ProofBatch,
+    };
+    use crate::error::DbError;
+    use crate::merkle_tree::IndexedMerkleTree;
+    use crate::replay::{replay_state, swap_in_replayed_state};
+    use arithmetic_lib::receipt::transaction_nullifier;
+    use tracing_test::traced_test;
+
+    /// Batch `amounts` and insert their transaction nullifiers the way the
+    /// unified batch service does
+    pub(super) async fn batch_through_tree(
+        test_db: &TestDatabase,
+        tree: &mut IndexedMerkleTree,
+        amounts: &[i32],
+    ) -> (ProofBatch, [u8; 32]) {
+        for amount in amounts {
+            submit_transaction(&test_db.pool, *amount)
+                .await
+                .expect("Failed to submit transaction");
+        }
+        let batch = create_batch(&test_db.pool, Some(amounts.len() as i32))
+            .await
+            .expect("Failed to create batch")
+            .expect("Expected a batch");
+
+        for (i, id) in batch.transaction_ids.iter().enumerate() {
+            let transaction = get_transaction_by_id(&test_db.pool, *id)
+                .await
+                .expect("Failed to get transaction")
+                .expect("Expected a transaction");
+            let inserted = tree
+                .insert_nullifier(transaction_nullifier(
+                    transaction.id,
+                    transaction.amount,
+                    transaction.created_at.timestamp(),
+                ))
+                .await
+                .expect("Failed to insert nullifier");
+            if i == 0 {
+                set_batch_tree_start_index(&test_db.pool, batch.id, inserted.nullifier.tree_index)
+                    .await
+                    .expect("Failed to record tree start index");
+            }
+        }
+        let root = tree.get_root().await.expect("Failed to get root");
+        store_ads_state_commit(&test_db.pool, batch.id, &root)
+            .await
+            .expect("Failed to store state commit");
+
+        (batch, root)
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_replay_detects_and_repairs_corrupted_tables() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+        let mut tree = IndexedMerkleTree::new(test_db.pool.clone());
+
+        batch_through_tree(&test_db, &mut tree, &[5, 7, 9]).await;
+        let (_, root) = batch_through_tree(&test_db, &mut tree, &[1, 2]).await;
+
+        let replay = replay_state(&test_db.pool).await.expect("Failed to replay");
+        assert_eq!(replay.divergence, None);
+        assert_eq!(replay.batches_replayed, 2);
+        assert_eq!(replay.transactions_replayed, 5);
+        assert_eq!(replay.tree.root(), root);
+        assert!(replay
+            .live
+            .as_ref()
+            .expect("Expected a comparison")
+            .is_clean());
+
+        // Corrupt a pointer and an internal node
+        sqlx::query("UPDATE nullifiers SET next_index = NULL, next_value = 0 WHERE tree_index = 3")
+            .execute(&test_db.pool)
+            .await
+            .expect("Failed to corrupt nullifier");
+        sqlx::query(
+            "UPDATE merkle_nodes SET hash_value = $1 WHERE tree_level = 1 AND node_index = 0",
+        )
+        .bind([7u8; 32].as_slice())
+        .execute(&test_db.pool)
+        .await
+        .expect("Failed to corrupt node");
+
+        let replay = replay_state(&test_db.pool).await.expect("Failed to replay");
+        let live = replay.live.clone().expect("Expected a comparison");
+        assert!(live.root_matches);
+        assert_eq!(live.nullifier_mismatches, 1);
+        assert_eq!(live.node_mismatches, 1);
+
+        swap_in_replayed_state(&test_db.pool, &replay)
+            .await
+            .expect("Failed to swap in replayed state");
+
+        let replay = replay_state(&test_db.pool).await.expect("Failed to replay");
+        assert!(replay.live.expect("Expected a comparison").is_clean());
+        assert_eq!(tree.get_root().await.expect("Failed to get root"), root);
+
+        // The rebuilt tables keep accepting insertions
+        let (_, next_root) = batch_through_tree(&test_db, &mut tree, &[3]).await;
+        let replay = replay_state(&test_db.pool).await.expect("Failed to replay");
+        assert_eq!(replay.divergence, None);
+        assert_eq!(replay.tree.root(), next_root);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_replay_reports_first_divergence() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+        let mut tree = IndexedMerkleTree::new(test_db.pool.clone());
+
+        batch_through_tree(&test_db, &mut tree, &[5]).await;
+        let (second, second_root) = batch_through_tree(&test_db, &mut tree, &[7]).await;
+        batch_through_tree(&test_db, &mut tree, &[9]).await;
+
+        sqlx::query("UPDATE ads_state_commits SET merkle_root = $1 WHERE batch_id = $2")
+            .bind([1u8; 32].as_slice())
+            .bind(second.id)
+            .execute(&test_db.pool)
+            .await
+            .expect("Failed to alter committed root");
+
+        let replay = replay_state(&test_db.pool).await.expect("Failed to replay");
+        let divergence = replay.divergence.clone().expect("Expected a divergence");
+        assert_eq!(divergence.batch_id, second.id);
+        assert_eq!(divergence.committed_root, Some(vec![1u8; 32]));
+        assert_eq!(divergence.replayed_root, Some(second_root.to_vec()));
+        assert_eq!(replay.batches_replayed, 1);
+        assert_eq!(replay.live, None);
+
+        assert!(matches!(
+            swap_in_replayed_state(&test_db.pool, &replay).await,
+            Err(DbError::InvalidState(_))
+        ));
+    }
+}
+
+mod snapshot_tests {
+    use super::replay_tests::batch_through_tree;
+    use super::*;
+    use crate::db::{
+        create_batch, get_current_counter_value, list_transactions, TransactionListFilter,
+    };
+    use crate::error::DbError;
+    use crate::merkle_tree::IndexedMerkleTree;
+    use crate::replay::replay_state;
+    use crate::snapshot::{export_snapshot, import_snapshot, StateSnapshot};
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_snapshot_round_trip_at_batch() {
+        let source = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+        let mut tree = IndexedMerkleTree::new(source.pool.clone());
+
+        batch_through_tree(&source, &mut tree, &[5, 7, 9]).await;
+        let (second, second_root) = batch_through_tree(&source, &mut tree, &[1, 2]).await;
+        batch_through_tree(&source, &mut tree, &[3]).await;
+
+        let snapshot = export_snapshot(&source.pool, Some(second.id))
+            .await
+            .expect("Failed to export snapshot");
+        assert_eq!(snapshot.manifest.batch_id, second.id);
+        assert_eq!(snapshot.manifest.counter_value, 24);
+        assert_eq!(snapshot.manifest.imt_root, second_root);
+        assert_eq!(snapshot.manifest.batch_count, 2);
+        assert_eq!(snapshot.manifest.transaction_count, 5);
+        assert_eq!(snapshot.manifest.nullifier_count, 6);
+
+        let bytes = snapshot.to_cbor().expect("Failed to encode snapshot");
+        let decoded = StateSnapshot::from_cbor(&bytes).expect("Failed to decode snapshot");
+        assert_eq!(decoded, snapshot);
+
+        let target = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+        import_snapshot(&target.pool, &decoded)
+            .await
+            .expect("Failed to import snapshot");
+
+        let replay = replay_state(&target.pool).await.expect("Failed to replay");
+        assert_eq!(replay.divergence, None);
+        assert_eq!(replay.batches_replayed, 2);
+        assert!(replay.live.expect("Expected a comparison").is_clean());
+        assert_eq!(
+            get_current_counter_value(&target.pool)
+                .await
+                .expect("Failed to get counter"),
+            24
+        );
+        let transactions = list_transactions(&target.pool, &TransactionListFilter::default())
+            .await
+            .expect("Failed to list transactions");
+        assert_eq!(transactions.items.len(), 5);
+
+        // The imported state keeps going, with ids after the imported rows
+        let mut target_tree = IndexedMerkleTree::new(target.pool.clone());
+        assert_eq!(
+            target_tree.get_root().await.expect("Failed to get root"),
+            second_root
+        );
+        let (third, _) = batch_through_tree(&target, &mut target_tree, &[4]).await;
+        assert_eq!(third.id, second.id + 1);
+        assert_eq!(third.transaction_ids, vec![7]);
+
+        // A second import would mix two histories
+        assert!(matches!(
+            import_snapshot(&target.pool, &decoded).await,
+            Err(DbError::InvalidState(_))
+        ));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_snapshot_import_rejects_tampered_state() {
+        let source = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+        let mut tree = IndexedMerkleTree::new(source.pool.clone());
+        batch_through_tree(&source, &mut tree, &[5, 7]).await;
+
+        let snapshot = export_snapshot(&source.pool, None)
+            .await
+            .expect("Failed to export snapshot");
+        let target = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+
+        // Any edit without a new checksum is caught by the checksum
+        let mut edited = snapshot.clone();
+        edited.contents.transactions[0].amount = 50;
+        assert!(matches!(
+            import_snapshot(&target.pool, &edited).await,
+            Err(DbError::InvalidSnapshot(_))
+        ));
+
+        // A consistent-looking edit still has to replay to the committed roots
+        edited.manifest.checksum = edited
+            .contents
+            .checksum()
+            .expect("Failed to compute checksum");
+        assert!(matches!(
+            import_snapshot(&target.pool, &edited).await,
+            Err(DbError::InvalidSnapshot(_))
+        ));
+
+        let mut edited = snapshot.clone();
+        edited.contents.nodes[0].hash = [9u8; 32];
+        edited.manifest.checksum = edited
+            .contents
+            .checksum()
+            .expect("Failed to compute checksum");
+        assert!(matches!(
+            import_snapshot(&target.pool, &edited).await,
+            Err(DbError::InvalidSnapshot(_))
+        ));
+
+        let batches: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM proof_batches")
+            .fetch_one(&target.pool)
+            .await
+            .expect("Failed to count batches");
+        assert_eq!(batches, 0);
+    }
+}
+
+mod merkle_engine_tests {
+    use super::*;
+    use crate::merkle_tree::IndexedMerkleTree;
+    use crate::merkle_tree_32::{
+        genesis_root, node_hash, zero_hashes, BatchUpdate, MerkleProof, MerkleTree32, GENESIS_LEAF,
+    };
+    use crate::replay::ReplayTree;
+    use tracing_test::traced_test;
+
+    /// Roots after inserting each value into the genesis tree, in order
+    const INSERTION_ROOTS: [(i64, &str); 3] = [
+        (
+            100,
+            "a1aa31005a5a2a8641bf5f962b4cadf611a33b9221227bd34d278d4827b26556",
+        ),
+        (
+            50,
+            "0a8a3796b17bc77337af28b56b06f8435ec9c04f11f9b6ef256539bb7d1b97dc",
+        ),
+        (
+            200,
+            "b52348b11c8e31c9f6d032ba43214105e5c63e9199d8d17c074a4c48bcdf6c20",
+        ),
+    ];
+
+    fn hash(value: &str) -> [u8; 32] {
+        hex::decode(value)
+            .expect("Invalid hex")
+            .try_into()
+            .expect("Expected 32 bytes")
+    }
+
+    #[test]
+    fn test_zero_hash_vectors() {
+        assert_eq!(
+            zero_hashes()[0],
+            hash("66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925")
+        );
+        assert_eq!(
+            zero_hashes()[32],
+            hash("b39f2380bce216f431684ea51c999b6fb8cbe91b0c61ce3914b605cce8ee88fa")
+        );
+    }
+
+    #[test]
+    fn test_genesis_root_vector() {
+        let genesis = hash("b3496ac277db8e304997228d44542a27422379552813adc40c4bb65072403956");
+        assert_eq!(genesis_root(), genesis);
+        assert_eq!(ReplayTree::genesis().root(), genesis);
+
+        // The genesis leaf's path is made of empty subtrees only
+        let proof = MerkleProof {
+            leaf_index: 0,
+            leaf_hash: GENESIS_LEAF,
+            siblings: zero_hashes()[..32].to_vec(),
+            path_indices: vec![false; 32],
+        };
+        assert!(proof.verify(&genesis));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_every_path_starts_from_the_genesis_root() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+        let engine = MerkleTree32::new(test_db.pool.clone());
+        engine
+            .initialize()
+            .await
+            .expect("Failed to initialize tree");
+        assert_eq!(
+            engine.get_root().await.expect("Failed to read root"),
+            genesis_root(),
+            "Migrated tree"
+        );
+
+        let mut tree = IndexedMerkleTree::new(test_db.pool.clone());
+        tree.insert_nullifier(INSERTION_ROOTS[0].0)
+            .await
+            .expect("Failed to insert nullifier");
+        let mut conn = test_db.pool.acquire().await.expect("Failed to connect");
+        let root = tree
+            .rollback_to_size(&mut conn, 1)
+            .await
+            .expect("Failed to roll back");
+        assert_eq!(root, genesis_root(), "Rolled back tree");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_nodes_hashed_with_zero_siblings_are_rebuilt() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+        let pool = &test_db.pool;
+        let mut tree = IndexedMerkleTree::new(pool.clone());
+        for (value, _) in INSERTION_ROOTS {
+            tree.insert_nullifier(value)
+                .await
+                .expect("Failed to insert nullifier");
+        }
+        let expected = hash(INSERTION_ROOTS[2].1);
+
+        // Rewrite the nodes as before the shared engine: missing siblings are zero bytes
+        let mut level_hashes = Vec::new();
+        for leaf_index in 0..4 {
+            let proof = tree
+                .generate_merkle_proof(leaf_index)
+                .await
+                .expect("Failed to generate proof");
+            level_hashes.push(proof.leaf_hash);
+        }
+        for level in 1..=32 {
+            level_hashes = level_hashes
+                .chunks(2)
+                .map(|pair| node_hash(&pair[0], pair.get(1).unwrap_or(&[0u8; 32])))
+                .collect();
+            for (index, hash) in level_hashes.iter().enumerate() {
+                sqlx::query(
+                    "UPDATE merkle_nodes SET hash_value = $3 WHERE tree_level = $1 AND node_index = $2",
+                )
+                .bind(level)
+                .bind(index as i64)
+                .bind(hash.as_slice())
+                .execute(pool)
+                .await
+                .expect("Failed to write node");
+            }
+        }
+        sqlx::query("UPDATE tree_state SET root_hash = $1")
+            .bind(level_hashes[0].as_slice())
+            .execute(pool)
+            .await
+            .expect("Failed to write root");
+        assert_ne!(level_hashes[0], expected);
+
+        let state = tree
+            .db
+            .state
+            .get_state(None)
+            .await
+            .expect("Failed to read tree state")
+            .expect("Tree state exists");
+        tree.recover_state(state)
+            .await
+            .expect("Failed to recover tree");
+        assert_eq!(
+            tree.get_root().await.expect("Failed to read root"),
+            expected
+        );
+        for leaf_index in 0..4 {
+            let proof = tree
+                .generate_merkle_proof(leaf_index)
+                .await
+                .expect("Failed to generate proof");
+            assert!(proof.verify(&expected), "Path of leaf {leaf_index}");
+        }
+
+        assert!(!tree
+            .merkle
+            .rebuild_stale_nodes()
+            .await
+            .expect("Failed to check nodes"));
+    }
+
+    #[test]
+    fn test_sparse_leaf_vector() {
+        // A lone leaf: every sibling is an empty subtree
+        let proof = MerkleProof {
+            leaf_index: 5,
+            leaf_hash: [0x42u8; 32],
+            siblings: zero_hashes()[..32].to_vec(),
+            path_indices: (0..32).map(|level| (5 >> level) & 1 == 1).collect(),
+        };
+        let root = hash("7a66a5c6fb6fde6366d7d4d206d22acb1dbb895c28ac7450c44ce3a21f8166e1");
+        assert_eq!(proof.compute_root(), Some(root));
+        assert!(proof.verify(&root));
+
+        let mut wrong_path = proof.clone();
+        wrong_path.path_indices[0] = false;
+        assert_eq!(wrong_path.compute_root(), None);
+        assert!(!wrong_path.verify(&root));
+
+        let mut short = proof;
+        short.siblings.pop();
+        assert!(!short.verify(&root));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_indexed_replay_and_engine_roots_agree() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+        let mut tree = IndexedMerkleTree::new(test_db.pool.clone());
+        let mut replay = ReplayTree::genesis();
+
+        for (value, root) in INSERTION_ROOTS {
+            let inserted = tree
+                .insert_nullifier(value)
+                .await
+                .expect("Failed to insert nullifier");
+            replay.insert(value).expect("Failed to replay insertion");
+
+            assert_eq!(inserted.new_root, hash(root), "IMT root after {value}");
+            assert_eq!(replay.root(), hash(root), "Replayed root after {value}");
+            assert!(tree.verify_insertion_proof(&inserted.insertion_proof, &inserted.new_root));
+        }
+
+        // The same leaves written straight through the engine
+        let engine_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+        let engine = MerkleTree32::new(engine_db.pool.clone());
+        let mut proofs = Vec::new();
+        for leaf_index in 0..4 {
+            proofs.push(
+                tree.generate_merkle_proof(leaf_index)
+                    .await
+                    .expect("Failed to generate proof"),
+            );
+        }
+        let updates: Vec<BatchUpdate> = proofs
+            .iter()
+            .map(|proof| BatchUpdate {
+                leaf_index: proof.leaf_index as usize,
+                new_value: proof.leaf_hash,
+            })
+            .collect();
+        let root = engine
+            .batch_update(&updates)
+            .await
+            .expect("Failed to update leaves");
+        assert_eq!(root, hash(INSERTION_ROOTS[2].1));
+
+        for proof in &proofs {
+            let engine_proof = engine
+                .generate_proof(proof.leaf_index)
+                .await
+                .expect("Failed to generate proof");
+            assert_eq!(&engine_proof, proof);
+            assert!(engine_proof.verify(&root));
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_cached_paths_follow_other_writers() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+        let reader = MerkleTree32::new(test_db.pool.clone());
+        let writer = MerkleTree32::new(test_db.pool.clone());
+
+        writer
+            .update_leaf(0, [0x11u8; 32])
+            .await
+            .expect("Failed to update leaf");
+        let before = reader
+            .generate_proof(0)
+            .await
+            .expect("Failed to generate proof");
+        assert!(reader.cached_nodes() > 0);
+
+        // A leaf in the far half of the tree changes the top sibling of leaf 0
+        let root = writer
+            .update_leaf(1 << 31, [0x22u8; 32])
+            .await
+            .expect("Failed to update leaf");
+        let after = reader
+            .generate_proof(0)
+            .await
+            .expect("Failed to generate proof");
+        assert_ne!(after.siblings[31], before.siblings[31]);
+        assert!(after.verify(&root));
+        assert_eq!(after, writer.generate_proof(0).await.unwrap());
+
+        assert!(reader.generate_stored_proof(1).await.unwrap().is_none());
+        let stored = reader
+            .generate_stored_proof(1 << 31)
+            .await
+            .expect("Failed to generate proof")
+            .expect("Leaf was written");
+        assert_eq!(stored.leaf_hash, [0x22u8; 32]);
+        assert!(stored.verify(&root));
+    }
+}
+
+mod outbox_tests {
+    use super::*;
+    use crate::db::{
+        create_batch, mark_batch_posted_to_contract, reset_failed_batch_proof,
+        unmark_batch_posted_to_contract, update_batch_proof, ProofBatch,
+    };
+    use crate::outbox::{
+        claim_outbox_jobs, complete_outbox_job, get_outbox_jobs, retry_outbox_job, OutboxJob,
+        OUTBOX_CONTRACT_POST, OUTBOX_PROOF_REQUEST,
+    };
+    use std::time::Duration;
+    use tracing_test::traced_test;
+
+    const LEASE: Duration = Duration::from_secs(600);
+
+    async fn batch_of(test_db: &TestDatabase, amount: i32) -> ProofBatch {
+        submit_transaction(&test_db.pool, amount)
+            .await
+            .expect("Failed to submit transaction");
+        create_batch(&test_db.pool, Some(1))
+            .await
+            .expect("Failed to create batch")
+            .expect("Expected a batch")
+    }
+
+    fn summary(jobs: &[OutboxJob]) -> Vec<(i32, &str, &str)> {
+        jobs.iter()
+            .map(|job| (job.batch_id, job.kind.as_str(), job.status.as_str()))
+            .collect()
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_work_is_queued_with_the_state_change() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+
+        let batch = batch_of(&test_db, 5).await;
+        let jobs = get_outbox_jobs(&test_db.pool, &[batch.id])
+            .await
+            .expect("Failed to get jobs");
+        assert_eq!(
+            summary(&jobs),
+            vec![(batch.id, OUTBOX_PROOF_REQUEST, "pending")]
+        );
+
+        // Only a proven batch gets a contract post
+        update_batch_proof(&test_db.pool, batch.id, "proof", "pending")
+            .await
+            .expect("Failed to update batch");
+        assert_eq!(
+            get_outbox_jobs(&test_db.pool, &[batch.id])
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        update_batch_proof(&test_db.pool, batch.id, "proof", "proven")
+            .await
+            .expect("Failed to update batch");
+        let jobs = get_outbox_jobs(&test_db.pool, &[batch.id])
+            .await
+            .expect("Failed to get jobs");
+        assert_eq!(
+            summary(&jobs),
+            vec![
+                (batch.id, OUTBOX_CONTRACT_POST, "pending"),
+                (batch.id, OUTBOX_PROOF_REQUEST, "pending"),
+            ]
+        );
+
+        // Reconciliation queues the finished post again
+        let claimed = claim_outbox_jobs(
+            &test_db.pool,
+            OUTBOX_CONTRACT_POST,
+            Some(&[batch.id]),
+            1,
+            LEASE,
+        )
+        .await
+        .expect("Failed to claim");
+        assert_eq!(claimed[0].id, jobs[0].id);
+        complete_outbox_job(&test_db.pool, &claimed[0])
+            .await
+            .expect("Failed to complete job");
+        mark_batch_posted_to_contract(&test_db.pool, batch.id)
+            .await
+            .expect("Failed to mark batch posted");
+        unmark_batch_posted_to_contract(&test_db.pool, batch.id)
+            .await
+            .expect("Failed to unmark batch");
+        let jobs = get_outbox_jobs(&test_db.pool, &[batch.id])
+            .await
+            .expect("Failed to get jobs");
+        assert_eq!(jobs[0].status, "pending");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_claimed_jobs_are_skipped_by_other_workers() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+        let pool = &test_db.pool;
+        let first = batch_of(&test_db, 5).await;
+        let second = batch_of(&test_db, 7).await;
+
+        let claimed_a = claim_outbox_jobs(pool, OUTBOX_PROOF_REQUEST, None, 1, LEASE)
+            .await
+            .expect("Failed to claim");
+        assert_eq!(
+            summary(&claimed_a),
+            vec![(first.id, OUTBOX_PROOF_REQUEST, "in_flight")]
+        );
+        assert_eq!(claimed_a[0].attempts, 1);
+        assert!(claimed_a[0].lease_expires_at > Some(chrono::Utc::now()));
+
+        // The claim is committed, so a job in flight is skipped without any
+        // transaction being held open
+        let claimed_b = claim_outbox_jobs(pool, OUTBOX_PROOF_REQUEST, None, 10, LEASE)
+            .await
+            .expect("Failed to claim");
+        assert_eq!(
+            summary(&claimed_b),
+            vec![(second.id, OUTBOX_PROOF_REQUEST, "in_flight")]
+        );
+
+        // A failed dispatch is held back, a finished one is never claimed again
+        retry_outbox_job(
+            pool,
+            &claimed_a[0],
+            Duration::from_secs(3600),
+            Some("Sindri unavailable"),
+        )
+        .await
+        .expect("Failed to reschedule job");
+        complete_outbox_job(pool, &claimed_b[0])
+            .await
+            .expect("Failed to complete job");
+
+        assert!(
+            claim_outbox_jobs(pool, OUTBOX_PROOF_REQUEST, None, 10, LEASE)
+                .await
+                .expect("Failed to claim")
+                .is_empty()
+        );
+
+        let jobs = get_outbox_jobs(pool, &[first.id])
+            .await
+            .expect("Failed to get jobs");
+        assert_eq!(jobs[0].status, "pending");
+        assert_eq!(jobs[0].last_error.as_deref(), Some("Sindri unavailable"));
+        assert!(jobs[0].available_at > chrono::Utc::now());
+
+        // Retrying the failed proof makes its request available right away
+        update_batch_proof(pool, first.id, "error_1", "failed")
+            .await
+            .expect("Failed to update batch");
+        reset_failed_batch_proof(pool, first.id)
+            .await
+            .expect("Failed to reset proof");
+        let claimed_c = claim_outbox_jobs(pool, OUTBOX_PROOF_REQUEST, None, 10, Duration::ZERO)
+            .await
+            .expect("Failed to claim");
+        assert_eq!(
+            summary(&claimed_c),
+            vec![(first.id, OUTBOX_PROOF_REQUEST, "in_flight")]
+        );
+        assert_eq!(claimed_c[0].attempts, 2);
+        assert_eq!(claimed_c[0].last_error, None);
+
+        // A worker whose lease ran out loses the job to the next one
+        let claimed_d = claim_outbox_jobs(pool, OUTBOX_PROOF_REQUEST, None, 10, LEASE)
+            .await
+            .expect("Failed to claim");
+        assert_eq!(
+            summary(&claimed_d),
+            vec![(first.id, OUTBOX_PROOF_REQUEST, "in_flight")]
+        );
+        assert_eq!(claimed_d[0].attempts, 3);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_an_expired_claim_cannot_finish_a_reclaimed_job() {
+        let test_db = TestDatabase::new()
+            .await
+            .expect("Failed to create test database");
+        let pool = &test_db.pool;
+        let batch = batch_of(&test_db, 5).await;
+
+        let expired = claim_outbox_jobs(pool, OUTBOX_PROOF_REQUEST, None, 1, Duration::ZERO)
+            .await
+            .expect("Failed to claim");
+        let current = claim_outbox_jobs(pool, OUTBOX_PROOF_REQUEST, None, 1, LEASE)
+            .await
+            .expect("Failed to claim");
+        assert_eq!(current[0].id, expired[0].id);
+
+        // The worker whose lease ran out reports late
+        assert!(!complete_outbox_job(pool, &expired[0])
+            .await
+            .expect("Failed to complete job"));
+        assert!(
+            !retry_outbox_job(pool, &expired[0], Duration::ZERO, Some("late"))
+                .await
+                .expect("Failed to reschedule job")
+        );
+
+        let jobs = get_outbox_jobs(pool, &[batch.id])
+            .await
+            .expect("Failed to get jobs");
+        assert_eq!(
+            summary(&jobs),
+            vec![(batch.id, OUTBOX_PROOF_REQUEST, "in_flight")]
+        );
+        assert_eq!(jobs[0].attempts, 2);
+        assert_eq!(jobs[0].last_error, None);
+        assert_eq!(jobs[0].lease_expires_at, current[0].lease_expires_at);
+
+        assert!(complete_outbox_job(pool, &current[0])
+            .await
+            .expect("Failed to complete job"));
+        let jobs = get_outbox_jobs(pool, &[batch.id])
+            .await
+            .expect("Failed to get jobs");
+        assert_eq!(jobs[0].status, "done");
+    }
+}
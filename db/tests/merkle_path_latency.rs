@@ -0,0 +1,174 @@
+//! Proof and insert latency of Merkle path reads
+//!
+//! Compares the per-level queries the engine used to issue (one round trip
+//! per node) with the single-query path reads and the upper-level node
+//! cache. Needs the Postgres from `docker-compose.yml`; run with
+//!
+//! ```bash
+//! cargo test -p db --features test-utils --release --test merkle_path_latency -- --ignored --nocapture
+//! ```
+
+use std::time::{Duration, Instant};
+
+use db::test_utils::TestDatabase;
+use db::{zero_hashes, MerkleProof, MerkleTree32, TREE_HEIGHT};
+use sha2::{Digest, Sha256};
+use sqlx::{PgConnection, PgPool};
+
+const LEAVES: i64 = 256;
+const PROOFS: i64 = 256;
+
+fn leaf(i: i64) -> [u8; 32] {
+    Sha256::digest(i.to_be_bytes()).into()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+async fn per_level_node(conn: &mut PgConnection, level: usize, index: i64) -> [u8; 32] {
+    let stored: Option<Vec<u8>> = sqlx::query_scalar(
+        "SELECT hash_value FROM merkle_nodes WHERE tree_level = $1 AND node_index = $2",
+    )
+    .bind(level as i32)
+    .bind(index)
+    .fetch_optional(&mut *conn)
+    .await
+    .expect("Failed to read node");
+    stored.map_or(zero_hashes()[level], |hash| {
+        hash.try_into().expect("Expected 32 bytes")
+    })
+}
+
+/// Leaf update as before: one read and one write per level
+async fn per_level_update(pool: &PgPool, leaf_index: i64, leaf_hash: [u8; 32]) {
+    const UPSERT: &str = r"
+        INSERT INTO merkle_nodes (tree_level, node_index, hash_value)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (tree_level, node_index)
+        DO UPDATE SET hash_value = EXCLUDED.hash_value, updated_at = NOW()
+        ";
+
+    let mut tx = pool.begin().await.expect("Failed to begin");
+    sqlx::query(UPSERT)
+        .bind(0i32)
+        .bind(leaf_index)
+        .bind(leaf_hash.as_slice())
+        .execute(&mut *tx)
+        .await
+        .expect("Failed to write leaf");
+
+    let mut current_hash = leaf_hash;
+    let mut current_index = leaf_index;
+    for level in 1..=TREE_HEIGHT {
+        let sibling = per_level_node(&mut tx, level - 1, current_index ^ 1).await;
+        current_hash = if current_index % 2 == 1 {
+            node_hash(&sibling, &current_hash)
+        } else {
+            node_hash(&current_hash, &sibling)
+        };
+        current_index /= 2;
+        sqlx::query(UPSERT)
+            .bind(level as i32)
+            .bind(current_index)
+            .bind(current_hash.as_slice())
+            .execute(&mut *tx)
+            .await
+            .expect("Failed to write node");
+    }
+    sqlx::query(
+        "UPDATE tree_state SET root_hash = $1, updated_at = NOW() WHERE tree_id = 'default'",
+    )
+    .bind(current_hash.as_slice())
+    .execute(&mut *tx)
+    .await
+    .expect("Failed to write root");
+    tx.commit().await.expect("Failed to commit");
+}
+
+/// Proof as before: one query per level
+async fn per_level_proof(pool: &PgPool, leaf_index: i64) -> MerkleProof {
+    let mut conn = pool.acquire().await.expect("Failed to acquire");
+    let leaf_hash = per_level_node(&mut conn, 0, leaf_index).await;
+    let mut siblings = Vec::with_capacity(TREE_HEIGHT);
+    let mut path_indices = Vec::with_capacity(TREE_HEIGHT);
+    for level in 0..TREE_HEIGHT {
+        siblings.push(per_level_node(&mut conn, level, (leaf_index >> level) ^ 1).await);
+        path_indices.push((leaf_index >> level) & 1 == 1);
+    }
+    MerkleProof {
+        leaf_index,
+        leaf_hash,
+        siblings,
+        path_indices,
+    }
+}
+
+fn report(name: &str, total: Duration, count: i64) {
+    println!(
+        "{name:<28} {:>10.1?} total {:>10.1?} each",
+        total,
+        total / count as u32
+    );
+}
+
+#[tokio::test]
+#[ignore = "benchmark, needs a running Postgres"]
+async fn bench_path_reads_before_and_after() {
+    let before = TestDatabase::new()
+        .await
+        .expect("Failed to create test database");
+    let after = TestDatabase::new()
+        .await
+        .expect("Failed to create test database");
+    let (before_pool, after_pool) = (before.pool.clone(), after.pool.clone());
+    let engine = MerkleTree32::new(after_pool.clone());
+
+    let start = Instant::now();
+    for i in 0..LEAVES {
+        per_level_update(&before_pool, i, leaf(i)).await;
+    }
+    report("insert, per level", start.elapsed(), LEAVES);
+
+    let start = Instant::now();
+    let mut root = [0u8; 32];
+    for i in 0..LEAVES {
+        root = engine
+            .update_leaf(i as usize, leaf(i))
+            .await
+            .expect("Failed to update leaf");
+    }
+    report("insert, single path", start.elapsed(), LEAVES);
+
+    let start = Instant::now();
+    let mut expected = Vec::with_capacity(PROOFS as usize);
+    for i in 0..PROOFS {
+        expected.push(per_level_proof(&before_pool, i % LEAVES).await);
+    }
+    report("proof, per level", start.elapsed(), PROOFS);
+
+    let start = Instant::now();
+    for i in 0..PROOFS {
+        let cold = MerkleTree32::new(after_pool.clone());
+        let proof = cold.generate_proof(i % LEAVES).await.expect("Proof failed");
+        assert_eq!(proof, expected[i as usize]);
+    }
+    report("proof, single query", start.elapsed(), PROOFS);
+
+    engine.generate_proof(0).await.expect("Proof failed");
+    let start = Instant::now();
+    for i in 0..PROOFS {
+        let proof = engine
+            .generate_proof(i % LEAVES)
+            .await
+            .expect("Proof failed");
+        assert!(proof.verify(&root));
+    }
+    report("proof, cached upper levels", start.elapsed(), PROOFS);
+
+    before.drop_database().await;
+    after.drop_database().await;
+}
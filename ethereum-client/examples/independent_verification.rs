@@ -122,11 +122,18 @@ async fn main() -> Result<()> {
     println!("📋 Step 5: Complete Independent Verification Process");
     println!("---------------------------------------------------");
 
-    match client.verify_proof_independently(mock_proof_id) {
+    match client.verify_proof_independently(mock_proof_id).await {
         Ok(result) => {
             println!("✅ Independent Verification Completed!");
-            println!("   SP1 Verification: {}", result.verified);
-            println!("   Result: {}", result.result.unwrap_or_default());
+            println!("   SP1 Verification: {}", result.sp1_verification_passed);
+            println!(
+                "   Consistency Checks: {}",
+                result.consistency_checks_passed
+            );
+            println!(
+                "   State Root: 0x{}",
+                hex::encode(result.verification_data.state_root)
+            );
             println!("💡 This proves the computation was done correctly");
         }
         Err(e) => {
@@ -1,6 +1,6 @@
 use alloy_primitives::Address;
 use ethereum_client::{
-    config::{ContractConfig, MonitoringConfig, NetworkConfig, RpcConfig},
+    config::{ContractConfig, GasConfig, MonitoringConfig, NetworkConfig, RpcConfig},
     Config,
 };
 use url::Url;
@@ -37,6 +37,7 @@ async fn main() -> ethereum_client::Result<()> {
             retry_attempts: 1,
             timeout_seconds: 10,
         },
+        gas: GasConfig::default(),
     };
 
     // Validate configuration
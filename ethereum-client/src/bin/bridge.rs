@@ -1,6 +1,7 @@
 use alloy_primitives::{Bytes, FixedBytes};
+use arithmetic_lib::bundle::{verify_bundle, BundleExpectations, ProofBundle};
 use clap::{Parser, Subcommand};
-use ethereum_client::{Config, EthereumClient, Result};
+use ethereum_client::{batch_state_id, batch_state_root, Config, EthereumClient, Result};
 use sindri::{client::SindriClient, integrations::sp1_v5::SP1ProofInfo, JobStatus};
 use sqlx::{PgPool, Row};
 use std::time::Duration;
@@ -35,6 +36,12 @@ enum Commands {
         #[arg(short, long)]
         result: i32,
     },
+    /// Verify a proof bundle (JSON or CBOR) offline and submit it for its batch
+    SubmitBundle {
+        /// Path to the bundle written by `cli download-proof`
+        #[arg(long)]
+        bundle: String,
+    },
 }
 
 #[tokio::main]
@@ -87,6 +94,9 @@ async fn main() -> Result<()> {
                 ));
             }
         }
+        Commands::SubmitBundle { bundle } => {
+            run_bundle_submission(&client, &bundle).await?;
+        }
     }
 
     Ok(())
@@ -238,6 +248,66 @@ async fn run_single_submission(
     handle_proof_submission(&bridge, result, proof_id_opt).await
 }
 
+/// Verify a proof bundle against the contract's program key and settle its batch
+///
+/// Uses the same state id and root as the API's settlement path, so a bundle
+/// submitted here is indistinguishable from one posted by the batch processor.
+async fn run_bundle_submission(client: &EthereumClient, path: &str) -> Result<()> {
+    info!("📦 Submitting proof bundle: {}", path);
+
+    let bytes = std::fs::read(path).map_err(|e| {
+        ethereum_client::EthereumError::Config(format!("Failed to read bundle {path}: {e}"))
+    })?;
+    let bundle = ProofBundle::decode(&bytes).map_err(|e| {
+        ethereum_client::EthereumError::Config(format!("Invalid proof bundle {path}: {e}"))
+    })?;
+
+    // The proof must be for the program the contract accepts
+    let contract_vkey = client.get_verifier_key().await?;
+    let vkey_hash: [u8; 32] = contract_vkey.as_ref().try_into().map_err(|_| {
+        ethereum_client::EthereumError::Contract(format!(
+            "Contract program key is {} bytes, expected 32",
+            contract_vkey.len()
+        ))
+    })?;
+
+    let summary = verify_bundle(
+        &bundle,
+        &BundleExpectations {
+            vkey_hash: Some(vkey_hash),
+            ..BundleExpectations::default()
+        },
+    )
+    .map_err(|e| ethereum_client::EthereumError::ProofVerificationFailed(e.to_string()))?;
+    info!(
+        "✅ Bundle verified: batch {} ({} -> {}, {})",
+        summary.batch_id,
+        summary.initial_balance,
+        summary.final_balance,
+        summary.proof_system.to_sindri_scheme()
+    );
+
+    let state_id = batch_state_id(summary.batch_id);
+    let new_state_root = batch_state_root(i64::from(summary.final_balance));
+    info!(
+        "📤 Submitting to contract: batch={}, state_root=0x{}",
+        summary.batch_id,
+        hex::encode(new_state_root.as_slice())
+    );
+
+    let state_update = client
+        .update_state(
+            state_id,
+            new_state_root,
+            Bytes::from(bundle.proof.clone()),
+            Bytes::from(bundle.public_values.clone()),
+        )
+        .await?;
+    UnifiedBridge::log_success(&state_update);
+
+    Ok(())
+}
+
 async fn query_proof_by_result(pool: &PgPool, result: i32) -> Result<Option<String>> {
     let query = r"
         SELECT sp.proof_id
@@ -234,7 +234,7 @@ async fn main() -> Result<()> {
             let state_id = parse_bytes32(&state_id)?;
 
             info!("Fetching historical states for state ID: {:?}", state_id);
-            let history = client.get_historical_states(state_id, limit).unwrap();
+            let history = client.get_historical_states(state_id, limit).await?;
 
             println!("State history:");
             println!("State ID: {:?}", history.state_id);
@@ -266,14 +266,11 @@ async fn main() -> Result<()> {
             if let Some(base_fee) = stats.base_fee {
                 println!("Base fee: {base_fee} wei");
             }
-            println!(
-                "Sync status: {}",
-                if stats.sync_status {
-                    "Synced"
-                } else {
-                    "Not synced"
-                }
-            );
+            println!("Block timestamp: {}", stats.block_timestamp);
+            match stats.highest_block {
+                Some(highest) => println!("Sync status: Syncing (highest block {highest})"),
+                None => println!("Sync status: Synced"),
+            }
         }
 
         Commands::CheckInclusion {
@@ -351,7 +348,10 @@ async fn main() -> Result<()> {
             let proof_id = parse_bytes32(&proof_id)?;
 
             info!("Retrieving proof data for {:?}...", proof_id);
-            let proof_data = client.get_proof_data(proof_id).await.unwrap();
+            let proof_data = client
+                .get_proof_data(proof_id)
+                .await
+                .or_else(not_found_as_none)?;
 
             println!("Proof Data:");
             println!("  Proof ID: 0x{}", hex::encode(proof_id));
@@ -422,7 +422,7 @@ async fn main() -> Result<()> {
             let state_id = parse_bytes32(&state_id)?;
 
             info!("Retrieving proof history for state {:?}...", state_id);
-            let proof_ids = client.get_state_proof_history(state_id).unwrap();
+            let proof_ids = client.get_state_proof_history(state_id).await?;
 
             println!("State Proof History:");
             println!("  State ID: 0x{}", hex::encode(state_id.as_slice()));
@@ -444,43 +444,61 @@ async fn main() -> Result<()> {
             );
             println!("🔍 Starting independent verification process...");
 
-            let result = client.verify_proof_independently(proof_id).unwrap();
+            let result = client.verify_proof_independently(proof_id).await?;
+            let data = &result.verification_data;
+            let checks = &result.consistency_details;
+            let mark = |passed: bool| if passed { "✅" } else { "❌" };
 
             println!("\n📋 Independent Verification Report:");
             println!("  Proof ID: 0x{}", hex::encode(result.proof_id));
+            println!("  State ID: 0x{}", hex::encode(data.state_id));
+            println!("  State Root: 0x{}", hex::encode(data.state_root));
+            println!("  Verifier Key: 0x{}", hex::encode(data.verifier_key));
+            println!("  Submitter: {}", data.submitter);
+            println!("  Timestamp: {}", data.timestamp);
+            if let Some(block_number) = data.block_number {
+                println!("  Block Number: {block_number}");
+            }
+            println!(
+                "  Proof: {} bytes, Public Values: {} bytes",
+                data.proof_bytes.len(),
+                data.public_values.len()
+            );
 
-            // Main verification results
             println!("\n✅ Verification Results:");
             println!(
-                "  Verification Status: {}",
-                if result.verified {
-                    "✅ PASSED"
-                } else {
-                    "❌ FAILED"
-                }
+                "  {} Local SP1 proof verification",
+                mark(result.sp1_verification_passed)
+            );
+            println!(
+                "  {} Verified on chain",
+                mark(result.on_chain_verification_status)
             );
-            println!("  Block Number: {}", result.block_number);
-            println!("  Gas Used: {}", result.gas_used);
-
-            if let Some(error) = &result.error_message {
-                println!("  Error: {error}");
-            }
 
-            if let Some(result_data) = &result.result {
-                println!("  Result Data: {} bytes", result_data.len());
-            }
+            println!("\n🔗 Consistency Checks:");
+            println!(
+                "  {} Proof ID matches proof hash",
+                mark(checks.proof_id_matches_hash)
+            );
+            println!("  {} State recorded in history", mark(checks.state_exists));
+            println!("  {} Proof data present", mark(checks.proof_data_present));
+            println!(
+                "  {} Timestamp reasonable",
+                mark(checks.timestamp_reasonable)
+            );
+            println!("  {} Verifier key valid", mark(checks.verifier_key_valid));
 
-            // Summary
+            let passed = result.sp1_verification_passed
+                && result.on_chain_verification_status
+                && result.consistency_checks_passed;
             println!(
                 "\n🎯 Overall Status: {}",
-                if result.verified {
+                if passed {
                     "✅ VERIFIED"
                 } else {
                     "❌ VERIFICATION FAILED"
                 }
             );
-
-            println!("  Block: {}", result.block_number);
         }
 
         Commands::GetVerifierVersion => {
@@ -511,7 +529,10 @@ async fn main() -> Result<()> {
 
             // Step 2: Get proof data
             println!("\n📋 Step 2: Retrieving proof data...");
-            let proof_data = client.get_proof_data(proof_id).await.unwrap();
+            let proof_data = client
+                .get_proof_data(proof_id)
+                .await
+                .or_else(not_found_as_none)?;
             if let Some(proof_bytes) = &proof_data {
                 println!("✅ Proof data: {} bytes", proof_bytes.len());
             } else {
@@ -520,7 +541,10 @@ async fn main() -> Result<()> {
 
             // Step 3: Get proof result
             println!("\n📋 Step 3: Retrieving proof result...");
-            let proof_result = client.get_proof_result(proof_id).await.unwrap();
+            let proof_result = client
+                .get_proof_result(proof_id)
+                .await
+                .or_else(not_found_as_none)?;
             if let Some(result_bytes) = &proof_result {
                 println!("✅ Proof result: {} bytes", result_bytes.len());
             } else {
@@ -538,28 +562,52 @@ async fn main() -> Result<()> {
 
             // Step 5: Independent verification
             println!("\n📋 Step 5: Performing independent verification...");
-            let verification_result = client.verify_proof_independently(proof_id).unwrap();
-
-            let trustless_summary = ethereum_client::types::TrustlessVerificationSummary {
-                proof_id,
-                verification_status: if verification_result.verified {
-                    ethereum_client::types::VerificationStatus::Verified
-                } else {
-                    ethereum_client::types::VerificationStatus::Failed
-                },
-                verifier_key: FixedBytes::ZERO, // Convert from Bytes to FixedBytes<32>
-                state_root: FixedBytes::ZERO, // Placeholder since verification_data is Option<Bytes>
-                independent_verification_passed: verification_result.verified,
-                verification_details: format!(
-                    "Verified: {}, Block: {}, Gas: {}",
-                    verification_result.verified,
-                    verification_result.block_number,
-                    verification_result.gas_used
-                ),
-                retrieved_at: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs(),
+            let retrieved_at = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            let trustless_summary = match client.verify_proof_independently(proof_id).await {
+                Ok(result) => {
+                    let passed = result.sp1_verification_passed
+                        && result.on_chain_verification_status
+                        && result.consistency_checks_passed;
+                    let checks = &result.consistency_details;
+                    ethereum_client::types::TrustlessVerificationSummary {
+                        proof_id,
+                        verification_status: if passed {
+                            ethereum_client::types::VerificationStatus::Verified
+                        } else {
+                            ethereum_client::types::VerificationStatus::Failed
+                        },
+                        verifier_key: result.verification_data.verifier_key,
+                        state_root: result.verification_data.state_root,
+                        independent_verification_passed: passed,
+                        verification_details: format!(
+                            "SP1 proof: {}, on-chain: {}, proof hash: {}, state history: {}, \
+                             data present: {}, timestamp: {}, verifier key: {}",
+                            result.sp1_verification_passed,
+                            result.on_chain_verification_status,
+                            checks.proof_id_matches_hash,
+                            checks.state_exists,
+                            checks.proof_data_present,
+                            checks.timestamp_reasonable,
+                            checks.verifier_key_valid
+                        ),
+                        retrieved_at,
+                    }
+                }
+                Err(ethereum_client::EthereumError::ContractProofNotFound) => {
+                    ethereum_client::types::TrustlessVerificationSummary {
+                        proof_id,
+                        verification_status: ethereum_client::types::VerificationStatus::NotFound,
+                        verifier_key: FixedBytes::from_slice(&verifier_key),
+                        state_root: FixedBytes::ZERO,
+                        independent_verification_passed: false,
+                        verification_details: "Proof not stored on chain".to_string(),
+                        retrieved_at,
+                    }
+                }
+                Err(e) => return Err(e),
             };
 
             // Display results
@@ -604,6 +652,14 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Treat a `ProofNotFound` revert as a missing value
+fn not_found_as_none<T>(error: ethereum_client::EthereumError) -> Result<Option<T>> {
+    match error {
+        ethereum_client::EthereumError::ContractProofNotFound => Ok(None),
+        e => Err(e),
+    }
+}
+
 fn parse_bytes32(s: &str) -> Result<alloy_primitives::FixedBytes<32>> {
     let s = s.strip_prefix("0x").unwrap_or(s);
     let bytes = hex::decode(s)
@@ -1,6 +1,14 @@
 #[cfg(feature = "database")]
-use crate::{error::Result, types::*};
-use sqlx::{PgPool, Row};
+use crate::{
+    client::ArithmeticEvent,
+    delivery::{EventPosition, PendingRetry},
+    error::{EthereumError, Result},
+    indexer::{IndexedEvent, IndexerCursor},
+    types::*,
+};
+use alloy_primitives::{Address, FixedBytes};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use std::time::Duration;
 use tracing::{debug, info};
 use uuid::Uuid;
 
@@ -89,6 +97,103 @@ impl EthereumCache {
         .execute(&self.pool)
         .await?;
 
+        sqlx::query(
+            r"
+            ALTER TABLE ethereum_network_stats
+                ADD COLUMN IF NOT EXISTS block_timestamp BIGINT,
+                ADD COLUMN IF NOT EXISTS highest_block BIGINT
+            ",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Chain indexer state, keyed by chain and contract so several indexers can share a database
+        sqlx::query(
+            r"
+            CREATE TABLE IF NOT EXISTS ethereum_indexer_cursors (
+                chain_id BIGINT NOT NULL,
+                contract_address BYTEA NOT NULL,
+                block_number BIGINT NOT NULL,
+                block_hash BYTEA NOT NULL,
+                updated_at TIMESTAMPTZ DEFAULT NOW(),
+                PRIMARY KEY (chain_id, contract_address)
+            )
+            ",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r"
+            CREATE TABLE IF NOT EXISTS ethereum_indexed_blocks (
+                chain_id BIGINT NOT NULL,
+                contract_address BYTEA NOT NULL,
+                block_number BIGINT NOT NULL,
+                block_hash BYTEA NOT NULL,
+                PRIMARY KEY (chain_id, contract_address, block_number)
+            )
+            ",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r"
+            CREATE TABLE IF NOT EXISTS ethereum_indexed_events (
+                chain_id BIGINT NOT NULL,
+                contract_address BYTEA NOT NULL,
+                block_number BIGINT NOT NULL,
+                log_index BIGINT NOT NULL,
+                block_hash BYTEA NOT NULL,
+                transaction_hash BYTEA NOT NULL,
+                event_type TEXT NOT NULL,
+                payload JSONB NOT NULL,
+                created_at TIMESTAMPTZ DEFAULT NOW(),
+                PRIMARY KEY (chain_id, contract_address, block_number, log_index)
+            )
+            ",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Per-handler delivery state for `EventManager`'s durable event delivery
+        sqlx::query(
+            r"
+            CREATE TABLE IF NOT EXISTS ethereum_handler_checkpoints (
+                handler TEXT NOT NULL,
+                chain_id BIGINT NOT NULL,
+                contract_address BYTEA NOT NULL,
+                block_number BIGINT NOT NULL,
+                log_index BIGINT NOT NULL,
+                updated_at TIMESTAMPTZ DEFAULT NOW(),
+                PRIMARY KEY (handler, chain_id, contract_address)
+            )
+            ",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r"
+            CREATE TABLE IF NOT EXISTS ethereum_handler_retries (
+                handler TEXT NOT NULL,
+                chain_id BIGINT NOT NULL,
+                contract_address BYTEA NOT NULL,
+                block_number BIGINT NOT NULL,
+                log_index BIGINT NOT NULL,
+                attempts INTEGER NOT NULL,
+                next_attempt_at TIMESTAMPTZ NOT NULL,
+                last_error TEXT NOT NULL,
+                dead BOOLEAN NOT NULL DEFAULT FALSE,
+                created_at TIMESTAMPTZ DEFAULT NOW(),
+                updated_at TIMESTAMPTZ DEFAULT NOW(),
+                PRIMARY KEY (handler, chain_id, contract_address, block_number, log_index)
+            )
+            ",
+        )
+        .execute(&self.pool)
+        .await?;
+
         // Create indexes for better query performance
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_state_updates_state_id ON ethereum_state_updates(state_id)")
             .execute(&self.pool)
@@ -114,6 +219,10 @@ impl EthereumCache {
             .execute(&self.pool)
             .await?;
 
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_indexed_events_event_type ON ethereum_indexed_events(event_type)")
+            .execute(&self.pool)
+            .await?;
+
         info!("Ethereum cache database initialized successfully");
         Ok(())
     }
@@ -203,8 +312,8 @@ impl EthereumCache {
             r"
             INSERT INTO ethereum_network_stats (
                 id, chain_id, block_number, gas_price, base_fee,
-                network_name, sync_status
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+                network_name, sync_status, block_timestamp, highest_block
+            ) VALUES ($1, $2, $3, $4::NUMERIC, $5::NUMERIC, $6, $7, $8, $9)
             ",
         )
         .bind(id)
@@ -214,6 +323,8 @@ impl EthereumCache {
         .bind(stats.base_fee.map(|f| f.to_string()))
         .bind(&stats.network_name)
         .bind(stats.sync_status)
+        .bind(stats.block_timestamp as i64)
+        .bind(stats.highest_block.map(|b| b as i64))
         .execute(&self.pool)
         .await?;
 
@@ -351,16 +462,19 @@ impl EthereumCache {
         Ok(events)
     }
 
-    pub async fn get_latest_network_stats(&self) -> Result<Option<NetworkStats>> {
+    pub async fn get_latest_network_stats(&self, chain_id: u64) -> Result<Option<NetworkStats>> {
         let row = sqlx::query(
             r"
-            SELECT chain_id, block_number, gas_price, base_fee,
-                   network_name, sync_status
-            FROM ethereum_network_stats 
-            ORDER BY recorded_at DESC 
+            SELECT chain_id, block_number, gas_price::TEXT AS gas_price,
+                   base_fee::TEXT AS base_fee, network_name, sync_status,
+                   block_timestamp, highest_block
+            FROM ethereum_network_stats
+            WHERE chain_id = $1
+            ORDER BY recorded_at DESC
             LIMIT 1
             ",
         )
+        .bind(chain_id as i64)
         .fetch_optional(&self.pool)
         .await?;
 
@@ -379,6 +493,10 @@ impl EthereumCache {
                         .and_then(|s| s.parse().ok()),
                     network_name: row.get("network_name"),
                     sync_status: row.get("sync_status"),
+                    block_timestamp: row
+                        .get::<Option<i64>, _>("block_timestamp")
+                        .unwrap_or_default() as u64,
+                    highest_block: row.get::<Option<i64>, _>("highest_block").map(|b| b as u64),
                 }))
             },
         )
@@ -415,4 +533,526 @@ impl EthereumCache {
         );
         Ok(deleted_events + deleted_stats)
     }
+
+    // ==========================================
+    // CHAIN INDEXER STATE
+    // ==========================================
+
+    pub async fn get_indexer_cursor(
+        &self,
+        chain_id: u64,
+        contract: Address,
+    ) -> Result<Option<IndexerCursor>> {
+        let row = sqlx::query(
+            r"
+            SELECT block_number, block_hash
+            FROM ethereum_indexer_cursors
+            WHERE chain_id = $1 AND contract_address = $2
+            ",
+        )
+        .bind(chain_id as i64)
+        .bind(contract.as_slice())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| IndexerCursor {
+            block_number: row.get::<i64, _>("block_number") as u64,
+            block_hash: FixedBytes::from_slice(row.get::<&[u8], _>("block_hash")),
+        }))
+    }
+
+    /// Store an indexed range and advance the cursor in one transaction
+    pub async fn commit_indexed_range(
+        &self,
+        chain_id: u64,
+        contract: Address,
+        blocks: &[(u64, FixedBytes<32>)],
+        events: &[IndexedEvent],
+        cursor: IndexerCursor,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        Self::upsert_indexed_blocks(&mut tx, chain_id, contract, blocks).await?;
+        Self::insert_indexed_events(&mut tx, chain_id, contract, events).await?;
+
+        sqlx::query(
+            r"
+            INSERT INTO ethereum_indexer_cursors (chain_id, contract_address, block_number, block_hash)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (chain_id, contract_address)
+            DO UPDATE SET block_number = EXCLUDED.block_number,
+                          block_hash = EXCLUDED.block_hash,
+                          updated_at = NOW()
+            ",
+        )
+        .bind(chain_id as i64)
+        .bind(contract.as_slice())
+        .bind(cursor.block_number as i64)
+        .bind(cursor.block_hash.as_slice())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Replace the stored events of `from_block..=to_block` without moving the cursor
+    pub async fn replace_indexed_events(
+        &self,
+        chain_id: u64,
+        contract: Address,
+        from_block: u64,
+        to_block: u64,
+        blocks: &[(u64, FixedBytes<32>)],
+        events: &[IndexedEvent],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r"
+            DELETE FROM ethereum_indexed_events
+            WHERE chain_id = $1 AND contract_address = $2
+              AND block_number >= $3 AND block_number <= $4
+            ",
+        )
+        .bind(chain_id as i64)
+        .bind(contract.as_slice())
+        .bind(from_block as i64)
+        .bind(to_block as i64)
+        .execute(&mut *tx)
+        .await?;
+
+        Self::upsert_indexed_blocks(&mut tx, chain_id, contract, blocks).await?;
+        Self::insert_indexed_events(&mut tx, chain_id, contract, events).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Discard everything indexed above `ancestor` and move the cursor back to it
+    ///
+    /// With no ancestor all indexer state for the contract is removed.
+    pub async fn rollback_indexer(
+        &self,
+        chain_id: u64,
+        contract: Address,
+        ancestor: Option<IndexerCursor>,
+    ) -> Result<()> {
+        // -1 keeps every block when there is no ancestor to preserve
+        let keep_through = ancestor.map_or(-1, |a| a.block_number as i64);
+        let mut tx = self.pool.begin().await?;
+
+        for table in [
+            "ethereum_indexed_events",
+            "ethereum_indexed_blocks",
+            "ethereum_handler_retries",
+        ] {
+            sqlx::query(&format!(
+                "DELETE FROM {table} WHERE chain_id = $1 AND contract_address = $2 AND block_number > $3"
+            ))
+            .bind(chain_id as i64)
+            .bind(contract.as_slice())
+            .bind(keep_through)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // Handlers that got past the ancestor see the re-indexed blocks again
+        sqlx::query(
+            r"
+            UPDATE ethereum_handler_checkpoints
+            SET block_number = $3, log_index = $4, updated_at = NOW()
+            WHERE chain_id = $1 AND contract_address = $2 AND block_number > $3
+            ",
+        )
+        .bind(chain_id as i64)
+        .bind(contract.as_slice())
+        .bind(keep_through)
+        .bind(i64::MAX)
+        .execute(&mut *tx)
+        .await?;
+
+        match ancestor {
+            Some(ancestor) => {
+                sqlx::query(
+                    r"
+                    UPDATE ethereum_indexer_cursors
+                    SET block_number = $3, block_hash = $4, updated_at = NOW()
+                    WHERE chain_id = $1 AND contract_address = $2
+                    ",
+                )
+                .bind(chain_id as i64)
+                .bind(contract.as_slice())
+                .bind(ancestor.block_number as i64)
+                .bind(ancestor.block_hash.as_slice())
+                .execute(&mut *tx)
+                .await?;
+            }
+            None => {
+                sqlx::query(
+                    "DELETE FROM ethereum_indexer_cursors WHERE chain_id = $1 AND contract_address = $2",
+                )
+                .bind(chain_id as i64)
+                .bind(contract.as_slice())
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        tx.commit().await?;
+        info!(
+            "Rolled back indexer for {} to block {:?}",
+            contract,
+            ancestor.map(|a| a.block_number)
+        );
+        Ok(())
+    }
+
+    /// Stored block hashes at or below `at_or_below`, newest first
+    pub async fn get_indexed_block_hashes(
+        &self,
+        chain_id: u64,
+        contract: Address,
+        at_or_below: u64,
+        limit: i64,
+    ) -> Result<Vec<(u64, FixedBytes<32>)>> {
+        let rows = sqlx::query(
+            r"
+            SELECT block_number, block_hash
+            FROM ethereum_indexed_blocks
+            WHERE chain_id = $1 AND contract_address = $2 AND block_number <= $3
+            ORDER BY block_number DESC
+            LIMIT $4
+            ",
+        )
+        .bind(chain_id as i64)
+        .bind(contract.as_slice())
+        .bind(at_or_below as i64)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                (
+                    row.get::<i64, _>("block_number") as u64,
+                    FixedBytes::from_slice(row.get::<&[u8], _>("block_hash")),
+                )
+            })
+            .collect())
+    }
+
+    pub async fn get_indexed_events(
+        &self,
+        chain_id: u64,
+        contract: Address,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<IndexedEvent>> {
+        let rows = sqlx::query(
+            r"
+            SELECT block_number, log_index, block_hash, transaction_hash, payload::TEXT AS payload
+            FROM ethereum_indexed_events
+            WHERE chain_id = $1 AND contract_address = $2
+              AND block_number >= $3 AND block_number <= $4
+            ORDER BY block_number, log_index
+            ",
+        )
+        .bind(chain_id as i64)
+        .bind(contract.as_slice())
+        .bind(from_block as i64)
+        .bind(to_block as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Self::indexed_event_from_row).collect()
+    }
+
+    /// Stored events after `after` (from the start when `None`), oldest first
+    pub async fn get_indexed_events_after(
+        &self,
+        chain_id: u64,
+        contract: Address,
+        after: Option<EventPosition>,
+        limit: i64,
+    ) -> Result<Vec<IndexedEvent>> {
+        let (block_number, log_index) =
+            after.map_or((-1, -1), |p| (p.block_number as i64, p.log_index as i64));
+
+        let rows = sqlx::query(
+            r"
+            SELECT block_number, log_index, block_hash, transaction_hash, payload::TEXT AS payload
+            FROM ethereum_indexed_events
+            WHERE chain_id = $1 AND contract_address = $2
+              AND (block_number, log_index) > ($3, $4)
+            ORDER BY block_number, log_index
+            LIMIT $5
+            ",
+        )
+        .bind(chain_id as i64)
+        .bind(contract.as_slice())
+        .bind(block_number)
+        .bind(log_index)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Self::indexed_event_from_row).collect()
+    }
+
+    // ==========================================
+    // HANDLER DELIVERY STATE
+    // ==========================================
+
+    pub async fn get_handler_checkpoint(
+        &self,
+        handler: &str,
+        chain_id: u64,
+        contract: Address,
+    ) -> Result<Option<EventPosition>> {
+        let row = sqlx::query(
+            r"
+            SELECT block_number, log_index
+            FROM ethereum_handler_checkpoints
+            WHERE handler = $1 AND chain_id = $2 AND contract_address = $3
+            ",
+        )
+        .bind(handler)
+        .bind(chain_id as i64)
+        .bind(contract.as_slice())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        // A checkpoint rolled back past every block is the same as none
+        Ok(row
+            .map(|row| {
+                (
+                    row.get::<i64, _>("block_number"),
+                    row.get::<i64, _>("log_index"),
+                )
+            })
+            .filter(|(block_number, _)| *block_number >= 0)
+            .map(|(block_number, log_index)| EventPosition {
+                block_number: block_number as u64,
+                log_index: log_index as u64,
+            }))
+    }
+
+    /// Move a handler's checkpoint to `position`; a failed delivery is queued
+    /// for retry after `retry_after` in the same transaction
+    pub async fn commit_handler_delivery(
+        &self,
+        handler: &str,
+        chain_id: u64,
+        contract: Address,
+        position: EventPosition,
+        failure: Option<(&str, Duration)>,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        if let Some((error, retry_after)) = failure {
+            sqlx::query(
+                r"
+                INSERT INTO ethereum_handler_retries (
+                    handler, chain_id, contract_address, block_number, log_index,
+                    attempts, next_attempt_at, last_error
+                ) VALUES ($1, $2, $3, $4, $5, 1, NOW() + $6 * INTERVAL '1 millisecond', $7)
+                ON CONFLICT (handler, chain_id, contract_address, block_number, log_index)
+                DO UPDATE SET attempts = ethereum_handler_retries.attempts + 1,
+                              next_attempt_at = EXCLUDED.next_attempt_at,
+                              last_error = EXCLUDED.last_error,
+                              dead = FALSE,
+                              updated_at = NOW()
+                ",
+            )
+            .bind(handler)
+            .bind(chain_id as i64)
+            .bind(contract.as_slice())
+            .bind(position.block_number as i64)
+            .bind(position.log_index as i64)
+            .bind(retry_after.as_millis() as i64)
+            .bind(error)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query(
+            r"
+            INSERT INTO ethereum_handler_checkpoints (handler, chain_id, contract_address, block_number, log_index)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (handler, chain_id, contract_address)
+            DO UPDATE SET block_number = EXCLUDED.block_number,
+                          log_index = EXCLUDED.log_index,
+                          updated_at = NOW()
+            ",
+        )
+        .bind(handler)
+        .bind(chain_id as i64)
+        .bind(contract.as_slice())
+        .bind(position.block_number as i64)
+        .bind(position.log_index as i64)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Queued retries of `handler` that are due, with their stored events
+    ///
+    /// Retries whose event was removed by a reorg no longer match and are skipped.
+    pub async fn get_due_handler_retries(
+        &self,
+        handler: &str,
+        chain_id: u64,
+        contract: Address,
+        limit: i64,
+    ) -> Result<Vec<PendingRetry>> {
+        let rows = sqlx::query(
+            r"
+            SELECT e.block_number, e.log_index, e.block_hash, e.transaction_hash,
+                   e.payload::TEXT AS payload, r.attempts
+            FROM ethereum_handler_retries r
+            JOIN ethereum_indexed_events e
+              ON e.chain_id = r.chain_id AND e.contract_address = r.contract_address
+             AND e.block_number = r.block_number AND e.log_index = r.log_index
+            WHERE r.handler = $1 AND r.chain_id = $2 AND r.contract_address = $3
+              AND NOT r.dead AND r.next_attempt_at <= NOW()
+            ORDER BY r.block_number, r.log_index
+            LIMIT $4
+            ",
+        )
+        .bind(handler)
+        .bind(chain_id as i64)
+        .bind(contract.as_slice())
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(PendingRetry {
+                    event: Self::indexed_event_from_row(row)?,
+                    attempts: row.get::<i32, _>("attempts") as u32,
+                })
+            })
+            .collect()
+    }
+
+    /// Record a retried delivery: removed on success, otherwise rescheduled
+    /// after `retry_after` or, when that is `None`, kept as dead
+    pub async fn finish_handler_retry(
+        &self,
+        handler: &str,
+        chain_id: u64,
+        contract: Address,
+        position: EventPosition,
+        failure: Option<(&str, Option<Duration>)>,
+    ) -> Result<()> {
+        let sql = if failure.is_some() {
+            r"
+            UPDATE ethereum_handler_retries
+            SET attempts = attempts + 1,
+                next_attempt_at = NOW() + COALESCE($6, 0) * INTERVAL '1 millisecond',
+                dead = $6 IS NULL,
+                last_error = $7,
+                updated_at = NOW()
+            WHERE handler = $1 AND chain_id = $2 AND contract_address = $3
+              AND block_number = $4 AND log_index = $5
+            "
+        } else {
+            r"
+            DELETE FROM ethereum_handler_retries
+            WHERE handler = $1 AND chain_id = $2 AND contract_address = $3
+              AND block_number = $4 AND log_index = $5
+            "
+        };
+
+        let mut query = sqlx::query(sql)
+            .bind(handler)
+            .bind(chain_id as i64)
+            .bind(contract.as_slice())
+            .bind(position.block_number as i64)
+            .bind(position.log_index as i64);
+        if let Some((error, retry_after)) = failure {
+            query = query
+                .bind(retry_after.map(|d| d.as_millis() as i64))
+                .bind(error);
+        }
+
+        query.execute(&self.pool).await?;
+        Ok(())
+    }
+
+    fn indexed_event_from_row(row: &sqlx::postgres::PgRow) -> Result<IndexedEvent> {
+        let event: ArithmeticEvent = serde_json::from_str(row.get::<&str, _>("payload"))?;
+        Ok(IndexedEvent {
+            block_number: row.get::<i64, _>("block_number") as u64,
+            block_hash: FixedBytes::from_slice(row.get::<&[u8], _>("block_hash")),
+            log_index: row.get::<i64, _>("log_index") as u64,
+            transaction_hash: FixedBytes::from_slice(row.get::<&[u8], _>("transaction_hash")),
+            event,
+        })
+    }
+
+    async fn upsert_indexed_blocks(
+        tx: &mut Transaction<'_, Postgres>,
+        chain_id: u64,
+        contract: Address,
+        blocks: &[(u64, FixedBytes<32>)],
+    ) -> Result<()> {
+        for (block_number, block_hash) in blocks {
+            sqlx::query(
+                r"
+                INSERT INTO ethereum_indexed_blocks (chain_id, contract_address, block_number, block_hash)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (chain_id, contract_address, block_number)
+                DO UPDATE SET block_hash = EXCLUDED.block_hash
+                ",
+            )
+            .bind(chain_id as i64)
+            .bind(contract.as_slice())
+            .bind(*block_number as i64)
+            .bind(block_hash.as_slice())
+            .execute(&mut **tx)
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn insert_indexed_events(
+        tx: &mut Transaction<'_, Postgres>,
+        chain_id: u64,
+        contract: Address,
+        events: &[IndexedEvent],
+    ) -> Result<()> {
+        for indexed in events {
+            let payload = serde_json::to_string(&indexed.event).map_err(EthereumError::from)?;
+            sqlx::query(
+                r"
+                INSERT INTO ethereum_indexed_events (
+                    chain_id, contract_address, block_number, log_index,
+                    block_hash, transaction_hash, event_type, payload
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8::JSONB)
+                ON CONFLICT (chain_id, contract_address, block_number, log_index)
+                DO UPDATE SET block_hash = EXCLUDED.block_hash,
+                              transaction_hash = EXCLUDED.transaction_hash,
+                              event_type = EXCLUDED.event_type,
+                              payload = EXCLUDED.payload
+                ",
+            )
+            .bind(chain_id as i64)
+            .bind(contract.as_slice())
+            .bind(indexed.block_number as i64)
+            .bind(indexed.log_index as i64)
+            .bind(indexed.block_hash.as_slice())
+            .bind(indexed.transaction_hash.as_slice())
+            .bind(indexed.event.event_type())
+            .bind(payload)
+            .execute(&mut **tx)
+            .await?;
+        }
+        Ok(())
+    }
 }
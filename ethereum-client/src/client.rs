@@ -2,10 +2,16 @@ use crate::{
     config::Config,
     contracts::{ContractAddresses, IArithmetic, IArithmeticInstance, ISP1Verifier},
     error::{EthereumError, Result},
+    gas::{self, NonceManager},
+    health,
+    signer::{self, SignerBackend},
+    subscription::{LogCursor, LogSubscription, ReconnectBackoff},
     types::{
-        BatchStateUpdate, InclusionProof, NetworkStats, ProofVerificationResult, StateHistory,
-        StateResponse, StateUpdate,
+        BatchStateUpdate, ConsistencyChecks, InclusionProof, IndependentVerificationResult,
+        NetworkStats, ProofVerificationResult, StateHistory, StateResponse, StateUpdate,
+        VerificationData,
     },
+    verification,
 };
 use alloy_network::EthereumWallet;
 use alloy_primitives::{keccak256, Address, Bytes, FixedBytes, U256};
@@ -16,9 +22,9 @@ use alloy_provider::{
     Identity, Provider, ProviderBuilder, RootProvider,
 };
 use alloy_rpc_types_eth::{Filter, TransactionReceipt};
-use alloy_signer_local::PrivateKeySigner;
 use alloy_sol_types::SolEvent;
 use hex;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{broadcast, RwLock};
@@ -28,6 +34,12 @@ use tracing::{debug, error, info, warn};
 #[cfg(feature = "database")]
 use uuid;
 
+/// Blocks searched for `StateUpdated` logs when no deployment block is configured
+const DEFAULT_LOG_LOOKBACK: u64 = 10_000;
+
+/// Allowed clock skew when checking on-chain proof timestamps
+const MAX_TIMESTAMP_DRIFT_SECS: u64 = 15 * 60;
+
 // Use a simpler provider type that works with the current Alloy version
 type EthProvider = FillProvider<
     JoinFill<
@@ -43,10 +55,16 @@ type EthProvider = FillProvider<
 #[cfg(feature = "database")]
 use crate::cache::EthereumCache;
 #[cfg(feature = "database")]
+use crate::delivery::{DeliveryConfig, HandlerDelivery};
+#[cfg(feature = "database")]
+use crate::event_manager::EventHandler;
+#[cfg(feature = "database")]
+use crate::indexer::{ChainIndexer, IndexedEvent, IndexerConfig};
+#[cfg(feature = "database")]
 use sqlx;
 
 /// Event data structures for comprehensive event handling
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ArithmeticEvent {
     StateUpdated {
         state_id: FixedBytes<32>,
@@ -97,6 +115,21 @@ pub enum ArithmeticEvent {
     },
 }
 
+impl ArithmeticEvent {
+    /// Solidity event name
+    #[must_use]
+    pub const fn event_type(&self) -> &'static str {
+        match self {
+            Self::StateUpdated { .. } => "StateUpdated",
+            Self::BatchStateUpdated { .. } => "BatchStateUpdated",
+            Self::ProofStored { .. } => "ProofStored",
+            Self::ProofVerified { .. } => "ProofVerified",
+            Self::StateReadRequested { .. } => "StateReadRequested",
+            Self::ProofReadRequested { .. } => "ProofReadRequested",
+        }
+    }
+}
+
 /// Event callback function type
 pub type EventCallback = Arc<dyn Fn(ArithmeticEvent) + Send + Sync>;
 
@@ -142,7 +175,8 @@ pub struct EthereumClient {
     http_provider: EthProvider,
     #[allow(dead_code)]
     contracts: ContractAddresses,
-    signer: PrivateKeySigner,
+    signer: Arc<dyn SignerBackend>,
+    nonces: Arc<NonceManager>,
 
     #[cfg(feature = "database")]
     cache: Option<EthereumCache>,
@@ -165,12 +199,9 @@ impl EthereumClient {
             .as_ref()
             .ok_or_else(|| EthereumError::Config("Signer required".to_string()))?;
 
-        let signer = PrivateKeySigner::from_bytes(&FixedBytes::<32>::try_from(
-            hex::decode(&signer_config.private_key)?.as_slice(),
-        )?)
-        .map_err(|e| EthereumError::Signer(e.to_string()))?;
-
-        let wallet = EthereumWallet::from(signer.clone());
+        let signer = signer::load_signer(signer_config).await?;
+        let wallet = signer.wallet();
+        let nonces = Arc::new(NonceManager::new(signer.address()));
 
         // Create provider with wallet for signing
         let http_provider = ProviderBuilder::new()
@@ -201,7 +232,8 @@ impl EthereumClient {
             config: config.clone(),
             http_provider: http_provider.clone(),
             contracts: contracts.clone(),
-            signer: signer.clone(),
+            signer: Arc::clone(&signer),
+            nonces: Arc::clone(&nonces),
 
             #[cfg(feature = "database")]
             cache: cache.clone(),
@@ -227,6 +259,7 @@ impl EthereumClient {
             http_provider,
             contracts,
             signer,
+            nonces,
 
             #[cfg(feature = "database")]
             cache,
@@ -263,28 +296,26 @@ impl EthereumClient {
         // Create contract instance
         let contract = IArithmetic::new(self.contracts.arithmetic, &self.http_provider);
 
-        // Send transaction (wallet handles signing automatically)
-        let tx_result = contract
+        let request = contract
             .updateState(
                 state_id,
                 new_state_root,
                 proof.clone(),
                 public_values.clone(),
             )
-            .send()
-            .await
-            .map_err(|e| {
-                error!("Failed to send state update transaction: {e}");
-                EthereumError::from_contract_error(&format!("Transaction failed: {e}"))
-            })?;
-
-        info!("State update transaction sent: {}", tx_result.tx_hash());
-
-        // Wait for confirmation
-        let receipt = tx_result.get_receipt().await.map_err(|e| {
-            error!("Failed to get transaction receipt: {e}");
-            EthereumError::Transaction(format!("Receipt error: {e}"))
-        })?;
+            .from(self.signer.address())
+            .into_transaction_request();
+
+        // Fees, nonce and replacement of stuck transactions are handled by the gas
+        // manager; the wallet still signs every attempt
+        let receipt = gas::submit_with_replacement(
+            &self.http_provider,
+            &self.nonces,
+            &self.config.gas,
+            request,
+            "State update",
+        )
+        .await?;
 
         let block_number = receipt.block_number.unwrap_or(0);
         let tx_hash = receipt.transaction_hash;
@@ -526,28 +557,24 @@ impl EthereumClient {
         // Create contract instance
         let contract = IArithmetic::new(self.contracts.arithmetic, &self.http_provider);
 
-        // Send batch transaction (wallet handles signing automatically)
-        let tx_result = contract
+        let request = contract
             .batchUpdateStates(
                 state_ids.clone(),
                 new_state_roots.clone(),
                 proofs.clone(),
                 results.clone(),
             )
-            .send()
-            .await
-            .map_err(|e| {
-                error!("Failed to send batch update transaction: {e}");
-                EthereumError::from_contract_error(&format!("Batch transaction failed: {e}"))
-            })?;
-
-        info!("Batch update transaction sent: {}", tx_result.tx_hash());
-
-        // Wait for confirmation
-        let receipt = tx_result.get_receipt().await.map_err(|e| {
-            error!("Failed to get batch transaction receipt: {e}");
-            EthereumError::Transaction(format!("Batch receipt error: {e}"))
-        })?;
+            .from(self.signer.address())
+            .into_transaction_request();
+
+        let receipt = gas::submit_with_replacement(
+            &self.http_provider,
+            &self.nonces,
+            &self.config.gas,
+            request,
+            "Batch update",
+        )
+        .await?;
 
         let block_number = receipt.block_number.unwrap_or(0);
         let tx_hash = receipt.transaction_hash;
@@ -560,9 +587,24 @@ impl EthereumClient {
             gas_used
         );
 
-        // For now, assume all updates succeeded
-        // TODO: Parse transaction logs to determine individual success/failure
-        let success_flags = vec![true; state_ids.len()];
+        let proof_ids = Self::decode_batch_results(
+            self.contracts.arithmetic,
+            receipt.inner.logs(),
+            &state_ids,
+            &new_state_roots,
+            &proofs,
+        )?;
+        let success_flags: Vec<bool> = proof_ids.iter().map(Option::is_some).collect();
+
+        let failed = success_flags.iter().filter(|ok| !**ok).count();
+        if failed > 0 {
+            warn!(
+                "Batch update {}: {} of {} items were rejected by the contract",
+                hex::encode(tx_hash.as_slice()),
+                failed,
+                success_flags.len()
+            );
+        }
 
         let batch_update = BatchStateUpdate {
             state_ids,
@@ -573,11 +615,105 @@ impl EthereumClient {
             block_number,
             gas_used,
             success_flags,
+            proof_ids,
         };
 
         Ok(batch_update)
     }
 
+    /// Number of blocks that include or build on `tx_hash`'s block
+    ///
+    /// Returns `None` when the node has no receipt for the transaction, e.g. after
+    /// it was reorged out.
+    pub async fn confirmation_depth(&self, tx_hash: FixedBytes<32>) -> Result<Option<u64>> {
+        let Some(receipt) = self.http_provider.get_transaction_receipt(tx_hash).await? else {
+            return Ok(None);
+        };
+        let Some(block_number) = receipt.block_number else {
+            return Ok(None);
+        };
+
+        let head = self.http_provider.get_block_number().await?;
+        Ok(Some(head.saturating_sub(block_number) + 1))
+    }
+
+    /// Match the logs of a `batchUpdateStates` receipt to its input items
+    ///
+    /// An item landed when the contract emitted a `StateUpdated` for its state id,
+    /// new root and proof id (`keccak256(proof)`) together with a successful
+    /// `ProofVerified`. Returns the proof id of every item that landed, `None` for
+    /// the ones the contract skipped. Fails if the receipt has no matching
+    /// `BatchStateUpdated` log, since then it is not a receipt for this batch.
+    pub fn decode_batch_results(
+        contract: Address,
+        logs: &[alloy_rpc_types_eth::Log],
+        state_ids: &[FixedBytes<32>],
+        new_state_roots: &[FixedBytes<32>],
+        proofs: &[Bytes],
+    ) -> Result<Vec<Option<FixedBytes<32>>>> {
+        let mut updates = Vec::new();
+        let mut verified = std::collections::HashSet::new();
+        let mut batch_logged = false;
+
+        for event in logs
+            .iter()
+            .filter(|log| log.address() == contract)
+            .filter_map(Self::convert_log_to_event)
+        {
+            match event {
+                ArithmeticEvent::StateUpdated {
+                    state_id,
+                    new_state,
+                    proof_id,
+                    ..
+                } => updates.push(Some((state_id, new_state, proof_id))),
+                ArithmeticEvent::ProofVerified {
+                    proof_id,
+                    success: true,
+                    ..
+                } => {
+                    verified.insert(proof_id);
+                }
+                ArithmeticEvent::BatchStateUpdated {
+                    state_ids: logged_ids,
+                    new_states,
+                    ..
+                } => batch_logged |= logged_ids == state_ids && new_states == new_state_roots,
+                _ => {}
+            }
+        }
+
+        if !batch_logged {
+            return Err(EthereumError::Transaction(
+                "Receipt has no BatchStateUpdated log for this batch".to_string(),
+            ));
+        }
+
+        let results = state_ids
+            .iter()
+            .zip(new_state_roots)
+            .zip(proofs)
+            .map(|((state_id, new_root), proof)| {
+                let proof_id = keccak256(proof);
+                // Each log is consumed once so repeated state ids in a batch are matched in order
+                let landed = updates.iter_mut().find(|update| {
+                    update.is_some_and(|(id, root, pid)| {
+                        id == *state_id && root == *new_root && pid == proof_id
+                    })
+                });
+                match landed {
+                    Some(update) if verified.contains(&proof_id) => {
+                        *update = None;
+                        Some(proof_id)
+                    }
+                    _ => None,
+                }
+            })
+            .collect();
+
+        Ok(results)
+    }
+
     pub async fn get_current_state(
         &self,
         state_id: FixedBytes<32>,
@@ -677,6 +813,14 @@ impl EthereumClient {
     }
 
     /// Start comprehensive event monitoring with enhanced capabilities
+    ///
+    /// With a database cache configured this runs the durable `ChainIndexer`,
+    /// which resumes from its stored cursor and handles reorgs. Without one it
+    /// follows the chain from the current block and keeps progress in memory only.
+    ///
+    /// When `NetworkConfig.ws_url` is set, new logs arrive over an
+    /// `eth_subscribe` stream (for the indexer they only trigger the next step);
+    /// HTTP polling takes over whenever the WebSocket is unavailable.
     #[allow(clippy::cognitive_complexity)]
     pub async fn start_event_monitoring(&self) -> Result<()> {
         info!(
@@ -684,6 +828,11 @@ impl EthereumClient {
             self.contracts.arithmetic
         );
 
+        #[cfg(feature = "database")]
+        if let Some(indexer) = self.indexer() {
+            return self.run_indexer(&indexer).await;
+        }
+
         // Initialize last processed block if not set
         {
             let mut last_block = self.last_processed_block.write().await;
@@ -693,6 +842,10 @@ impl EthereumClient {
             }
         }
 
+        if let Some(ws_url) = self.config.network.ws_url.clone() {
+            return self.run_log_subscription(&ws_url).await;
+        }
+
         // Start monitoring loop
         loop {
             match self.process_new_events().await {
@@ -712,6 +865,257 @@ impl EthereumClient {
         }
     }
 
+    /// Database cache, if one is configured
+    #[cfg(feature = "database")]
+    #[must_use]
+    pub const fn cache(&self) -> Option<&EthereumCache> {
+        self.cache.as_ref()
+    }
+
+    /// Checkpointed delivery of this contract's indexed events to `handler`;
+    /// `None` without a database cache
+    #[cfg(feature = "database")]
+    #[must_use]
+    pub fn handler_delivery(
+        &self,
+        handler: Arc<dyn EventHandler>,
+        config: DeliveryConfig,
+    ) -> Option<HandlerDelivery> {
+        self.cache.as_ref().map(|cache| {
+            HandlerDelivery::new(
+                cache.clone(),
+                handler,
+                self.config.network.chain_id,
+                self.contracts.arithmetic,
+                config,
+            )
+        })
+    }
+
+    /// Durable indexer over this client's provider and cache
+    #[cfg(feature = "database")]
+    fn indexer(&self) -> Option<ChainIndexer<EthProvider>> {
+        self.cache.as_ref().map(|cache| {
+            ChainIndexer::new(
+                self.http_provider.clone(),
+                cache.clone(),
+                self.config.network.chain_id,
+                self.contracts.arithmetic,
+                IndexerConfig::from_config(&self.config),
+            )
+        })
+    }
+
+    /// Follow contract logs over the WebSocket at `ws_url`, backfilling any
+    /// gap over HTTP after each (re)connect and polling while disconnected
+    async fn run_log_subscription(&self, ws_url: &url::Url) -> Result<()> {
+        let mut cursor = LogCursor::new(*self.last_processed_block.read().await);
+        let mut backoff = ReconnectBackoff::new(self.listener_config.retry_delay);
+
+        loop {
+            if backoff.ready() {
+                match LogSubscription::connect(ws_url, &self.log_filter()).await {
+                    Ok(mut subscription) => {
+                        info!("Subscribed to contract logs over {}", ws_url);
+                        backoff.reset();
+                        if let Err(e) = self
+                            .deliver_subscription(&mut subscription, &mut cursor)
+                            .await
+                        {
+                            error!("Error in log subscription: {}", e);
+                        }
+                        warn!("Log subscription ended, falling back to HTTP polling");
+                    }
+                    Err(e) => {
+                        warn!(
+                            "Log subscription over {} failed, polling over HTTP: {}",
+                            ws_url, e
+                        );
+                    }
+                }
+                backoff.failed();
+            }
+
+            match self.backfill(&mut cursor).await {
+                Ok(processed_count) => {
+                    if processed_count > 0 {
+                        debug!("Processed {} new events", processed_count);
+                    }
+                }
+                Err(e) => error!("Error in event monitoring: {}", e),
+            }
+
+            sleep(self.listener_config.poll_interval.min(backoff.remaining())).await;
+        }
+    }
+
+    /// Backfill up to the head, then dispatch streamed logs until the connection drops
+    async fn deliver_subscription(
+        &self,
+        subscription: &mut LogSubscription,
+        cursor: &mut LogCursor,
+    ) -> Result<()> {
+        self.backfill(cursor).await?;
+
+        while let Some(log) = subscription.next().await {
+            if log.removed {
+                warn!(
+                    "Ignoring log removed by a reorg: tx={:?}, block={:?}",
+                    log.transaction_hash, log.block_number
+                );
+                continue;
+            }
+            let (Some(block_number), Some(log_index)) = (log.block_number, log.log_index) else {
+                continue;
+            };
+
+            if cursor.accept(block_number, log_index) {
+                if let Some(event) = Self::convert_log_to_event(&log) {
+                    self.process_and_dispatch_event(event).await;
+                }
+            }
+            *self.last_processed_block.write().await = cursor.complete_through();
+        }
+
+        Ok(())
+    }
+
+    /// Dispatch every not yet delivered log up to the current head over HTTP
+    async fn backfill(&self, cursor: &mut LogCursor) -> Result<usize> {
+        let current_block = self.http_provider.get_block_number().await?;
+        let mut event_count = 0;
+
+        while cursor.complete_through() < current_block {
+            let from_block = cursor.complete_through() + 1;
+            let to_block = std::cmp::min(
+                current_block,
+                cursor.complete_through() + self.listener_config.max_blocks_per_query,
+            );
+
+            for log in self.fetch_logs_in_range(from_block, to_block).await? {
+                let (Some(block_number), Some(log_index)) = (log.block_number, log.log_index)
+                else {
+                    continue;
+                };
+                if !cursor.accept_backfilled(block_number, log_index) {
+                    continue;
+                }
+                if let Some(event) = Self::convert_log_to_event(&log) {
+                    self.process_and_dispatch_event(event).await;
+                    event_count += 1;
+                }
+            }
+
+            cursor.complete(to_block);
+            *self.last_processed_block.write().await = to_block;
+        }
+
+        Ok(event_count)
+    }
+
+    /// Wait up to `timeout` for the log subscription to report contract
+    /// activity, (re)connecting it when due; a plain sleep without a WebSocket URL
+    #[cfg(feature = "database")]
+    async fn wait_for_logs(
+        &self,
+        subscription: &mut Option<LogSubscription>,
+        backoff: &mut ReconnectBackoff,
+        timeout: Duration,
+    ) {
+        let Some(ws_url) = &self.config.network.ws_url else {
+            sleep(timeout).await;
+            return;
+        };
+
+        if subscription.is_none() && backoff.ready() {
+            match LogSubscription::connect(ws_url, &self.log_filter()).await {
+                Ok(connected) => {
+                    info!("Subscribed to contract logs over {}", ws_url);
+                    backoff.reset();
+                    *subscription = Some(connected);
+                }
+                Err(e) => {
+                    warn!(
+                        "Log subscription over {} failed, indexing over HTTP polling: {}",
+                        ws_url, e
+                    );
+                    backoff.failed();
+                }
+            }
+        }
+
+        let Some(active) = subscription else {
+            sleep(timeout).await;
+            return;
+        };
+        if let Ok(None) = tokio::time::timeout(timeout, active.next()).await {
+            warn!("Log subscription ended, indexing over HTTP polling");
+            *subscription = None;
+            backoff.failed();
+        }
+    }
+
+    #[cfg(feature = "database")]
+    async fn run_indexer(&self, indexer: &ChainIndexer<EthProvider>) -> Result<()> {
+        if let Some(cache) = &self.cache {
+            cache.initialize().await?;
+        }
+        if let Some(cursor) = indexer.cursor().await? {
+            info!(
+                "Resuming event indexing after block {}",
+                cursor.block_number
+            );
+        }
+
+        let mut subscription = None;
+        let mut backoff = ReconnectBackoff::new(self.listener_config.retry_delay);
+
+        loop {
+            match indexer.step().await {
+                Ok(step) => {
+                    if let Some(cursor) = step.cursor {
+                        *self.last_processed_block.write().await = cursor.block_number;
+                    }
+                    for indexed in step.events {
+                        self.process_and_dispatch_event(indexed.event).await;
+                    }
+                    if !step.caught_up {
+                        // More blocks may be waiting; skip the poll delay
+                        continue;
+                    }
+                }
+                Err(e) => {
+                    error!("Error in event indexing: {}", e);
+                    sleep(self.listener_config.retry_delay).await;
+                }
+            }
+
+            // New logs wake the indexer early; reorgs and gaps are still
+            // resolved by the step itself
+            self.wait_for_logs(
+                &mut subscription,
+                &mut backoff,
+                indexer.config().poll_interval,
+            )
+            .await;
+        }
+    }
+
+    /// Re-fetch the events of an already indexed block range, store them again
+    /// and dispatch them to subscribers
+    #[cfg(feature = "database")]
+    pub async fn replay_events(&self, from_block: u64, to_block: u64) -> Result<Vec<IndexedEvent>> {
+        let indexer = self.indexer().ok_or_else(|| {
+            EthereumError::Config("Replaying events requires DATABASE_URL".to_string())
+        })?;
+
+        let events = indexer.replay(from_block, to_block).await?;
+        for indexed in &events {
+            self.process_and_dispatch_event(indexed.event.clone()).await;
+        }
+        Ok(events)
+    }
+
     /// Process new events since last check
     async fn process_new_events(&self) -> Result<usize> {
         let current_block = self.http_provider.get_block_number().await?;
@@ -758,15 +1162,7 @@ impl EthereumClient {
         from_block: u64,
         to_block: u64,
     ) -> Result<Vec<ArithmeticEvent>> {
-        let filter = Filter::new()
-            .address(self.contracts.arithmetic)
-            .from_block(from_block)
-            .to_block(to_block);
-
-        let logs = self.http_provider.get_logs(&filter).await.map_err(|e| {
-            error!("Failed to fetch logs: {}", e);
-            EthereumError::External(format!("Log retrieval failed: {e}"))
-        })?;
+        let logs = self.fetch_logs_in_range(from_block, to_block).await?;
 
         let mut events = Vec::new();
         for log in logs {
@@ -778,8 +1174,26 @@ impl EthereumClient {
         Ok(events)
     }
 
+    /// Filter matching every log of the arithmetic contract
+    fn log_filter(&self) -> Filter {
+        Filter::new().address(self.contracts.arithmetic)
+    }
+
+    async fn fetch_logs_in_range(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<alloy_rpc_types_eth::Log>> {
+        let filter = self.log_filter().from_block(from_block).to_block(to_block);
+
+        self.http_provider.get_logs(&filter).await.map_err(|e| {
+            error!("Failed to fetch logs: {}", e);
+            EthereumError::External(format!("Log retrieval failed: {e}"))
+        })
+    }
+
     /// Convert RPC log to `ArithmeticEvent`
-    fn convert_log_to_event(log: &alloy_rpc_types_eth::Log) -> Option<ArithmeticEvent> {
+    pub(crate) fn convert_log_to_event(log: &alloy_rpc_types_eth::Log) -> Option<ArithmeticEvent> {
         let primitive_log = Self::convert_rpc_log_to_primitive(log);
         let block_number = log.block_number.unwrap_or(0);
         let tx_hash = log.transaction_hash.unwrap_or_default();
@@ -883,15 +1297,7 @@ impl EthereumClient {
     fn event_matches_filter(event: &ArithmeticEvent, filter: &EventFilter) -> bool {
         // Check event type filter
         if let Some(event_types) = &filter.event_types {
-            let event_type = match event {
-                ArithmeticEvent::StateUpdated { .. } => "StateUpdated",
-                ArithmeticEvent::BatchStateUpdated { .. } => "BatchStateUpdated",
-                ArithmeticEvent::ProofStored { .. } => "ProofStored",
-                ArithmeticEvent::ProofVerified { .. } => "ProofVerified",
-                ArithmeticEvent::StateReadRequested { .. } => "StateReadRequested",
-                ArithmeticEvent::ProofReadRequested { .. } => "ProofReadRequested",
-            };
-            if !event_types.contains(&event_type.to_string()) {
+            if !event_types.iter().any(|t| t == event.event_type()) {
                 return false;
             }
         }
@@ -1160,60 +1566,253 @@ impl EthereumClient {
         Ok(None)
     }
 
-    pub fn verify_proof_independently(
+    /// Verify a stored proof using only on-chain data
+    ///
+    /// Fetches the proof and public values the contract stored under `proof_id`,
+    /// verifies them locally against the contract's `arithmeticProgramVKey`, and
+    /// cross-checks the proof metadata, the `StateUpdated` event that recorded it
+    /// and the contract's `stateHistory`.
+    #[allow(clippy::cognitive_complexity)]
+    pub async fn verify_proof_independently(
         &self,
         proof_id: FixedBytes<32>,
-    ) -> Result<ProofVerificationResult> {
-        Err(EthereumError::General(eyre::eyre!(
-            "verify_proof_independently: not implemented (proof_id: 0x{})",
-            hex::encode(proof_id)
-        )))
+    ) -> Result<IndependentVerificationResult> {
+        let contract = IArithmetic::new(self.contracts.arithmetic, &self.http_provider);
+
+        let verifier_key = contract.arithmeticProgramVKey().call().await.map_err(|e| {
+            EthereumError::from_contract_error(&format!("Verifier key query failed: {e}"))
+        })?;
+        let proof_bytes = self
+            .get_proof_data(proof_id)
+            .await?
+            .ok_or(EthereumError::ContractProofNotFound)?;
+        let public_values = self.get_proof_result(proof_id).await?.unwrap_or_default();
+        let metadata = contract
+            .getProofMetadata(proof_id)
+            .call()
+            .await
+            .map_err(|e| {
+                EthereumError::from_contract_error(&format!("Proof metadata query failed: {e}"))
+            })?;
+        let verified_on_chain = contract
+            .isProofVerified(proof_id)
+            .call()
+            .await
+            .map_err(|e| {
+                EthereumError::from_contract_error(&format!("Proof status query failed: {e}"))
+            })?;
+
+        // The StateUpdated event that recorded this proof, and the history it extended
+        let recorded = self
+            .find_state_updates(Filter::new().topic3(proof_id))
+            .await?
+            .into_iter()
+            .find_map(|event| match event {
+                ArithmeticEvent::StateUpdated {
+                    state_id,
+                    new_state,
+                    timestamp,
+                    block_number,
+                    ..
+                } => Some((state_id, new_state, timestamp, block_number)),
+                _ => None,
+            });
+        let history = match recorded {
+            Some((state_id, ..)) => contract
+                .readStateHistory(state_id, U256::ZERO)
+                .call()
+                .await
+                .map_err(|e| {
+                    EthereumError::from_contract_error(&format!("State history query failed: {e}"))
+                })?,
+            None => Vec::new(),
+        };
+
+        let sp1_result = verification::verify_sp1_proof(&proof_bytes, &public_values, verifier_key);
+        match &sp1_result {
+            Ok(system) => info!("SP1 {:?} proof verified locally", system),
+            Err(e) => warn!("Local SP1 verification failed: {}", e),
+        }
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let timestamp = u64::try_from(metadata.timestamp).unwrap_or(u64::MAX);
+
+        let mut checks = ConsistencyChecks {
+            proof_id_matches_hash: keccak256(&proof_bytes) == proof_id
+                && metadata.proofId == proof_id,
+            state_exists: recorded.is_some_and(|(state_id, new_state, ..)| {
+                state_id == metadata.stateId && history.contains(&new_state)
+            }),
+            proof_data_present: !proof_bytes.is_empty() && !public_values.is_empty(),
+            timestamp_reasonable: timestamp > 0
+                && timestamp <= now + MAX_TIMESTAMP_DRIFT_SECS
+                && recorded.is_none_or(|(.., event_timestamp, _)| event_timestamp == timestamp),
+            verifier_key_valid: verifier_key != FixedBytes::ZERO
+                && Self::load_local_verification_key().map_or(true, |local| local == verifier_key),
+            all_passed: false,
+        };
+        checks.all_passed = checks.proof_id_matches_hash
+            && checks.state_exists
+            && checks.proof_data_present
+            && checks.timestamp_reasonable
+            && checks.verifier_key_valid;
+
+        Ok(IndependentVerificationResult {
+            proof_id,
+            sp1_verification_passed: sp1_result.is_ok(),
+            on_chain_verification_status: verified_on_chain && metadata.verified,
+            consistency_checks_passed: checks.all_passed,
+            consistency_details: checks,
+            verification_data: VerificationData {
+                proof_id,
+                state_id: metadata.stateId,
+                proof_bytes,
+                public_values,
+                verifier_key,
+                state_root: recorded.map_or(FixedBytes::ZERO, |(_, new_state, ..)| new_state),
+                submitter: metadata.submitter,
+                timestamp,
+                verified_on_chain,
+                block_number: recorded.map(|(.., block_number)| block_number),
+            },
+            verified_at: now,
+        })
     }
 
-    pub fn get_historical_states(
+    /// State roots recorded for `state_id` (the most recent `limit`, oldest first)
+    ///
+    /// Roots come from the contract's `stateHistory`. Every history entry is
+    /// pushed together with one `StateUpdated` event, so the newest events are
+    /// matched to the roots in order; a root whose event is missing or disagrees
+    /// gets block 0, timestamp 0 and no proof id.
+    /// The newest `limit` roots of `stateHistory(state_id)`, oldest first; all of them when `limit` is 0
+    pub async fn read_state_history(
+        &self,
+        state_id: FixedBytes<32>,
+        limit: u64,
+    ) -> Result<Vec<FixedBytes<32>>> {
+        let contract = IArithmetic::new(self.contracts.arithmetic, &self.http_provider);
+
+        contract
+            .readStateHistory(state_id, U256::from(limit))
+            .call()
+            .await
+            .map_err(|e| {
+                EthereumError::from_contract_error(&format!("State history query failed: {e}"))
+            })
+    }
+
+    pub async fn get_historical_states(
         &self,
         state_id: FixedBytes<32>,
         limit: Option<u64>,
     ) -> Result<StateHistory> {
-        // TODO: Implement historical states retrieval
+        let limit = limit.unwrap_or(100);
+        let state_roots = self.read_state_history(state_id, limit).await?;
+
+        let updates: Vec<_> = self
+            .find_state_updates(Filter::new().topic1(state_id))
+            .await?
+            .into_iter()
+            .filter_map(|event| match event {
+                ArithmeticEvent::StateUpdated {
+                    new_state,
+                    proof_id,
+                    timestamp,
+                    block_number,
+                    ..
+                } => Some((new_state, proof_id, timestamp, block_number)),
+                _ => None,
+            })
+            .collect();
+
+        // Align the newest events with the newest roots
+        let skipped_events = updates.len().saturating_sub(state_roots.len());
+        let missing_events = state_roots.len().saturating_sub(updates.len());
+
+        let mut block_numbers = Vec::with_capacity(state_roots.len());
+        let mut timestamps = Vec::with_capacity(state_roots.len());
+        let mut proof_ids = Vec::with_capacity(state_roots.len());
+        for (i, root) in state_roots.iter().enumerate() {
+            let update = i
+                .checked_sub(missing_events)
+                .map(|j| updates[skipped_events + j])
+                .filter(|(new_state, ..)| new_state == root);
+            if update.is_none() {
+                warn!(
+                    "No StateUpdated event found for history entry {} ({})",
+                    i, root
+                );
+            }
+            block_numbers.push(update.map_or(0, |(.., block_number)| block_number));
+            timestamps.push(update.map_or(0, |(_, _, timestamp, _)| timestamp));
+            proof_ids.push(update.map(|(_, proof_id, ..)| proof_id));
+        }
+
         Ok(StateHistory {
             state_id,
-            state_roots: vec![FixedBytes::ZERO],
-            block_numbers: vec![0],
-            timestamps: vec![0],
-            proof_ids: vec![Some(FixedBytes::ZERO)],
-            limit: limit.unwrap_or(100),
+            state_roots,
+            block_numbers,
+            timestamps,
+            proof_ids,
+            limit,
         })
     }
 
-    pub async fn get_network_stats(&self) -> Result<NetworkStats> {
-        // TODO: Implement network stats retrieval
-        let current_block = self.http_provider.get_block_number().await?;
+    /// `StateUpdated` events matching `filter`, scanned from the deployment block
+    /// (or the last `DEFAULT_LOG_LOOKBACK` blocks) in `max_block_range` chunks
+    async fn find_state_updates(&self, filter: Filter) -> Result<Vec<ArithmeticEvent>> {
+        let head = self.http_provider.get_block_number().await?;
+        let start = self
+            .config
+            .contract
+            .deployment_block
+            .unwrap_or_else(|| head.saturating_sub(DEFAULT_LOG_LOOKBACK));
+        let range = self.config.monitoring.max_block_range.max(1);
+        let filter = filter
+            .address(self.contracts.arithmetic)
+            .event_signature(IArithmetic::StateUpdated::SIGNATURE_HASH);
 
-        Ok(NetworkStats {
-            chain_id: 1,
-            network_name: "mainnet".to_string(),
-            block_number: current_block,
-            gas_price: U256::ZERO,
-            base_fee: Some(U256::ZERO),
-            sync_status: true,
-        })
+        let mut events = Vec::new();
+        let mut from_block = start;
+        while from_block <= head {
+            let to_block = head.min(from_block + range - 1);
+            let logs = self
+                .http_provider
+                .get_logs(&filter.clone().from_block(from_block).to_block(to_block))
+                .await
+                .map_err(|e| EthereumError::External(format!("Log retrieval failed: {e}")))?;
+            events.extend(logs.iter().filter_map(Self::convert_log_to_event));
+            from_block = to_block + 1;
+        }
+
+        Ok(events)
     }
 
+    pub async fn get_network_stats(&self) -> Result<NetworkStats> {
+        health::fetch_network_stats(&self.http_provider, &self.config.network.name).await
+    }
+
+    /// Check a Merkle inclusion path (SHA-256 pairs, siblings ordered from the leaf up)
     pub fn check_inclusion_proof(
         &self,
-        _leaf_hash: FixedBytes<32>,
-        _leaf_index: u64,
-        _siblings: Vec<FixedBytes<32>>,
-        _root: FixedBytes<32>,
+        leaf_hash: FixedBytes<32>,
+        leaf_index: u64,
+        siblings: Vec<FixedBytes<32>>,
+        root: FixedBytes<32>,
     ) -> Result<InclusionProof> {
-        // TODO: Implement inclusion proof checking
+        let verified =
+            verification::compute_merkle_root(leaf_hash, leaf_index, &siblings) == Some(root);
+
         Ok(InclusionProof {
-            leaf_hash: FixedBytes::ZERO,
-            leaf_index: 0,
-            siblings: vec![],
-            root: FixedBytes::ZERO,
-            verified: false,
+            leaf_hash,
+            leaf_index,
+            siblings,
+            root,
+            verified,
         })
     }
 
@@ -1251,12 +1850,21 @@ impl EthereumClient {
         Ok(state_root)
     }
 
-    pub fn get_state_proof_history(
+    /// Proof ids the contract recorded for `state_id`, oldest first
+    pub async fn get_state_proof_history(
         &self,
-        _state_id: FixedBytes<32>,
+        state_id: FixedBytes<32>,
     ) -> Result<Vec<FixedBytes<32>>> {
-        // TODO: Implement state proof history retrieval from contract
-        Ok(vec![FixedBytes::ZERO])
+        let contract = IArithmetic::new(self.contracts.arithmetic, &self.http_provider);
+
+        contract
+            .getProofsByStateId(state_id)
+            .call()
+            .await
+            .map_err(|e| {
+                warn!("Failed to get state proof history: {}", e);
+                EthereumError::from_contract_error(&format!("Proof history query failed: {e}"))
+            })
     }
 
     pub async fn get_verifier_version(&self) -> Result<String> {
@@ -1277,12 +1885,9 @@ impl EthereumClient {
             .as_ref()
             .ok_or_else(|| EthereumError::Config("Signer required".to_string()))?;
 
-        let signer = PrivateKeySigner::from_bytes(&FixedBytes::<32>::try_from(
-            hex::decode(&signer_config.private_key)?.as_slice(),
-        )?)
-        .map_err(|e| EthereumError::Signer(e.to_string()))?;
-
-        let wallet = EthereumWallet::from(signer.clone());
+        let signer = signer::load_signer(signer_config).await?;
+        let wallet = signer.wallet();
+        let nonces = Arc::new(NonceManager::new(signer.address()));
 
         // Create provider with wallet for signing
         let http_provider = ProviderBuilder::new()
@@ -1313,6 +1918,7 @@ impl EthereumClient {
             http_provider,
             contracts,
             signer,
+            nonces,
 
             #[cfg(feature = "database")]
             cache,
@@ -3,6 +3,8 @@ use alloy_primitives::{Address, FixedBytes};
 use alloy_signer_local::PrivateKeySigner;
 use serde::{Deserialize, Serialize};
 use std::env;
+use std::fmt;
+use std::path::PathBuf;
 use url::Url;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +14,7 @@ pub struct Config {
     pub rpc: RpcConfig,
     pub signer: Option<SignerConfig>,
     pub monitoring: MonitoringConfig,
+    pub gas: GasConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,10 +42,48 @@ pub struct RpcConfig {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SignerConfig {
-    pub private_key: String,
+    pub source: SignerSource,
     pub address: Address,
 }
 
+/// Where the transaction signing key lives
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SignerSource {
+    /// Raw hex key, kept for local development only
+    LocalKey {
+        #[serde(skip_serializing, default)]
+        private_key: String,
+    },
+    /// Encrypted JSON keystore; prompts for the passphrase when no file is given
+    Keystore {
+        path: PathBuf,
+        passphrase_file: Option<PathBuf>,
+    },
+    /// Web3Signer-compatible `eth_signTransaction` endpoint
+    Remote { url: Url },
+}
+
+impl fmt::Debug for SignerSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LocalKey { .. } => f
+                .debug_struct("LocalKey")
+                .field("private_key", &"<redacted>")
+                .finish(),
+            Self::Keystore {
+                path,
+                passphrase_file,
+            } => f
+                .debug_struct("Keystore")
+                .field("path", path)
+                .field("passphrase_file", passphrase_file)
+                .finish(),
+            Self::Remote { url } => f.debug_struct("Remote").field("url", url).finish(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MonitoringConfig {
     pub enable_event_monitoring: bool,
@@ -52,25 +93,305 @@ pub struct MonitoringConfig {
     pub timeout_seconds: u64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasConfig {
+    pub strategy: FeeStrategy,
+    /// Seconds to wait for a receipt before re-sending with bumped fees
+    pub replacement_timeout_seconds: u64,
+    /// Fee increase per replacement; nodes require at least 10%
+    pub fee_bump_percent: u64,
+    pub max_replacements: u32,
+    /// Upper bound on `gas_limit * max_fee_per_gas` for a single submission
+    pub max_spend_per_batch_wei: Option<u128>,
+}
+
+/// How EIP-1559 fees are chosen for contract submissions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FeeStrategy {
+    /// Always use the configured fees
+    Fixed {
+        max_fee_per_gas: u128,
+        max_priority_fee_per_gas: u128,
+    },
+    /// Tip at the given reward percentile of recent blocks, max fee at twice the next base fee plus tip
+    Percentile {
+        reward_percentile: f64,
+        block_count: u64,
+    },
+    /// Provider estimate, never exceeding `max_fee_per_gas` (replacements included)
+    Capped { max_fee_per_gas: u128 },
+}
+
+impl Default for GasConfig {
+    fn default() -> Self {
+        Self {
+            strategy: FeeStrategy::Percentile {
+                reward_percentile: 50.0,
+                block_count: 10,
+            },
+            replacement_timeout_seconds: 180,
+            fee_bump_percent: 15,
+            max_replacements: 5,
+            max_spend_per_batch_wei: None,
+        }
+    }
+}
+
+const WEI_PER_GWEI: f64 = 1_000_000_000.0;
+
+/// Read an optional gwei amount from the environment as wei
+fn gwei_from_env(name: &str) -> Result<Option<u128>> {
+    env::var(name)
+        .ok()
+        .map(|value| {
+            value
+                .parse::<f64>()
+                .ok()
+                .filter(|gwei| gwei.is_finite() && *gwei >= 0.0)
+                .map(|gwei| (gwei * WEI_PER_GWEI) as u128)
+                .ok_or_else(|| EthereumError::Config(format!("Invalid {name}: {value}")))
+        })
+        .transpose()
+}
+
+impl GasConfig {
+    pub fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+
+        let strategy = match env::var("GAS_STRATEGY")
+            .unwrap_or_else(|_| "percentile".to_string())
+            .as_str()
+        {
+            "fixed" => FeeStrategy::Fixed {
+                max_fee_per_gas: gwei_from_env("GAS_MAX_FEE_GWEI")?.ok_or_else(|| {
+                    EthereumError::Config(
+                        "GAS_MAX_FEE_GWEI is required for the fixed gas strategy".to_string(),
+                    )
+                })?,
+                max_priority_fee_per_gas: gwei_from_env("GAS_PRIORITY_FEE_GWEI")?.ok_or_else(
+                    || {
+                        EthereumError::Config(
+                            "GAS_PRIORITY_FEE_GWEI is required for the fixed gas strategy"
+                                .to_string(),
+                        )
+                    },
+                )?,
+            },
+            "percentile" => FeeStrategy::Percentile {
+                reward_percentile: env::var("GAS_FEE_PERCENTILE")
+                    .unwrap_or_else(|_| "50".to_string())
+                    .parse()
+                    .unwrap_or(50.0),
+                block_count: env::var("GAS_FEE_HISTORY_BLOCKS")
+                    .unwrap_or_else(|_| "10".to_string())
+                    .parse()
+                    .unwrap_or(10),
+            },
+            "capped" => FeeStrategy::Capped {
+                max_fee_per_gas: gwei_from_env("GAS_MAX_FEE_GWEI")?.ok_or_else(|| {
+                    EthereumError::Config(
+                        "GAS_MAX_FEE_GWEI is required for the capped gas strategy".to_string(),
+                    )
+                })?,
+            },
+            other => {
+                return Err(EthereumError::Config(format!(
+                    "Unknown GAS_STRATEGY '{other}' (expected fixed, percentile or capped)"
+                )))
+            }
+        };
+
+        Ok(Self {
+            strategy,
+            replacement_timeout_seconds: env::var("TX_REPLACEMENT_TIMEOUT_SECONDS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(defaults.replacement_timeout_seconds),
+            fee_bump_percent: env::var("TX_FEE_BUMP_PERCENT")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(defaults.fee_bump_percent),
+            max_replacements: env::var("TX_MAX_REPLACEMENTS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(defaults.max_replacements),
+            max_spend_per_batch_wei: gwei_from_env("MAX_SPEND_PER_BATCH_GWEI")?,
+        })
+    }
+}
+
+/// Thresholds and cadence of the chain health monitor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthConfig {
+    pub check_interval_seconds: u64,
+    /// Oldest acceptable timestamp of the latest block, in seconds
+    pub max_head_age_seconds: u64,
+    /// Blocks the node may trail the highest known block while syncing
+    pub max_sync_lag_blocks: u64,
+    /// Gas price above which the chain is reported as expensive
+    pub max_gas_price_wei: Option<u128>,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: 60,
+            max_head_age_seconds: 120,
+            max_sync_lag_blocks: 5,
+            max_gas_price_wei: None,
+        }
+    }
+}
+
+impl HealthConfig {
+    pub fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+
+        Ok(Self {
+            check_interval_seconds: env::var("HEALTH_CHECK_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(defaults.check_interval_seconds)
+                .max(1),
+            max_head_age_seconds: env::var("HEALTH_MAX_HEAD_AGE_SECONDS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(defaults.max_head_age_seconds),
+            max_sync_lag_blocks: env::var("HEALTH_MAX_SYNC_LAG_BLOCKS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(defaults.max_sync_lag_blocks),
+            max_gas_price_wei: gwei_from_env("HEALTH_MAX_GAS_PRICE_GWEI")?,
+        })
+    }
+}
+
+/// Networks every proven batch is settled on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettlementConfig {
+    pub targets: Vec<SettlementTarget>,
+}
+
+/// One settlement network with its own RPC, contracts, signer and confirmation depth
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettlementTarget {
+    /// Unique target name, also used as the network name (`base-sepolia`, `optimism`, ...)
+    pub name: String,
+    /// Defaults to the chain id of a known network `name`
+    #[serde(default)]
+    pub chain_id: Option<u64>,
+    pub rpc_url: Url,
+    pub arithmetic_contract: Address,
+    pub verifier_contract: Address,
+    #[serde(default)]
+    pub deployment_block: Option<u64>,
+    /// Falls back to the signer configured through the environment
+    #[serde(default)]
+    pub signer: Option<SignerConfig>,
+    /// Blocks on top of the inclusion block before a submission counts as confirmed
+    #[serde(default = "default_confirmations")]
+    pub confirmations: u64,
+}
+
+const fn default_confirmations() -> u64 {
+    1
+}
+
+impl SettlementConfig {
+    /// Read targets from the JSON file at `SETTLEMENT_CONFIG`, or settle on the
+    /// single network described by [`Config::from_env`] when it is unset
+    pub fn from_env() -> Result<Self> {
+        let Ok(path) = env::var("SETTLEMENT_CONFIG") else {
+            let config = Config::from_env()?;
+            return Ok(Self {
+                targets: vec![SettlementTarget {
+                    name: config.network.name,
+                    chain_id: Some(config.network.chain_id),
+                    rpc_url: config.network.rpc_url,
+                    arithmetic_contract: config.contract.arithmetic_contract,
+                    verifier_contract: config.contract.verifier_contract,
+                    deployment_block: config.contract.deployment_block,
+                    signer: config.signer,
+                    confirmations: env::var("SETTLEMENT_CONFIRMATIONS")
+                        .ok()
+                        .and_then(|s| s.parse().ok())
+                        .unwrap_or_else(default_confirmations),
+                }],
+            });
+        };
+
+        let contents = std::fs::read_to_string(&path)?;
+        let settlement: Self = serde_json::from_str(&contents)
+            .map_err(|e| EthereumError::Config(format!("Invalid SETTLEMENT_CONFIG {path}: {e}")))?;
+        settlement.validate()?;
+        Ok(settlement)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.targets.is_empty() {
+            return Err(EthereumError::Config(
+                "At least one settlement target is required".to_string(),
+            ));
+        }
+
+        let mut names = std::collections::HashSet::new();
+        for target in &self.targets {
+            if !names.insert(target.name.as_str()) {
+                return Err(EthereumError::Config(format!(
+                    "Duplicate settlement target '{}'",
+                    target.name
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SettlementTarget {
+    /// Client configuration for this target; the signer (when the target has
+    /// none) and gas settings come from the environment
+    pub fn to_config(&self) -> Result<Config> {
+        let chain_id = self
+            .chain_id
+            .unwrap_or_else(|| Config::default_chain_id(&self.name));
+
+        let signer = match &self.signer {
+            Some(signer) => Some(signer.clone()),
+            None => Config::signer_from_env()?,
+        };
+
+        Ok(Config {
+            network: NetworkConfig {
+                name: self.name.clone(),
+                chain_id,
+                rpc_url: self.rpc_url.clone(),
+                ws_url: Config::build_ws_url_from_rpc(&self.rpc_url).ok(),
+                explorer_url: Config::get_explorer_url(&self.name),
+                is_testnet: Config::is_testnet(chain_id),
+            },
+            contract: ContractConfig {
+                arithmetic_contract: self.arithmetic_contract,
+                verifier_contract: self.verifier_contract,
+                deployment_block: self.deployment_block,
+            },
+            signer,
+            gas: GasConfig::from_env()?,
+            ..Config::default()
+        })
+    }
+}
+
 impl Config {
     #[allow(clippy::too_many_lines)]
     pub fn from_env() -> Result<Self> {
         let network_name = env::var("ETHEREUM_NETWORK").unwrap_or_else(|_| "sepolia".to_string());
         let chain_id: u64 = env::var("CHAIN_ID")
-            .unwrap_or_else(|_| {
-                #[allow(clippy::wildcard_in_or_patterns)]
-                match network_name.as_str() {
-                    "mainnet" => "1".to_string(),
-                    "base" => "8453".to_string(),
-                    "base-sepolia" => "84532".to_string(),
-                    "arbitrum" => "42161".to_string(),
-                    "arbitrum-sepolia" => "421614".to_string(),
-                    "optimism" => "10".to_string(),
-                    "optimism-sepolia" => "11155420".to_string(),
-                    "sepolia" | _ => "11155111".to_string(),
-                }
-            })
-            .parse()
+            .map_or_else(
+                |_| Ok(Self::default_chain_id(&network_name)),
+                |id| id.parse(),
+            )
             .map_err(|e| EthereumError::Config(format!("Invalid chain ID: {e}")))?;
 
         let rpc_url = env::var("ETHEREUM_RPC_URL")
@@ -78,7 +399,7 @@ impl Config {
 
         let rpc_url = Url::parse(&rpc_url)
             .map_err(|e| EthereumError::Config(format!("Invalid ETHEREUM_RPC_URL: {e}")))?;
-        let ws_url = Self::build_ws_url_from_rpc(&rpc_url).ok();
+        let ws_url = Self::ws_url_from_env(&rpc_url)?;
 
         let ethereum_contract = env::var("ETHEREUM_CONTRACT_ADDRESS")
             .map_err(|_| {
@@ -96,39 +417,7 @@ impl Config {
                 EthereumError::InvalidAddress(format!("Invalid verifier contract address: {e}"))
             })?;
 
-        let signer = if let Ok(private_key) = env::var("ETHEREUM_WALLET_PRIVATE_KEY") {
-            let deployer_address = env::var("ETHEREUM_DEPLOYER_ADDRESS")
-                .map_err(|_| {
-                    EthereumError::Config(
-                        "ETHEREUM_DEPLOYER_ADDRESS is required when ETHEREUM_WALLET_PRIVATE_KEY is provided".to_string(),
-                    )
-                })?
-                .parse::<Address>()
-                .map_err(|e| {
-                    EthereumError::InvalidAddress(format!("Invalid deployer address: {e}"))
-                })?;
-
-            // Validate that the private key corresponds to the deployer address
-            let signer = PrivateKeySigner::from_bytes(&FixedBytes::<32>::try_from(
-                hex::decode(&private_key)?.as_slice(),
-            )?)
-            .map_err(|e| EthereumError::Signer(e.to_string()))?;
-
-            if signer.address() != deployer_address {
-                return Err(EthereumError::Config(format!(
-                    "Private key address ({}) does not match ETHEREUM_DEPLOYER_ADDRESS ({})",
-                    signer.address(),
-                    deployer_address
-                )));
-            }
-
-            Some(SignerConfig {
-                private_key,
-                address: deployer_address,
-            })
-        } else {
-            None
-        };
+        let signer = Self::signer_from_env()?;
 
         Ok(Self {
             network: NetworkConfig {
@@ -180,9 +469,127 @@ impl Config {
                     .parse()
                     .unwrap_or(30),
             },
+            gas: GasConfig::from_env()?,
         })
     }
 
+    /// Pick the signer backend from `ETHEREUM_SIGNER`, or infer it from whichever
+    /// of `ETHEREUM_KEYSTORE_PATH`, `ETHEREUM_REMOTE_SIGNER_URL` or
+    /// `ETHEREUM_WALLET_PRIVATE_KEY` is set
+    fn signer_from_env() -> Result<Option<SignerConfig>> {
+        let kind = match env::var("ETHEREUM_SIGNER") {
+            Ok(kind) => kind,
+            Err(_) if env::var("ETHEREUM_KEYSTORE_PATH").is_ok() => "keystore".to_string(),
+            Err(_) if env::var("ETHEREUM_REMOTE_SIGNER_URL").is_ok() => "remote".to_string(),
+            Err(_) if env::var("ETHEREUM_WALLET_PRIVATE_KEY").is_ok() => "local".to_string(),
+            Err(_) => return Ok(None),
+        };
+
+        let source = match kind.as_str() {
+            "local" => {
+                let private_key = env::var("ETHEREUM_WALLET_PRIVATE_KEY").map_err(|_| {
+                    EthereumError::Config(
+                        "ETHEREUM_WALLET_PRIVATE_KEY is required for the local signer".to_string(),
+                    )
+                })?;
+                SignerSource::LocalKey { private_key }
+            }
+            "keystore" => SignerSource::Keystore {
+                path: env::var("ETHEREUM_KEYSTORE_PATH")
+                    .map_err(|_| {
+                        EthereumError::Config(
+                            "ETHEREUM_KEYSTORE_PATH is required for the keystore signer"
+                                .to_string(),
+                        )
+                    })?
+                    .into(),
+                passphrase_file: env::var("ETHEREUM_KEYSTORE_PASSPHRASE_FILE")
+                    .ok()
+                    .map(PathBuf::from),
+            },
+            "remote" => {
+                let url = env::var("ETHEREUM_REMOTE_SIGNER_URL").map_err(|_| {
+                    EthereumError::Config(
+                        "ETHEREUM_REMOTE_SIGNER_URL is required for the remote signer".to_string(),
+                    )
+                })?;
+                SignerSource::Remote {
+                    url: Url::parse(&url).map_err(|e| {
+                        EthereumError::Config(format!("Invalid ETHEREUM_REMOTE_SIGNER_URL: {e}"))
+                    })?,
+                }
+            }
+            other => {
+                return Err(EthereumError::Config(format!(
+                    "Unknown ETHEREUM_SIGNER '{other}' (expected local, keystore or remote)"
+                )))
+            }
+        };
+
+        let deployer_address = env::var("ETHEREUM_DEPLOYER_ADDRESS")
+            .map_err(|_| {
+                EthereumError::Config(
+                    "ETHEREUM_DEPLOYER_ADDRESS is required when a signer is configured".to_string(),
+                )
+            })?
+            .parse::<Address>()
+            .map_err(|e| EthereumError::InvalidAddress(format!("Invalid deployer address: {e}")))?;
+
+        // A raw key can be checked right away; keystore and remote signers are
+        // checked against the address when they are loaded
+        if let SignerSource::LocalKey { private_key } = &source {
+            let signer = PrivateKeySigner::from_bytes(&FixedBytes::<32>::try_from(
+                hex::decode(private_key)?.as_slice(),
+            )?)
+            .map_err(|e| EthereumError::Signer(e.to_string()))?;
+
+            if signer.address() != deployer_address {
+                return Err(EthereumError::Config(format!(
+                    "Private key address ({}) does not match ETHEREUM_DEPLOYER_ADDRESS ({})",
+                    signer.address(),
+                    deployer_address
+                )));
+            }
+        }
+
+        Ok(Some(SignerConfig {
+            source,
+            address: deployer_address,
+        }))
+    }
+
+    /// Chain id for a known network name, Sepolia for anything else
+    #[must_use]
+    pub fn default_chain_id(network: &str) -> u64 {
+        #[allow(clippy::wildcard_in_or_patterns)]
+        match network {
+            "mainnet" => 1,
+            "base" => 8453,
+            "base-sepolia" => 84532,
+            "arbitrum" => 42161,
+            "arbitrum-sepolia" => 421_614,
+            "optimism" => 10,
+            "optimism-sepolia" => 11_155_420,
+            "sepolia" | _ => 11_155_111,
+        }
+    }
+
+    /// `ETHEREUM_WS_URL` if set, otherwise derived from the RPC URL; `None`
+    /// when `ENABLE_WEBSOCKET=false`, which leaves event monitoring on HTTP polling
+    fn ws_url_from_env(rpc_url: &Url) -> Result<Option<Url>> {
+        let enabled = env::var("ENABLE_WEBSOCKET").map_or(true, |v| v.parse().unwrap_or(true));
+        if !enabled {
+            return Ok(None);
+        }
+
+        match env::var("ETHEREUM_WS_URL") {
+            Ok(url) => Url::parse(&url)
+                .map(Some)
+                .map_err(|e| EthereumError::Config(format!("Invalid ETHEREUM_WS_URL: {e}"))),
+            Err(_) => Ok(Self::build_ws_url_from_rpc(rpc_url).ok()),
+        }
+    }
+
     fn build_ws_url_from_rpc(rpc_url: &Url) -> Result<Url> {
         let mut ws_url = rpc_url.clone();
 
@@ -236,6 +643,12 @@ impl Config {
             ));
         }
 
+        if self.gas.fee_bump_percent < 10 {
+            return Err(EthereumError::Config(
+                "Fee bump must be at least 10% for replacements to be accepted".to_string(),
+            ));
+        }
+
         if self.monitoring.polling_interval_seconds == 0 {
             return Err(EthereumError::Config(
                 "Polling interval must be greater than 0".to_string(),
@@ -274,6 +687,7 @@ impl Default for Config {
                 retry_attempts: 3,
                 timeout_seconds: 30,
             },
+            gas: GasConfig::default(),
         }
     }
 }
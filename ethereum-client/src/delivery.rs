@@ -0,0 +1,262 @@
+//! At-least-once delivery of indexed events to `EventHandler`s
+//!
+//! `HandlerDelivery` reads the events `ChainIndexer` stored in Postgres and
+//! hands them to one handler in chain order. Each handler has its own
+//! checkpoint in `ethereum_handler_checkpoints`, advanced only after the
+//! handler returned, so a crash redelivers the events it was working on.
+//!
+//! A handler that returns an error or panics does not hold up the events
+//! behind it: the event is queued in `ethereum_handler_retries` in the same
+//! transaction that moves the checkpoint past it and is retried with
+//! exponential backoff. After `max_attempts` failures it stays in the queue
+//! marked dead for an operator to look at.
+//!
+//! A reorg rolls checkpoints back to the common ancestor and drops queued
+//! retries above it (see `EthereumCache::rollback_indexer`), so handlers see
+//! the replacement events as new deliveries.
+
+use crate::{
+    cache::EthereumCache,
+    error::{EthereumError, Result},
+    event_manager::{dispatch_event, EventHandler},
+    indexer::IndexedEvent,
+};
+use alloy_primitives::Address;
+use futures::FutureExt;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration};
+use tracing::{debug, error, info, warn};
+
+/// Position of an event in the contract's log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EventPosition {
+    pub block_number: u64,
+    pub log_index: u64,
+}
+
+impl From<&IndexedEvent> for EventPosition {
+    fn from(event: &IndexedEvent) -> Self {
+        Self {
+            block_number: event.block_number,
+            log_index: event.log_index,
+        }
+    }
+}
+
+/// A failed delivery waiting for another attempt
+#[derive(Debug, Clone)]
+pub struct PendingRetry {
+    pub event: IndexedEvent,
+    /// Failed attempts so far
+    pub attempts: u32,
+}
+
+/// Delivery configuration
+#[derive(Debug, Clone)]
+pub struct DeliveryConfig {
+    /// Events read from the database per round trip
+    pub batch_size: i64,
+    /// Wait between polls once the handler has caught up
+    pub poll_interval: Duration,
+    /// Delay before the first retry; doubled after every failure
+    pub retry_base_delay: Duration,
+    pub max_retry_delay: Duration,
+    /// Failed attempts after which an event is marked dead
+    pub max_attempts: u32,
+}
+
+impl Default for DeliveryConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 100,
+            poll_interval: Duration::from_secs(2),
+            retry_base_delay: Duration::from_secs(5),
+            max_retry_delay: Duration::from_secs(600),
+            max_attempts: 10,
+        }
+    }
+}
+
+impl DeliveryConfig {
+    /// Delay before retrying an event that has failed `attempts` times, or
+    /// `None` once it has used up `max_attempts`
+    #[must_use]
+    pub fn retry_delay(&self, attempts: u32) -> Option<Duration> {
+        if attempts >= self.max_attempts {
+            return None;
+        }
+        let factor = 1u32 << attempts.saturating_sub(1).min(16);
+        Some(
+            self.retry_base_delay
+                .saturating_mul(factor)
+                .min(self.max_retry_delay),
+        )
+    }
+}
+
+/// Delivers one contract's indexed events to one handler
+pub struct HandlerDelivery {
+    cache: EthereumCache,
+    handler: Arc<dyn EventHandler>,
+    chain_id: u64,
+    contract: Address,
+    config: DeliveryConfig,
+}
+
+impl HandlerDelivery {
+    #[must_use]
+    pub fn new(
+        cache: EthereumCache,
+        handler: Arc<dyn EventHandler>,
+        chain_id: u64,
+        contract: Address,
+        config: DeliveryConfig,
+    ) -> Self {
+        Self {
+            cache,
+            handler,
+            chain_id,
+            contract,
+            config,
+        }
+    }
+
+    /// Deliver new events and retry failed ones forever
+    pub async fn run(&self) {
+        info!(
+            "Delivering events of {} on chain {} to handler '{}'",
+            self.contract,
+            self.chain_id,
+            self.handler.name()
+        );
+
+        loop {
+            let step = async {
+                Ok::<_, EthereumError>(self.retry_due().await? + self.deliver_new().await?)
+            };
+            match step.await {
+                Ok(handled) if handled as i64 >= self.config.batch_size => continue,
+                Ok(_) => {}
+                Err(e) => warn!(
+                    "Event delivery to handler '{}' failed, will retry: {}",
+                    self.handler.name(),
+                    e
+                ),
+            }
+            sleep(self.config.poll_interval).await;
+        }
+    }
+
+    /// Deliver the next batch of events past the handler's checkpoint and
+    /// return how many were handed over
+    pub async fn deliver_new(&self) -> Result<usize> {
+        let name = self.handler.name();
+        let checkpoint = self
+            .cache
+            .get_handler_checkpoint(name, self.chain_id, self.contract)
+            .await?;
+        let events = self
+            .cache
+            .get_indexed_events_after(
+                self.chain_id,
+                self.contract,
+                checkpoint,
+                self.config.batch_size,
+            )
+            .await?;
+
+        for indexed in &events {
+            let position = EventPosition::from(indexed);
+            let failure = self.deliver(indexed).await.err().map(|e| {
+                warn!(
+                    "Handler '{}' failed on event at block {} log {}: {}",
+                    name, position.block_number, position.log_index, e
+                );
+                e.to_string()
+            });
+            let retry_after = self.config.retry_delay(1).unwrap_or_default();
+
+            self.cache
+                .commit_handler_delivery(
+                    name,
+                    self.chain_id,
+                    self.contract,
+                    position,
+                    failure.as_deref().map(|e| (e, retry_after)),
+                )
+                .await?;
+        }
+
+        if !events.is_empty() {
+            debug!("Delivered {} events to handler '{}'", events.len(), name);
+        }
+        Ok(events.len())
+    }
+
+    /// Retry the handler's queued events that are due and return how many
+    /// were attempted
+    pub async fn retry_due(&self) -> Result<usize> {
+        let name = self.handler.name();
+        let retries = self
+            .cache
+            .get_due_handler_retries(name, self.chain_id, self.contract, self.config.batch_size)
+            .await?;
+
+        for retry in &retries {
+            let position = EventPosition::from(&retry.event);
+            let attempts = retry.attempts + 1;
+            let failure = self
+                .deliver(&retry.event)
+                .await
+                .err()
+                .map(|e| e.to_string());
+
+            match &failure {
+                None => info!(
+                    "Handler '{}' processed event at block {} log {} on attempt {}",
+                    name, position.block_number, position.log_index, attempts
+                ),
+                Some(e) if self.config.retry_delay(attempts).is_none() => error!(
+                    "Handler '{}' gave up on event at block {} log {} after {} attempts: {}",
+                    name, position.block_number, position.log_index, attempts, e
+                ),
+                Some(e) => warn!(
+                    "Handler '{}' failed on event at block {} log {} (attempt {}): {}",
+                    name, position.block_number, position.log_index, attempts, e
+                ),
+            }
+
+            self.cache
+                .finish_handler_retry(
+                    name,
+                    self.chain_id,
+                    self.contract,
+                    position,
+                    failure
+                        .as_deref()
+                        .map(|e| (e, self.config.retry_delay(attempts))),
+                )
+                .await?;
+        }
+
+        Ok(retries.len())
+    }
+
+    /// Hand one event to the handler, turning a panic into an error
+    async fn deliver(&self, indexed: &IndexedEvent) -> Result<()> {
+        AssertUnwindSafe(dispatch_event(&*self.handler, indexed.event.clone()))
+            .catch_unwind()
+            .await
+            .unwrap_or_else(|panic| {
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .map(|s| (*s).to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown panic".to_string());
+                Err(EthereumError::External(format!(
+                    "handler panicked: {message}"
+                )))
+            })
+    }
+}
@@ -1,3 +1,4 @@
+use alloy_primitives::TxHash;
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, EthereumError>;
@@ -43,6 +44,14 @@ pub enum EthereumError {
     #[error("Timeout error: {0}")]
     Timeout(String),
 
+    /// A sent transaction was still not mined when the gas manager gave up; it
+    /// may yet be mined under `tx_hash`, the last hash it was sent with
+    #[error("Timeout error: {message} (last sent as {tx_hash})")]
+    TransactionTimeout { tx_hash: TxHash, message: String },
+
+    #[error("Spend limit exceeded: submission may cost up to {required} wei, limit is {limit} wei")]
+    SpendLimitExceeded { required: u128, limit: u128 },
+
     #[error("Alchemy API error: {status_code} - {message}")]
     AlchemyApi { status_code: u16, message: String },
 
@@ -127,6 +136,25 @@ impl EthereumError {
         // If no signature match, return generic contract error
         Self::Contract(error_msg.to_string())
     }
+
+    /// Whether the contract rejected the call, either when its gas was
+    /// estimated or when it was mined
+    #[must_use]
+    pub fn is_revert(&self) -> bool {
+        matches!(
+            self,
+            Self::Contract(_)
+                | Self::TransactionFailed(_)
+                | Self::UnauthorizedAccess
+                | Self::InvalidArrayLength
+                | Self::ContractStateNotFound
+                | Self::ContractProofNotFound
+                | Self::InvalidLimit
+                | Self::InvalidIndex
+                | Self::ProofAlreadyExists
+                | Self::ProofInvalid
+        )
+    }
 }
 
 #[cfg(test)]
@@ -198,6 +226,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_only_contract_rejections_are_reverts() {
+        let reverted = super::EthereumError::from_contract_error("Transaction failed: 0xb8cdb9bd");
+        assert!(reverted.is_revert());
+        assert!(super::EthereumError::TransactionFailed("reverted".to_string()).is_revert());
+
+        let timed_out = super::EthereumError::TransactionTimeout {
+            tx_hash: alloy_primitives::TxHash::ZERO,
+            message: "not mined".to_string(),
+        };
+        assert!(!timed_out.is_revert());
+        let over_limit = super::EthereumError::SpendLimitExceeded {
+            required: 2,
+            limit: 1,
+        };
+        assert!(!over_limit.is_revert());
+    }
+
     #[test]
     fn test_parse_proof_already_exists_error() {
         let error_msg = "Transaction failed: execution reverted 0xb8cdb9bd";
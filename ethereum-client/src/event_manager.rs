@@ -1,54 +1,199 @@
 use crate::{
-    client::{ArithmeticEvent, EthereumClient, EventFilter, SubscriptionId},
+    client::{ArithmeticEvent, EthereumClient, EventCallback, EventFilter, SubscriptionId},
     error::Result,
 };
 use alloy_primitives::{Address, FixedBytes};
+use async_trait::async_trait;
 use std::sync::Arc;
 use tokio::sync::broadcast;
 use tracing::{debug, error, info, warn};
 
+#[cfg(feature = "database")]
+use crate::{
+    delivery::{DeliveryConfig, HandlerDelivery},
+    types::batch_state_id,
+};
+#[cfg(feature = "database")]
+use arithmetic_db::{
+    get_batch_submissions, get_proven_unposted_batches, record_batch_submission,
+    BatchSubmissionUpdate,
+};
+#[cfg(feature = "database")]
+use sqlx::PgPool;
+
 /// Event handler trait for vApp server integration
+///
+/// With a database cache every event is delivered at least once: a handler
+/// that returns an error or panics gets the event again later, and one that
+/// crashes mid-way sees it again after a restart (see [`crate::delivery`]).
+/// Handlers should therefore be idempotent. Methods a handler does not
+/// override accept the event without doing anything.
+#[async_trait]
 pub trait EventHandler: Send + Sync {
-    fn handle_state_updated(
+    /// Stable name under which the handler's delivery checkpoint is stored
+    fn name(&self) -> &str;
+
+    async fn handle_state_updated(
         &self,
-        state_id: FixedBytes<32>,
-        new_state: FixedBytes<32>,
-        proof_id: FixedBytes<32>,
-        updater: Address,
-        block_number: u64,
-    );
+        _state_id: FixedBytes<32>,
+        _new_state: FixedBytes<32>,
+        _proof_id: FixedBytes<32>,
+        _updater: Address,
+        _block_number: u64,
+        _tx_hash: FixedBytes<32>,
+    ) -> Result<()> {
+        Ok(())
+    }
 
-    fn handle_batch_state_updated(
+    async fn handle_batch_state_updated(
         &self,
-        state_ids: Vec<FixedBytes<32>>,
-        new_states: Vec<FixedBytes<32>>,
-        updater: Address,
-        block_number: u64,
-    );
+        _state_ids: Vec<FixedBytes<32>>,
+        _new_states: Vec<FixedBytes<32>>,
+        _updater: Address,
+        _block_number: u64,
+        _tx_hash: FixedBytes<32>,
+    ) -> Result<()> {
+        Ok(())
+    }
 
-    fn handle_proof_stored(
+    async fn handle_proof_stored(
         &self,
-        proof_id: FixedBytes<32>,
-        state_id: FixedBytes<32>,
-        submitter: Address,
-        block_number: u64,
-    );
+        _proof_id: FixedBytes<32>,
+        _state_id: FixedBytes<32>,
+        _submitter: Address,
+        _block_number: u64,
+    ) -> Result<()> {
+        Ok(())
+    }
 
-    fn handle_proof_verified(&self, proof_id: FixedBytes<32>, success: bool, block_number: u64);
+    async fn handle_proof_verified(
+        &self,
+        _proof_id: FixedBytes<32>,
+        _success: bool,
+        _block_number: u64,
+    ) -> Result<()> {
+        Ok(())
+    }
 
-    fn handle_state_read_requested(
+    async fn handle_state_read_requested(
         &self,
-        state_id: FixedBytes<32>,
-        reader: Address,
-        block_number: u64,
-    );
+        _state_id: FixedBytes<32>,
+        _reader: Address,
+        _block_number: u64,
+    ) -> Result<()> {
+        Ok(())
+    }
 
-    fn handle_proof_read_requested(
+    async fn handle_proof_read_requested(
         &self,
-        proof_id: FixedBytes<32>,
-        reader: Address,
-        block_number: u64,
-    );
+        _proof_id: FixedBytes<32>,
+        _reader: Address,
+        _block_number: u64,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Dispatch event to the matching handler method
+pub(crate) async fn dispatch_event(
+    handler: &dyn EventHandler,
+    event: ArithmeticEvent,
+) -> Result<()> {
+    match event {
+        ArithmeticEvent::StateUpdated {
+            state_id,
+            new_state,
+            proof_id,
+            updater,
+            block_number,
+            tx_hash,
+            ..
+        } => {
+            handler
+                .handle_state_updated(
+                    state_id,
+                    new_state,
+                    proof_id,
+                    updater,
+                    block_number,
+                    tx_hash,
+                )
+                .await
+        }
+        ArithmeticEvent::BatchStateUpdated {
+            state_ids,
+            new_states,
+            updater,
+            block_number,
+            tx_hash,
+            ..
+        } => {
+            handler
+                .handle_batch_state_updated(state_ids, new_states, updater, block_number, tx_hash)
+                .await
+        }
+        ArithmeticEvent::ProofStored {
+            proof_id,
+            state_id,
+            submitter,
+            block_number,
+            ..
+        } => {
+            handler
+                .handle_proof_stored(proof_id, state_id, submitter, block_number)
+                .await
+        }
+        ArithmeticEvent::ProofVerified {
+            proof_id,
+            success,
+            block_number,
+            ..
+        } => {
+            handler
+                .handle_proof_verified(proof_id, success, block_number)
+                .await
+        }
+        ArithmeticEvent::StateReadRequested {
+            state_id,
+            reader,
+            block_number,
+            ..
+        } => {
+            handler
+                .handle_state_read_requested(state_id, reader, block_number)
+                .await
+        }
+        ArithmeticEvent::ProofReadRequested {
+            proof_id,
+            reader,
+            block_number,
+            ..
+        } => {
+            handler
+                .handle_proof_read_requested(proof_id, reader, block_number)
+                .await
+        }
+    }
+}
+
+/// Callback that hands each event to `handler` on its own task
+///
+/// Subscriptions are best effort: a failure is logged and the event dropped.
+fn spawn_dispatch(handler: Arc<dyn EventHandler>) -> EventCallback {
+    Arc::new(move |event: ArithmeticEvent| {
+        let handler = Arc::clone(&handler);
+        tokio::spawn(async move {
+            let event_type = event.event_type();
+            if let Err(e) = dispatch_event(&*handler, event).await {
+                warn!(
+                    "Handler '{}' failed on {} event: {}",
+                    handler.name(),
+                    event_type,
+                    e
+                );
+            }
+        });
+    })
 }
 
 /// Event manager for vApp server integration
@@ -56,6 +201,9 @@ pub struct EventManager {
     ethereum_client: Arc<EthereumClient>,
     event_handlers: Vec<Arc<dyn EventHandler>>,
     global_event_receiver: Option<broadcast::Receiver<ArithmeticEvent>>,
+
+    #[cfg(feature = "database")]
+    delivery_config: DeliveryConfig,
 }
 
 impl EventManager {
@@ -66,13 +214,34 @@ impl EventManager {
             global_event_receiver: Some(ethereum_client.get_event_stream()),
             ethereum_client,
             event_handlers: Vec::new(),
+
+            #[cfg(feature = "database")]
+            delivery_config: DeliveryConfig::default(),
         }
     }
 
+    /// Batch size, polling and retry settings for durable delivery
+    #[cfg(feature = "database")]
+    #[must_use]
+    pub fn with_delivery_config(mut self, config: DeliveryConfig) -> Self {
+        self.delivery_config = config;
+        self
+    }
+
     /// Register an event handler for all events
     pub fn register_handler(&mut self, handler: Arc<dyn EventHandler>) {
+        if self
+            .event_handlers
+            .iter()
+            .any(|registered| registered.name() == handler.name())
+        {
+            warn!(
+                "Handler '{}' is already registered; both share one checkpoint",
+                handler.name()
+            );
+        }
+        info!("Registered event handler '{}'", handler.name());
         self.event_handlers.push(handler);
-        info!("Registered new event handler");
     }
 
     /// Subscribe to specific state events
@@ -92,29 +261,7 @@ impl EventManager {
             to_block: None,
         };
 
-        let callback = Arc::new(move |event: ArithmeticEvent| match event {
-            ArithmeticEvent::StateUpdated {
-                state_id,
-                new_state,
-                proof_id,
-                updater,
-                block_number,
-                ..
-            } => {
-                handler.handle_state_updated(state_id, new_state, proof_id, updater, block_number);
-            }
-            ArithmeticEvent::StateReadRequested {
-                state_id,
-                reader,
-                block_number,
-                ..
-            } => {
-                handler.handle_state_read_requested(state_id, reader, block_number);
-            }
-            _ => {
-                debug!("Ignoring non-state event in state subscription");
-            }
-        });
+        let callback = spawn_dispatch(handler);
 
         self.ethereum_client
             .subscribe_to_events(filter, callback)
@@ -138,36 +285,7 @@ impl EventManager {
             to_block: None,
         };
 
-        let callback = Arc::new(move |event: ArithmeticEvent| match event {
-            ArithmeticEvent::ProofStored {
-                proof_id,
-                state_id,
-                submitter,
-                block_number,
-                ..
-            } => {
-                handler.handle_proof_stored(proof_id, state_id, submitter, block_number);
-            }
-            ArithmeticEvent::ProofVerified {
-                proof_id,
-                success,
-                block_number,
-                ..
-            } => {
-                handler.handle_proof_verified(proof_id, success, block_number);
-            }
-            ArithmeticEvent::ProofReadRequested {
-                proof_id,
-                reader,
-                block_number,
-                ..
-            } => {
-                handler.handle_proof_read_requested(proof_id, reader, block_number);
-            }
-            _ => {
-                debug!("Ignoring non-proof event in proof subscription");
-            }
-        });
+        let callback = spawn_dispatch(handler);
 
         self.ethereum_client
             .subscribe_to_events(filter, callback)
@@ -187,20 +305,7 @@ impl EventManager {
             to_block: None,
         };
 
-        let callback = Arc::new(move |event: ArithmeticEvent| match event {
-            ArithmeticEvent::BatchStateUpdated {
-                state_ids,
-                new_states,
-                updater,
-                block_number,
-                ..
-            } => {
-                handler.handle_batch_state_updated(state_ids, new_states, updater, block_number);
-            }
-            _ => {
-                debug!("Ignoring non-batch event in batch subscription");
-            }
-        });
+        let callback = spawn_dispatch(handler);
 
         self.ethereum_client
             .subscribe_to_events(filter, callback)
@@ -221,18 +326,27 @@ impl EventManager {
             to_block: None,
         };
 
-        let callback = Arc::new(move |event: ArithmeticEvent| {
-            Self::dispatch_event_to_handler(&*handler, event);
-        });
+        let callback = spawn_dispatch(handler);
 
         self.ethereum_client
             .subscribe_to_events(filter, callback)
             .await
     }
 
-    /// Start processing global event stream
-    #[allow(clippy::cognitive_complexity)]
+    /// Deliver events to every registered handler until the event source ends
+    ///
+    /// With a database cache each handler gets its own checkpointed delivery
+    /// loop over the events `EthereumClient::start_event_monitoring` indexes,
+    /// so that must be running as well. Without one, events from the client's
+    /// broadcast stream are handed to each handler in turn and failures are
+    /// only logged.
     pub async fn start_processing_events(&mut self) -> Result<()> {
+        #[cfg(feature = "database")]
+        if self.ethereum_client.cache().is_some() {
+            self.run_durable_delivery().await;
+            return Ok(());
+        }
+
         if let Some(mut receiver) = self.global_event_receiver.take() {
             info!("Starting global event processing loop");
 
@@ -243,7 +357,14 @@ impl EventManager {
 
                         // Dispatch to all registered handlers
                         for handler in &self.event_handlers {
-                            Self::dispatch_event_to_handler(&**handler, event.clone());
+                            if let Err(e) = dispatch_event(&**handler, event.clone()).await {
+                                warn!(
+                                    "Handler '{}' failed on {} event: {}",
+                                    handler.name(),
+                                    event.event_type(),
+                                    e
+                                );
+                            }
                         }
                     }
                     Err(broadcast::error::RecvError::Lagged(count)) => {
@@ -260,60 +381,27 @@ impl EventManager {
         Ok(())
     }
 
-    /// Dispatch event to a specific handler
-    fn dispatch_event_to_handler(handler: &dyn EventHandler, event: ArithmeticEvent) {
-        match event {
-            ArithmeticEvent::StateUpdated {
-                state_id,
-                new_state,
-                proof_id,
-                updater,
-                block_number,
-                ..
-            } => {
-                handler.handle_state_updated(state_id, new_state, proof_id, updater, block_number);
-            }
-            ArithmeticEvent::BatchStateUpdated {
-                state_ids,
-                new_states,
-                updater,
-                block_number,
-                ..
-            } => {
-                handler.handle_batch_state_updated(state_ids, new_states, updater, block_number);
-            }
-            ArithmeticEvent::ProofStored {
-                proof_id,
-                state_id,
-                submitter,
-                block_number,
-                ..
-            } => {
-                handler.handle_proof_stored(proof_id, state_id, submitter, block_number);
-            }
-            ArithmeticEvent::ProofVerified {
-                proof_id,
-                success,
-                block_number,
-                ..
-            } => {
-                handler.handle_proof_verified(proof_id, success, block_number);
-            }
-            ArithmeticEvent::StateReadRequested {
-                state_id,
-                reader,
-                block_number,
-                ..
-            } => {
-                handler.handle_state_read_requested(state_id, reader, block_number);
+    /// Run a `HandlerDelivery` per registered handler
+    #[cfg(feature = "database")]
+    async fn run_durable_delivery(&self) {
+        info!(
+            "Starting durable event delivery to {} handlers",
+            self.event_handlers.len()
+        );
+
+        let mut tasks = tokio::task::JoinSet::new();
+        for handler in &self.event_handlers {
+            if let Some(delivery) = self
+                .ethereum_client
+                .handler_delivery(Arc::clone(handler), self.delivery_config.clone())
+            {
+                tasks.spawn(async move { delivery.run().await });
             }
-            ArithmeticEvent::ProofReadRequested {
-                proof_id,
-                reader,
-                block_number,
-                ..
-            } => {
-                handler.handle_proof_read_requested(proof_id, reader, block_number);
+        }
+
+        while let Some(result) = tasks.join_next().await {
+            if let Err(e) = result {
+                error!("Event delivery task ended: {}", e);
             }
         }
     }
@@ -380,27 +468,131 @@ impl Default for EventFilterBuilder {
     }
 }
 
-/// Example implementation of `EventHandler` for vApp servers
+/// Proven batches scanned for a match when reconciling an on-chain state update
+#[cfg(feature = "database")]
+const RECONCILE_SCAN_LIMIT: i32 = 1000;
+
+/// Where `VAppEventHandler` records settlements it finds on chain
+#[cfg(feature = "database")]
+struct Reconciliation {
+    pool: PgPool,
+    target: String,
+    chain_id: i64,
+}
+
+/// `EventHandler` for vApp servers
+///
+/// Logs every event. With [`VAppEventHandler::with_reconciliation`] it also
+/// matches state updates against proven batches that are not marked posted
+/// yet, so a batch settled by a previous run, another replica or by hand is
+/// recorded as submitted on the target and the batch processor confirms it
+/// instead of posting it again.
 pub struct VAppEventHandler {
     server_name: String,
+
+    #[cfg(feature = "database")]
+    reconciliation: Option<Reconciliation>,
 }
 
 impl VAppEventHandler {
     #[must_use]
     pub const fn new(server_name: String) -> Self {
-        Self { server_name }
+        Self {
+            server_name,
+
+            #[cfg(feature = "database")]
+            reconciliation: None,
+        }
+    }
+
+    /// Record state updates of unposted batches in `batch_submissions` for
+    /// the settlement target `target` on `chain_id`
+    #[cfg(feature = "database")]
+    #[must_use]
+    pub fn with_reconciliation(mut self, pool: PgPool, target: String, chain_id: u64) -> Self {
+        self.reconciliation = Some(Reconciliation {
+            pool,
+            target,
+            chain_id: chain_id as i64,
+        });
+        self
+    }
+
+    /// Mark the proven, unposted batches behind `state_ids` as submitted in `tx_hash`
+    #[cfg(feature = "database")]
+    async fn reconcile(
+        &self,
+        state_ids: &[FixedBytes<32>],
+        block_number: u64,
+        tx_hash: FixedBytes<32>,
+    ) -> Result<()> {
+        let Some(reconciliation) = &self.reconciliation else {
+            return Ok(());
+        };
+
+        let batch_ids: Vec<i32> =
+            get_proven_unposted_batches(&reconciliation.pool, Some(RECONCILE_SCAN_LIMIT))
+                .await?
+                .into_iter()
+                .map(|batch| batch.id)
+                .filter(|id| state_ids.contains(&batch_state_id(*id)))
+                .collect();
+        if batch_ids.is_empty() {
+            return Ok(());
+        }
+
+        let tx_hash = tx_hash.to_string();
+        let submissions = get_batch_submissions(&reconciliation.pool, &batch_ids).await?;
+        for batch_id in batch_ids {
+            let known = submissions.iter().any(|s| {
+                s.batch_id == batch_id
+                    && s.target == reconciliation.target
+                    && (s.status == "confirmed"
+                        || (s.status == "submitted"
+                            && s.transaction_hash.as_deref() == Some(tx_hash.as_str())))
+            });
+            if known {
+                continue;
+            }
+
+            info!(
+                "[{}] Batch {} was settled on {} in {} (block {})",
+                self.server_name, batch_id, reconciliation.target, tx_hash, block_number
+            );
+            record_batch_submission(
+                &reconciliation.pool,
+                &BatchSubmissionUpdate {
+                    batch_id,
+                    target: reconciliation.target.clone(),
+                    chain_id: reconciliation.chain_id,
+                    status: "submitted".to_string(),
+                    transaction_hash: Some(tx_hash.clone()),
+                    block_number: Some(block_number as i64),
+                    error: None,
+                },
+            )
+            .await?;
+        }
+
+        Ok(())
     }
 }
 
+#[async_trait]
 impl EventHandler for VAppEventHandler {
-    fn handle_state_updated(
+    fn name(&self) -> &str {
+        &self.server_name
+    }
+
+    async fn handle_state_updated(
         &self,
         state_id: FixedBytes<32>,
         new_state: FixedBytes<32>,
         proof_id: FixedBytes<32>,
         updater: Address,
         block_number: u64,
-    ) {
+        tx_hash: FixedBytes<32>,
+    ) -> Result<()> {
         info!(
             "[{}] State updated: id={}, new_state={}, proof={}, updater={}, block={}",
             self.server_name,
@@ -410,15 +602,23 @@ impl EventHandler for VAppEventHandler {
             updater,
             block_number
         );
+
+        #[cfg(feature = "database")]
+        self.reconcile(&[state_id], block_number, tx_hash).await?;
+        #[cfg(not(feature = "database"))]
+        let _ = tx_hash;
+
+        Ok(())
     }
 
-    fn handle_batch_state_updated(
+    async fn handle_batch_state_updated(
         &self,
         state_ids: Vec<FixedBytes<32>>,
         _new_states: Vec<FixedBytes<32>>,
         updater: Address,
         block_number: u64,
-    ) {
+        tx_hash: FixedBytes<32>,
+    ) -> Result<()> {
         info!(
             "[{}] Batch state updated: {} states, updater={}, block={}",
             self.server_name,
@@ -426,15 +626,22 @@ impl EventHandler for VAppEventHandler {
             updater,
             block_number
         );
+
+        #[cfg(feature = "database")]
+        self.reconcile(&state_ids, block_number, tx_hash).await?;
+        #[cfg(not(feature = "database"))]
+        let _ = tx_hash;
+
+        Ok(())
     }
 
-    fn handle_proof_stored(
+    async fn handle_proof_stored(
         &self,
         proof_id: FixedBytes<32>,
         state_id: FixedBytes<32>,
         submitter: Address,
         block_number: u64,
-    ) {
+    ) -> Result<()> {
         info!(
             "[{}] Proof stored: proof={}, state={}, submitter={}, block={}",
             self.server_name,
@@ -443,9 +650,15 @@ impl EventHandler for VAppEventHandler {
             submitter,
             block_number
         );
+        Ok(())
     }
 
-    fn handle_proof_verified(&self, proof_id: FixedBytes<32>, success: bool, block_number: u64) {
+    async fn handle_proof_verified(
+        &self,
+        proof_id: FixedBytes<32>,
+        success: bool,
+        block_number: u64,
+    ) -> Result<()> {
         info!(
             "[{}] Proof verified: proof={}, success={}, block={}",
             self.server_name,
@@ -453,14 +666,15 @@ impl EventHandler for VAppEventHandler {
             success,
             block_number
         );
+        Ok(())
     }
 
-    fn handle_state_read_requested(
+    async fn handle_state_read_requested(
         &self,
         state_id: FixedBytes<32>,
         reader: Address,
         block_number: u64,
-    ) {
+    ) -> Result<()> {
         debug!(
             "[{}] State read requested: state={}, reader={}, block={}",
             self.server_name,
@@ -468,14 +682,15 @@ impl EventHandler for VAppEventHandler {
             reader,
             block_number
         );
+        Ok(())
     }
 
-    fn handle_proof_read_requested(
+    async fn handle_proof_read_requested(
         &self,
         proof_id: FixedBytes<32>,
         reader: Address,
         block_number: u64,
-    ) {
+    ) -> Result<()> {
         debug!(
             "[{}] Proof read requested: proof={}, reader={}, block={}",
             self.server_name,
@@ -483,5 +698,6 @@ impl EventHandler for VAppEventHandler {
             reader,
             block_number
         );
+        Ok(())
     }
 }
@@ -0,0 +1,308 @@
+//! Fee estimation, nonce tracking and stuck-transaction replacement
+//!
+//! Contract submissions go through [`submit_with_replacement`], which pins the
+//! nonce and gas limit, prices the transaction with the configured
+//! [`FeeStrategy`] and re-sends it with bumped fees whenever it sits in the
+//! mempool longer than `replacement_timeout_seconds`. A submission whose
+//! worst-case cost would exceed `max_spend_per_batch_wei` is refused with an
+//! alert rather than sent.
+
+use crate::config::{FeeStrategy, GasConfig};
+use crate::error::{EthereumError, Result};
+use alloy_primitives::{Address, TxHash};
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::{BlockNumberOrTag, FeeHistory, TransactionReceipt, TransactionRequest};
+use tokio::sync::Mutex;
+use tokio::time::{sleep, Duration, Instant};
+use tracing::{error, info, warn};
+
+/// Headroom added on top of `eth_estimateGas`
+const GAS_LIMIT_BUFFER_PERCENT: u64 = 20;
+
+/// How often pending submissions are checked for a receipt
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Eip1559Fees {
+    pub max_fee_per_gas: u128,
+    pub max_priority_fee_per_gas: u128,
+}
+
+impl Eip1559Fees {
+    /// Raise both fees by `percent` (at least 1 wei each), as required for a replacement
+    #[must_use]
+    pub fn bumped(self, percent: u64) -> Self {
+        let bump = |fee: u128| (fee * (100 + u128::from(percent)) / 100).max(fee + 1);
+        Self {
+            max_fee_per_gas: bump(self.max_fee_per_gas),
+            max_priority_fee_per_gas: bump(self.max_priority_fee_per_gas),
+        }
+    }
+
+    /// Component-wise maximum, so a replacement never undercuts the fresh estimate
+    #[must_use]
+    pub fn max(self, other: Self) -> Self {
+        Self {
+            max_fee_per_gas: self.max_fee_per_gas.max(other.max_fee_per_gas),
+            max_priority_fee_per_gas: self
+                .max_priority_fee_per_gas
+                .max(other.max_priority_fee_per_gas),
+        }
+    }
+
+    /// Clamp to `cap`, keeping the tip no larger than the max fee
+    #[must_use]
+    pub fn capped(self, cap: u128) -> Self {
+        let max_fee_per_gas = self.max_fee_per_gas.min(cap);
+        Self {
+            max_fee_per_gas,
+            max_priority_fee_per_gas: self.max_priority_fee_per_gas.min(max_fee_per_gas),
+        }
+    }
+
+    /// Worst-case cost of a transaction with `gas_limit`
+    #[must_use]
+    pub fn max_cost(&self, gas_limit: u64) -> u128 {
+        u128::from(gas_limit).saturating_mul(self.max_fee_per_gas)
+    }
+}
+
+/// Fees from an `eth_feeHistory` response queried with a single reward percentile
+pub fn fees_from_history(history: &FeeHistory) -> Result<Eip1559Fees> {
+    let next_base_fee = history
+        .base_fee_per_gas
+        .last()
+        .copied()
+        .ok_or_else(|| EthereumError::External("Fee history has no base fee".to_string()))?;
+
+    let mut tips: Vec<u128> = history
+        .reward
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|rewards| rewards.first().copied())
+        .collect();
+    tips.sort_unstable();
+    let tip = tips.get(tips.len() / 2).copied().unwrap_or_default();
+
+    Ok(Eip1559Fees {
+        max_fee_per_gas: next_base_fee.saturating_mul(2).saturating_add(tip),
+        max_priority_fee_per_gas: tip,
+    })
+}
+
+impl FeeStrategy {
+    pub async fn estimate<P: Provider>(&self, provider: &P) -> Result<Eip1559Fees> {
+        match self {
+            Self::Fixed {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => Ok(Eip1559Fees {
+                max_fee_per_gas: *max_fee_per_gas,
+                max_priority_fee_per_gas: *max_priority_fee_per_gas,
+            }),
+            Self::Percentile {
+                reward_percentile,
+                block_count,
+            } => {
+                let history = provider
+                    .get_fee_history(*block_count, BlockNumberOrTag::Latest, &[*reward_percentile])
+                    .await?;
+                fees_from_history(&history)
+            }
+            Self::Capped { max_fee_per_gas } => {
+                let estimate = provider.estimate_eip1559_fees().await?;
+                Ok(Eip1559Fees {
+                    max_fee_per_gas: estimate.max_fee_per_gas,
+                    max_priority_fee_per_gas: estimate.max_priority_fee_per_gas,
+                }
+                .capped(*max_fee_per_gas))
+            }
+        }
+    }
+
+    /// Hard ceiling on the max fee, if the strategy has one
+    #[must_use]
+    pub const fn cap(&self) -> Option<u128> {
+        match self {
+            Self::Capped { max_fee_per_gas } => Some(*max_fee_per_gas),
+            Self::Fixed { .. } | Self::Percentile { .. } => None,
+        }
+    }
+}
+
+/// Hands out consecutive nonces for one sender so that concurrent submissions
+/// and replacements never collide
+pub struct NonceManager {
+    address: Address,
+    next: Mutex<Option<u64>>,
+}
+
+impl NonceManager {
+    #[must_use]
+    pub const fn new(address: Address) -> Self {
+        Self {
+            address,
+            next: Mutex::const_new(None),
+        }
+    }
+
+    /// Reserve the next nonce, seeding from the pending transaction count on first use
+    pub async fn reserve<P: Provider>(&self, provider: &P) -> Result<u64> {
+        let mut next = self.next.lock().await;
+        let nonce = match *next {
+            Some(nonce) => nonce,
+            None => {
+                provider
+                    .get_transaction_count(self.address)
+                    .pending()
+                    .await?
+            }
+        };
+        *next = Some(nonce + 1);
+        Ok(nonce)
+    }
+
+    /// Forget the local count; the next reservation re-reads it from the node
+    pub async fn reset(&self) {
+        *self.next.lock().await = None;
+    }
+}
+
+/// Send `request` and wait for it to be mined, replacing it with higher fees
+/// every time it stays pending for the configured timeout
+pub async fn submit_with_replacement<P: Provider>(
+    provider: &P,
+    nonces: &NonceManager,
+    config: &GasConfig,
+    mut request: TransactionRequest,
+    label: &str,
+) -> Result<TransactionReceipt> {
+    let estimated_gas = provider.estimate_gas(request.clone()).await.map_err(|e| {
+        error!("Failed to estimate gas for {label}: {e}");
+        EthereumError::from_contract_error(&format!("Transaction failed: {e}"))
+    })?;
+    let gas_limit = estimated_gas + estimated_gas * GAS_LIMIT_BUFFER_PERCENT / 100;
+
+    let mut fees = config.strategy.estimate(provider).await?;
+    check_spend(config, gas_limit, &fees, label)?;
+
+    let nonce = nonces.reserve(provider).await?;
+    request.nonce = Some(nonce);
+    request.gas = Some(gas_limit);
+
+    let timeout = Duration::from_secs(config.replacement_timeout_seconds);
+    let mut sent: Vec<TxHash> = Vec::new();
+    let mut replacements = 0;
+    let mut resend = true;
+
+    loop {
+        if resend {
+            let mut tx = request.clone();
+            tx.max_fee_per_gas = Some(fees.max_fee_per_gas);
+            tx.max_priority_fee_per_gas = Some(fees.max_priority_fee_per_gas);
+
+            match provider.send_transaction(tx).await {
+                Ok(pending) => {
+                    info!(
+                        "{label} transaction sent: {} (nonce {nonce}, max fee {} wei, tip {} wei)",
+                        pending.tx_hash(),
+                        fees.max_fee_per_gas,
+                        fees.max_priority_fee_per_gas
+                    );
+                    sent.push(*pending.tx_hash());
+                }
+                Err(e) if sent.is_empty() => {
+                    nonces.reset().await;
+                    error!("Failed to send {label} transaction: {e}");
+                    return Err(EthereumError::from_contract_error(&format!(
+                        "Transaction failed: {e}"
+                    )));
+                }
+                // Usually an earlier attempt was just mined; the receipt check below finds it
+                Err(e) => warn!("Replacement for {label} transaction rejected: {e}"),
+            }
+        }
+
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            if let Some(receipt) = find_receipt(provider, &sent).await? {
+                if !receipt.status() {
+                    return Err(EthereumError::TransactionFailed(format!(
+                        "{label} transaction {} reverted",
+                        receipt.transaction_hash
+                    )));
+                }
+                return Ok(receipt);
+            }
+            sleep(RECEIPT_POLL_INTERVAL).await;
+        }
+
+        if replacements >= config.max_replacements {
+            nonces.reset().await;
+            return Err(EthereumError::TransactionTimeout {
+                tx_hash: sent.last().copied().unwrap_or_default(),
+                message: format!(
+                    "{label} transaction with nonce {nonce} not mined after {replacements} replacements"
+                ),
+            });
+        }
+        replacements += 1;
+
+        let mut next_fees = fees
+            .bumped(config.fee_bump_percent)
+            .max(config.strategy.estimate(provider).await?);
+        if let Some(cap) = config.strategy.cap() {
+            next_fees = next_fees.capped(cap);
+            if next_fees.max_fee_per_gas < fees.bumped(10).max_fee_per_gas {
+                warn!(
+                    "{label} transaction is stuck at the fee cap ({cap} wei); waiting without replacing"
+                );
+                resend = false;
+                continue;
+            }
+        }
+        if let Err(e) = check_spend(config, gas_limit, &next_fees, label) {
+            nonces.reset().await;
+            return Err(e);
+        }
+
+        warn!(
+            "{label} transaction not mined after {}s, replacing (attempt {replacements}/{})",
+            timeout.as_secs(),
+            config.max_replacements
+        );
+        fees = next_fees;
+        resend = true;
+    }
+}
+
+async fn find_receipt<P: Provider>(
+    provider: &P,
+    hashes: &[TxHash],
+) -> Result<Option<TransactionReceipt>> {
+    for hash in hashes {
+        if let Some(receipt) = provider.get_transaction_receipt(*hash).await? {
+            return Ok(Some(receipt));
+        }
+    }
+    Ok(None)
+}
+
+fn check_spend(config: &GasConfig, gas_limit: u64, fees: &Eip1559Fees, label: &str) -> Result<()> {
+    let Some(limit) = config.max_spend_per_batch_wei else {
+        return Ok(());
+    };
+
+    let required = fees.max_cost(gas_limit);
+    if required > limit {
+        error!(
+            alert = "spend_limit_exceeded",
+            "ALERT: {label} would cost up to {required} wei (gas limit {gas_limit}, max fee {} wei), above the {limit} wei limit",
+            fees.max_fee_per_gas
+        );
+        return Err(EthereumError::SpendLimitExceeded { required, limit });
+    }
+
+    Ok(())
+}
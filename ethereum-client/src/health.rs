@@ -0,0 +1,278 @@
+//! Network statistics and chain health monitoring
+//!
+//! [`fetch_network_stats`] reads `eth_chainId`, `eth_gasPrice`, the latest
+//! block and `eth_syncing` from a node. [`ChainHealthMonitor`] samples them on
+//! an interval, records every sample through `EthereumCache` when a database
+//! is configured, and keeps the latest [`ChainHealth`] for the API to report.
+
+use crate::config::{Config, HealthConfig};
+use crate::error::{EthereumError, Result};
+use crate::types::NetworkStats;
+use alloy_primitives::U256;
+use alloy_provider::{Provider, ProviderBuilder};
+use alloy_rpc_types_eth::{BlockNumberOrTag, SyncStatus};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::RwLock;
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn};
+use url::Url;
+
+#[cfg(feature = "database")]
+use crate::cache::EthereumCache;
+
+/// Current statistics of the chain behind `provider`
+pub async fn fetch_network_stats<P: Provider>(
+    provider: &P,
+    network_name: &str,
+) -> Result<NetworkStats> {
+    let chain_id = provider.get_chain_id().await?;
+    let gas_price = provider.get_gas_price().await?;
+    let block = provider
+        .get_block_by_number(BlockNumberOrTag::Latest)
+        .await?
+        .ok_or_else(|| EthereumError::External("Node returned no latest block".to_string()))?;
+    let highest_block = match provider.syncing().await? {
+        SyncStatus::Info(progress) => Some(progress.highest_block.saturating_to()),
+        SyncStatus::None => None,
+    };
+
+    Ok(NetworkStats {
+        chain_id,
+        block_number: block.header.number,
+        gas_price: U256::from(gas_price),
+        base_fee: block.header.base_fee_per_gas.map(U256::from),
+        network_name: network_name.to_string(),
+        sync_status: highest_block.is_none(),
+        block_timestamp: block.header.timestamp,
+        highest_block,
+    })
+}
+
+/// A condition that makes a chain unfit for settlement
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HealthIssue {
+    /// The latest block is older than `max_head_age_seconds`
+    HeadLagging { head_age_seconds: u64 },
+    /// The node is syncing and trails the highest known block
+    Syncing {
+        block_number: u64,
+        highest_block: u64,
+    },
+    /// The node serves a different chain than configured
+    ChainIdMismatch { expected: u64, actual: u64 },
+    /// The gas price is above `max_gas_price_wei`
+    GasPriceAboveThreshold { gas_price: u128, threshold: u128 },
+}
+
+impl fmt::Display for HealthIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::HeadLagging { head_age_seconds } => {
+                write!(f, "latest block is {head_age_seconds}s old")
+            }
+            Self::Syncing {
+                block_number,
+                highest_block,
+            } => write!(
+                f,
+                "node is syncing at block {block_number} of {highest_block}"
+            ),
+            Self::ChainIdMismatch { expected, actual } => {
+                write!(f, "chain id is {actual}, expected {expected}")
+            }
+            Self::GasPriceAboveThreshold {
+                gas_price,
+                threshold,
+            } => write!(
+                f,
+                "gas price {gas_price} wei is above the {threshold} wei threshold"
+            ),
+        }
+    }
+}
+
+/// Issues found in `stats`, sampled at `now` (seconds since the epoch)
+#[must_use]
+pub fn assess(
+    stats: &NetworkStats,
+    expected_chain_id: u64,
+    config: &HealthConfig,
+    now: u64,
+) -> Vec<HealthIssue> {
+    let mut issues = Vec::new();
+
+    if stats.chain_id != expected_chain_id {
+        issues.push(HealthIssue::ChainIdMismatch {
+            expected: expected_chain_id,
+            actual: stats.chain_id,
+        });
+    }
+
+    let head_age_seconds = now.saturating_sub(stats.block_timestamp);
+    if head_age_seconds > config.max_head_age_seconds {
+        issues.push(HealthIssue::HeadLagging { head_age_seconds });
+    }
+
+    if let Some(highest_block) = stats.highest_block {
+        if highest_block.saturating_sub(stats.block_number) > config.max_sync_lag_blocks {
+            issues.push(HealthIssue::Syncing {
+                block_number: stats.block_number,
+                highest_block,
+            });
+        }
+    }
+
+    if let Some(threshold) = config.max_gas_price_wei {
+        let gas_price: u128 = stats.gas_price.saturating_to();
+        if gas_price > threshold {
+            issues.push(HealthIssue::GasPriceAboveThreshold {
+                gas_price,
+                threshold,
+            });
+        }
+    }
+
+    issues
+}
+
+/// Outcome of one health check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainHealth {
+    pub network_name: String,
+    pub expected_chain_id: u64,
+    /// `None` when the node could not be queried
+    pub stats: Option<NetworkStats>,
+    pub issues: Vec<HealthIssue>,
+    /// Why the node could not be queried
+    pub error: Option<String>,
+    /// Seconds since the epoch
+    pub checked_at: u64,
+}
+
+impl ChainHealth {
+    #[must_use]
+    pub fn is_healthy(&self) -> bool {
+        self.error.is_none() && self.issues.is_empty()
+    }
+}
+
+/// Periodic health checks of one network; clones share the latest result
+#[derive(Clone)]
+pub struct ChainHealthMonitor {
+    network_name: String,
+    expected_chain_id: u64,
+    rpc_url: Url,
+    latest: Arc<RwLock<Option<ChainHealth>>>,
+
+    #[cfg(feature = "database")]
+    cache: Option<EthereumCache>,
+}
+
+impl ChainHealthMonitor {
+    /// Monitor for the network described by `config`
+    #[must_use]
+    pub fn new(config: &Config) -> Self {
+        Self {
+            network_name: config.network.name.clone(),
+            expected_chain_id: config.network.chain_id,
+            rpc_url: config.network.rpc_url.clone(),
+            latest: Arc::new(RwLock::new(None)),
+
+            #[cfg(feature = "database")]
+            cache: None,
+        }
+    }
+
+    /// Record every sample in `ethereum_network_stats`
+    #[cfg(feature = "database")]
+    #[must_use]
+    pub fn with_cache(mut self, cache: EthereumCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Start checking in the background; the returned handle sees every result
+    #[must_use]
+    pub fn spawn(self, health: HealthConfig) -> Self {
+        let provider = ProviderBuilder::new().connect_http(self.rpc_url.clone());
+        let task = self.clone();
+        tokio::spawn(async move {
+            info!(
+                "Monitoring chain health of {} every {}s",
+                task.network_name, health.check_interval_seconds
+            );
+            loop {
+                let result = task.check(&provider, &health).await;
+
+                #[cfg(feature = "database")]
+                if let (Some(cache), Some(stats)) = (&task.cache, &result.stats) {
+                    if let Err(e) = cache.store_network_stats(stats).await {
+                        warn!(
+                            "Failed to record network stats for {}: {}",
+                            task.network_name, e
+                        );
+                    }
+                }
+
+                *task.latest.write().await = Some(result);
+                sleep(Duration::from_secs(health.check_interval_seconds)).await;
+            }
+        });
+
+        self
+    }
+
+    /// Sample the node once and log any issue found
+    pub async fn check<P: Provider>(&self, provider: &P, config: &HealthConfig) -> ChainHealth {
+        let checked_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let (stats, issues, error) = match fetch_network_stats(provider, &self.network_name).await {
+            Ok(stats) => {
+                let issues = assess(&stats, self.expected_chain_id, config, checked_at);
+                (Some(stats), issues, None)
+            }
+            Err(e) => (None, Vec::new(), Some(e.to_string())),
+        };
+
+        if let Some(error) = &error {
+            warn!(
+                "Chain health check of {} failed: {}",
+                self.network_name, error
+            );
+        }
+        for issue in &issues {
+            warn!("Chain {} is unhealthy: {}", self.network_name, issue);
+        }
+
+        ChainHealth {
+            network_name: self.network_name.clone(),
+            expected_chain_id: self.expected_chain_id,
+            stats,
+            issues,
+            error,
+            checked_at,
+        }
+    }
+
+    #[must_use]
+    pub fn network_name(&self) -> &str {
+        &self.network_name
+    }
+
+    #[must_use]
+    pub const fn expected_chain_id(&self) -> u64 {
+        self.expected_chain_id
+    }
+
+    /// Result of the most recent check; `None` until the first one completes
+    pub async fn latest(&self) -> Option<ChainHealth> {
+        self.latest.read().await.clone()
+    }
+}
@@ -0,0 +1,361 @@
+//! Durable, resumable contract event indexer
+//!
+//! `ChainIndexer` walks the arithmetic contract's logs from
+//! `ContractConfig.deployment_block` forward and stores decoded
+//! `ArithmeticEvent`s, the hashes of the blocks it indexed and its cursor in
+//! Postgres through `EthereumCache`. A restart resumes from the stored cursor.
+//!
+//! Before each step the indexer checks that the cursor block is still canonical.
+//! If it is not, it walks back through the stored block hashes to the newest
+//! block that still matches the chain, deletes everything indexed above it and
+//! re-ingests from there.
+
+use crate::{
+    cache::EthereumCache,
+    client::{ArithmeticEvent, EthereumClient},
+    config::Config,
+    error::{EthereumError, Result},
+};
+use alloy_primitives::{Address, FixedBytes};
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::{BlockNumberOrTag, Filter};
+use std::collections::BTreeMap;
+use tokio::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Stored block hashes compared per round trip while looking for a reorg's common ancestor
+const ANCESTOR_SEARCH_PAGE: i64 = 64;
+
+/// Indexer configuration
+#[derive(Debug, Clone)]
+pub struct IndexerConfig {
+    /// First block to index when no cursor is stored; the chain head when `None`
+    pub start_block: Option<u64>,
+    /// Maximum number of blocks fetched per `eth_getLogs` call
+    pub max_block_range: u64,
+    /// Blocks to stay behind the head
+    pub confirmations: u64,
+    pub poll_interval: Duration,
+}
+
+impl Default for IndexerConfig {
+    fn default() -> Self {
+        Self {
+            start_block: None,
+            max_block_range: 1000,
+            confirmations: 0,
+            poll_interval: Duration::from_secs(12),
+        }
+    }
+}
+
+impl IndexerConfig {
+    #[must_use]
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            start_block: config.contract.deployment_block,
+            max_block_range: config.monitoring.max_block_range.max(1),
+            confirmations: std::env::var("INDEXER_CONFIRMATIONS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            poll_interval: Duration::from_secs(config.monitoring.polling_interval_seconds),
+        }
+    }
+}
+
+/// Last block the indexer has fully processed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexerCursor {
+    pub block_number: u64,
+    pub block_hash: FixedBytes<32>,
+}
+
+/// A decoded event together with its position on chain
+#[derive(Debug, Clone)]
+pub struct IndexedEvent {
+    pub block_number: u64,
+    pub block_hash: FixedBytes<32>,
+    pub log_index: u64,
+    pub transaction_hash: FixedBytes<32>,
+    pub event: ArithmeticEvent,
+}
+
+/// Outcome of one `ChainIndexer::step`
+#[derive(Debug, Clone, Default)]
+pub struct IndexerStep {
+    /// Cursor block found reorged out; everything above `cursor` was discarded
+    pub reorg_detected_at: Option<u64>,
+    /// Newly indexed events, in chain order
+    pub events: Vec<IndexedEvent>,
+    /// Cursor after the step (`None` until the first range is indexed)
+    pub cursor: Option<IndexerCursor>,
+    /// Whether the cursor has reached the confirmed head
+    pub caught_up: bool,
+}
+
+pub struct ChainIndexer<P> {
+    provider: P,
+    cache: EthereumCache,
+    chain_id: u64,
+    contract: Address,
+    config: IndexerConfig,
+}
+
+impl<P: Provider> ChainIndexer<P> {
+    pub const fn new(
+        provider: P,
+        cache: EthereumCache,
+        chain_id: u64,
+        contract: Address,
+        config: IndexerConfig,
+    ) -> Self {
+        Self {
+            provider,
+            cache,
+            chain_id,
+            contract,
+            config,
+        }
+    }
+
+    pub const fn config(&self) -> &IndexerConfig {
+        &self.config
+    }
+
+    pub async fn cursor(&self) -> Result<Option<IndexerCursor>> {
+        self.cache
+            .get_indexer_cursor(self.chain_id, self.contract)
+            .await
+    }
+
+    /// Index the next range of blocks, or roll back if the cursor block was reorged out
+    pub async fn step(&self) -> Result<IndexerStep> {
+        let cursor = self.cursor().await?;
+
+        if let Some(cursor) = cursor {
+            if self.canonical_hash(cursor.block_number).await? != Some(cursor.block_hash) {
+                let ancestor = self.rollback_to_common_ancestor(cursor).await?;
+                return Ok(IndexerStep {
+                    reorg_detected_at: Some(cursor.block_number),
+                    events: Vec::new(),
+                    cursor: ancestor,
+                    caught_up: false,
+                });
+            }
+        }
+
+        let head = self.provider.get_block_number().await?;
+        let safe_head = head.saturating_sub(self.config.confirmations);
+        let from_block = match cursor {
+            Some(cursor) => cursor.block_number + 1,
+            None => self.initial_block(safe_head),
+        };
+
+        if from_block > safe_head {
+            return Ok(IndexerStep {
+                cursor,
+                caught_up: true,
+                ..IndexerStep::default()
+            });
+        }
+
+        let to_block = safe_head.min(from_block + self.config.max_block_range - 1);
+        let Some((events, blocks, to_hash)) = self.fetch_range(from_block, to_block).await? else {
+            // The chain moved under us; retry on the next step
+            return Ok(IndexerStep {
+                cursor,
+                ..IndexerStep::default()
+            });
+        };
+
+        let new_cursor = IndexerCursor {
+            block_number: to_block,
+            block_hash: to_hash,
+        };
+        self.cache
+            .commit_indexed_range(self.chain_id, self.contract, &blocks, &events, new_cursor)
+            .await?;
+
+        debug!(
+            "Indexed blocks {}..={} ({} events)",
+            from_block,
+            to_block,
+            events.len()
+        );
+
+        Ok(IndexerStep {
+            reorg_detected_at: None,
+            events,
+            cursor: Some(new_cursor),
+            caught_up: to_block == safe_head,
+        })
+    }
+
+    /// Re-fetch and re-store the events in `from_block..=to_block`
+    ///
+    /// The range is clamped to the cursor: blocks that have not been indexed
+    /// yet are left to `step`. Returns the events now stored for the range.
+    pub async fn replay(&self, from_block: u64, to_block: u64) -> Result<Vec<IndexedEvent>> {
+        let Some(cursor) = self.cursor().await? else {
+            return Ok(Vec::new());
+        };
+        let to_block = to_block.min(cursor.block_number);
+        if from_block > to_block {
+            return Ok(Vec::new());
+        }
+
+        info!("Replaying blocks {}..={}", from_block, to_block);
+
+        let mut replayed = Vec::new();
+        let mut start = from_block;
+        while start <= to_block {
+            let end = to_block.min(start + self.config.max_block_range - 1);
+            let (events, blocks, _) = self.fetch_range(start, end).await?.ok_or_else(|| {
+                EthereumError::External(format!(
+                    "Chain changed while replaying blocks {start}..={end}"
+                ))
+            })?;
+
+            self.cache
+                .replace_indexed_events(self.chain_id, self.contract, start, end, &blocks, &events)
+                .await?;
+            replayed.extend(events);
+            start = end + 1;
+        }
+
+        Ok(replayed)
+    }
+
+    /// Stored events in `from_block..=to_block`
+    pub async fn events_in_range(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<IndexedEvent>> {
+        self.cache
+            .get_indexed_events(self.chain_id, self.contract, from_block, to_block)
+            .await
+    }
+
+    fn initial_block(&self, safe_head: u64) -> u64 {
+        self.config.start_block.unwrap_or_else(|| {
+            warn!(
+                "No deployment block configured; indexing {} from the current block {}",
+                self.contract, safe_head
+            );
+            safe_head
+        })
+    }
+
+    async fn canonical_hash(&self, block_number: u64) -> Result<Option<FixedBytes<32>>> {
+        let block = self
+            .provider
+            .get_block_by_number(BlockNumberOrTag::Number(block_number))
+            .await?;
+        Ok(block.map(|block| block.header.hash))
+    }
+
+    /// Fetch the decoded events and the hashes of the blocks they live in
+    ///
+    /// Returns `None` if a log's block hash disagrees with the header fetched
+    /// for the end of the range, meaning a reorg happened mid-fetch.
+    #[allow(clippy::type_complexity)]
+    async fn fetch_range(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<
+        Option<(
+            Vec<IndexedEvent>,
+            Vec<(u64, FixedBytes<32>)>,
+            FixedBytes<32>,
+        )>,
+    > {
+        let to_hash = self
+            .canonical_hash(to_block)
+            .await?
+            .ok_or_else(|| EthereumError::External(format!("Block {to_block} not found")))?;
+
+        let filter = Filter::new()
+            .address(self.contract)
+            .from_block(from_block)
+            .to_block(to_block);
+        let logs = self
+            .provider
+            .get_logs(&filter)
+            .await
+            .map_err(|e| EthereumError::External(format!("Log retrieval failed: {e}")))?;
+
+        let mut blocks = BTreeMap::from([(to_block, to_hash)]);
+        let mut events = Vec::with_capacity(logs.len());
+        for log in &logs {
+            let (Some(block_number), Some(block_hash)) = (log.block_number, log.block_hash) else {
+                continue;
+            };
+            if *blocks.entry(block_number).or_insert(block_hash) != block_hash {
+                warn!("Block {} changed while fetching logs", block_number);
+                return Ok(None);
+            }
+
+            if let Some(event) = EthereumClient::convert_log_to_event(log) {
+                events.push(IndexedEvent {
+                    block_number,
+                    block_hash,
+                    log_index: log.log_index.unwrap_or_default(),
+                    transaction_hash: log.transaction_hash.unwrap_or_default(),
+                    event,
+                });
+            }
+        }
+
+        Ok(Some((events, blocks.into_iter().collect(), to_hash)))
+    }
+
+    /// Find the newest stored block that is still canonical and roll back to it
+    async fn rollback_to_common_ancestor(
+        &self,
+        cursor: IndexerCursor,
+    ) -> Result<Option<IndexerCursor>> {
+        let mut below = cursor.block_number;
+        let ancestor = 'search: loop {
+            let stored = self
+                .cache
+                .get_indexed_block_hashes(self.chain_id, self.contract, below, ANCESTOR_SEARCH_PAGE)
+                .await?;
+            if stored.is_empty() {
+                break None;
+            }
+            for &(block_number, block_hash) in &stored {
+                if block_number < cursor.block_number
+                    && self.canonical_hash(block_number).await? == Some(block_hash)
+                {
+                    break 'search Some(IndexerCursor {
+                        block_number,
+                        block_hash,
+                    });
+                }
+            }
+            match stored.last() {
+                Some(&(oldest, _)) if oldest > 0 => below = oldest - 1,
+                _ => break None,
+            }
+        };
+
+        match ancestor {
+            Some(ancestor) => warn!(
+                "Reorg detected at block {}; rolling back to block {}",
+                cursor.block_number, ancestor.block_number
+            ),
+            None => warn!(
+                "Reorg detected at block {} with no surviving indexed block; re-indexing from the start",
+                cursor.block_number
+            ),
+        }
+
+        self.cache
+            .rollback_indexer(self.chain_id, self.contract, ancestor)
+            .await?;
+        Ok(ancestor)
+    }
+}
@@ -11,16 +11,35 @@ pub mod config;
 pub mod contracts;
 pub mod error;
 pub mod event_manager;
+pub mod gas;
+pub mod health;
+pub mod signer;
+pub mod subscription;
 pub mod types;
+pub mod verification;
 
 pub use client::{ArithmeticEvent, EthereumClient, EventCallback, EventFilter, SubscriptionId};
-pub use config::{Config, NetworkConfig};
+pub use config::{
+    Config, FeeStrategy, GasConfig, HealthConfig, NetworkConfig, SettlementConfig,
+    SettlementTarget, SignerConfig, SignerSource,
+};
 pub use error::{EthereumError, Result};
 pub use event_manager::{EventFilterBuilder, EventHandler, EventManager, VAppEventHandler};
+pub use health::{ChainHealth, ChainHealthMonitor, HealthIssue};
+pub use signer::{KeystoreSigner, LocalKeySigner, RemoteSigner, SignerBackend};
+pub use subscription::{LogCursor, LogSubscription};
 pub use types::*;
 
 #[cfg(feature = "database")]
 pub mod cache;
+#[cfg(feature = "database")]
+pub mod delivery;
+#[cfg(feature = "database")]
+pub mod indexer;
 
 #[cfg(feature = "database")]
 pub use cache::EthereumCache;
+#[cfg(feature = "database")]
+pub use delivery::{DeliveryConfig, EventPosition, HandlerDelivery, PendingRetry};
+#[cfg(feature = "database")]
+pub use indexer::{ChainIndexer, IndexedEvent, IndexerConfig, IndexerCursor, IndexerStep};
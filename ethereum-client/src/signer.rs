@@ -0,0 +1,319 @@
+//! Signing backends for contract submissions
+//!
+//! Every backend hands out an [`EthereumWallet`], so `update_state` and
+//! `batch_update_states` sign the same way regardless of where the key lives.
+//! The remote backend talks to a Web3Signer-compatible service over the
+//! `eth_signTransaction` JSON-RPC method and checks that the returned
+//! signature covers exactly the transaction we asked it to sign.
+
+use crate::config::{SignerConfig, SignerSource};
+use crate::error::{EthereumError, Result};
+use alloy_consensus::{SignableTransaction, Transaction, TxEnvelope};
+use alloy_network::{eip2718::Decodable2718, EthereumWallet, TxSigner};
+use alloy_primitives::{Address, Bytes, FixedBytes, Signature};
+use alloy_rpc_types_eth::TransactionRequest;
+use alloy_signer_local::PrivateKeySigner;
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::info;
+use url::Url;
+
+/// A source of transaction signatures for the configured deployer address
+pub trait SignerBackend: Send + Sync {
+    /// Address every signature is produced for
+    fn address(&self) -> Address;
+
+    /// Short human-readable description, safe to log
+    fn describe(&self) -> String;
+
+    /// Wallet used by the provider's `WalletFiller`
+    fn wallet(&self) -> EthereumWallet;
+}
+
+/// Load the backend described by `config` and check it signs for `config.address`
+pub async fn load_signer(config: &SignerConfig) -> Result<Arc<dyn SignerBackend>> {
+    let backend: Arc<dyn SignerBackend> = match &config.source {
+        SignerSource::LocalKey { private_key } => Arc::new(LocalKeySigner::from_hex(private_key)?),
+        SignerSource::Keystore {
+            path,
+            passphrase_file,
+        } => {
+            let path = path.clone();
+            let passphrase_file = passphrase_file.clone();
+            // scrypt is deliberately slow and the prompt blocks on stdin
+            tokio::task::spawn_blocking(move || {
+                KeystoreSigner::open(&path, passphrase_file.as_deref())
+            })
+            .await
+            .map_err(|e| EthereumError::Signer(format!("Keystore task failed: {e}")))?
+            .map(Arc::new)?
+        }
+        SignerSource::Remote { url } => {
+            Arc::new(RemoteSigner::connect(url.clone(), config.address).await?)
+        }
+    };
+
+    if backend.address() != config.address {
+        return Err(EthereumError::Config(format!(
+            "Signer address ({}) does not match ETHEREUM_DEPLOYER_ADDRESS ({})",
+            backend.address(),
+            config.address
+        )));
+    }
+
+    info!("Using {} for {}", backend.describe(), backend.address());
+    Ok(backend)
+}
+
+/// Raw private key held in memory
+#[derive(Clone)]
+pub struct LocalKeySigner {
+    signer: PrivateKeySigner,
+}
+
+impl LocalKeySigner {
+    #[must_use]
+    pub const fn new(signer: PrivateKeySigner) -> Self {
+        Self { signer }
+    }
+
+    pub fn from_hex(private_key: &str) -> Result<Self> {
+        let key = hex::decode(private_key.trim_start_matches("0x"))?;
+        let signer = PrivateKeySigner::from_bytes(&FixedBytes::<32>::try_from(key.as_slice())?)
+            .map_err(|e| EthereumError::Signer(e.to_string()))?;
+        Ok(Self::new(signer))
+    }
+}
+
+impl SignerBackend for LocalKeySigner {
+    fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    fn describe(&self) -> String {
+        "local key signer".to_string()
+    }
+
+    fn wallet(&self) -> EthereumWallet {
+        EthereumWallet::from(self.signer.clone())
+    }
+}
+
+/// Key decrypted from an encrypted JSON (Web3 Secret Storage) keystore
+#[derive(Clone)]
+pub struct KeystoreSigner {
+    signer: PrivateKeySigner,
+    path: PathBuf,
+}
+
+impl KeystoreSigner {
+    /// Decrypt `path`, reading the passphrase from `passphrase_file` or, when
+    /// none is given, prompting for it on the terminal
+    pub fn open(path: &Path, passphrase_file: Option<&Path>) -> Result<Self> {
+        let passphrase = match passphrase_file {
+            Some(file) => std::fs::read_to_string(file)?
+                .trim_end_matches(['\r', '\n'])
+                .to_string(),
+            None => rpassword::prompt_password(format!(
+                "Passphrase for keystore {}: ",
+                path.display()
+            ))?,
+        };
+
+        Self::decrypt(path, &passphrase)
+    }
+
+    pub fn decrypt(path: &Path, passphrase: &str) -> Result<Self> {
+        let signer = PrivateKeySigner::decrypt_keystore(path, passphrase).map_err(|e| {
+            EthereumError::Signer(format!(
+                "Failed to decrypt keystore {}: {e}",
+                path.display()
+            ))
+        })?;
+
+        Ok(Self {
+            signer,
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+impl SignerBackend for KeystoreSigner {
+    fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    fn describe(&self) -> String {
+        format!("keystore signer ({})", self.path.display())
+    }
+
+    fn wallet(&self) -> EthereumWallet {
+        EthereumWallet::from(self.signer.clone())
+    }
+}
+
+/// Web3Signer-style remote signer; the key never leaves the signing service
+#[derive(Clone)]
+pub struct RemoteSigner {
+    http: reqwest::Client,
+    url: Url,
+    address: Address,
+    next_id: Arc<AtomicU64>,
+}
+
+#[derive(Deserialize)]
+struct JsonRpcResponse<T> {
+    result: Option<T>,
+    error: Option<JsonRpcError>,
+}
+
+#[derive(Deserialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+impl RemoteSigner {
+    #[must_use]
+    pub fn new(url: Url, address: Address) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            url,
+            address,
+            next_id: Arc::new(AtomicU64::new(1)),
+        }
+    }
+
+    /// Create the signer and make sure the service holds a key for `address`
+    pub async fn connect(url: Url, address: Address) -> Result<Self> {
+        let signer = Self::new(url, address);
+
+        let accounts: Vec<Address> = signer.call("eth_accounts", serde_json::json!([])).await?;
+        if !accounts.contains(&address) {
+            return Err(EthereumError::Signer(format!(
+                "Remote signer at {} has no key for {address}",
+                signer.url
+            )));
+        }
+
+        Ok(signer)
+    }
+
+    async fn call<T: for<'de> Deserialize<'de>>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<T> {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id.fetch_add(1, Ordering::Relaxed),
+            "method": method,
+            "params": params,
+        });
+
+        let response: JsonRpcResponse<T> = self
+            .http
+            .post(self.url.clone())
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        if let Some(error) = response.error {
+            return Err(EthereumError::Signer(format!(
+                "{method} failed ({}): {}",
+                error.code, error.message
+            )));
+        }
+
+        response
+            .result
+            .ok_or_else(|| EthereumError::Signer(format!("{method} returned no result")))
+    }
+
+    /// Translate an unsigned transaction into the request shape `eth_signTransaction` expects
+    fn to_request(&self, tx: &dyn SignableTransaction<Signature>) -> Result<TransactionRequest> {
+        if tx.blob_versioned_hashes().is_some() {
+            return Err(EthereumError::Signer(
+                "Remote signer does not support blob transactions".to_string(),
+            ));
+        }
+
+        let mut request = TransactionRequest {
+            from: Some(self.address),
+            to: Some(tx.kind()),
+            value: Some(tx.value()),
+            nonce: Some(tx.nonce()),
+            gas: Some(tx.gas_limit()),
+            chain_id: tx.chain_id(),
+            access_list: tx.access_list().cloned(),
+            transaction_type: Some(tx.ty()),
+            input: tx.input().clone().into(),
+            ..Default::default()
+        };
+
+        if tx.is_dynamic_fee() {
+            request.max_fee_per_gas = Some(tx.max_fee_per_gas());
+            request.max_priority_fee_per_gas = tx.max_priority_fee_per_gas();
+        } else {
+            request.gas_price = tx.gas_price();
+        }
+
+        Ok(request)
+    }
+}
+
+#[async_trait]
+impl TxSigner<Signature> for RemoteSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    async fn sign_transaction(
+        &self,
+        tx: &mut dyn SignableTransaction<Signature>,
+    ) -> alloy_signer::Result<Signature> {
+        let request = self.to_request(tx).map_err(alloy_signer::Error::other)?;
+
+        let raw: Bytes = self
+            .call("eth_signTransaction", serde_json::json!([request]))
+            .await
+            .map_err(alloy_signer::Error::other)?;
+
+        let envelope =
+            TxEnvelope::decode_2718(&mut raw.as_ref()).map_err(alloy_signer::Error::other)?;
+        let signature = *envelope.signature();
+
+        // The service must have signed our transaction, not something it filled in itself
+        let recovered = signature
+            .recover_address_from_prehash(&tx.signature_hash())
+            .map_err(alloy_signer::Error::other)?;
+        if recovered != self.address {
+            return Err(alloy_signer::Error::other(EthereumError::Signer(format!(
+                "Remote signature recovers to {recovered}, expected {}",
+                self.address
+            ))));
+        }
+
+        Ok(signature)
+    }
+}
+
+impl SignerBackend for RemoteSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn describe(&self) -> String {
+        format!("remote signer ({})", self.url)
+    }
+
+    fn wallet(&self) -> EthereumWallet {
+        EthereumWallet::new(self.clone())
+    }
+}
@@ -0,0 +1,154 @@
+//! WebSocket log subscriptions for contract events
+//!
+//! `EthereumClient::start_event_monitoring` prefers an `eth_subscribe("logs")`
+//! stream over `NetworkConfig.ws_url` and falls back to HTTP polling while the
+//! socket is down. The transport's own reconnect is disabled, so a dropped
+//! connection ends the stream instead of silently resubscribing; the client
+//! then backfills the missed block range over HTTP before subscribing again.
+//! [`LogCursor`] makes sure a log seen through both paths is dispatched once.
+
+use crate::error::Result;
+use alloy_provider::{DynProvider, Provider, ProviderBuilder, WsConnect};
+use alloy_rpc_types_eth::{Filter, Log};
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use std::collections::BTreeSet;
+use tokio::time::{Duration, Instant};
+use url::Url;
+
+/// An open `eth_subscribe("logs")` stream together with the connection feeding it
+pub struct LogSubscription {
+    // Dropping the provider shuts the WebSocket down
+    _provider: DynProvider,
+    stream: BoxStream<'static, Log>,
+}
+
+impl LogSubscription {
+    /// Connect to `ws_url` and subscribe to logs matching `filter`
+    pub async fn connect(ws_url: &Url, filter: &Filter) -> Result<Self> {
+        let ws = WsConnect::new(ws_url.as_str()).with_max_retries(0);
+        let provider = ProviderBuilder::new().connect_ws(ws).await?.erased();
+        let stream = provider.subscribe_logs(filter).await?.into_stream().boxed();
+
+        Ok(Self {
+            _provider: provider,
+            stream,
+        })
+    }
+
+    /// Next log, or `None` once the connection is gone
+    pub async fn next(&mut self) -> Option<Log> {
+        self.stream.next().await
+    }
+}
+
+/// Tracks which logs have been dispatched so the subscription and the HTTP
+/// backfill never deliver the same log twice
+#[derive(Debug, Clone, Default)]
+pub struct LogCursor {
+    complete_through: u64,
+    /// Block the subscription is currently delivering and the log indices seen in it
+    partial: Option<(u64, BTreeSet<u64>)>,
+}
+
+impl LogCursor {
+    /// Start with every block up to and including `block` already delivered
+    #[must_use]
+    pub const fn new(block: u64) -> Self {
+        Self {
+            complete_through: block,
+            partial: None,
+        }
+    }
+
+    /// Last block whose logs have all been delivered
+    #[must_use]
+    pub const fn complete_through(&self) -> u64 {
+        self.complete_through
+    }
+
+    /// Record a streamed log and return whether it still needs dispatching
+    ///
+    /// Subscription logs arrive in block order, so the first log of a new
+    /// block completes the previous one.
+    pub fn accept(&mut self, block_number: u64, log_index: u64) -> bool {
+        if block_number <= self.complete_through {
+            return false;
+        }
+
+        match &mut self.partial {
+            Some((block, seen)) if *block == block_number => seen.insert(log_index),
+            _ => {
+                self.complete_through = self.complete_through.max(block_number - 1);
+                self.partial = Some((block_number, BTreeSet::from([log_index])));
+                true
+            }
+        }
+    }
+
+    /// Record a backfilled log from a block above the cursor and return
+    /// whether it still needs dispatching
+    pub fn accept_backfilled(&mut self, block_number: u64, log_index: u64) -> bool {
+        if block_number <= self.complete_through {
+            return false;
+        }
+
+        match &mut self.partial {
+            Some((block, seen)) if *block == block_number => seen.insert(log_index),
+            _ => true,
+        }
+    }
+
+    /// Mark every block up to and including `block` as delivered
+    pub fn complete(&mut self, block: u64) {
+        self.complete_through = self.complete_through.max(block);
+        if matches!(self.partial, Some((partial, _)) if partial <= block) {
+            self.partial = None;
+        }
+    }
+}
+
+/// Longest wait between WebSocket reconnect attempts
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(300);
+
+/// Exponential backoff between WebSocket reconnect attempts
+#[derive(Debug, Clone)]
+pub struct ReconnectBackoff {
+    initial: Duration,
+    delay: Duration,
+    next_attempt: Instant,
+}
+
+impl ReconnectBackoff {
+    #[must_use]
+    pub fn new(initial: Duration) -> Self {
+        Self {
+            initial,
+            delay: initial,
+            next_attempt: Instant::now(),
+        }
+    }
+
+    /// Whether the next connection attempt is due
+    #[must_use]
+    pub fn ready(&self) -> bool {
+        Instant::now() >= self.next_attempt
+    }
+
+    /// Time left until the next connection attempt
+    #[must_use]
+    pub fn remaining(&self) -> Duration {
+        self.next_attempt.saturating_duration_since(Instant::now())
+    }
+
+    /// Schedule the next attempt after a failed or dropped connection
+    pub fn failed(&mut self) {
+        self.next_attempt = Instant::now() + self.delay;
+        self.delay = (self.delay * 2).min(MAX_RECONNECT_DELAY);
+    }
+
+    /// Start over from the initial delay after a successful connection
+    pub fn reset(&mut self) {
+        self.delay = self.initial;
+    }
+}
@@ -1,10 +1,22 @@
-use alloy_primitives::{Address, Bytes, FixedBytes, U256};
+use alloy_primitives::{keccak256, Address, Bytes, FixedBytes, U256};
 use serde::{Deserialize, Serialize};
 
 pub type StateRoot = FixedBytes<32>;
 pub type ProofId = FixedBytes<32>;
 pub type StateId = FixedBytes<32>;
 
+/// State id under which the API settles proof batch `batch_id`
+#[must_use]
+pub fn batch_state_id(batch_id: i32) -> StateId {
+    keccak256(format!("batch_{batch_id}").as_bytes())
+}
+
+/// Root the API posts for a proof batch ending at `final_counter_value`
+#[must_use]
+pub fn batch_state_root(final_counter_value: i64) -> StateRoot {
+    keccak256(format!("state_root_{final_counter_value}").as_bytes())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StateUpdate {
     pub state_id: StateId,
@@ -90,7 +102,28 @@ pub struct BatchStateUpdate {
     pub transaction_hash: FixedBytes<32>,
     pub block_number: u64,
     pub gas_used: U256,
+    /// Whether each item was accepted, decoded from the receipt logs
     pub success_flags: Vec<bool>,
+    /// Proof id of each accepted item, `None` where the contract rejected the proof
+    pub proof_ids: Vec<Option<ProofId>>,
+}
+
+impl BatchStateUpdate {
+    #[must_use]
+    pub fn all_succeeded(&self) -> bool {
+        self.success_flags.iter().all(|ok| *ok)
+    }
+
+    /// Input positions the contract rejected
+    #[must_use]
+    pub fn failed_indices(&self) -> Vec<usize> {
+        self.success_flags
+            .iter()
+            .enumerate()
+            .filter(|(_, ok)| !**ok)
+            .map(|(i, _)| i)
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -111,7 +144,14 @@ pub struct NetworkStats {
     pub gas_price: U256,
     pub base_fee: Option<U256>,
     pub network_name: String,
+    /// Whether the node reports itself as fully synced
     pub sync_status: bool,
+    /// Timestamp of the latest block, in seconds
+    #[serde(default)]
+    pub block_timestamp: u64,
+    /// Highest block the node knows of while it is still syncing
+    #[serde(default)]
+    pub highest_block: Option<u64>,
 }
 
 // ==========================================
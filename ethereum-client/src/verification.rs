@@ -0,0 +1,77 @@
+//! Local checks used by trustless verification
+//!
+//! Everything here works on data read from the chain and needs no trust in the
+//! service that produced it. Proofs are checked with SP1's verifier for the EVM
+//! proof encoding (the bytes the contract stores and passes to the SP1 verifier
+//! gateway). Merkle paths use the SHA-256 pair hashing of the `db` trees.
+
+use alloy_primitives::FixedBytes;
+use sha2::{Digest, Sha256};
+use sp1_verifier::{Groth16Verifier, PlonkVerifier, GROTH16_VK_BYTES, PLONK_VK_BYTES};
+
+/// Proof systems the SP1 verifier gateway accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnChainProofSystem {
+    Groth16,
+    Plonk,
+}
+
+/// Verify an EVM-encoded SP1 proof against a program verification key hash
+///
+/// The proof's 4-byte selector decides the proof system; a proof for a
+/// different program or different public values fails.
+pub fn verify_sp1_proof(
+    proof: &[u8],
+    public_values: &[u8],
+    program_vkey: FixedBytes<32>,
+) -> Result<OnChainProofSystem, String> {
+    let vkey_hash = format!("0x{}", hex::encode(program_vkey));
+
+    let groth16_error =
+        match Groth16Verifier::verify(proof, public_values, &vkey_hash, *GROTH16_VK_BYTES) {
+            Ok(()) => return Ok(OnChainProofSystem::Groth16),
+            Err(e) => e.to_string(),
+        };
+
+    match PlonkVerifier::verify(proof, public_values, &vkey_hash, *PLONK_VK_BYTES) {
+        Ok(()) => Ok(OnChainProofSystem::Plonk),
+        Err(plonk_error) => Err(format!(
+            "not a valid Groth16 ({groth16_error}) or PLONK ({plonk_error}) proof"
+        )),
+    }
+}
+
+/// SHA-256 of the concatenated children
+#[must_use]
+pub fn hash_pair(left: &FixedBytes<32>, right: &FixedBytes<32>) -> FixedBytes<32> {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    FixedBytes::from(<[u8; 32]>::from(hasher.finalize()))
+}
+
+/// Root implied by a leaf, its index and its siblings ordered from the leaf up
+///
+/// Bit `i` of `leaf_index` says whether the node at level `i` is a right child.
+/// Returns `None` if the index does not fit in a tree of `siblings.len()` levels.
+#[must_use]
+pub fn compute_merkle_root(
+    leaf_hash: FixedBytes<32>,
+    leaf_index: u64,
+    siblings: &[FixedBytes<32>],
+) -> Option<FixedBytes<32>> {
+    if siblings.len() < 64 && leaf_index >> siblings.len() != 0 {
+        return None;
+    }
+
+    let mut current = leaf_hash;
+    for (level, sibling) in siblings.iter().enumerate() {
+        let is_right = level < 64 && (leaf_index >> level) & 1 == 1;
+        current = if is_right {
+            hash_pair(sibling, &current)
+        } else {
+            hash_pair(&current, sibling)
+        };
+    }
+    Some(current)
+}
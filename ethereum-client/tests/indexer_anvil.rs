@@ -0,0 +1,370 @@
+//! Chain indexer tests against a local anvil node
+//!
+//! These need the `anvil` binary on `PATH` and a Postgres `DATABASE_URL`:
+//!
+//! ```shell
+//! DATABASE_URL=postgres://... cargo test -p ethereum-client --test indexer_anvil -- --ignored
+//! ```
+//!
+//! Instead of deploying the full contract (which needs an SP1 verifier), a stub
+//! is installed with `anvil_setCode` that emits `LOG4` with the first four
+//! calldata words as topics and the rest of the calldata as data. Each test uses
+//! a fresh random address, so indexer state never collides between runs.
+#![cfg(feature = "database")]
+
+use alloy_network::{EthereumWallet, TransactionBuilder};
+use alloy_primitives::{Address, Bytes, FixedBytes, U256};
+use alloy_provider::{Provider, ProviderBuilder};
+use alloy_rpc_types_eth::TransactionRequest;
+use alloy_signer_local::PrivateKeySigner;
+use alloy_sol_types::{SolEvent, SolValue};
+use ethereum_client::{
+    contracts::IArithmetic, ArithmeticEvent, ChainIndexer, DeliveryConfig, EthereumCache,
+    EventHandler, HandlerDelivery, IndexerConfig,
+};
+use std::net::TcpListener;
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// `CALLDATACOPY` everything, then `LOG4(0x80, size - 0x80, word0, word1, word2, word3)`
+const LOG4_STUB: &str = "366000600037606035604035602035600035608036036080a400";
+
+/// First anvil dev account
+const ANVIL_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+const ANVIL_CHAIN_ID: u64 = 31337;
+
+struct Anvil {
+    child: Child,
+    url: url::Url,
+}
+
+impl Drop for Anvil {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+    }
+}
+
+async fn spawn_anvil() -> Anvil {
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let child = Command::new("anvil")
+        .args(["--port", &port.to_string(), "--silent"])
+        .stdout(Stdio::null())
+        .spawn()
+        .expect("anvil must be installed");
+    let url: url::Url = format!("http://127.0.0.1:{port}").parse().unwrap();
+
+    let provider = ProviderBuilder::new().connect_http(url.clone());
+    for _ in 0..50 {
+        if provider.get_block_number().await.is_ok() {
+            return Anvil { child, url };
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("anvil did not start");
+}
+
+async fn cache() -> EthereumCache {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = sqlx::PgPool::connect(&database_url).await.unwrap();
+    let cache = EthereumCache::new(pool);
+    cache.initialize().await.unwrap();
+    cache
+}
+
+async fn install_stub(provider: &impl Provider) -> Address {
+    let address = Address::random();
+    let _: () = provider
+        .raw_request(
+            "anvil_setCode".into(),
+            (address, Bytes::from(hex::decode(LOG4_STUB).unwrap())),
+        )
+        .await
+        .unwrap();
+    address
+}
+
+/// Emit `StateUpdated` from the stub in its own block
+async fn emit_state_updated(provider: &impl Provider, stub: Address, state_id: u8, new_state: u8) {
+    let mut calldata = IArithmetic::StateUpdated::SIGNATURE_HASH.to_vec();
+    calldata.extend_from_slice(FixedBytes::<32>::repeat_byte(state_id).as_slice());
+    calldata.extend_from_slice(FixedBytes::<32>::repeat_byte(new_state).as_slice());
+    calldata.extend_from_slice(FixedBytes::<32>::repeat_byte(0xAA).as_slice());
+    calldata.extend_from_slice(
+        &(Address::repeat_byte(0x11), U256::from(1_700_000_000u64)).abi_encode(),
+    );
+
+    let tx = TransactionRequest::default()
+        .with_to(stub)
+        .with_input(calldata);
+    provider
+        .send_transaction(tx)
+        .await
+        .unwrap()
+        .get_receipt()
+        .await
+        .unwrap();
+}
+
+async fn mine(provider: &impl Provider, blocks: u64) {
+    let _: () = provider
+        .raw_request("anvil_mine".into(), (U256::from(blocks),))
+        .await
+        .unwrap();
+}
+
+fn new_states(events: &[ethereum_client::IndexedEvent]) -> Vec<u8> {
+    events
+        .iter()
+        .filter_map(|indexed| match &indexed.event {
+            ArithmeticEvent::StateUpdated { new_state, .. } => Some(new_state[0]),
+            _ => None,
+        })
+        .collect()
+}
+
+fn config(start_block: u64) -> IndexerConfig {
+    IndexerConfig {
+        start_block: Some(start_block),
+        max_block_range: 5,
+        ..IndexerConfig::default()
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires anvil and DATABASE_URL"]
+async fn resumes_from_stored_cursor_after_restart() {
+    let anvil = spawn_anvil().await;
+    let signer: PrivateKeySigner = ANVIL_KEY.parse().unwrap();
+    let provider = ProviderBuilder::new()
+        .wallet(EthereumWallet::from(signer))
+        .connect_http(anvil.url.clone());
+    let cache = cache().await;
+    let stub = install_stub(&provider).await;
+    let start = provider.get_block_number().await.unwrap() + 1;
+
+    emit_state_updated(&provider, stub, 1, 1).await;
+    mine(&provider, 7).await;
+    emit_state_updated(&provider, stub, 1, 2).await;
+
+    let indexer = ChainIndexer::new(
+        provider.clone(),
+        cache.clone(),
+        ANVIL_CHAIN_ID,
+        stub,
+        config(start),
+    );
+    let mut indexed = Vec::new();
+    loop {
+        let step = indexer.step().await.unwrap();
+        indexed.extend(step.events);
+        if step.caught_up {
+            break;
+        }
+    }
+    assert_eq!(new_states(&indexed), vec![1, 2]);
+
+    // Events emitted while "down" are picked up by a fresh indexer instance
+    emit_state_updated(&provider, stub, 1, 3).await;
+    let restarted = ChainIndexer::new(provider.clone(), cache, ANVIL_CHAIN_ID, stub, config(start));
+    let step = restarted.step().await.unwrap();
+    assert_eq!(new_states(&step.events), vec![3]);
+
+    let head = provider.get_block_number().await.unwrap();
+    let stored = restarted.events_in_range(start, head).await.unwrap();
+    assert_eq!(new_states(&stored), vec![1, 2, 3]);
+}
+
+#[tokio::test]
+#[ignore = "requires anvil and DATABASE_URL"]
+async fn rolls_back_and_reingests_on_reorg() {
+    let anvil = spawn_anvil().await;
+    let signer: PrivateKeySigner = ANVIL_KEY.parse().unwrap();
+    let provider = ProviderBuilder::new()
+        .wallet(EthereumWallet::from(signer))
+        .connect_http(anvil.url.clone());
+    let cache = cache().await;
+    let stub = install_stub(&provider).await;
+    let start = provider.get_block_number().await.unwrap() + 1;
+
+    emit_state_updated(&provider, stub, 1, 1).await;
+    let snapshot: U256 = provider
+        .raw_request("evm_snapshot".into(), Vec::<()>::new())
+        .await
+        .unwrap();
+    emit_state_updated(&provider, stub, 1, 2).await;
+
+    let indexer = ChainIndexer::new(provider.clone(), cache, ANVIL_CHAIN_ID, stub, config(start));
+    let step = indexer.step().await.unwrap();
+    assert_eq!(new_states(&step.events), vec![1, 2]);
+
+    // Replace the block holding event 2 with a longer fork holding event 9
+    let _: bool = provider
+        .raw_request("evm_revert".into(), (snapshot,))
+        .await
+        .unwrap();
+    emit_state_updated(&provider, stub, 1, 9).await;
+    mine(&provider, 2).await;
+
+    let step = indexer.step().await.unwrap();
+    assert!(step.reorg_detected_at.is_some());
+    assert_eq!(step.cursor.map(|c| c.block_number), Some(start));
+
+    let step = indexer.step().await.unwrap();
+    assert_eq!(new_states(&step.events), vec![9]);
+
+    let head = provider.get_block_number().await.unwrap();
+    let stored = indexer.events_in_range(start, head).await.unwrap();
+    assert_eq!(new_states(&stored), vec![1, 9]);
+}
+
+#[tokio::test]
+#[ignore = "requires anvil and DATABASE_URL"]
+async fn replay_restores_deleted_events() {
+    let anvil = spawn_anvil().await;
+    let signer: PrivateKeySigner = ANVIL_KEY.parse().unwrap();
+    let provider = ProviderBuilder::new()
+        .wallet(EthereumWallet::from(signer))
+        .connect_http(anvil.url.clone());
+    let cache = cache().await;
+    let stub = install_stub(&provider).await;
+    let start = provider.get_block_number().await.unwrap() + 1;
+
+    emit_state_updated(&provider, stub, 1, 1).await;
+    emit_state_updated(&provider, stub, 1, 2).await;
+
+    let indexer = ChainIndexer::new(
+        provider.clone(),
+        cache.clone(),
+        ANVIL_CHAIN_ID,
+        stub,
+        config(start),
+    );
+    indexer.step().await.unwrap();
+
+    let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM ethereum_indexed_events WHERE contract_address = $1")
+        .bind(stub.as_slice())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let head = provider.get_block_number().await.unwrap();
+    let replayed = indexer.replay(start, head + 100).await.unwrap();
+    assert_eq!(new_states(&replayed), vec![1, 2]);
+    assert_eq!(
+        new_states(&indexer.events_in_range(start, head).await.unwrap()),
+        vec![1, 2]
+    );
+}
+
+/// Records delivered new states and fails the first delivery of states 2 (error) and 3 (panic)
+#[derive(Default)]
+struct FlakyHandler {
+    received: std::sync::Mutex<Vec<u8>>,
+    failed: std::sync::Mutex<Vec<u8>>,
+}
+
+#[async_trait::async_trait]
+impl EventHandler for FlakyHandler {
+    fn name(&self) -> &str {
+        "flaky"
+    }
+
+    async fn handle_state_updated(
+        &self,
+        _state_id: FixedBytes<32>,
+        new_state: FixedBytes<32>,
+        _proof_id: FixedBytes<32>,
+        _updater: Address,
+        _block_number: u64,
+        _tx_hash: FixedBytes<32>,
+    ) -> ethereum_client::Result<()> {
+        let state = new_state[0];
+        let first_attempt = {
+            let mut failed = self.failed.lock().unwrap();
+            let first = matches!(state, 2 | 3) && !failed.contains(&state);
+            if first {
+                failed.push(state);
+            }
+            first
+        };
+        match (state, first_attempt) {
+            (2, true) => Err(ethereum_client::EthereumError::External(
+                "temporarily unavailable".to_string(),
+            )),
+            (3, true) => panic!("handler bug"),
+            _ => {
+                self.received.lock().unwrap().push(state);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires anvil and DATABASE_URL"]
+async fn delivers_to_handlers_at_least_once() {
+    let anvil = spawn_anvil().await;
+    let signer: PrivateKeySigner = ANVIL_KEY.parse().unwrap();
+    let provider = ProviderBuilder::new()
+        .wallet(EthereumWallet::from(signer))
+        .connect_http(anvil.url.clone());
+    let cache = cache().await;
+    let stub = install_stub(&provider).await;
+    let start = provider.get_block_number().await.unwrap() + 1;
+
+    for new_state in 1..=4 {
+        emit_state_updated(&provider, stub, 1, new_state).await;
+    }
+    let indexer = ChainIndexer::new(
+        provider.clone(),
+        cache.clone(),
+        ANVIL_CHAIN_ID,
+        stub,
+        config(start),
+    );
+    while !indexer.step().await.unwrap().caught_up {}
+
+    let handler = Arc::new(FlakyHandler::default());
+    let delivery_config = DeliveryConfig {
+        retry_base_delay: Duration::ZERO,
+        ..DeliveryConfig::default()
+    };
+    let delivery = HandlerDelivery::new(
+        cache.clone(),
+        handler.clone(),
+        ANVIL_CHAIN_ID,
+        stub,
+        delivery_config.clone(),
+    );
+
+    // Failures do not hold up later events
+    assert_eq!(delivery.deliver_new().await.unwrap(), 4);
+    assert_eq!(*handler.received.lock().unwrap(), vec![1, 4]);
+
+    // Both failed events come back through the retry queue, once
+    assert_eq!(delivery.retry_due().await.unwrap(), 2);
+    assert_eq!(delivery.retry_due().await.unwrap(), 0);
+    assert_eq!(*handler.received.lock().unwrap(), vec![1, 4, 2, 3]);
+
+    // A restarted delivery resumes from the stored checkpoint
+    emit_state_updated(&provider, stub, 1, 5).await;
+    while !indexer.step().await.unwrap().caught_up {}
+    let restarted = HandlerDelivery::new(
+        cache,
+        handler.clone(),
+        ANVIL_CHAIN_ID,
+        stub,
+        delivery_config,
+    );
+    assert_eq!(restarted.deliver_new().await.unwrap(), 1);
+    assert_eq!(*handler.received.lock().unwrap(), vec![1, 4, 2, 3, 5]);
+}
@@ -0,0 +1,182 @@
+//! Signer backend tests
+//!
+//! The remote signer is exercised against a stub JSON-RPC server that holds a
+//! local key and answers `eth_accounts` and `eth_signTransaction` the way
+//! Web3Signer does, returning the raw EIP-2718 encoded transaction.
+
+use alloy_consensus::transaction::SignerRecoverable;
+use alloy_network::{eip2718::Encodable2718, Ethereum, EthereumWallet, NetworkWallet};
+use alloy_primitives::{Address, Bytes, U256};
+use alloy_rpc_types_eth::TransactionRequest;
+use alloy_signer_local::PrivateKeySigner;
+use ethereum_client::{
+    signer::load_signer, KeystoreSigner, LocalKeySigner, RemoteSigner, SignerBackend,
+    SignerConfig, SignerSource,
+};
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use url::Url;
+
+const CHAIN_ID: u64 = 11_155_111;
+
+/// Spawn a stub signer for `key`; with `tamper` set it signs a different nonce
+/// than the one it was asked for
+async fn spawn_stub_signer(key: PrivateKeySigner, tamper: bool) -> Url {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let key = key.clone();
+            tokio::spawn(async move { handle_connection(stream, key, tamper).await });
+        }
+    });
+
+    url
+}
+
+async fn handle_connection(mut stream: TcpStream, key: PrivateKeySigner, tamper: bool) {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let body = loop {
+        let n = stream.read(&mut chunk).await.unwrap();
+        assert!(n > 0, "connection closed before the request was complete");
+        buf.extend_from_slice(&chunk[..n]);
+
+        let Some(header_end) = buf.windows(4).position(|w| w == b"\r\n\r\n") else {
+            continue;
+        };
+        let headers = String::from_utf8_lossy(&buf[..header_end]).to_lowercase();
+        let content_length: usize = headers
+            .lines()
+            .find_map(|l| l.strip_prefix("content-length:"))
+            .map_or(0, |v| v.trim().parse().unwrap());
+        if buf.len() >= header_end + 4 + content_length {
+            break buf[header_end + 4..header_end + 4 + content_length].to_vec();
+        }
+    };
+
+    let request: Value = serde_json::from_slice(&body).unwrap();
+    let result = match request["method"].as_str().unwrap() {
+        "eth_accounts" => json!([key.address()]),
+        "eth_signTransaction" => {
+            let mut tx: TransactionRequest =
+                serde_json::from_value(request["params"][0].clone()).unwrap();
+            assert_eq!(tx.from, Some(key.address()));
+            if tamper {
+                tx.nonce = tx.nonce.map(|n| n + 1);
+            }
+            let wallet = EthereumWallet::from(key);
+            let envelope = <EthereumWallet as NetworkWallet<Ethereum>>::sign_request(&wallet, tx)
+                .await
+                .unwrap();
+            json!(Bytes::from(envelope.encoded_2718()))
+        }
+        other => panic!("unexpected method {other}"),
+    };
+
+    let response = json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }).to_string();
+    let http = format!(
+        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{response}",
+        response.len()
+    );
+    stream.write_all(http.as_bytes()).await.unwrap();
+}
+
+fn sample_request(from: Address) -> TransactionRequest {
+    TransactionRequest {
+        from: Some(from),
+        to: Some(Address::from_slice(&[0x11; 20]).into()),
+        value: Some(U256::from(1_000u64)),
+        nonce: Some(7),
+        gas: Some(100_000),
+        max_fee_per_gas: Some(30_000_000_000),
+        max_priority_fee_per_gas: Some(1_000_000_000),
+        chain_id: Some(CHAIN_ID),
+        input: Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]).into(),
+        ..Default::default()
+    }
+}
+
+async fn sign_with(backend: &dyn SignerBackend) -> Result<Address, String> {
+    let wallet = backend.wallet();
+    let envelope = <EthereumWallet as NetworkWallet<Ethereum>>::sign_request(
+        &wallet,
+        sample_request(backend.address()),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(envelope.recover_signer().unwrap())
+}
+
+#[tokio::test]
+async fn test_local_key_signer() {
+    let key = PrivateKeySigner::random();
+    let backend = LocalKeySigner::from_hex(&format!("0x{}", hex::encode(key.to_bytes()))).unwrap();
+
+    assert_eq!(backend.address(), key.address());
+    assert_eq!(sign_with(&backend).await.unwrap(), key.address());
+}
+
+#[tokio::test]
+async fn test_keystore_signer() {
+    let dir = tempfile::tempdir().unwrap();
+    let key = PrivateKeySigner::random();
+    PrivateKeySigner::encrypt_keystore(
+        dir.path(),
+        &mut rand::thread_rng(),
+        key.to_bytes(),
+        "correct horse",
+        Some("deployer"),
+    )
+    .unwrap();
+    let keystore = dir.path().join("deployer");
+
+    let passphrase_file = dir.path().join("passphrase");
+    std::fs::write(&passphrase_file, "correct horse\n").unwrap();
+
+    let backend = KeystoreSigner::open(&keystore, Some(&passphrase_file)).unwrap();
+    assert_eq!(backend.address(), key.address());
+    assert_eq!(sign_with(&backend).await.unwrap(), key.address());
+
+    assert!(KeystoreSigner::decrypt(&keystore, "battery staple").is_err());
+
+    // The configured address must match the key inside the keystore
+    let config = SignerConfig {
+        source: SignerSource::Keystore {
+            path: keystore,
+            passphrase_file: Some(passphrase_file),
+        },
+        address: Address::from_slice(&[0x22; 20]),
+    };
+    assert!(load_signer(&config).await.is_err());
+}
+
+#[tokio::test]
+async fn test_remote_signer() {
+    let key = PrivateKeySigner::random();
+    let url = spawn_stub_signer(key.clone(), false).await;
+
+    let config = SignerConfig {
+        source: SignerSource::Remote { url: url.clone() },
+        address: key.address(),
+    };
+    let backend = load_signer(&config).await.unwrap();
+    assert_eq!(sign_with(backend.as_ref()).await.unwrap(), key.address());
+
+    // The stub holds no key for any other address
+    let unknown = RemoteSigner::connect(url, Address::from_slice(&[0x33; 20])).await;
+    assert!(unknown.is_err());
+}
+
+#[tokio::test]
+async fn test_remote_signer_rejects_different_transaction() {
+    let key = PrivateKeySigner::random();
+    let url = spawn_stub_signer(key.clone(), true).await;
+
+    let backend = RemoteSigner::connect(url, key.address()).await.unwrap();
+    let err = sign_with(&backend).await.unwrap_err();
+    assert!(err.contains("expected"), "unexpected error: {err}");
+}
@@ -0,0 +1,256 @@
+//! WebSocket event subscription tests against a local anvil node
+//!
+//! These need the `anvil` binary on `PATH`:
+//!
+//! ```shell
+//! cargo test -p ethereum-client --test subscription_anvil -- --ignored
+//! ```
+//!
+//! Events come from the same `LOG4` stub as the indexer tests. The client
+//! reaches anvil's WebSocket through a TCP proxy the tests can take offline,
+//! which simulates a dropped connection while HTTP keeps working.
+
+use alloy_network::{EthereumWallet, TransactionBuilder};
+use alloy_primitives::{Address, Bytes, FixedBytes, U256};
+use alloy_provider::{Provider, ProviderBuilder};
+use alloy_rpc_types_eth::TransactionRequest;
+use alloy_signer_local::PrivateKeySigner;
+use alloy_sol_types::{SolEvent, SolValue};
+use ethereum_client::{
+    contracts::IArithmetic, ArithmeticEvent, Config, EthereumClient, SignerConfig, SignerSource,
+};
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, watch};
+
+/// `CALLDATACOPY` everything, then `LOG4(0x80, size - 0x80, word0, word1, word2, word3)`
+const LOG4_STUB: &str = "366000600037606035604035602035600035608036036080a400";
+
+/// First anvil dev account
+const ANVIL_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+const ANVIL_CHAIN_ID: u64 = 31337;
+
+struct Anvil {
+    child: Child,
+    port: u16,
+}
+
+impl Drop for Anvil {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+    }
+}
+
+impl Anvil {
+    fn http_url(&self) -> url::Url {
+        format!("http://127.0.0.1:{}", self.port).parse().unwrap()
+    }
+}
+
+async fn spawn_anvil() -> Anvil {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let child = Command::new("anvil")
+        .args(["--port", &port.to_string(), "--silent"])
+        .stdout(Stdio::null())
+        .spawn()
+        .expect("anvil must be installed");
+    let anvil = Anvil { child, port };
+
+    let provider = ProviderBuilder::new().connect_http(anvil.http_url());
+    for _ in 0..50 {
+        if provider.get_block_number().await.is_ok() {
+            return anvil;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("anvil did not start");
+}
+
+/// Forward connections to anvil's port until `online` is set to false, which
+/// drops every open connection and refuses new ones until it is set again
+async fn spawn_proxy(target_port: u16) -> (url::Url, Arc<watch::Sender<bool>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap())
+        .parse()
+        .unwrap();
+    let online = Arc::new(watch::channel(true).0);
+
+    let switch = Arc::clone(&online);
+    tokio::spawn(async move {
+        loop {
+            let (mut inbound, _) = listener.accept().await.unwrap();
+            if !*switch.borrow() {
+                continue;
+            }
+            let mut offline = switch.subscribe();
+            tokio::spawn(async move {
+                let mut upstream = TcpStream::connect(("127.0.0.1", target_port))
+                    .await
+                    .unwrap();
+                tokio::select! {
+                    _ = tokio::io::copy_bidirectional(&mut inbound, &mut upstream) => {}
+                    _ = offline.wait_for(|online| !*online) => {}
+                }
+            });
+        }
+    });
+
+    (url, online)
+}
+
+async fn install_stub(provider: &impl Provider) -> Address {
+    let address = Address::random();
+    let _: () = provider
+        .raw_request(
+            "anvil_setCode".into(),
+            (address, Bytes::from(hex::decode(LOG4_STUB).unwrap())),
+        )
+        .await
+        .unwrap();
+    address
+}
+
+/// Emit `StateUpdated` from the stub in its own block
+async fn emit_state_updated(provider: &impl Provider, stub: Address, new_state: u8) {
+    let mut calldata = IArithmetic::StateUpdated::SIGNATURE_HASH.to_vec();
+    calldata.extend_from_slice(FixedBytes::<32>::repeat_byte(1).as_slice());
+    calldata.extend_from_slice(FixedBytes::<32>::repeat_byte(new_state).as_slice());
+    calldata.extend_from_slice(FixedBytes::<32>::repeat_byte(0xAA).as_slice());
+    calldata.extend_from_slice(
+        &(Address::repeat_byte(0x11), U256::from(1_700_000_000u64)).abi_encode(),
+    );
+
+    let tx = TransactionRequest::default()
+        .with_to(stub)
+        .with_input(calldata);
+    provider
+        .send_transaction(tx)
+        .await
+        .unwrap()
+        .get_receipt()
+        .await
+        .unwrap();
+}
+
+/// Start monitoring `stub` with a client whose WebSocket goes to `ws_url`
+async fn start_client(
+    anvil: &Anvil,
+    ws_url: url::Url,
+    stub: Address,
+) -> broadcast::Receiver<ArithmeticEvent> {
+    // Exercise the in-memory monitoring path rather than the durable indexer
+    std::env::remove_var("DATABASE_URL");
+
+    let signer: PrivateKeySigner = ANVIL_KEY.parse().unwrap();
+    let mut config = Config::default();
+    config.network.name = "anvil".to_string();
+    config.network.chain_id = ANVIL_CHAIN_ID;
+    config.network.rpc_url = anvil.http_url();
+    config.network.ws_url = Some(ws_url);
+    config.contract.arithmetic_contract = stub;
+    config.signer = Some(SignerConfig {
+        source: SignerSource::LocalKey {
+            private_key: ANVIL_KEY.to_string(),
+        },
+        address: signer.address(),
+    });
+
+    let client = Arc::new(
+        EthereumClient::new_without_validation(config)
+            .await
+            .unwrap(),
+    );
+    let events = client.get_event_stream();
+    tokio::spawn(async move { client.start_event_monitoring().await });
+
+    // Give the subscription time to come up
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    events
+}
+
+/// New states of the `StateUpdated` events received within `wait`
+async fn receive_states(
+    events: &mut broadcast::Receiver<ArithmeticEvent>,
+    count: usize,
+    wait: Duration,
+) -> Vec<u8> {
+    let mut states = Vec::new();
+    let _ = tokio::time::timeout(wait, async {
+        while states.len() < count {
+            if let ArithmeticEvent::StateUpdated { new_state, .. } = events.recv().await.unwrap() {
+                states.push(new_state[0]);
+            }
+        }
+    })
+    .await;
+    states
+}
+
+#[tokio::test]
+#[ignore = "requires anvil"]
+async fn delivers_events_over_websocket() {
+    let anvil = spawn_anvil().await;
+    let provider = ProviderBuilder::new()
+        .wallet(EthereumWallet::from(
+            ANVIL_KEY.parse::<PrivateKeySigner>().unwrap(),
+        ))
+        .connect_http(anvil.http_url());
+    let stub = install_stub(&provider).await;
+
+    let ws_url = format!("ws://127.0.0.1:{}", anvil.port).parse().unwrap();
+    let mut events = start_client(&anvil, ws_url, stub).await;
+
+    for new_state in 1..=3 {
+        emit_state_updated(&provider, stub, new_state).await;
+    }
+
+    // Well inside the 12s polling interval, so these came over the subscription
+    let states = receive_states(&mut events, 3, Duration::from_secs(5)).await;
+    assert_eq!(states, vec![1, 2, 3]);
+}
+
+#[tokio::test]
+#[ignore = "requires anvil"]
+async fn backfills_gap_after_reconnect() {
+    let anvil = spawn_anvil().await;
+    let provider = ProviderBuilder::new()
+        .wallet(EthereumWallet::from(
+            ANVIL_KEY.parse::<PrivateKeySigner>().unwrap(),
+        ))
+        .connect_http(anvil.http_url());
+    let stub = install_stub(&provider).await;
+
+    let (ws_url, online) = spawn_proxy(anvil.port).await;
+    let mut events = start_client(&anvil, ws_url, stub).await;
+
+    emit_state_updated(&provider, stub, 1).await;
+    assert_eq!(
+        receive_states(&mut events, 1, Duration::from_secs(5)).await,
+        vec![1]
+    );
+
+    // Events emitted while the WebSocket is down
+    online.send_replace(false);
+    emit_state_updated(&provider, stub, 2).await;
+    emit_state_updated(&provider, stub, 3).await;
+    online.send_replace(true);
+
+    assert_eq!(
+        receive_states(&mut events, 2, Duration::from_secs(30)).await,
+        vec![2, 3]
+    );
+
+    // Nothing was delivered twice
+    emit_state_updated(&provider, stub, 4).await;
+    assert_eq!(
+        receive_states(&mut events, 2, Duration::from_secs(20)).await,
+        vec![4]
+    );
+}
@@ -96,6 +96,40 @@ mod tests {
         assert_eq!(computed_hash, parent);
     }
 
+    #[test]
+    fn test_compute_merkle_root() {
+        use alloy_primitives::FixedBytes;
+        use ethereum_client::verification::{compute_merkle_root, hash_pair};
+
+        // 4-leaf tree
+        let leaves: Vec<_> = (1u8..=4).map(FixedBytes::<32>::repeat_byte).collect();
+        let left = hash_pair(&leaves[0], &leaves[1]);
+        let right = hash_pair(&leaves[2], &leaves[3]);
+        let root = hash_pair(&left, &right);
+
+        assert_eq!(
+            compute_merkle_root(leaves[0], 0, &[leaves[1], right]),
+            Some(root)
+        );
+        assert_eq!(
+            compute_merkle_root(leaves[2], 2, &[leaves[3], left]),
+            Some(root)
+        );
+        assert_eq!(
+            compute_merkle_root(leaves[3], 3, &[leaves[2], left]),
+            Some(root)
+        );
+
+        // Wrong position
+        assert_ne!(
+            compute_merkle_root(leaves[2], 3, &[leaves[3], left]),
+            Some(root)
+        );
+
+        // Index out of range for a 2-level tree
+        assert_eq!(compute_merkle_root(leaves[0], 4, &[leaves[1], right]), None);
+    }
+
     #[test]
     fn test_error_types() {
         use ethereum_client::EthereumError;
@@ -159,4 +193,238 @@ mod tests {
         env::remove_var("VERIFIER_CONTRACT_ADDRESS");
         env::remove_var("ETHEREUM_NETWORK");
     }
+
+    #[test]
+    fn test_fee_bump_and_cap() {
+        use ethereum_client::gas::Eip1559Fees;
+
+        let fees = Eip1559Fees {
+            max_fee_per_gas: 40_000_000_000,
+            max_priority_fee_per_gas: 2_000_000_000,
+        };
+
+        let bumped = fees.bumped(15);
+        assert_eq!(bumped.max_fee_per_gas, 46_000_000_000);
+        assert_eq!(bumped.max_priority_fee_per_gas, 2_300_000_000);
+
+        // Tiny fees still move by at least one wei
+        let dust = Eip1559Fees {
+            max_fee_per_gas: 1,
+            max_priority_fee_per_gas: 0,
+        };
+        assert_eq!(dust.bumped(10).max_fee_per_gas, 2);
+        assert_eq!(dust.bumped(10).max_priority_fee_per_gas, 1);
+
+        let capped = bumped.capped(2_000_000_000);
+        assert_eq!(capped.max_fee_per_gas, 2_000_000_000);
+        assert_eq!(capped.max_priority_fee_per_gas, 2_000_000_000);
+
+        assert_eq!(fees.max_cost(100_000), 4_000_000_000_000_000);
+    }
+
+    #[test]
+    fn test_fees_from_history() {
+        use alloy_rpc_types_eth::FeeHistory;
+        use ethereum_client::gas::fees_from_history;
+
+        let history = FeeHistory {
+            base_fee_per_gas: vec![10, 12, 14, 20],
+            reward: Some(vec![vec![3], vec![1], vec![2]]),
+            ..Default::default()
+        };
+
+        let fees = fees_from_history(&history).unwrap();
+        assert_eq!(fees.max_priority_fee_per_gas, 2);
+        assert_eq!(fees.max_fee_per_gas, 42);
+
+        assert!(fees_from_history(&FeeHistory::default()).is_err());
+    }
+
+    #[test]
+    fn test_gas_config_rejects_small_fee_bump() {
+        let mut config = Config::default();
+        config.network.rpc_url = Url::parse("https://test.example.com").unwrap();
+        config.contract.arithmetic_contract = Address::from_slice(&[1; 20]);
+
+        config.gas.fee_bump_percent = 5;
+        assert!(config.validate().is_err());
+
+        config.gas.fee_bump_percent = 10;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_decode_batch_results() {
+        use alloy_primitives::{keccak256, Bytes, FixedBytes, U256};
+        use alloy_sol_types::SolEvent;
+        use ethereum_client::{contracts::IArithmetic, EthereumClient};
+
+        let contract = Address::from_slice(&[7; 20]);
+        let updater = Address::from_slice(&[8; 20]);
+        let state_ids = vec![FixedBytes::from([1; 32]), FixedBytes::from([2; 32])];
+        let roots = vec![FixedBytes::from([3; 32]), FixedBytes::from([4; 32])];
+        let proofs = vec![Bytes::from_static(b"good"), Bytes::from_static(b"bad")];
+        let proof_id = keccak256(&proofs[0]);
+
+        let rpc_log =
+            |address: Address, data: alloy_primitives::LogData| alloy_rpc_types_eth::Log {
+                inner: alloy_primitives::Log { address, data },
+                ..Default::default()
+            };
+
+        // Only the first item verified; the second was skipped by the contract
+        let logs = vec![
+            rpc_log(
+                contract,
+                IArithmetic::ProofVerified {
+                    proofId: proof_id,
+                    success: true,
+                    result: Bytes::new(),
+                    timestamp: U256::from(1),
+                }
+                .encode_log_data(),
+            ),
+            rpc_log(
+                contract,
+                IArithmetic::StateUpdated {
+                    stateId: state_ids[0],
+                    newState: roots[0],
+                    proofId: proof_id,
+                    updater,
+                    timestamp: U256::from(1),
+                }
+                .encode_log_data(),
+            ),
+            rpc_log(
+                contract,
+                IArithmetic::BatchStateUpdated {
+                    stateIds: state_ids.clone(),
+                    newStates: roots.clone(),
+                    updater,
+                    timestamp: U256::from(1),
+                }
+                .encode_log_data(),
+            ),
+        ];
+
+        let results =
+            EthereumClient::decode_batch_results(contract, &logs, &state_ids, &roots, &proofs)
+                .unwrap();
+        assert_eq!(results, vec![Some(proof_id), None]);
+
+        // Logs from another contract are not ours
+        let other = Address::from_slice(&[9; 20]);
+        assert!(
+            EthereumClient::decode_batch_results(other, &logs, &state_ids, &roots, &proofs)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_log_cursor_dedupes_stream_and_backfill() {
+        use ethereum_client::LogCursor;
+
+        let mut cursor = LogCursor::new(10);
+
+        // Already delivered blocks are skipped
+        assert!(!cursor.accept(10, 0));
+
+        // Streamed logs of block 12; a duplicate is dropped
+        assert!(cursor.accept(12, 0));
+        assert!(cursor.accept(12, 1));
+        assert!(!cursor.accept(12, 1));
+        assert_eq!(cursor.complete_through(), 11);
+
+        // After a reconnect the backfill re-reads block 12 and only delivers what is new
+        assert!(!cursor.accept_backfilled(12, 0));
+        assert!(cursor.accept_backfilled(12, 2));
+        assert!(cursor.accept_backfilled(13, 0));
+        cursor.complete(13);
+        assert_eq!(cursor.complete_through(), 13);
+
+        // Logs buffered by the new subscription during the backfill are not repeated
+        assert!(!cursor.accept(13, 0));
+        assert!(cursor.accept(14, 0));
+        assert_eq!(cursor.complete_through(), 13);
+    }
+
+    #[test]
+    fn test_chain_health_assessment() {
+        use alloy_primitives::U256;
+        use ethereum_client::{health::assess, HealthConfig, HealthIssue, NetworkStats};
+
+        let config = HealthConfig {
+            max_gas_price_wei: Some(50_000_000_000),
+            ..HealthConfig::default()
+        };
+        let now = 1_700_000_000;
+        let healthy = NetworkStats {
+            chain_id: 11_155_111,
+            block_number: 100,
+            gas_price: U256::from(20_000_000_000u64),
+            base_fee: Some(U256::from(19_000_000_000u64)),
+            network_name: "sepolia".to_string(),
+            sync_status: true,
+            block_timestamp: now - 12,
+            highest_block: None,
+        };
+        assert!(assess(&healthy, 11_155_111, &config, now).is_empty());
+
+        let unhealthy = NetworkStats {
+            chain_id: 1,
+            gas_price: U256::from(80_000_000_000u64),
+            sync_status: false,
+            block_timestamp: now - 600,
+            highest_block: Some(200),
+            ..healthy
+        };
+        assert_eq!(
+            assess(&unhealthy, 11_155_111, &config, now),
+            vec![
+                HealthIssue::ChainIdMismatch {
+                    expected: 11_155_111,
+                    actual: 1
+                },
+                HealthIssue::HeadLagging {
+                    head_age_seconds: 600
+                },
+                HealthIssue::Syncing {
+                    block_number: 100,
+                    highest_block: 200
+                },
+                HealthIssue::GasPriceAboveThreshold {
+                    gas_price: 80_000_000_000,
+                    threshold: 50_000_000_000
+                },
+            ]
+        );
+    }
+
+    #[cfg(feature = "database")]
+    #[test]
+    fn test_delivery_retry_backoff() {
+        use ethereum_client::DeliveryConfig;
+        use std::time::Duration;
+
+        let config = DeliveryConfig {
+            retry_base_delay: Duration::from_secs(5),
+            max_retry_delay: Duration::from_secs(30),
+            max_attempts: 5,
+            ..DeliveryConfig::default()
+        };
+
+        let delays: Vec<_> = (1..=5)
+            .map(|attempts| config.retry_delay(attempts))
+            .collect();
+        assert_eq!(
+            delays,
+            vec![
+                Some(Duration::from_secs(5)),
+                Some(Duration::from_secs(10)),
+                Some(Duration::from_secs(20)),
+                Some(Duration::from_secs(30)),
+                None,
+            ]
+        );
+    }
 }
@@ -0,0 +1,551 @@
+//! Portable proof bundles
+//!
+//! A `ProofBundle` carries everything needed to check a batch proof without
+//! talking to the service that produced it: the EVM-encoded SP1 proof, its
+//! public values, the program verification key hash, and the IMT roots the
+//! batch moved between. Bundles are versioned and checksummed, and encode as
+//! JSON (byte fields as `0x` hex) or CBOR (byte fields as raw bytes). The
+//! checksum is computed over the decoded fields, so converting a bundle between
+//! encodings keeps it valid.
+
+use crate::{process_transactions, ProofSystem, PublicValuesStruct};
+use alloy_sol_types::SolType;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Value of the `format` field, identifies a file as a proof bundle
+pub const PROOF_BUNDLE_FORMAT: &str = "arithmetic-proof-bundle";
+
+/// Bundle layout version written by this crate
+pub const PROOF_BUNDLE_VERSION: u32 = 1;
+
+/// Errors from decoding or checking a proof bundle
+#[derive(Error, Debug)]
+pub enum BundleError {
+    #[error("Not a proof bundle: format is {0:?}")]
+    UnknownFormat(String),
+
+    #[error("Unsupported proof bundle version {0} (supported: {PROOF_BUNDLE_VERSION})")]
+    UnsupportedVersion(u32),
+
+    #[error("Checksum mismatch: bundle says 0x{expected}, contents hash to 0x{actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+
+    #[error("Invalid public values: {0}")]
+    InvalidPublicValues(String),
+
+    #[error("Transactions lead from {initial} to {computed}, public values say {expected}")]
+    TransactionMismatch {
+        initial: i32,
+        computed: i32,
+        expected: i32,
+    },
+
+    #[error("{field} mismatch: expected {expected}, bundle has {actual}")]
+    Mismatch {
+        field: &'static str,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("Proof verification failed: {0}")]
+    InvalidProof(String),
+
+    #[error("JSON encoding error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("CBOR encoding error: {0}")]
+    Cbor(String),
+}
+
+/// Versioned, self-describing proof of one batch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProofBundle {
+    /// Always `PROOF_BUNDLE_FORMAT`
+    pub format: String,
+    pub version: u32,
+    pub batch_id: i32,
+    pub proof_system: ProofSystem,
+    /// SP1 program verification key hash (`vk.bytes32()`)
+    #[serde(with = "bytes32")]
+    pub vkey_hash: [u8; 32],
+    /// ABI-encoded `PublicValuesStruct`
+    #[serde(with = "byte_vec")]
+    pub public_values: Vec<u8>,
+    /// EVM-encoded proof, as passed to the SP1 verifier gateway
+    #[serde(with = "byte_vec")]
+    pub proof: Vec<u8>,
+    /// IMT root before the batch
+    #[serde(with = "bytes32")]
+    pub prev_root: [u8; 32],
+    /// IMT root after the batch
+    #[serde(with = "bytes32")]
+    pub new_root: [u8; 32],
+    /// Batch transaction amounts; private, so only present in operator bundles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transactions: Option<Vec<i32>>,
+    /// SHA-256 over every other field, see `ProofBundle::compute_checksum`
+    #[serde(with = "bytes32")]
+    pub checksum: [u8; 32],
+}
+
+/// Balances and roots of a bundle that passed its checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BundleSummary {
+    pub batch_id: i32,
+    pub proof_system: ProofSystem,
+    pub initial_balance: i32,
+    pub final_balance: i32,
+    pub prev_root: [u8; 32],
+    pub new_root: [u8; 32],
+}
+
+impl ProofBundle {
+    /// Build a bundle without transactions and compute its checksum
+    #[must_use]
+    pub fn new(
+        batch_id: i32,
+        proof_system: ProofSystem,
+        vkey_hash: [u8; 32],
+        public_values: Vec<u8>,
+        proof: Vec<u8>,
+        prev_root: [u8; 32],
+        new_root: [u8; 32],
+    ) -> Self {
+        let mut bundle = Self {
+            format: PROOF_BUNDLE_FORMAT.to_string(),
+            version: PROOF_BUNDLE_VERSION,
+            batch_id,
+            proof_system,
+            vkey_hash,
+            public_values,
+            proof,
+            prev_root,
+            new_root,
+            transactions: None,
+            checksum: [0u8; 32],
+        };
+        bundle.checksum = bundle.compute_checksum();
+        bundle
+    }
+
+    /// Attach the batch transactions (operator view) and recompute the checksum
+    #[must_use]
+    pub fn with_transactions(mut self, transactions: Vec<i32>) -> Self {
+        self.transactions = Some(transactions);
+        self.checksum = self.compute_checksum();
+        self
+    }
+
+    /// SHA-256 over a length-prefixed encoding of every field but the checksum
+    ///
+    /// Independent of JSON/CBOR so both encodings of a bundle share a checksum.
+    #[must_use]
+    pub fn compute_checksum(&self) -> [u8; 32] {
+        fn field(hasher: &mut Sha256, bytes: &[u8]) {
+            hasher.update((bytes.len() as u64).to_be_bytes());
+            hasher.update(bytes);
+        }
+
+        let mut hasher = Sha256::new();
+        field(&mut hasher, self.format.as_bytes());
+        hasher.update(self.version.to_be_bytes());
+        hasher.update(self.batch_id.to_be_bytes());
+        field(&mut hasher, self.proof_system.to_sindri_scheme().as_bytes());
+        hasher.update(self.vkey_hash);
+        field(&mut hasher, &self.public_values);
+        field(&mut hasher, &self.proof);
+        hasher.update(self.prev_root);
+        hasher.update(self.new_root);
+        match &self.transactions {
+            None => hasher.update([0u8]),
+            Some(transactions) => {
+                hasher.update([1u8]);
+                hasher.update((transactions.len() as u64).to_be_bytes());
+                for amount in transactions {
+                    hasher.update(amount.to_be_bytes());
+                }
+            }
+        }
+        hasher.finalize().into()
+    }
+
+    /// Balances committed to by the public values
+    ///
+    /// # Errors
+    ///
+    /// Returns `BundleError::InvalidPublicValues` if they are not an ABI-encoded
+    /// `PublicValuesStruct`.
+    pub fn balances(&self) -> Result<(i32, i32), BundleError> {
+        let decoded = PublicValuesStruct::abi_decode(&self.public_values)
+            .map_err(|e| BundleError::InvalidPublicValues(e.to_string()))?;
+        Ok((decoded.initial_balance, decoded.final_balance))
+    }
+
+    /// Structural checks that need no cryptography
+    ///
+    /// Checks the format and version, the checksum, that the public values
+    /// decode, and, for operator bundles, that the transactions lead from the
+    /// initial to the final balance.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first `BundleError` found.
+    pub fn validate(&self) -> Result<BundleSummary, BundleError> {
+        self.check_header()?;
+
+        let actual = self.compute_checksum();
+        if actual != self.checksum {
+            return Err(BundleError::ChecksumMismatch {
+                expected: hex::encode(self.checksum),
+                actual: hex::encode(actual),
+            });
+        }
+
+        let (initial_balance, final_balance) = self.balances()?;
+
+        if let Some(transactions) = &self.transactions {
+            let computed = process_transactions(initial_balance, transactions);
+            if computed != final_balance {
+                return Err(BundleError::TransactionMismatch {
+                    initial: initial_balance,
+                    computed,
+                    expected: final_balance,
+                });
+            }
+        }
+
+        Ok(BundleSummary {
+            batch_id: self.batch_id,
+            proof_system: self.proof_system,
+            initial_balance,
+            final_balance,
+            prev_root: self.prev_root,
+            new_root: self.new_root,
+        })
+    }
+
+    /// Pretty-printed JSON encoding
+    ///
+    /// # Errors
+    ///
+    /// Returns `BundleError::Json` if serialization fails.
+    pub fn to_json(&self) -> Result<String, BundleError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// CBOR encoding
+    ///
+    /// # Errors
+    ///
+    /// Returns `BundleError::Cbor` if serialization fails.
+    pub fn to_cbor(&self) -> Result<Vec<u8>, BundleError> {
+        let mut buffer = Vec::new();
+        ciborium::into_writer(self, &mut buffer).map_err(|e| BundleError::Cbor(e.to_string()))?;
+        Ok(buffer)
+    }
+
+    /// Decode a JSON bundle and check its format and version
+    ///
+    /// # Errors
+    ///
+    /// Returns `BundleError` if the JSON does not describe a supported bundle.
+    pub fn from_json(json: &str) -> Result<Self, BundleError> {
+        let bundle: Self = serde_json::from_str(json)?;
+        bundle.check_header()?;
+        Ok(bundle)
+    }
+
+    /// Decode a CBOR bundle and check its format and version
+    ///
+    /// # Errors
+    ///
+    /// Returns `BundleError` if the bytes do not describe a supported bundle.
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, BundleError> {
+        let bundle: Self =
+            ciborium::from_reader(bytes).map_err(|e| BundleError::Cbor(e.to_string()))?;
+        bundle.check_header()?;
+        Ok(bundle)
+    }
+
+    /// Decode either encoding; a JSON bundle starts with `{`, anything else is
+    /// read as CBOR
+    ///
+    /// # Errors
+    ///
+    /// Returns `BundleError` if the bytes do not describe a supported bundle.
+    pub fn decode(bytes: &[u8]) -> Result<Self, BundleError> {
+        match bytes.iter().find(|b| !b.is_ascii_whitespace()) {
+            Some(b'{') => {
+                let bundle: Self = serde_json::from_slice(bytes)?;
+                bundle.check_header()?;
+                Ok(bundle)
+            }
+            _ => Self::from_cbor(bytes),
+        }
+    }
+
+    fn check_header(&self) -> Result<(), BundleError> {
+        if self.format != PROOF_BUNDLE_FORMAT {
+            return Err(BundleError::UnknownFormat(self.format.clone()));
+        }
+        if self.version != PROOF_BUNDLE_VERSION {
+            return Err(BundleError::UnsupportedVersion(self.version));
+        }
+        Ok(())
+    }
+}
+
+/// What the verifier expects of a bundle; `None` fields are not checked
+#[derive(Debug, Clone, Default)]
+pub struct BundleExpectations {
+    pub batch_id: Option<i32>,
+    pub vkey_hash: Option<[u8; 32]>,
+    pub initial_balance: Option<i32>,
+    pub final_balance: Option<i32>,
+    pub prev_root: Option<[u8; 32]>,
+    pub new_root: Option<[u8; 32]>,
+}
+
+/// Strictly verify a proof bundle offline
+///
+/// Runs `ProofBundle::validate`, compares the bundle against `expected`, then
+/// verifies the proof with SP1's verifier for the bundle's declared proof
+/// system. Nothing is defaulted: a missing or malformed field fails.
+///
+/// # Errors
+///
+/// Returns the first `BundleError` found.
+#[cfg(feature = "verify")]
+pub fn verify_bundle(
+    bundle: &ProofBundle,
+    expected: &BundleExpectations,
+) -> Result<BundleSummary, BundleError> {
+    use sp1_verifier::{Groth16Verifier, PlonkVerifier, GROTH16_VK_BYTES, PLONK_VK_BYTES};
+
+    fn expect<T: PartialEq + std::fmt::Display>(
+        field: &'static str,
+        expected: Option<T>,
+        actual: T,
+    ) -> Result<(), BundleError> {
+        match expected {
+            Some(expected) if expected != actual => Err(BundleError::Mismatch {
+                field,
+                expected: expected.to_string(),
+                actual: actual.to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    let hex32 = |bytes: [u8; 32]| format!("0x{}", hex::encode(bytes));
+
+    let summary = bundle.validate()?;
+
+    expect("batch_id", expected.batch_id, bundle.batch_id)?;
+    expect(
+        "vkey_hash",
+        expected.vkey_hash.map(hex32),
+        hex32(bundle.vkey_hash),
+    )?;
+    expect(
+        "initial_balance",
+        expected.initial_balance,
+        summary.initial_balance,
+    )?;
+    expect(
+        "final_balance",
+        expected.final_balance,
+        summary.final_balance,
+    )?;
+    expect(
+        "prev_root",
+        expected.prev_root.map(hex32),
+        hex32(bundle.prev_root),
+    )?;
+    expect(
+        "new_root",
+        expected.new_root.map(hex32),
+        hex32(bundle.new_root),
+    )?;
+
+    let vkey_hash = hex32(bundle.vkey_hash);
+    let result = match bundle.proof_system {
+        ProofSystem::Groth16 => Groth16Verifier::verify(
+            &bundle.proof,
+            &bundle.public_values,
+            &vkey_hash,
+            *GROTH16_VK_BYTES,
+        )
+        .map_err(|e| e.to_string()),
+        ProofSystem::Plonk => PlonkVerifier::verify(
+            &bundle.proof,
+            &bundle.public_values,
+            &vkey_hash,
+            *PLONK_VK_BYTES,
+        )
+        .map_err(|e| e.to_string()),
+    };
+    result.map_err(BundleError::InvalidProof)?;
+
+    Ok(summary)
+}
+
+/// Parse `0x`-prefixed (or bare) hex into a fixed-size array
+///
+/// # Errors
+///
+/// Returns a message if the input is not valid hex of exactly `N` bytes.
+pub fn parse_hex_array<const N: usize>(value: &str) -> Result<[u8; N], String> {
+    let bytes = hex::decode(value.strip_prefix("0x").unwrap_or(value))
+        .map_err(|e| format!("invalid hex: {e}"))?;
+    bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| format!("expected {N} bytes, got {}", bytes.len()))
+}
+
+/// Byte strings as `0x` hex in human-readable formats, raw bytes otherwise
+pub mod byte_vec {
+    use serde::de::{Error, SeqAccess, Visitor};
+    use serde::{Deserializer, Serializer};
+    use std::fmt;
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+        } else {
+            serializer.serialize_bytes(bytes)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        struct BytesVisitor;
+
+        impl<'de> Visitor<'de> for BytesVisitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a 0x-prefixed hex string or a byte string")
+            }
+
+            fn visit_str<E: Error>(self, value: &str) -> Result<Vec<u8>, E> {
+                let digits = value
+                    .strip_prefix("0x")
+                    .ok_or_else(|| E::custom("hex string must start with 0x"))?;
+                hex::decode(digits).map_err(E::custom)
+            }
+
+            fn visit_bytes<E: Error>(self, value: &[u8]) -> Result<Vec<u8>, E> {
+                Ok(value.to_vec())
+            }
+
+            fn visit_byte_buf<E: Error>(self, value: Vec<u8>) -> Result<Vec<u8>, E> {
+                Ok(value)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(byte) = seq.next_element()? {
+                    bytes.push(byte);
+                }
+                Ok(bytes)
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(BytesVisitor)
+        } else {
+            deserializer.deserialize_bytes(BytesVisitor)
+        }
+    }
+}
+
+/// Fixed 32-byte values, encoded like `byte_vec`
+pub mod bytes32 {
+    use serde::de::Error;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8; 32], serializer: S) -> Result<S::Ok, S::Error> {
+        super::byte_vec::serialize(bytes, serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 32], D::Error> {
+        let bytes = super::byte_vec::deserialize(deserializer)?;
+        bytes
+            .try_into()
+            .map_err(|bytes: Vec<u8>| D::Error::invalid_length(bytes.len(), &"32 bytes"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_sol_types::SolValue;
+
+    fn sample_bundle() -> ProofBundle {
+        let public_values = PublicValuesStruct {
+            initial_balance: 10,
+            final_balance: 22,
+        }
+        .abi_encode();
+        ProofBundle::new(
+            3,
+            ProofSystem::Groth16,
+            [7u8; 32],
+            public_values,
+            vec![0xa4, 0x59, 0x4c, 0x59, 1, 2, 3],
+            [1u8; 32],
+            [2u8; 32],
+        )
+    }
+
+    #[test]
+    fn json_and_cbor_round_trip_with_the_same_checksum() {
+        let bundle = sample_bundle().with_transactions(vec![5, 7]);
+
+        let json = bundle.to_json().unwrap();
+        assert!(json.contains("\"proof_system\": \"groth16\""));
+        assert!(json.contains(&format!("0x{}", hex::encode([7u8; 32]))));
+
+        let from_json = ProofBundle::decode(json.as_bytes()).unwrap();
+        let from_cbor = ProofBundle::decode(&bundle.to_cbor().unwrap()).unwrap();
+        assert_eq!(from_json, bundle);
+        assert_eq!(from_cbor, bundle);
+
+        let summary = from_cbor.validate().unwrap();
+        assert_eq!((summary.initial_balance, summary.final_balance), (10, 22));
+    }
+
+    #[test]
+    fn validate_rejects_tampering() {
+        let mut bundle = sample_bundle();
+        bundle.new_root = [3u8; 32];
+        assert!(matches!(
+            bundle.validate(),
+            Err(BundleError::ChecksumMismatch { .. })
+        ));
+
+        let bundle = sample_bundle().with_transactions(vec![5, 6]);
+        assert!(matches!(
+            bundle.validate(),
+            Err(BundleError::TransactionMismatch { computed: 21, .. })
+        ));
+    }
+
+    #[test]
+    fn decode_rejects_unknown_fields_and_versions() {
+        let mut value: serde_json::Value =
+            serde_json::from_str(&sample_bundle().to_json().unwrap()).unwrap();
+        value["verifying_key"] = serde_json::json!("0x00");
+        assert!(ProofBundle::from_json(&value.to_string()).is_err());
+
+        let mut bundle = sample_bundle();
+        bundle.version = PROOF_BUNDLE_VERSION + 1;
+        assert!(matches!(
+            ProofBundle::decode(&bundle.to_cbor().unwrap()),
+            Err(BundleError::UnsupportedVersion(_))
+        ));
+    }
+}
@@ -1,4 +1,13 @@
 use alloy_sol_types::sol;
+use serde::{Deserialize, Serialize};
+
+// Proof bundles are host-side only; the zkVM program never needs them
+#[cfg(feature = "bundle")]
+pub mod bundle;
+
+// Transaction receipts are checked by end users, never inside the zkVM
+#[cfg(feature = "receipt")]
+pub mod receipt;
 
 // Proof module only available for host-side operations
 #[cfg(feature = "sp1")]
@@ -14,6 +23,27 @@ sol! {
     }
 }
 
+/// Available EVM-compatible proof systems
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum ProofSystem {
+    Plonk,
+    #[default]
+    Groth16,
+}
+
+impl ProofSystem {
+    /// Convert to the proving scheme string expected by Sindri
+    #[must_use]
+    pub const fn to_sindri_scheme(&self) -> &'static str {
+        match self {
+            Self::Plonk => "plonk",
+            Self::Groth16 => "groth16",
+        }
+    }
+}
+
 /// Compute the result of the arithmetic operation (wrapping around on overflows), using normal Rust code.
 #[must_use]
 pub const fn addition(a: i32, b: i32) -> i32 {
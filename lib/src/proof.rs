@@ -4,37 +4,20 @@
 //! and verification that can be used by both the CLI script and API server.
 //! It consolidates the feature-rich logic from the script implementation.
 
+use crate::bundle::ProofBundle;
 use crate::PublicValuesStruct;
 use alloy_sol_types::SolType;
 use serde::{Deserialize, Serialize};
 use sindri::integrations::sp1_v5::SP1ProofInfo;
 use sindri::{client::SindriClient, JobStatus, ProofInfoResponse, ProofInput};
-use sp1_sdk::{HashableKey, SP1Stdin};
+use sp1_sdk::{HashableKey, SP1Proof, SP1Stdin};
 use std::convert::TryInto;
 use std::path::PathBuf;
 use std::time::Duration;
 use thiserror::Error;
 use tracing::{error, info, warn};
 
-/// Available EVM-compatible proof systems
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize, Default)]
-#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
-pub enum ProofSystem {
-    Plonk,
-    #[default]
-    Groth16,
-}
-
-impl ProofSystem {
-    /// Convert to the proving scheme string expected by Sindri
-    #[must_use]
-    pub const fn to_sindri_scheme(&self) -> &'static str {
-        match self {
-            Self::Plonk => "plonk",
-            Self::Groth16 => "groth16",
-        }
-    }
-}
+pub use crate::ProofSystem;
 
 /// Request for proof generation (batch processing)
 #[derive(Debug, Clone)]
@@ -627,6 +610,16 @@ pub async fn get_sindri_proof_data(proof_id: &str) -> Result<SindriProofData, Pr
         .get_sp1_verifying_key()
         .map_err(|e| ProofError::SindriError(format!("Failed to extract verification key: {e}")))?;
 
+    let proof_system = match &sp1_proof.proof {
+        SP1Proof::Groth16(_) => ProofSystem::Groth16,
+        SP1Proof::Plonk(_) => ProofSystem::Plonk,
+        _ => {
+            return Err(ProofError::SindriError(format!(
+                "Proof {proof_id} is not an EVM-verifiable Groth16 or PLONK proof"
+            )))
+        }
+    };
+
     // Convert hex string to bytes for verifying key
     let vkey_hex = verifying_key.bytes32();
     let vkey_bytes = hex::decode(vkey_hex.strip_prefix("0x").unwrap_or(&vkey_hex))
@@ -637,6 +630,7 @@ pub async fn get_sindri_proof_data(proof_id: &str) -> Result<SindriProofData, Pr
         proof_bytes: sp1_proof.bytes(),
         public_values: sp1_proof.public_values.as_slice().to_vec(),
         verifying_key: vkey_bytes,
+        proof_system,
     })
 }
 
@@ -647,6 +641,38 @@ pub struct SindriProofData {
     pub proof_bytes: Vec<u8>,
     pub public_values: Vec<u8>,
     pub verifying_key: Vec<u8>,
+    pub proof_system: ProofSystem,
+}
+
+impl SindriProofData {
+    /// Package the proof as a portable bundle for a batch
+    ///
+    /// # Errors
+    ///
+    /// Returns `ProofError::SindriError` if the verifying key is not a 32-byte hash.
+    pub fn into_bundle(
+        self,
+        batch_id: i32,
+        prev_root: [u8; 32],
+        new_root: [u8; 32],
+    ) -> Result<ProofBundle, ProofError> {
+        let vkey_hash: [u8; 32] = self.verifying_key.try_into().map_err(|key: Vec<u8>| {
+            ProofError::SindriError(format!(
+                "Verifying key hash must be 32 bytes, got {}",
+                key.len()
+            ))
+        })?;
+
+        Ok(ProofBundle::new(
+            batch_id,
+            self.proof_system,
+            vkey_hash,
+            self.public_values,
+            self.proof_bytes,
+            prev_root,
+            new_root,
+        ))
+    }
 }
 
 /// Create EVM-compatible fixture from Sindri proof for batch processing
@@ -0,0 +1,462 @@
+//! Transaction receipts
+//!
+//! A `TransactionReceipt` lets the submitter of a transaction show that it was
+//! included in a batch. Every link of the chain can be checked offline:
+//!
+//! 1. the transaction's id, amount and submission time hash to its nullifier
+//! 2. the nullifier is a leaf of the IMT, and its Merkle path leads to the root
+//!    the batch committed to
+//! 3. that root is the `new_root` of the batch's proof bundle, whose SP1 proof
+//!    fixes the batch's final balance
+//!
+//! The last link, that the final balance was posted on-chain, needs a lookup
+//! of the batch's state root on the settlement contract.
+
+use crate::bundle::{bytes32, BundleSummary};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Value of the `format` field, identifies a file as a transaction receipt
+pub const TRANSACTION_RECEIPT_FORMAT: &str = "arithmetic-transaction-receipt";
+
+/// Receipt layout version written by this crate
+pub const TRANSACTION_RECEIPT_VERSION: u32 = 1;
+
+/// Errors from decoding or checking a transaction receipt
+#[derive(Error, Debug)]
+pub enum ReceiptError {
+    #[error("Not a transaction receipt: format is {0:?}")]
+    UnknownFormat(String),
+
+    #[error(
+        "Unsupported transaction receipt version {0} (supported: {TRANSACTION_RECEIPT_VERSION})"
+    )]
+    UnsupportedVersion(u32),
+
+    #[error("Transaction hashes to nullifier {computed}, receipt says {claimed}")]
+    NullifierMismatch { computed: i64, claimed: i64 },
+
+    #[error("Proven leaf holds {leaf}, not the transaction's nullifier {nullifier}")]
+    LeafMismatch { leaf: i64, nullifier: i64 },
+
+    #[error("Invalid membership proof: {0}")]
+    InvalidPath(String),
+
+    #[error("Membership proof leads to 0x{computed}, batch root is 0x{expected}")]
+    RootMismatch { expected: String, computed: String },
+
+    #[error("{field} mismatch: receipt has {expected}, proof bundle has {actual}")]
+    Mismatch {
+        field: &'static str,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("JSON encoding error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Nullifier the batch service inserts into the IMT for a transaction
+///
+/// The first 8 bytes of BLAKE3 over the little-endian id, amount and
+/// submission time (Unix seconds), mapped into `1..=i64::MAX`.
+#[must_use]
+pub fn transaction_nullifier(transaction_id: i32, amount: i32, created_at: i64) -> i64 {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&transaction_id.to_le_bytes());
+    hasher.update(&amount.to_le_bytes());
+    hasher.update(&created_at.to_le_bytes());
+
+    let digest = hasher.finalize();
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&digest.as_bytes()[..8]);
+    let hash = u64::from_le_bytes(prefix);
+
+    // IMT nullifiers are positive; zero is the tree's sentinel
+    ((hash % (i64::MAX as u64)) as i64) + 1
+}
+
+/// Merkle path from one IMT leaf to the root
+///
+/// Carries the leaf's preimage rather than its hash, so the verifier sees
+/// which nullifier the leaf holds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ImtMembershipProof {
+    pub leaf_index: i64,
+    /// Nullifier stored in the leaf
+    pub value: i64,
+    /// Leaf index of the next larger nullifier, 0 for the largest
+    pub next_index: i64,
+    /// Next larger nullifier, 0 for the largest
+    pub next_value: i64,
+    /// Sibling hashes from the leaf level up
+    #[serde(with = "bytes32_vec")]
+    pub siblings: Vec<[u8; 32]>,
+    /// `true` where the path node is a right child
+    pub path_indices: Vec<bool>,
+}
+
+impl ImtMembershipProof {
+    /// Leaf hash, SHA-256 over the big-endian value, next index and next value
+    #[must_use]
+    pub fn leaf_hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(self.value.to_be_bytes());
+        hasher.update(self.next_index.to_be_bytes());
+        hasher.update(self.next_value.to_be_bytes());
+        hasher.finalize().into()
+    }
+
+    /// Hash the leaf up the path
+    ///
+    /// # Errors
+    ///
+    /// Returns `ReceiptError::InvalidPath` if the path is empty, its length
+    /// differs from the number of siblings, or it does not match `leaf_index`.
+    pub fn compute_root(&self) -> Result<[u8; 32], ReceiptError> {
+        if self.siblings.is_empty() || self.siblings.len() != self.path_indices.len() {
+            return Err(ReceiptError::InvalidPath(format!(
+                "{} siblings for {} path positions",
+                self.siblings.len(),
+                self.path_indices.len()
+            )));
+        }
+        if self.leaf_index < 0 || self.leaf_index >> self.path_indices.len().min(63) != 0 {
+            return Err(ReceiptError::InvalidPath(format!(
+                "leaf index {} does not fit a tree of height {}",
+                self.leaf_index,
+                self.path_indices.len()
+            )));
+        }
+
+        let mut current = self.leaf_hash();
+        for (level, (sibling, is_right)) in self.siblings.iter().zip(&self.path_indices).enumerate()
+        {
+            if *is_right != ((self.leaf_index >> level) & 1 == 1) {
+                return Err(ReceiptError::InvalidPath(format!(
+                    "path position {level} does not match leaf index {}",
+                    self.leaf_index
+                )));
+            }
+            let mut hasher = Sha256::new();
+            if *is_right {
+                hasher.update(sibling);
+                hasher.update(current);
+            } else {
+                hasher.update(current);
+                hasher.update(sibling);
+            }
+            current = hasher.finalize().into();
+        }
+        Ok(current)
+    }
+}
+
+/// Settlement of the receipt's batch on one target network
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReceiptSettlement {
+    pub target: String,
+    pub chain_id: i64,
+    /// One of `pending`, `submitted`, `confirmed`, `failed`
+    pub status: String,
+    pub transaction_hash: Option<String>,
+    pub block_number: Option<i64>,
+}
+
+/// Proof that a transaction was included in a batch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransactionReceipt {
+    /// Always `TRANSACTION_RECEIPT_FORMAT`
+    pub format: String,
+    pub version: u32,
+    pub transaction_id: i32,
+    pub amount: i32,
+    /// Submission time in Unix seconds, one of the nullifier inputs
+    pub created_at: i64,
+    pub batch_id: i32,
+    pub nullifier: i64,
+    pub membership: ImtMembershipProof,
+    /// IMT root the batch committed to, the `new_root` of its proof bundle
+    #[serde(with = "bytes32")]
+    pub batch_root: [u8; 32],
+    /// Counter value after the batch; its on-chain state root derives from it
+    pub final_counter_value: i64,
+    /// Proof status of the batch when the receipt was issued
+    pub proof_status: String,
+    /// Path of the batch's proof bundle on the API, once the batch is proven
+    #[serde(default)]
+    pub proof_bundle: Option<String>,
+    #[serde(default)]
+    pub settlements: Vec<ReceiptSettlement>,
+}
+
+impl TransactionReceipt {
+    /// Build a receipt for a batch that is not proven or settled yet
+    #[must_use]
+    pub fn new(
+        transaction_id: i32,
+        amount: i32,
+        created_at: i64,
+        batch_id: i32,
+        membership: ImtMembershipProof,
+        batch_root: [u8; 32],
+        final_counter_value: i64,
+    ) -> Self {
+        Self {
+            format: TRANSACTION_RECEIPT_FORMAT.to_string(),
+            version: TRANSACTION_RECEIPT_VERSION,
+            transaction_id,
+            amount,
+            created_at,
+            batch_id,
+            nullifier: transaction_nullifier(transaction_id, amount, created_at),
+            membership,
+            batch_root,
+            final_counter_value,
+            proof_status: "pending".to_string(),
+            proof_bundle: None,
+            settlements: Vec::new(),
+        }
+    }
+
+    /// Check that the transaction is a leaf under the batch root
+    ///
+    /// Recomputes the nullifier from the transaction, checks that the proven
+    /// leaf holds it, and hashes the Merkle path up to `batch_root`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first `ReceiptError` found.
+    pub fn verify(&self) -> Result<(), ReceiptError> {
+        self.check_header()?;
+
+        let computed = transaction_nullifier(self.transaction_id, self.amount, self.created_at);
+        if computed != self.nullifier {
+            return Err(ReceiptError::NullifierMismatch {
+                computed,
+                claimed: self.nullifier,
+            });
+        }
+        if self.membership.value != self.nullifier {
+            return Err(ReceiptError::LeafMismatch {
+                leaf: self.membership.value,
+                nullifier: self.nullifier,
+            });
+        }
+
+        let root = self.membership.compute_root()?;
+        if root != self.batch_root {
+            return Err(ReceiptError::RootMismatch {
+                expected: hex::encode(self.batch_root),
+                computed: hex::encode(root),
+            });
+        }
+        Ok(())
+    }
+
+    /// Check that a verified proof bundle is the proof of this receipt's batch
+    ///
+    /// # Errors
+    ///
+    /// Returns `ReceiptError::Mismatch` for the first field that differs.
+    pub fn check_bundle(&self, bundle: &BundleSummary) -> Result<(), ReceiptError> {
+        let mismatch = |field, expected: String, actual: String| ReceiptError::Mismatch {
+            field,
+            expected,
+            actual,
+        };
+
+        if bundle.batch_id != self.batch_id {
+            return Err(mismatch(
+                "batch_id",
+                self.batch_id.to_string(),
+                bundle.batch_id.to_string(),
+            ));
+        }
+        if bundle.new_root != self.batch_root {
+            return Err(mismatch(
+                "batch_root",
+                format!("0x{}", hex::encode(self.batch_root)),
+                format!("0x{}", hex::encode(bundle.new_root)),
+            ));
+        }
+        if i64::from(bundle.final_balance) != self.final_counter_value {
+            return Err(mismatch(
+                "final_counter_value",
+                self.final_counter_value.to_string(),
+                bundle.final_balance.to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Pretty-printed JSON encoding
+    ///
+    /// # Errors
+    ///
+    /// Returns `ReceiptError::Json` if serialization fails.
+    pub fn to_json(&self) -> Result<String, ReceiptError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Decode a JSON receipt and check its format and version
+    ///
+    /// # Errors
+    ///
+    /// Returns `ReceiptError` if the JSON does not describe a supported receipt.
+    pub fn from_json(json: &str) -> Result<Self, ReceiptError> {
+        let receipt: Self = serde_json::from_str(json)?;
+        receipt.check_header()?;
+        Ok(receipt)
+    }
+
+    fn check_header(&self) -> Result<(), ReceiptError> {
+        if self.format != TRANSACTION_RECEIPT_FORMAT {
+            return Err(ReceiptError::UnknownFormat(self.format.clone()));
+        }
+        if self.version != TRANSACTION_RECEIPT_VERSION {
+            return Err(ReceiptError::UnsupportedVersion(self.version));
+        }
+        Ok(())
+    }
+}
+
+/// Lists of 32-byte hashes, each encoded like `bytes32`
+mod bytes32_vec {
+    use serde::de::{SeqAccess, Visitor};
+    use serde::ser::SerializeSeq;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::fmt;
+
+    struct Hash([u8; 32]);
+
+    impl Serialize for Hash {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            super::bytes32::serialize(&self.0, serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Hash {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            super::bytes32::deserialize(deserializer).map(Hash)
+        }
+    }
+
+    pub fn serialize<S: Serializer>(hashes: &[[u8; 32]], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(hashes.len()))?;
+        for hash in hashes {
+            seq.serialize_element(&Hash(*hash))?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<[u8; 32]>, D::Error> {
+        struct HashesVisitor;
+
+        impl<'de> Visitor<'de> for HashesVisitor {
+            type Value = Vec<[u8; 32]>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a list of 32-byte hashes")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut hashes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(Hash(hash)) = seq.next_element()? {
+                    hashes.push(hash);
+                }
+                Ok(hashes)
+            }
+        }
+
+        deserializer.deserialize_seq(HashesVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProofSystem;
+
+    fn node(left: [u8; 32], right: [u8; 32]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(left);
+        hasher.update(right);
+        hasher.finalize().into()
+    }
+
+    /// Receipt for transaction 7 at leaf 2 of a height-2 tree
+    fn sample_receipt() -> TransactionReceipt {
+        let nullifier = transaction_nullifier(7, 5, 1_700_000_000);
+        let membership = ImtMembershipProof {
+            leaf_index: 2,
+            value: nullifier,
+            next_index: 0,
+            next_value: 0,
+            siblings: vec![[0u8; 32], [9u8; 32]],
+            path_indices: vec![false, true],
+        };
+        let root = node([9u8; 32], node(membership.leaf_hash(), [0u8; 32]));
+        TransactionReceipt::new(7, 5, 1_700_000_000, 3, membership, root, 22)
+    }
+
+    #[test]
+    fn receipt_verifies_and_round_trips() {
+        let receipt = sample_receipt();
+        receipt.verify().unwrap();
+        assert!(receipt.nullifier > 0);
+
+        let decoded = TransactionReceipt::from_json(&receipt.to_json().unwrap()).unwrap();
+        assert_eq!(decoded, receipt);
+
+        let summary = BundleSummary {
+            batch_id: 3,
+            proof_system: ProofSystem::Groth16,
+            initial_balance: 10,
+            final_balance: 22,
+            prev_root: [0u8; 32],
+            new_root: receipt.batch_root,
+        };
+        receipt.check_bundle(&summary).unwrap();
+        assert!(matches!(
+            receipt.check_bundle(&BundleSummary {
+                final_balance: 23,
+                ..summary
+            }),
+            Err(ReceiptError::Mismatch {
+                field: "final_counter_value",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn verify_rejects_tampering() {
+        let mut receipt = sample_receipt();
+        receipt.amount = 6;
+        assert!(matches!(
+            receipt.verify(),
+            Err(ReceiptError::NullifierMismatch { .. })
+        ));
+
+        let mut receipt = sample_receipt();
+        receipt.membership.next_value = 1;
+        assert!(matches!(
+            receipt.verify(),
+            Err(ReceiptError::RootMismatch { .. })
+        ));
+
+        let mut receipt = sample_receipt();
+        receipt.membership.path_indices = vec![true, true];
+        assert!(matches!(
+            receipt.verify(),
+            Err(ReceiptError::InvalidPath(_))
+        ));
+    }
+}